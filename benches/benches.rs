@@ -8,9 +8,11 @@ use criterion::{criterion_group, criterion_main};
 
 use color_picker::*;
 use hotkey::*;
+use render::*;
 
 mod color_picker;
 mod hotkey;
+mod render;
 
 criterion_group!(
     benches,
@@ -18,6 +20,8 @@ criterion_group!(
     bench_hsv_argb,
     bench_multiply_color_channel,
     bench_key_poll,
-    bench_key_process
+    bench_key_process,
+    bench_render,
+    bench_render_window
 );
 criterion_main!(benches);
@@ -9,7 +9,7 @@ use std::time::{Duration, Instant};
 
 use criterion::Criterion;
 
-use simple_crosshair_overlay::private::hotkey::KeyBindings;
+use simple_crosshair_overlay::private::hotkey::{KeyBindings, ScaleEasing};
 use simple_crosshair_overlay::private::platform;
 use simple_crosshair_overlay::private::platform::KeyboardState;
 
@@ -28,7 +28,8 @@ pub fn bench_key_process(c: &mut Criterion) {
     let mut group = c.benchmark_group("Key process");
 
     let mut hotkey_manager =
-        platform::generic::HotkeyManager::new(&KeyBindings::default()).unwrap();
+        platform::generic::HotkeyManager::new(&KeyBindings::default(), ScaleEasing::default())
+            .unwrap();
 
     group.bench_function("bitmask", |bencher| {
         bencher.iter_custom(|iters| {
@@ -0,0 +1,118 @@
+// This file is part of simple-crosshair-overlay and is licenced under the GNU GPL v3.0.
+// See LICENSE file for full text.
+// Copyright © 2023 Michael Ripley
+
+//! Crosshair rendering benchmarks.
+
+use criterion::{BatchSize, BenchmarkId, Criterion};
+use std::hint::black_box;
+use std::time::Instant;
+
+use simple_crosshair_overlay::private::settings::{RenderMode, Settings};
+use simple_crosshair_overlay::private::util::image::draw;
+
+/// side-lengths to render at. The default crosshair is 16.
+const SIZES: [u32; 4] = [16, 64, 256, 1024];
+
+/// Config settings that give the drawing code the most work: thick, outlined, tapered lines with a drop shadow, and
+/// thicker rings. `shadow_color` can only be set through the config, so this goes through [`Settings::from_toml`].
+const STYLED: &str = r#"
+line_thickness = 3
+outline_thickness = 2
+taper = 0.5
+shadow_offset = [2, 2]
+shadow_color = "80000000"
+circle_thickness = 3
+ring_thickness = 3
+"#;
+
+/// the defaults, or the defaults with [`STYLED`] on top
+fn base_settings(styled: bool) -> Settings {
+    if !styled {
+        return Settings::default();
+    }
+    let mut table: toml::Table = Settings::default().to_toml().parse().unwrap();
+    table.extend(STYLED.parse::<toml::Table>().unwrap());
+    Settings::from_toml(&table.to_string()).unwrap()
+}
+
+/// build settings for a generated crosshair of the given mode with a `size` by `size` window
+fn settings_for(render_mode: RenderMode, styled: bool, size: u32) -> Settings {
+    let mut settings = base_settings(styled);
+    settings.render_mode = render_mode;
+    settings.persisted.window_height = size;
+    settings.persisted.window_width = size;
+    settings.persisted.ring_radius = size / 2;
+    settings.persisted.dot_radius = size / 16;
+    settings.persisted.outer_radius = size / 2;
+    settings.persisted.inner_radius = size / 4;
+    settings
+}
+
+/// every generated mode, plain and [`STYLED`], at every size, along with its benchmark ID
+fn cases() -> impl Iterator<Item = (BenchmarkId, Settings)> {
+    RenderMode::GENERATED.into_iter().flat_map(|render_mode| {
+        [("plain", false), ("styled", true)]
+            .into_iter()
+            .flat_map(move |(style, styled)| {
+                SIZES.into_iter().map(move |size| {
+                    (
+                        BenchmarkId::new(format!("{render_mode:?}/{style}"), size),
+                        settings_for(render_mode, styled, size),
+                    )
+                })
+            })
+    })
+}
+
+pub fn bench_render(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Crosshair rendering");
+
+    for (id, settings) in cases() {
+        let content_size = settings.content_size();
+        let width = content_size.width as usize;
+        let height = content_size.height as usize;
+        group.bench_with_input(id, &settings, |bencher, settings| {
+            bencher.iter_batched_ref(
+                || vec![0; width * height],
+                |buffer| {
+                    draw::render_content(black_box(buffer.as_mut_slice()), width, height, settings)
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+/// the whole window, including the on-screen display, like a frame right after a hotkey press
+pub fn bench_render_window(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Window rendering");
+
+    for (id, mut settings) in cases() {
+        settings.persisted.osd = true;
+        settings.osd.show("SCALE 100", Instant::now());
+        let window_size = settings.size();
+        let width = window_size.width as usize;
+        let height = window_size.height as usize;
+        group.bench_with_input(id, &settings, |bencher, settings| {
+            bencher.iter_batched_ref(
+                || vec![0; width * height],
+                |buffer| {
+                    draw::render_window(
+                        black_box(buffer.as_mut_slice()),
+                        width,
+                        height,
+                        settings,
+                        None,
+                        (0, 0),
+                    )
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+
+    group.finish();
+}
@@ -0,0 +1,1280 @@
+// This file is part of simple-crosshair-overlay and is licenced under the GNU GPL v3.0.
+// See LICENSE file for full text.
+// Copyright © 2023 Michael Ripley
+
+//! How the overlay reacts to hotkeys and tray clicks, kept apart from winit and the tray so it can be tested without a
+//! display.
+//!
+//! The app feeds the [`Controller`] ticks and [`MenuAction`]s. The controller changes the settings, marks what needs
+//! redrawing or moving in [`Dirty`], and hands back an [`Effect`] for anything that needs a real window or tray.
+
+use std::mem;
+use std::time::Instant;
+
+use debug_print::debug_println;
+use winit::dpi::PhysicalSize;
+
+use crate::private::hotkey::{HotkeyManager, Keycode};
+use crate::private::platform::{self, KeyboardState, KeycodeType};
+use crate::private::settings::{Adjustment, OverlayLevel, Settings};
+use crate::private::util::custom_serializer;
+use crate::private::util::dialog;
+use crate::private::util::history::History;
+use crate::private::util::image::{self, Color};
+use crate::private::util::monitor::{MonitorInfo, SnapAnchor};
+use crate::private::util::timing::AutoHideTimer;
+
+/// Work the controller has queued up for the windows. The app clears these flags once it's done the work.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Dirty {
+    /// the next redraw should happen even for known buffer contents. This doesn't request a redraw on its own.
+    pub force_redraw: bool,
+    /// every window should be redrawn as soon as possible
+    pub request_redraw: bool,
+    pub window_position: bool,
+    /// the window size changed, which also means the position needs recomputing
+    pub window_scale: bool,
+}
+
+/// Something the controller can't do on its own, because it needs a real window or the tray
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Effect {
+    /// show or hide every window
+    SetVisible(bool),
+    /// The color picker opened or closed. `hotkey` is `true` if a hotkey did it, in which case focus should go back to
+    /// the previous window once the picker closes.
+    ColorPick { enabled: bool, hotkey: bool },
+    /// a different monitor was selected, so the tray's monitor menu needs updating
+    MonitorSelected(usize),
+    /// the fps may have changed
+    TickIntervalChanged,
+    /// the tray's check marks may no longer match the controller
+    SyncTray,
+    /// the window level setting may have changed
+    WindowLevelChanged,
+}
+
+/// A tray menu click the controller knows how to handle. Check items carry their new checked state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuAction {
+    Visible(bool),
+    Adjust(bool),
+    PickColor(bool),
+    Magnifier(bool),
+    HotkeysEnabled(bool),
+    Locked(bool),
+    Eyedropper(bool),
+    ToggleImage,
+    ReloadLastImage,
+    /// switch to the next generated crosshair style
+    NextCrosshairMode,
+    Reset,
+    SelectMonitor(usize),
+    /// snap on a monitor of the given size
+    Snap(SnapAnchor, PhysicalSize<u32>),
+}
+
+pub struct Controller<KS, K>
+where
+    KS: KeyboardState<K>,
+    K: KeycodeType,
+{
+    pub settings: Settings,
+    pub hotkey_manager: HotkeyManager<KS, K>,
+    /// `true` while in adjust mode, which is when most hotkeys work
+    pub adjust_mode: bool,
+    /// `true` if the magnifier should show while in adjust mode
+    pub magnifier_enabled: bool,
+    pub visible: bool,
+    /// `false` while the whole overlay is clickable, so it can be dragged around with the mouse
+    pub click_through: bool,
+    /// `true` while the hotkeys that adjust the crosshair are locked out
+    pub locked: bool,
+    /// the color picker pixel selected with the keyboard (or most recently hovered with the mouse)
+    pub picker_cursor: (usize, usize),
+    /// `true` while the next left click anywhere on screen picks the color under the cursor
+    pub eyedropper: bool,
+    /// monitors as of the last poll, in the same order as the tray's monitor menu
+    pub known_monitors: Vec<MonitorInfo>,
+    pub dirty: Dirty,
+    /// hides the overlay once it's gone untouched for a while, if configured
+    auto_hide_timer: AutoHideTimer,
+    /// recent crosshair adjustments, for undo and redo
+    history: History<Adjustment>,
+    effects: Vec<Effect>,
+}
+
+impl<KS, K> Controller<KS, K>
+where
+    KS: KeyboardState<K>,
+    K: KeycodeType,
+{
+    pub fn new(settings: Settings, mut hotkey_manager: HotkeyManager<KS, K>, now: Instant) -> Self {
+        hotkey_manager.set_enabled(settings.persisted.hotkeys_enabled);
+        let locked = settings.startup_locked();
+        hotkey_manager.set_locked(locked);
+        hotkey_manager.set_move_deadzone_frames(settings.persisted.move_deadzone_frames);
+        hotkey_manager.set_tick_interval(settings.tick_interval);
+        let auto_hide_timer = AutoHideTimer::new(settings.persisted.auto_hide_after, now);
+        let visible = settings.startup_visible();
+        // a hidden overlay is never in adjust mode
+        let adjust_mode = visible && settings.startup_adjust_mode();
+        let history = History::new(settings.adjustment());
+        Controller {
+            settings,
+            hotkey_manager,
+            adjust_mode,
+            magnifier_enabled: false,
+            visible,
+            click_through: true,
+            locked,
+            picker_cursor: (image::COLOR_PICKER_SIZE / 2, image::COLOR_PICKER_SIZE / 2),
+            eyedropper: false,
+            known_monitors: Vec::new(),
+            dirty: Dirty::default(),
+            auto_hide_timer,
+            history,
+            effects: Vec::new(),
+        }
+    }
+
+    /// Everything the app needs to do that the controller couldn't, in the order it came up
+    pub fn take_effects(&mut self) -> Vec<Effect> {
+        mem::take(&mut self.effects)
+    }
+
+    /// Handle one tick worth of hotkeys. The keyboard must already have been polled.
+    pub fn on_tick(&mut self, now: Instant) {
+        self.hotkey_manager.process_keys();
+
+        // this gates every other hotkey check below, so it has to happen first
+        if self.hotkey_manager.toggle_hotkeys() {
+            let hotkeys_enabled = !self.settings.persisted.hotkeys_enabled;
+            self.set_hotkeys_enabled(hotkeys_enabled);
+            self.show_osd(
+                if hotkeys_enabled {
+                    "HOTKEYS ON"
+                } else {
+                    "HOTKEYS OFF"
+                },
+                now,
+            );
+        }
+
+        // the lock also gates most of what's below
+        if self.hotkey_manager.toggle_lock() {
+            let locked = !self.locked;
+            self.set_locked(locked);
+            self.show_osd(if locked { "LOCKED" } else { "UNLOCKED" }, now);
+        }
+
+        let adjust_mode = self.adjust_mode;
+        // while the color picker is open the movement keys belong to its cursor, not the window
+        let pick_color = self.settings.get_pick_color();
+        if pick_color {
+            self.update_picker_cursor(now);
+        }
+
+        if adjust_mode {
+            let hotkeys = &self.hotkey_manager;
+            if !pick_color && hotkeys.move_up() != 0 {
+                self.settings.persisted.window_dy -= hotkeys.move_up() as i32;
+                self.dirty.window_position = true;
+            }
+
+            if !pick_color && hotkeys.move_down() != 0 {
+                self.settings.persisted.window_dy += hotkeys.move_down() as i32;
+                self.dirty.window_position = true;
+            }
+
+            if !pick_color && hotkeys.move_left() != 0 {
+                self.settings.persisted.window_dx -= hotkeys.move_left() as i32;
+                self.dirty.window_position = true;
+            }
+
+            if !pick_color && hotkeys.move_right() != 0 {
+                self.settings.persisted.window_dx += hotkeys.move_right() as i32;
+                self.dirty.window_position = true;
+            }
+
+            // monitor cycling makes no sense when windows are pinned to specific monitors
+            if self.hotkey_manager.cycle_monitor()
+                && self.settings.persisted.monitors.is_empty()
+                && !self.known_monitors.is_empty()
+            {
+                let current = self.settings.layout_monitor_index(&self.known_monitors);
+                let monitor_index = (current + 1) % self.known_monitors.len();
+                self.select_monitor(monitor_index);
+                self.show_osd(format!("MONITOR {}", monitor_index + 1), now);
+            }
+
+            if self.settings.is_scalable() && self.hotkey_manager.scale_increase() != 0 {
+                self.settings.scale_up(self.hotkey_manager.scale_increase());
+                self.dirty.window_scale = true;
+            }
+
+            if self.settings.is_scalable() && self.hotkey_manager.scale_decrease() != 0 {
+                self.settings
+                    .scale_down(self.hotkey_manager.scale_decrease());
+                self.dirty.window_scale = true;
+            }
+
+            if self.settings.is_scalable() && self.hotkey_manager.scale_increase_fixed() {
+                self.settings
+                    .scale_up(self.settings.persisted.fixed_scale_step);
+                self.dirty.window_scale = true;
+            }
+
+            if self.settings.is_scalable() && self.hotkey_manager.scale_decrease_fixed() {
+                self.settings
+                    .scale_down(self.settings.persisted.fixed_scale_step);
+                self.dirty.window_scale = true;
+            }
+
+            // these only do anything while the aspect ratio is unlocked
+            let step = self.settings.persisted.fixed_scale_step;
+            if self.hotkey_manager.width_increase() && self.settings.resize_width(true, step) {
+                self.dirty.window_scale = true;
+            }
+
+            if self.hotkey_manager.width_decrease() && self.settings.resize_width(false, step) {
+                self.dirty.window_scale = true;
+            }
+
+            if self.hotkey_manager.height_increase() && self.settings.resize_height(true, step) {
+                self.dirty.window_scale = true;
+            }
+
+            if self.hotkey_manager.height_decrease() && self.settings.resize_height(false, step) {
+                self.dirty.window_scale = true;
+            }
+
+            if self.hotkey_manager.toggle_magnifier() && platform::SCREEN_CAPTURE_SUPPORTED {
+                let magnifier = !self.magnifier_enabled;
+                self.magnifier_enabled = magnifier;
+                self.effects.push(Effect::SyncTray);
+                self.show_osd(
+                    if magnifier {
+                        "MAGNIFIER ON"
+                    } else {
+                        "MAGNIFIER OFF"
+                    },
+                    now,
+                );
+            }
+
+            if self.hotkey_manager.toggle_adjust() {
+                self.set_adjust_mode(false);
+                self.show_osd("ADJUST OFF", now);
+            }
+        } else if self.hotkey_manager.toggle_adjust() {
+            self.set_adjust_mode(true);
+            self.show_osd("ADJUST ON", now);
+        }
+
+        if adjust_mode {
+            // the user is actively fiddling with the overlay, so don't hide it out from under them
+            self.auto_hide_timer.reset(now);
+        }
+
+        if self.hotkey_manager.toggle_hidden() {
+            self.set_visible(!self.visible);
+            self.settings.remember_visible(self.visible);
+            self.auto_hide_timer.reset(now);
+        } else if self.visible && self.auto_hide_timer.tick(now) {
+            debug_println!("auto-hiding overlay");
+            self.set_visible(false);
+        }
+        self.tick_osd(now);
+        // both have to advance every tick, so no short-circuiting here
+        let animated = self.settings.advance_animation(now);
+        let color_cycled = self.settings.advance_color_cycle();
+        if animated || color_cycled {
+            self.dirty.force_redraw = true;
+            self.dirty.request_redraw = true;
+        }
+
+        // only enable this hotkey if the color picker is already visible OR if adjust mode is on
+        if self.hotkey_manager.toggle_color_picker()
+            && (adjust_mode || self.settings.get_pick_color())
+        {
+            let color_pick = self.settings.toggle_pick_color();
+            self.effects.push(Effect::ColorPick {
+                enabled: color_pick,
+                hotkey: true,
+            });
+            self.effects.push(Effect::SyncTray);
+            self.dirty.window_scale = true;
+        }
+
+        // a save binding usually contains the matching load binding, so saving takes priority
+        if let Some(slot) = self.hotkey_manager.save_slot() {
+            debug_println!("saving slot {slot}");
+            match self.settings.save_slot(slot) {
+                Ok(()) => self.show_osd(format!("SAVED SLOT {slot}"), now),
+                Err(e) => dialog::show_warning(format!(
+                    "Error saving slot {slot} to \"{}\".\n\n{}",
+                    Settings::slot_path(slot).display(),
+                    e
+                )),
+            }
+        } else if let Some(slot) = self.hotkey_manager.load_slot() {
+            debug_println!("loading slot {slot}");
+            let result = self.settings.load_slot(slot);
+            self.on_slot_loaded(slot, result, now);
+        }
+
+        if self.hotkey_manager.toggle_image() {
+            self.toggle_image(now);
+        }
+
+        let switch_profile = if self.hotkey_manager.next_profile() {
+            Some(true)
+        } else if self.hotkey_manager.prev_profile() {
+            Some(false)
+        } else {
+            None
+        };
+        if let Some(profile) =
+            switch_profile.and_then(|forward| self.settings.switch_profile(forward))
+        {
+            debug_println!("switched to profile {profile}");
+            self.dirty.force_redraw = true;
+            self.dirty.window_scale = true;
+            self.effects.push(Effect::SyncTray);
+            self.show_osd(format!("PROFILE {profile}"), now);
+        }
+
+        if self.hotkey_manager.opacity_increase() {
+            self.change_opacity(true, now);
+        } else if self.hotkey_manager.opacity_decrease() {
+            self.change_opacity(false, now);
+        }
+
+        if self.hotkey_manager.undo() {
+            self.step_history(true, now);
+        } else if self.hotkey_manager.redo() {
+            self.step_history(false, now);
+        }
+
+        if self.hotkey_manager.fps_increase() {
+            self.change_fps(true, now);
+        } else if self.hotkey_manager.fps_decrease() {
+            self.change_fps(false, now);
+        }
+
+        if self.hotkey_manager.toggle_window_level() {
+            self.toggle_window_level(now);
+        }
+
+        if self.hotkey_manager.toggle_clickthrough() {
+            self.click_through = !self.click_through;
+            self.show_osd(
+                if self.click_through {
+                    "CLICK-THROUGH ON"
+                } else {
+                    "CLICK-THROUGH OFF"
+                },
+                now,
+            );
+        }
+
+        // handy outside adjust mode too, for when the crosshair got bumped off-center
+        if self.hotkey_manager.reset_position() {
+            self.settings.reset_position();
+            self.dirty.window_position = true;
+            self.show_osd("POSITION RESET", now);
+        }
+
+        // re-read the image from disk, e.g. after saving changes to it in an image editor
+        if self.hotkey_manager.reload_image() {
+            let result = self.settings.reload_last_image();
+            let reloaded = result.is_ok();
+            self.on_image_loaded(result);
+            if reloaded {
+                self.show_osd("IMAGE RELOADED", now);
+            }
+        }
+
+        if self.hotkey_manager.spotlight_changed() {
+            self.settings.spotlight = self.hotkey_manager.spotlight();
+            self.dirty.force_redraw = true;
+            self.dirty.request_redraw = true;
+        }
+
+        self.update_magnifier_visibility();
+    }
+
+    /// Handle a click on one of the tray items the controller knows about
+    pub fn on_menu(&mut self, action: MenuAction, now: Instant) {
+        match action {
+            MenuAction::Visible(visible) => {
+                self.set_visible(visible);
+                self.settings.remember_visible(visible);
+                self.auto_hide_timer.reset(now);
+            }
+            MenuAction::Adjust(adjust_mode) => self.set_adjust_mode(adjust_mode),
+            MenuAction::PickColor(pick_color) => {
+                self.settings.set_pick_color(pick_color);
+                self.effects.push(Effect::ColorPick {
+                    enabled: pick_color,
+                    hotkey: false,
+                });
+                self.dirty.window_scale = true;
+            }
+            MenuAction::Magnifier(magnifier) => {
+                self.magnifier_enabled = magnifier;
+                self.update_magnifier_visibility();
+            }
+            MenuAction::HotkeysEnabled(hotkeys_enabled) => {
+                self.set_hotkeys_enabled(hotkeys_enabled)
+            }
+            MenuAction::Locked(locked) => self.set_locked(locked),
+            MenuAction::Eyedropper(eyedropper) => self.eyedropper = eyedropper,
+            MenuAction::ToggleImage => self.toggle_image(now),
+            MenuAction::NextCrosshairMode => {
+                let mode = self.settings.persisted.crosshair_mode.next_generated();
+                self.settings.set_crosshair_mode(mode);
+                self.dirty.force_redraw = true;
+                self.dirty.window_scale = true;
+                self.show_osd(format!("{mode:?}").to_uppercase(), now);
+            }
+            MenuAction::ReloadLastImage => {
+                let result = self.settings.reload_last_image();
+                self.on_image_loaded(result);
+            }
+            MenuAction::Reset => {
+                self.settings.reset();
+                self.dirty.force_redraw = true;
+                self.dirty.window_scale = true;
+                self.effects.push(Effect::SyncTray);
+            }
+            MenuAction::SelectMonitor(monitor_index) => self.select_monitor(monitor_index),
+            MenuAction::Snap(anchor, monitor_size) => {
+                self.settings.snap_to(anchor, monitor_size);
+                self.dirty.window_position = true;
+            }
+        }
+    }
+
+    /// Load a new image picked from the tray
+    pub fn load_image(&mut self, path: std::path::PathBuf) {
+        let result = self.settings.load_image(path);
+        self.on_image_loaded(result);
+    }
+
+    /// save what's currently being drawn to a PNG, and let the user know how that went
+    pub fn export_png(&self, path: std::path::PathBuf) {
+        match self.settings.export_png(&path) {
+            Ok(()) => dialog::show_info(format!("Exported PNG to \"{}\".", path.display())),
+            Err(e) => dialog::show_warning(format!("Error exporting PNG.\n\n{}", e)),
+        }
+    }
+
+    /// Set the crosshair color from a typed-in `AARRGGBB` hex string, or explain what's wrong with it
+    pub fn set_color_hex(&mut self, text: &str, now: Instant) {
+        match custom_serializer::parse_argb_hex(text) {
+            Ok(argb) => {
+                self.settings.set_color(image::Color::from_argb(argb));
+                self.dirty.force_redraw = true;
+                self.dirty.request_redraw = true;
+                self.show_osd("COLOR SET", now);
+            }
+            Err(e) => dialog::show_warning(format!("Error setting color.\n\n{e}")),
+        }
+    }
+
+    /// redraw everything for a freshly loaded 1-indexed slot, or explain why it couldn't be loaded
+    fn on_slot_loaded(&mut self, slot: usize, result: std::io::Result<()>, now: Instant) {
+        match result {
+            Ok(()) => {
+                self.dirty.force_redraw = true;
+                self.dirty.window_scale = true;
+                // a slot can have a different fps
+                self.hotkey_manager
+                    .set_tick_interval(self.settings.tick_interval);
+                self.effects.push(Effect::TickIntervalChanged);
+                self.effects.push(Effect::SyncTray);
+                self.effects.push(Effect::WindowLevelChanged);
+                self.show_osd(format!("LOADED SLOT {slot}"), now);
+            }
+            Err(e) => dialog::show_warning(format!(
+                "Error loading slot {slot} from \"{}\".\n\n{}",
+                Settings::slot_path(slot).display(),
+                e
+            )),
+        }
+    }
+
+    /// resize and redraw for a freshly loaded image, or explain why it couldn't be loaded
+    fn on_image_loaded(&mut self, result: std::io::Result<()>) {
+        match result {
+            Ok(()) => {
+                self.dirty.force_redraw = true;
+                self.dirty.window_scale = true;
+                self.effects.push(Effect::SyncTray);
+            }
+            Err(e) => dialog::show_warning(format!("Error loading image.\n\n{}", e)),
+        }
+    }
+
+    /// Set the crosshair color from a pixel of the color picker, then close the picker
+    pub fn pick_color(&mut self, x: usize, y: usize, now: Instant) {
+        self.settings
+            .set_color(image::hue_alpha_color_from_coordinates(
+                x,
+                y,
+                self.settings.picker_saturation,
+            ));
+        self.effects.push(Effect::ColorPick {
+            enabled: false,
+            hotkey: false,
+        });
+        self.effects.push(Effect::SyncTray);
+        self.dirty.window_scale = true;
+        self.show_osd("COLOR PICKED", now);
+    }
+
+    /// Set the crosshair color from a pixel sampled off the screen by the eyedropper, then put the eyedropper away
+    pub fn pick_screen_color(&mut self, color: Color, now: Instant) {
+        self.settings.set_color(color);
+        self.eyedropper = false;
+        self.effects.push(Effect::SyncTray);
+        self.dirty.force_redraw = true;
+        self.dirty.window_scale = true;
+        self.show_osd("COLOR PICKED", now);
+    }
+
+    pub fn set_picker_cursor(&mut self, cursor: (usize, usize)) {
+        if cursor != self.picker_cursor {
+            self.picker_cursor = cursor;
+            self.dirty.force_redraw = true;
+            self.dirty.request_redraw = true;
+        }
+    }
+
+    /// Scroll the color picker's saturation up or down a notch
+    pub fn scroll_picker_saturation(&mut self, increase: bool, now: Instant) {
+        const SATURATION_STEP: u8 = 16;
+        let saturation = self.settings.picker_saturation;
+        self.settings.picker_saturation = if increase {
+            saturation.saturating_add(SATURATION_STEP)
+        } else {
+            saturation.saturating_sub(SATURATION_STEP)
+        };
+        if self.settings.picker_saturation != saturation {
+            self.dirty.force_redraw = true;
+            self.dirty.request_redraw = true;
+            let percent = u32::from(self.settings.picker_saturation) * 100 / u32::from(u8::MAX);
+            self.show_osd(format!("SATURATION {percent}%"), now);
+        }
+    }
+
+    /// Switch the overlay to a different 0-indexed monitor
+    pub fn select_monitor(&mut self, monitor_index: usize) {
+        self.settings.set_monitor_index(monitor_index);
+        self.settings.remember_monitor_name(&self.known_monitors);
+        self.settings.remember_monitor_layout(&self.known_monitors);
+        if let Some(monitor) = self.known_monitors.get(monitor_index) {
+            self.settings.monitor_size = monitor.size;
+        }
+        self.effects.push(Effect::MonitorSelected(monitor_index));
+        self.dirty.window_scale = true;
+    }
+
+    /// `true` if something happened this tick, or the user is in the middle of something. Ticking only slows down
+    /// while this stays `false`.
+    pub fn is_active(&self) -> bool {
+        self.dirty != Dirty::default()
+            || self.hotkey_manager.any_key_held()
+            || self.adjust_mode
+            || self.settings.get_pick_color()
+            || self.eyedropper
+    }
+
+    /// The settings were changed from outside the controller, e.g. the settings window. Anything might be different now.
+    pub fn settings_changed(&mut self) {
+        self.dirty.force_redraw = true;
+        self.dirty.window_scale = true;
+        self.hotkey_manager
+            .set_tick_interval(self.settings.tick_interval);
+        self.effects.push(Effect::TickIntervalChanged);
+        self.effects.push(Effect::SyncTray);
+    }
+
+    /// Rebind the hotkey called `name` in the config to `combination`. If that fails, everything's left as it was.
+    pub fn set_key_binding(&mut self, name: &str, combination: Vec<Keycode>) -> Result<(), String> {
+        let key_bindings = self
+            .settings
+            .persisted
+            .key_bindings
+            .with_binding(name, combination)?;
+        self.hotkey_manager.set_key_bindings(&key_bindings)?;
+        self.settings.persisted.key_bindings = key_bindings;
+        Ok(())
+    }
+
+    fn set_adjust_mode(&mut self, adjust_mode: bool) {
+        self.adjust_mode = adjust_mode;
+        self.effects.push(Effect::SyncTray);
+        self.update_magnifier_visibility();
+    }
+
+    fn set_hotkeys_enabled(&mut self, hotkeys_enabled: bool) {
+        self.settings.persisted.hotkeys_enabled = hotkeys_enabled;
+        self.hotkey_manager.set_enabled(hotkeys_enabled);
+        self.effects.push(Effect::SyncTray);
+    }
+
+    fn set_locked(&mut self, locked: bool) {
+        self.locked = locked;
+        self.settings.remember_locked(locked);
+        self.hotkey_manager.set_locked(locked);
+        self.effects.push(Effect::SyncTray);
+    }
+
+    fn set_visible(&mut self, visible: bool) {
+        if needs_redraw_on_show(self.visible, visible) {
+            // the buffer age can't be trusted across a hide and show, which sometimes left a stale or blank frame up
+            self.dirty.force_redraw = true;
+        }
+        self.visible = visible;
+        self.effects.push(Effect::SetVisible(visible));
+        if visible {
+            self.dirty.request_redraw = true;
+            self.effects.push(Effect::SyncTray);
+        } else {
+            self.set_adjust_mode(false);
+        }
+    }
+
+    /// Step the overall opacity and show it on the OSD
+    fn change_opacity(&mut self, increase: bool, now: Instant) {
+        if self.settings.change_opacity(increase) {
+            self.dirty.force_redraw = true;
+            self.dirty.request_redraw = true;
+            self.show_osd(format!("OPACITY {}", self.settings.persisted.opacity), now);
+        }
+    }
+
+    /// Record the crosshair's current state as an undo step if it changed. This waits until every key is released, so
+    /// holding an arrow key down only makes one step.
+    pub fn commit_history(&mut self) {
+        if !self.hotkey_manager.any_key_held() {
+            self.history.commit(self.settings.adjustment());
+        }
+    }
+
+    fn step_history(&mut self, undo: bool, now: Instant) {
+        // whatever was done since the last commit is the first thing to undo
+        self.history.commit(self.settings.adjustment());
+        let restored = if undo {
+            self.history.undo()
+        } else {
+            self.history.redo()
+        };
+        if let Some(adjustment) = restored {
+            self.settings.apply_adjustment(adjustment);
+            self.dirty.force_redraw = true;
+            self.dirty.window_scale = true;
+            self.show_osd(if undo { "UNDO" } else { "REDO" }, now);
+        }
+    }
+
+    fn change_fps(&mut self, increase: bool, now: Instant) {
+        if self.settings.change_fps(increase) {
+            self.hotkey_manager
+                .set_tick_interval(self.settings.tick_interval);
+            self.effects.push(Effect::TickIntervalChanged);
+            self.show_osd(format!("FPS {}", self.settings.fps()), now);
+        }
+    }
+
+    fn toggle_window_level(&mut self, now: Instant) {
+        let window_level = self.settings.persisted.window_level.toggled();
+        self.settings.persisted.window_level = window_level;
+        self.effects.push(Effect::WindowLevelChanged);
+        self.show_osd(
+            match window_level {
+                OverlayLevel::AlwaysOnTop => "ALWAYS ON TOP",
+                OverlayLevel::Normal => "NOT ON TOP",
+            },
+            now,
+        );
+    }
+
+    /// Flip between the loaded image and the generated crosshair
+    fn toggle_image(&mut self, now: Instant) {
+        if self.settings.toggle_image() {
+            self.dirty.force_redraw = true;
+            self.dirty.window_scale = true;
+            self.show_osd(
+                if self.settings.image_shown() {
+                    "IMAGE ON"
+                } else {
+                    "IMAGE OFF"
+                },
+                now,
+            );
+        }
+        // a tray click toggles the check mark even when nothing changed, so always put it back
+        self.effects.push(Effect::SyncTray);
+    }
+
+    /// the magnifier only shows up in adjust mode
+    fn update_magnifier_visibility(&mut self) {
+        let magnifier = self.adjust_mode && self.magnifier_enabled;
+        if magnifier != self.settings.magnifier {
+            self.settings.magnifier = magnifier;
+            self.dirty.force_redraw = true;
+            self.dirty.window_scale = true;
+        }
+    }
+
+    /// Move the color picker's keyboard cursor with the movement hotkeys, and pick its color on confirm
+    fn update_picker_cursor(&mut self, now: Instant) {
+        let hotkeys = &self.hotkey_manager;
+        let dx = hotkeys.move_right() as i32 - hotkeys.move_left() as i32;
+        let dy = hotkeys.move_down() as i32 - hotkeys.move_up() as i32;
+        self.set_picker_cursor(image::move_picker_cursor(self.picker_cursor, dx, dy));
+
+        if self.hotkey_manager.confirm_color() {
+            let (x, y) = self.picker_cursor;
+            self.pick_color(x, y, now);
+        }
+    }
+
+    /// Flash a message under the crosshair, if the on-screen display is enabled
+    fn show_osd(&mut self, message: impl Into<String>, now: Instant) {
+        if self.settings.persisted.osd {
+            self.settings.osd.show(message, now);
+            self.dirty.force_redraw = true;
+            self.dirty.window_scale = true;
+        }
+    }
+
+    /// Fade out the on-screen display, and shrink the window back down once it's gone
+    fn tick_osd(&mut self, now: Instant) {
+        if self.settings.osd.tick(now) {
+            self.dirty.force_redraw = true;
+            self.dirty.request_redraw = true;
+            if self.settings.osd.message().is_none() {
+                self.dirty.window_scale = true;
+            }
+        }
+    }
+}
+
+/// `true` if going from `was_visible` to `visible` shows the overlay, which always needs a full redraw
+fn needs_redraw_on_show(was_visible: bool, visible: bool) -> bool {
+    !was_visible && visible
+}
+
+#[cfg(test)]
+mod test_visibility {
+    use super::*;
+
+    #[test]
+    fn test_show_forces_redraw() {
+        assert!(needs_redraw_on_show(false, true));
+    }
+
+    #[test]
+    fn test_other_transitions_dont() {
+        assert!(!needs_redraw_on_show(true, true));
+        assert!(!needs_redraw_on_show(true, false));
+        assert!(!needs_redraw_on_show(false, false));
+    }
+}
+
+#[cfg(test)]
+mod test_scenarios {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::private::hotkey::test_keyboard::{TestHotkeyManager, TestKeyboardState};
+    use crate::private::hotkey::{KeyBindings, Keycode};
+    use crate::private::settings::RenderMode;
+    use crate::private::util::image::Color;
+    use winit::dpi::PhysicalPosition;
+
+    const FRAME: Duration = Duration::from_millis(16);
+    const ADJUST: [Keycode; 2] = [Keycode::LControl, Keycode::J];
+
+    /// a controller driven by a fake keyboard and a fake clock
+    struct Harness {
+        controller: Controller<TestKeyboardState, Keycode>,
+        now: Instant,
+    }
+
+    impl Harness {
+        fn new() -> Self {
+            let now = Instant::now();
+            let hotkey_manager = TestHotkeyManager::new_test(&KeyBindings::default());
+            Harness {
+                controller: Controller::new(Settings::default(), hotkey_manager, now),
+                now,
+            }
+        }
+
+        /// simulate one tick with the given keys held
+        fn tick(&mut self, pressed: &[Keycode]) {
+            self.now += FRAME;
+            self.controller.hotkey_manager.set_pressed(pressed);
+            self.controller.on_tick(self.now);
+        }
+
+        /// press and release a key combination
+        fn tap(&mut self, pressed: &[Keycode]) {
+            self.tick(pressed);
+            self.tick(&[]);
+        }
+
+        fn offset(&self) -> (i32, i32) {
+            let persisted = &self.controller.settings.persisted;
+            (persisted.window_dx, persisted.window_dy)
+        }
+    }
+
+    #[test]
+    fn test_adjust_and_move() {
+        let mut harness = Harness::new();
+        harness.tap(&ADJUST);
+        assert!(harness.controller.adjust_mode);
+        assert!(harness
+            .controller
+            .take_effects()
+            .contains(&Effect::SyncTray));
+
+        // holding a movement key moves one pixel, then pauses before repeating
+        for _ in 0..5 {
+            harness.tick(&[Keycode::Up]);
+        }
+        assert_eq!(harness.offset(), (0, -1));
+        assert!(harness.controller.dirty.window_position);
+        harness.tick(&[]);
+
+        for _ in 0..5 {
+            harness.tap(&[Keycode::Right]);
+        }
+        assert_eq!(harness.offset(), (5, -1));
+    }
+
+    /// a slot with its own key bindings loads its crosshair, but the running hotkeys stay as they were
+    #[test]
+    fn test_load_slot_keeps_bindings() {
+        let mut slot = Settings::default();
+        slot.set_color(Color::from_argb(0xFF00FF00));
+        let toml = slot.to_toml().replace("up = [\"Up\"]", "up = [\"W\"]");
+        assert!(toml.contains("up = [\"W\"]"));
+        let mut path = std::env::temp_dir();
+        path.push("DELETEME_simple-crosshair-overlay-test-controller-slot.toml");
+        std::fs::write(&path, toml).expect("write failed");
+
+        let mut harness = Harness::new();
+        harness.tap(&ADJUST);
+        let result = harness.controller.settings.load_slot_from_path(&path);
+        std::fs::remove_file(&path).expect("cleanup failed");
+        let now = harness.now;
+        harness.controller.on_slot_loaded(1, result, now);
+        assert_eq!(
+            harness.controller.settings.straight_color(),
+            Color::from_argb(0xFF00FF00)
+        );
+
+        // the manager still answers to the old binding, and the settings agree with it
+        harness.tap(&[Keycode::W]);
+        assert_eq!(harness.offset(), (0, 0));
+        harness.tap(&[Keycode::Up]);
+        assert_eq!(harness.offset(), (0, -1));
+        assert!(harness
+            .controller
+            .settings
+            .to_toml()
+            .contains("up = [\"Up\"]"));
+    }
+
+    #[test]
+    fn test_set_color_hex() {
+        let mut harness = Harness::new();
+        let now = harness.now;
+        harness.controller.set_color_hex("#80FF00FF", now);
+        assert_eq!(
+            harness.controller.settings.straight_color(),
+            Color::from_argb(0x80FF00FF)
+        );
+        assert!(harness.controller.dirty.request_redraw);
+
+        // bad input leaves the color alone
+        harness.controller.set_color_hex("FF00FF", now);
+        assert_eq!(
+            harness.controller.settings.straight_color(),
+            Color::from_argb(0x80FF00FF)
+        );
+    }
+
+    #[test]
+    fn test_movement_needs_adjust_mode() {
+        let mut harness = Harness::new();
+        harness.tap(&[Keycode::Up]);
+        harness.tap(&[Keycode::PageUp]);
+        assert_eq!(harness.offset(), (0, 0));
+        assert_eq!(harness.controller.dirty, Dirty::default());
+    }
+
+    #[test]
+    fn test_hiding_leaves_adjust_mode() {
+        let mut harness = Harness::new();
+        harness.tap(&ADJUST);
+        harness.controller.take_effects();
+
+        harness.tap(&[Keycode::LControl, Keycode::H]);
+        assert!(!harness.controller.visible);
+        assert!(!harness.controller.adjust_mode);
+        assert!(!harness.controller.settings.startup_visible());
+        assert!(harness
+            .controller
+            .take_effects()
+            .contains(&Effect::SetVisible(false)));
+
+        // showing it again needs a full redraw
+        harness.tap(&[Keycode::LControl, Keycode::H]);
+        assert!(harness.controller.visible);
+        assert!(harness.controller.settings.startup_visible());
+        assert!(harness.controller.dirty.force_redraw);
+        assert!(harness.controller.dirty.request_redraw);
+    }
+
+    #[test]
+    fn test_disabled_hotkeys() {
+        let mut harness = Harness::new();
+        harness.tap(&[Keycode::LControl, Keycode::P]);
+        assert!(!harness.controller.settings.persisted.hotkeys_enabled);
+
+        harness.tap(&ADJUST);
+        assert!(!harness.controller.adjust_mode);
+
+        harness.tap(&[Keycode::LControl, Keycode::P]);
+        harness.tap(&ADJUST);
+        assert!(harness.controller.adjust_mode);
+    }
+
+    #[test]
+    fn test_lock() {
+        let mut harness = Harness::new();
+        harness.tap(&ADJUST);
+        harness
+            .controller
+            .on_menu(MenuAction::Locked(true), harness.now);
+        assert!(harness.controller.locked);
+
+        harness.tap(&[Keycode::Up]);
+        harness.tap(&[Keycode::PageUp]);
+        harness.tap(&ADJUST);
+        assert_eq!(harness.offset(), (0, 0));
+        assert!(harness.controller.adjust_mode);
+
+        // hiding still works
+        harness.tap(&[Keycode::LControl, Keycode::H]);
+        assert!(!harness.controller.visible);
+
+        harness
+            .controller
+            .on_menu(MenuAction::Locked(false), harness.now);
+        harness.tap(&[Keycode::LControl, Keycode::H]);
+        harness.tap(&ADJUST);
+        harness.tap(&[Keycode::Up]);
+        assert_eq!(harness.offset(), (0, -1));
+    }
+
+    #[test]
+    fn test_cycle_monitor() {
+        let mut harness = Harness::new();
+        let monitor = |x: i32, height: u32| MonitorInfo {
+            name: None,
+            position: PhysicalPosition::new(x, 0),
+            size: PhysicalSize::new(1920, height),
+        };
+        harness.controller.known_monitors = vec![monitor(0, 1080), monitor(1920, 1200)];
+        harness.tap(&ADJUST);
+        harness.controller.take_effects();
+
+        harness.tap(&[Keycode::LControl, Keycode::M]);
+        assert_eq!(harness.controller.settings.monitor_index, 1);
+        assert_eq!(
+            harness.controller.settings.monitor_size,
+            PhysicalSize::new(1920, 1200)
+        );
+        assert!(harness
+            .controller
+            .take_effects()
+            .contains(&Effect::MonitorSelected(1)));
+
+        harness.tap(&[Keycode::LControl, Keycode::M]);
+        assert_eq!(harness.controller.settings.monitor_index, 0);
+    }
+
+    /// open the picker with a hotkey, move its cursor with the arrows, and pick with Enter
+    #[test]
+    fn test_pick_color_with_keyboard() {
+        let mut harness = Harness::new();
+        harness.tap(&ADJUST);
+        harness.tap(&[Keycode::LControl, Keycode::K]);
+        assert_eq!(
+            harness.controller.settings.render_mode,
+            RenderMode::ColorPicker
+        );
+        assert!(harness
+            .controller
+            .take_effects()
+            .contains(&Effect::ColorPick {
+                enabled: true,
+                hotkey: true
+            }));
+
+        let cursor = harness.controller.picker_cursor;
+        harness.tap(&[Keycode::Left]);
+        assert_eq!(harness.controller.picker_cursor, (cursor.0 - 1, cursor.1));
+        // the arrows belong to the picker, so the window stays put
+        assert_eq!(harness.offset(), (0, 0));
+
+        let color = harness.controller.settings.color;
+        harness.tap(&[Keycode::Enter]);
+        assert_eq!(
+            harness.controller.settings.render_mode,
+            RenderMode::Crosshair
+        );
+        assert_ne!(harness.controller.settings.color, color);
+        assert!(harness
+            .controller
+            .take_effects()
+            .contains(&Effect::ColorPick {
+                enabled: false,
+                hotkey: false
+            }));
+    }
+
+    #[test]
+    fn test_picker_saturation() {
+        let mut harness = Harness::new();
+        let now = harness.now;
+        harness.controller.scroll_picker_saturation(true, now);
+        assert_eq!(harness.controller.settings.picker_saturation, u8::MAX);
+        assert_eq!(harness.controller.dirty, Dirty::default());
+
+        for _ in 0..20 {
+            harness.controller.scroll_picker_saturation(false, now);
+        }
+        assert_eq!(harness.controller.settings.picker_saturation, 0);
+        assert!(harness.controller.dirty.force_redraw);
+
+        // a desaturated pick comes out gray
+        harness.controller.pick_color(0, 0, now);
+        assert_eq!(
+            harness.controller.settings.straight_color(),
+            Color::from_argb(0xFFFFFFFF)
+        );
+    }
+
+    #[test]
+    fn test_eyedropper() {
+        let mut harness = Harness::new();
+        let now = harness.now;
+        harness.controller.dirty = Dirty::default();
+        harness
+            .controller
+            .on_menu(MenuAction::Eyedropper(true), now);
+        assert!(harness.controller.eyedropper);
+        assert!(harness.controller.is_active());
+
+        harness
+            .controller
+            .pick_screen_color(Color::from_rgb(0x12, 0x34, 0x56), now);
+        assert!(!harness.controller.eyedropper);
+        assert_eq!(
+            harness.controller.settings.straight_color(),
+            Color::from_argb(0xFF123456)
+        );
+        assert_eq!(
+            harness.controller.settings.color,
+            Color::from_argb(0xFF123456)
+        );
+        assert!(harness.controller.dirty.force_redraw);
+        assert!(harness
+            .controller
+            .take_effects()
+            .contains(&Effect::SyncTray));
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut harness = Harness::new();
+        let now = harness.now;
+        harness.tap(&ADJUST);
+        harness.tap(&[Keycode::Right]);
+        harness.controller.commit_history();
+        harness.tap(&[Keycode::Right]);
+        harness.controller.commit_history();
+        assert_eq!(harness.offset(), (2, 0));
+
+        harness.controller.dirty = Dirty::default();
+        harness.controller.step_history(true, now);
+        assert_eq!(harness.offset(), (1, 0));
+        assert!(harness.controller.dirty.window_scale);
+        harness.controller.step_history(true, now);
+        assert_eq!(harness.offset(), (0, 0));
+        // nothing further back
+        harness.controller.step_history(true, now);
+        assert_eq!(harness.offset(), (0, 0));
+
+        harness.controller.step_history(false, now);
+        assert_eq!(harness.offset(), (1, 0));
+    }
+
+    /// a change that was never committed still gets undone, and holding a key doesn't commit partway through
+    #[test]
+    fn test_undo_uncommitted() {
+        let mut harness = Harness::new();
+        let now = harness.now;
+        harness.tap(&ADJUST);
+        harness
+            .controller
+            .hotkey_manager
+            .set_pressed(&[Keycode::Down]);
+        harness.controller.on_tick(now);
+        harness.controller.commit_history();
+        harness.tick(&[]);
+        assert_eq!(harness.offset(), (0, 1));
+
+        harness.controller.step_history(true, now);
+        assert_eq!(harness.offset(), (0, 0));
+    }
+
+    #[test]
+    fn test_menu_actions() {
+        let mut harness = Harness::new();
+        let now = harness.now;
+        harness.controller.on_menu(
+            MenuAction::Snap(SnapAnchor::Top, PhysicalSize::new(1920, 1080)),
+            now,
+        );
+        assert_ne!(harness.offset(), (0, 0));
+        assert!(harness.controller.dirty.window_position);
+
+        harness
+            .controller
+            .settings
+            .set_color(Color::from_argb(0xFF00FF00));
+        harness.controller.on_menu(MenuAction::Reset, now);
+        assert_eq!(harness.offset(), (0, 0));
+        assert_ne!(
+            harness.controller.settings.color,
+            Color::from_argb(0xFF00FF00)
+        );
+
+        harness
+            .controller
+            .on_menu(MenuAction::NextCrosshairMode, now);
+        assert_eq!(
+            harness.controller.settings.render_mode,
+            RenderMode::Crosshair.next_generated()
+        );
+        assert!(harness.controller.dirty.window_scale);
+
+        // the magnifier waits for adjust mode
+        harness.controller.on_menu(MenuAction::Magnifier(true), now);
+        assert!(!harness.controller.settings.magnifier);
+        harness.controller.on_menu(MenuAction::Adjust(true), now);
+        assert!(harness.controller.settings.magnifier);
+    }
+
+    #[test]
+    fn test_change_fps() {
+        let mut harness = Harness::new();
+        harness.controller.take_effects();
+        let now = harness.now;
+
+        harness.controller.change_fps(true, now);
+        assert_eq!(harness.controller.settings.fps(), 70);
+        assert_eq!(
+            harness.controller.settings.tick_interval,
+            Duration::from_millis(15)
+        );
+        assert!(harness
+            .controller
+            .take_effects()
+            .contains(&Effect::TickIntervalChanged));
+
+        // already at the bottom, so nothing to tell the tick sender
+        harness.controller.settings.set_fps(1);
+        harness.controller.change_fps(false, now);
+        assert_eq!(harness.controller.settings.fps(), 1);
+        assert!(harness.controller.take_effects().is_empty());
+    }
+
+    #[test]
+    fn test_toggle_window_level() {
+        let mut harness = Harness::new();
+        harness.controller.take_effects();
+        let now = harness.now;
+
+        harness.controller.toggle_window_level(now);
+        assert_eq!(
+            harness.controller.settings.persisted.window_level,
+            OverlayLevel::Normal
+        );
+        assert!(harness
+            .controller
+            .take_effects()
+            .contains(&Effect::WindowLevelChanged));
+
+        harness.controller.toggle_window_level(now);
+        assert_eq!(
+            harness.controller.settings.persisted.window_level,
+            OverlayLevel::AlwaysOnTop
+        );
+    }
+
+    /// changes made behind the controller's back still reach the windows and the tick sender
+    #[test]
+    fn test_settings_changed() {
+        let mut harness = Harness::new();
+        harness.controller.take_effects();
+        harness.controller.dirty = Dirty::default();
+        harness.controller.settings.set_fps(30);
+        harness.controller.settings_changed();
+        assert!(harness.controller.dirty.window_scale);
+        assert!(harness.controller.dirty.force_redraw);
+        assert!(harness
+            .controller
+            .take_effects()
+            .contains(&Effect::TickIntervalChanged));
+    }
+
+    /// a keybinding changed from the settings window takes over right away and gets saved
+    #[test]
+    fn test_set_key_binding() {
+        let mut harness = Harness::new();
+        harness
+            .controller
+            .set_key_binding("toggle_adjust", vec![Keycode::F9])
+            .unwrap();
+        harness.tap(&ADJUST);
+        assert!(!harness.controller.adjust_mode);
+        harness.tap(&[Keycode::F9]);
+        assert!(harness.controller.adjust_mode);
+        assert!(harness
+            .controller
+            .settings
+            .to_toml()
+            .contains("toggle_adjust = [\"F9\"]"));
+
+        // a bad name changes nothing
+        assert!(harness
+            .controller
+            .set_key_binding("toggle_everything", vec![Keycode::F10])
+            .is_err());
+        harness.tap(&[Keycode::F9]);
+        assert!(!harness.controller.adjust_mode);
+    }
+}
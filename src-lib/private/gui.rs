@@ -0,0 +1,592 @@
+// This file is part of simple-crosshair-overlay and is licenced under the GNU GPL v3.0.
+// See LICENSE file for full text.
+// Copyright © 2023 Michael Ripley
+
+//! A tiny settings window for people who'd rather not edit the config file by hand.
+//!
+//! Everything is drawn with the on-screen display's bitmap font straight into a softbuffer, so this doesn't pull in a
+//! whole GUI toolkit. Each setting gets a row with a `-` and a `+` button. Clicking a keybinding records the next key
+//! combination pressed as its new binding, and Escape on its own cancels. The save and load slots are lists of
+//! bindings, so those are left to the config file.
+
+use winit::dpi::PhysicalSize;
+
+use crate::private::hotkey::Keycode;
+use crate::private::settings::{RenderMode, Settings};
+use crate::private::util::image::Color;
+use crate::private::util::osd;
+
+const WIDTH: u32 = 360;
+const MARGIN: u32 = 8;
+const ROW_HEIGHT: u32 = 18;
+const BUTTON_SIZE: u32 = 14;
+const BUTTON_GAP: u32 = 4;
+/// where the current value of a setting is drawn
+const VALUE_X: u32 = 176;
+/// how much one click changes a color channel. 17 clicks goes from 0 to 255 exactly.
+const COLOR_STEP: u8 = 15;
+
+const BACKGROUND_COLOR: Color = Color::from_argb(0xFF202020);
+const TEXT_COLOR: Color = Color::from_argb(0xFFE0E0E0);
+const HEADING_COLOR: Color = Color::from_argb(0xFF909090);
+const BUTTON_COLOR: Color = Color::from_argb(0xFF505050);
+const RECORDING_COLOR: Color = Color::from_argb(0xFFFFC040);
+
+/// keybindings that hold a list of bindings rather than a single one, so they can't be recorded here
+const SLOT_BINDINGS: [&str; 2] = ["save_slots", "load_slots"];
+
+/// A setting that can be changed from the settings window, in the order the rows are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Red,
+    Green,
+    Blue,
+    Alpha,
+    Size,
+    Shape,
+    Fps,
+}
+
+impl Field {
+    pub const ALL: [Field; 7] = [
+        Field::Red,
+        Field::Green,
+        Field::Blue,
+        Field::Alpha,
+        Field::Size,
+        Field::Shape,
+        Field::Fps,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Field::Red => "RED",
+            Field::Green => "GREEN",
+            Field::Blue => "BLUE",
+            Field::Alpha => "ALPHA",
+            Field::Size => "SIZE",
+            Field::Shape => "SHAPE",
+            Field::Fps => "FPS",
+        }
+    }
+
+    fn value(self, settings: &Settings) -> String {
+        let [red, green, blue, alpha] = settings.straight_color().to_rgba_bytes();
+        match self {
+            Field::Red => red.to_string(),
+            Field::Green => green.to_string(),
+            Field::Blue => blue.to_string(),
+            Field::Alpha => alpha.to_string(),
+            Field::Size => settings.crosshair_size().height.to_string(),
+            Field::Shape => format!("{:?}", settings.persisted.crosshair_mode),
+            Field::Fps => settings.fps().to_string(),
+        }
+    }
+}
+
+/// which of a row's two buttons was clicked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Decrease,
+    Increase,
+}
+
+impl Step {
+    fn apply_u8(self, value: u8, amount: u8) -> u8 {
+        match self {
+            Step::Decrease => value.saturating_sub(amount),
+            Step::Increase => value.saturating_add(amount),
+        }
+    }
+
+    fn apply_u32(self, value: u32, amount: u32) -> u32 {
+        match self {
+            Step::Decrease => value.saturating_sub(amount),
+            Step::Increase => value.saturating_add(amount),
+        }
+    }
+}
+
+/// What a click in the window landed on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Click {
+    Button(Field, Step),
+    /// a keybinding row, by the binding's name in the config
+    KeyBinding(String),
+}
+
+/// What happened to a [`KeyCapture`] on the latest frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaptureStep {
+    Recording,
+    Cancelled,
+    Done(Vec<Keycode>),
+}
+
+/// Records a new key combination for one keybinding. Everything pressed counts until all of it is let go again.
+#[derive(Debug)]
+pub struct KeyCapture {
+    name: String,
+    keys: Vec<Keycode>,
+    /// `false` until nothing is held, so the click that started recording doesn't end up in the binding
+    armed: bool,
+}
+
+impl KeyCapture {
+    pub fn new(name: String) -> Self {
+        KeyCapture {
+            name,
+            keys: Vec::new(),
+            armed: false,
+        }
+    }
+
+    /// the binding's name in the config
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Feed in the keys held this frame
+    pub fn update(&mut self, held: &[Keycode]) -> CaptureStep {
+        if !self.armed {
+            self.armed = held.is_empty();
+            return CaptureStep::Recording;
+        }
+        for &key in held {
+            if !self.keys.contains(&key) {
+                self.keys.push(key);
+            }
+        }
+        if !held.is_empty() || self.keys.is_empty() {
+            CaptureStep::Recording
+        } else if self.keys == [Keycode::Escape] {
+            CaptureStep::Cancelled
+        } else {
+            CaptureStep::Done(std::mem::take(&mut self.keys))
+        }
+    }
+}
+
+/// Keybinding names in the config and what they're bound to, in the order they're displayed
+fn key_binding_rows(settings: &Settings) -> Vec<(String, String)> {
+    let Ok(toml::Value::Table(table)) = toml::Value::try_from(&settings.persisted.key_bindings)
+    else {
+        return Vec::new();
+    };
+    table
+        .iter()
+        .map(|(name, binding)| (name.clone(), describe_binding(binding)))
+        .collect()
+}
+
+/// `["LControl", "J"]` becomes `LControl+J`, and lists of bindings are separated by commas
+fn describe_binding(binding: &toml::Value) -> String {
+    match binding {
+        toml::Value::Array(keys) if keys.is_empty() => "UNBOUND".to_string(),
+        toml::Value::Array(keys) if keys.iter().all(toml::Value::is_array) => keys
+            .iter()
+            .map(describe_binding)
+            .collect::<Vec<_>>()
+            .join(","),
+        toml::Value::Array(keys) => keys
+            .iter()
+            .map(|key| key.as_str().unwrap_or("?"))
+            .collect::<Vec<_>>()
+            .join("+"),
+        other => other.to_string(),
+    }
+}
+
+/// The window size needed to fit every row
+pub fn window_size(settings: &Settings) -> PhysicalSize<u32> {
+    // one row per field, a blank row, a heading, then the keybindings
+    let rows = first_key_binding_row() + key_binding_rows(settings).len();
+    PhysicalSize::new(WIDTH, MARGIN * 2 + ROW_HEIGHT * rows as u32)
+}
+
+fn row_top(row: usize) -> u32 {
+    MARGIN + ROW_HEIGHT * row as u32
+}
+
+fn button_x(step: Step) -> u32 {
+    match step {
+        Step::Decrease => WIDTH - MARGIN - BUTTON_SIZE * 2 - BUTTON_GAP,
+        Step::Increase => WIDTH - MARGIN - BUTTON_SIZE,
+    }
+}
+
+/// the first keybinding row, after the fields, a blank row, and the heading
+fn first_key_binding_row() -> usize {
+    Field::ALL.len() + 2
+}
+
+/// Figure out what, if anything, is under a click at `(x, y)`. Buttons only count right on them, but a keybinding
+/// can be clicked anywhere along its row.
+pub fn hit_test(x: f64, y: f64, settings: &Settings) -> Option<Click> {
+    if x < 0.0 || y < 0.0 {
+        return None;
+    }
+    let (x, y) = (x as u32, y as u32);
+    if x >= WIDTH {
+        return None;
+    }
+
+    if y >= row_top(first_key_binding_row()) {
+        let index = ((y - row_top(first_key_binding_row())) / ROW_HEIGHT) as usize;
+        let (name, _) = key_binding_rows(settings).into_iter().nth(index)?;
+        return (!SLOT_BINDINGS.contains(&name.as_str())).then_some(Click::KeyBinding(name));
+    }
+
+    let field = Field::ALL
+        .into_iter()
+        .enumerate()
+        .find_map(|(row, field)| {
+            let top = row_top(row) + (ROW_HEIGHT - BUTTON_SIZE) / 2;
+            (top..top + BUTTON_SIZE).contains(&y).then_some(field)
+        })?;
+    let step = [Step::Decrease, Step::Increase]
+        .into_iter()
+        .find(|&step| (button_x(step)..button_x(step) + BUTTON_SIZE).contains(&x))?;
+    Some(Click::Button(field, step))
+}
+
+/// nudge one RGBA channel of the crosshair color
+fn step_color_channel(settings: &mut Settings, channel: usize, step: Step) {
+    let mut color = settings.straight_color().to_rgba_bytes();
+    color[channel] = step.apply_u8(color[channel], COLOR_STEP);
+    settings.set_color(Color::from_rgba_bytes(color));
+}
+
+/// Change a setting in response to a button click
+pub fn apply(settings: &mut Settings, field: Field, step: Step) {
+    match field {
+        Field::Red => step_color_channel(settings, 0, step),
+        Field::Green => step_color_channel(settings, 1, step),
+        Field::Blue => step_color_channel(settings, 2, step),
+        Field::Alpha => step_color_channel(settings, 3, step),
+        Field::Size => {
+            if settings.is_scalable() {
+                let amount = settings.persisted.fixed_scale_step;
+                match step {
+                    Step::Decrease => settings.scale_down(amount),
+                    Step::Increase => settings.scale_up(amount),
+                }
+            } else if settings.image().is_none() {
+                // the round crosshairs are sized by their radius instead
+                let persisted = &mut settings.persisted;
+                match persisted.crosshair_mode {
+                    RenderMode::CircleDot => {
+                        persisted.ring_radius = step.apply_u32(persisted.ring_radius, 1).max(1)
+                    }
+                    RenderMode::Donut => {
+                        persisted.outer_radius = step.apply_u32(persisted.outer_radius, 1).max(1);
+                        persisted.inner_radius = persisted.inner_radius.min(persisted.outer_radius);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Field::Shape => {
+            let mode = settings.persisted.crosshair_mode;
+            settings.set_crosshair_mode(match step {
+                Step::Decrease => mode.previous_generated(),
+                Step::Increase => mode.next_generated(),
+            });
+        }
+        Field::Fps => {
+            settings.change_fps(step == Step::Increase);
+        }
+    }
+}
+
+fn fill_rect(buffer: &mut [u32], buffer_width: usize, x: u32, y: u32, size: u32, color: Color) {
+    let (x, y, size) = (x as usize, y as usize, size as usize);
+    for row in buffer.chunks_exact_mut(buffer_width).skip(y).take(size) {
+        let end = (x + size).min(buffer_width);
+        if x < end {
+            row[x..end].fill(color.argb());
+        }
+    }
+}
+
+/// Draw the whole window into a buffer of [`window_size`]. The keybinding being recorded, if any, is highlighted.
+pub fn draw(
+    buffer: &mut [u32],
+    buffer_width: usize,
+    settings: &Settings,
+    key_capture: Option<&KeyCapture>,
+) {
+    buffer.fill(BACKGROUND_COLOR.argb());
+    let text_offset = (ROW_HEIGHT - osd::text_size("0").height) / 2;
+
+    for (row, field) in Field::ALL.into_iter().enumerate() {
+        let top = row_top(row);
+        let text_y = (top + text_offset) as usize;
+        osd::draw_text(
+            buffer,
+            buffer_width,
+            MARGIN as usize,
+            text_y,
+            field.label(),
+            TEXT_COLOR,
+        );
+        osd::draw_text(
+            buffer,
+            buffer_width,
+            VALUE_X as usize,
+            text_y,
+            &field.value(settings),
+            TEXT_COLOR,
+        );
+        for (step, glyph) in [(Step::Decrease, "-"), (Step::Increase, "+")] {
+            let x = button_x(step);
+            let y = top + (ROW_HEIGHT - BUTTON_SIZE) / 2;
+            fill_rect(buffer, buffer_width, x, y, BUTTON_SIZE, BUTTON_COLOR);
+            let glyph_size = osd::text_size(glyph);
+            osd::draw_text(
+                buffer,
+                buffer_width,
+                (x + (BUTTON_SIZE - glyph_size.width) / 2) as usize,
+                (y + (BUTTON_SIZE - glyph_size.height) / 2) as usize,
+                glyph,
+                TEXT_COLOR,
+            );
+        }
+    }
+
+    let heading_row = first_key_binding_row() - 1;
+    osd::draw_text(
+        buffer,
+        buffer_width,
+        MARGIN as usize,
+        (row_top(heading_row) + text_offset) as usize,
+        "KEYBINDINGS",
+        HEADING_COLOR,
+    );
+    for (index, (name, keys)) in key_binding_rows(settings).into_iter().enumerate() {
+        let text_y = (row_top(first_key_binding_row() + index) + text_offset) as usize;
+        let (keys, color) = match key_capture {
+            Some(key_capture) if key_capture.name() == name => {
+                ("PRESS KEYS".to_string(), RECORDING_COLOR)
+            }
+            _ => (keys, TEXT_COLOR),
+        };
+        osd::draw_text(
+            buffer,
+            buffer_width,
+            MARGIN as usize,
+            text_y,
+            &name.replace('_', " "),
+            color,
+        );
+        osd::draw_text(buffer, buffer_width, VALUE_X as usize, text_y, &keys, color);
+    }
+}
+
+#[cfg(test)]
+mod test_gui {
+    use super::*;
+
+    /// click the middle of a button
+    fn click(field: Field, step: Step) -> Option<Click> {
+        let row = Field::ALL.iter().position(|&f| f == field).unwrap();
+        let x = button_x(step) + BUTTON_SIZE / 2;
+        let y = row_top(row) + ROW_HEIGHT / 2;
+        hit_test(x as f64, y as f64, &Settings::default())
+    }
+
+    /// click the middle of a keybinding row's value
+    fn click_key_binding(settings: &Settings, name: &str) -> Option<Click> {
+        let rows = key_binding_rows(settings);
+        let index = rows.iter().position(|(row, _)| row == name).unwrap();
+        let y = row_top(first_key_binding_row() + index) + ROW_HEIGHT / 2;
+        hit_test(VALUE_X as f64, y as f64, settings)
+    }
+
+    #[test]
+    fn test_hit_test() {
+        let settings = Settings::default();
+        for field in Field::ALL {
+            for step in [Step::Decrease, Step::Increase] {
+                assert_eq!(click(field, step), Some(Click::Button(field, step)));
+            }
+        }
+        // labels and values aren't clickable
+        assert_eq!(
+            hit_test(MARGIN as f64, (MARGIN + 4) as f64, &settings),
+            None
+        );
+        assert_eq!(
+            hit_test(VALUE_X as f64, (MARGIN + 4) as f64, &settings),
+            None
+        );
+        // neither is the heading above the keybindings, or anything past the last one
+        let heading_y = row_top(first_key_binding_row() - 1) + ROW_HEIGHT / 2;
+        assert_eq!(hit_test(VALUE_X as f64, heading_y as f64, &settings), None);
+        let size = window_size(&settings);
+        assert_eq!(
+            hit_test(VALUE_X as f64, (size.height - 1) as f64, &settings),
+            None
+        );
+        assert_eq!(hit_test(-1.0, -1.0, &settings), None);
+    }
+
+    #[test]
+    fn test_hit_test_key_bindings() {
+        let settings = Settings::default();
+        assert_eq!(
+            click_key_binding(&settings, "toggle_adjust"),
+            Some(Click::KeyBinding("toggle_adjust".to_string()))
+        );
+        assert_eq!(click_key_binding(&settings, "save_slots"), None);
+        assert_eq!(click_key_binding(&settings, "load_slots"), None);
+    }
+
+    #[test]
+    fn test_capture() {
+        let mut capture = KeyCapture::new("toggle_hidden".to_string());
+        // the click that started recording is still held
+        assert_eq!(
+            capture.update(&[Keycode::MouseLeft]),
+            CaptureStep::Recording
+        );
+        assert_eq!(capture.update(&[]), CaptureStep::Recording);
+        assert_eq!(capture.update(&[Keycode::LShift]), CaptureStep::Recording);
+        assert_eq!(
+            capture.update(&[Keycode::LShift, Keycode::F9]),
+            CaptureStep::Recording
+        );
+        // letting go of one key early still keeps it in the combination
+        assert_eq!(capture.update(&[Keycode::F9]), CaptureStep::Recording);
+        assert_eq!(
+            capture.update(&[]),
+            CaptureStep::Done(vec![Keycode::LShift, Keycode::F9])
+        );
+    }
+
+    #[test]
+    fn test_capture_cancel() {
+        let mut capture = KeyCapture::new("toggle_hidden".to_string());
+        capture.update(&[]);
+        capture.update(&[Keycode::Escape]);
+        assert_eq!(capture.update(&[]), CaptureStep::Cancelled);
+
+        // Escape as part of a bigger combination is fine
+        let mut capture = KeyCapture::new("toggle_hidden".to_string());
+        capture.update(&[]);
+        capture.update(&[Keycode::LShift, Keycode::Escape]);
+        assert_eq!(
+            capture.update(&[]),
+            CaptureStep::Done(vec![Keycode::LShift, Keycode::Escape])
+        );
+    }
+
+    #[test]
+    fn test_color() {
+        let mut settings = Settings::default();
+        settings.set_color(Color::from_rgba_bytes([0, 250, 128, 255]));
+        apply(&mut settings, Field::Red, Step::Increase);
+        apply(&mut settings, Field::Green, Step::Increase);
+        apply(&mut settings, Field::Blue, Step::Decrease);
+        apply(&mut settings, Field::Alpha, Step::Increase);
+        assert_eq!(
+            settings.straight_color().to_rgba_bytes(),
+            [15, 255, 113, 255]
+        );
+    }
+
+    #[test]
+    fn test_size() {
+        let mut settings = Settings::default();
+        settings.persisted.fixed_scale_step = 4;
+        let height = settings.crosshair_size().height;
+        apply(&mut settings, Field::Size, Step::Increase);
+        assert_eq!(settings.crosshair_size().height, height + 4);
+
+        // round crosshairs change their radius instead
+        apply(&mut settings, Field::Shape, Step::Increase);
+        assert_eq!(settings.render_mode, RenderMode::CircleDot);
+        let ring_radius = settings.persisted.ring_radius;
+        apply(&mut settings, Field::Size, Step::Decrease);
+        assert_eq!(settings.persisted.ring_radius, ring_radius - 1);
+    }
+
+    #[test]
+    fn test_shape_wraps() {
+        let mut settings = Settings::default();
+        apply(&mut settings, Field::Shape, Step::Decrease);
+        assert_eq!(settings.persisted.crosshair_mode, RenderMode::TShape);
+        assert_eq!(settings.render_mode, RenderMode::TShape);
+        apply(&mut settings, Field::Shape, Step::Increase);
+        assert_eq!(settings.render_mode, RenderMode::Crosshair);
+    }
+
+    /// the shape is remembered while picking a color, but doesn't kick the picker off screen
+    #[test]
+    fn test_shape_while_picking() {
+        let mut settings = Settings::default();
+        settings.set_pick_color(true);
+        apply(&mut settings, Field::Shape, Step::Increase);
+        assert_eq!(settings.persisted.crosshair_mode, RenderMode::CircleDot);
+        assert_eq!(settings.render_mode, RenderMode::ColorPicker);
+        settings.set_pick_color(false);
+        assert_eq!(settings.render_mode, RenderMode::CircleDot);
+    }
+
+    #[test]
+    fn test_fps() {
+        let mut settings = Settings::default();
+        apply(&mut settings, Field::Fps, Step::Increase);
+        assert_eq!(settings.fps(), 70);
+        for _ in 0..10 {
+            apply(&mut settings, Field::Fps, Step::Decrease);
+        }
+        assert_eq!(settings.fps(), 1);
+        assert_eq!(settings.tick_interval.as_millis(), 1000);
+    }
+
+    #[test]
+    fn test_key_binding_rows() {
+        let settings = Settings::default();
+        let rows = key_binding_rows(&settings);
+        assert!(rows.iter().any(|(name, _)| name == "toggle_adjust"));
+        let (_, save_slots) = rows.iter().find(|(name, _)| name == "save_slots").unwrap();
+        assert_eq!(save_slots, "UNBOUND");
+    }
+
+    #[test]
+    fn test_describe_binding() {
+        let binding: toml::Value =
+            toml::from_str::<toml::Table>("a = [[\"LShift\", \"F1\"], [\"F2\"]]")
+                .unwrap()
+                .remove("a")
+                .unwrap();
+        assert_eq!(describe_binding(&binding), "LShift+F1,F2");
+    }
+
+    #[test]
+    fn test_draw() {
+        let settings = Settings::default();
+        let size = window_size(&settings);
+        let width = size.width as usize;
+        let mut buffer = vec![0; width * size.height as usize];
+        draw(&mut buffer, width, &settings, None);
+        assert!(buffer.contains(&BACKGROUND_COLOR.argb()));
+        assert!(buffer.contains(&TEXT_COLOR.argb()));
+        assert!(!buffer.contains(&RECORDING_COLOR.argb()));
+        // the buttons are where hit testing expects them
+        let (x, y) = (button_x(Step::Decrease) as usize, row_top(0) as usize + 2);
+        assert_eq!(buffer[y * width + x], BUTTON_COLOR.argb());
+    }
+
+    #[test]
+    fn test_draw_recording() {
+        let settings = Settings::default();
+        let size = window_size(&settings);
+        let width = size.width as usize;
+        let mut buffer = vec![0; width * size.height as usize];
+        let capture = KeyCapture::new("toggle_adjust".to_string());
+        draw(&mut buffer, width, &settings, Some(&capture));
+        assert!(buffer.contains(&RECORDING_COLOR.argb()));
+    }
+}
@@ -11,6 +11,7 @@
 //! heavy use of bitmasks.
 
 use std::marker::PhantomData;
+use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 
@@ -20,7 +21,74 @@ use super::Keycode;
 
 /// the number of bits in this mask is the number of distinct keys that can be used across all keybinds
 type Bitmask = u32;
-type KeyBinding = Vec<Keycode>;
+
+/// one bitmask per alternative key combination of a single hotkey
+type Masks = Vec<Bitmask>;
+
+/// The tick interval at the default 60 fps, which the movement and scale ramps were tuned at. At other frame rates
+/// the ramps are stretched so they take the same amount of time.
+const RAMP_REFERENCE_TICK: Duration = Duration::from_millis(17);
+
+/// The key combinations that trigger a single hotkey. Any one of them fully held is enough. No combinations at all
+/// means the hotkey is unbound.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(from = "KeyBindingFormat", into = "KeyBindingFormat")]
+pub struct KeyBinding {
+    combinations: Vec<Vec<Keycode>>,
+}
+
+impl From<Vec<Keycode>> for KeyBinding {
+    /// a binding with just the one combination. An empty combination leaves the hotkey unbound.
+    fn from(combination: Vec<Keycode>) -> Self {
+        let combinations = if combination.is_empty() {
+            Vec::new()
+        } else {
+            vec![combination]
+        };
+        KeyBinding { combinations }
+    }
+}
+
+impl<const N: usize> From<[Keycode; N]> for KeyBinding {
+    fn from(combination: [Keycode; N]) -> Self {
+        KeyBinding::from(combination.to_vec())
+    }
+}
+
+impl From<Vec<Vec<Keycode>>> for KeyBinding {
+    fn from(combinations: Vec<Vec<Keycode>>) -> Self {
+        KeyBinding { combinations }
+    }
+}
+
+/// How a [`KeyBinding`] is written in the config. Configs from before alternatives existed only have a single
+/// combination, e.g. `["LControl", "H"]`. Alternatives are a list of those, e.g. `[["PageUp"], ["NumpadAdd"]]`.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum KeyBindingFormat {
+    Single(Vec<Keycode>),
+    Alternatives(Vec<Vec<Keycode>>),
+}
+
+impl From<KeyBindingFormat> for KeyBinding {
+    fn from(format: KeyBindingFormat) -> Self {
+        match format {
+            KeyBindingFormat::Single(combination) => KeyBinding::from(combination),
+            KeyBindingFormat::Alternatives(combinations) => KeyBinding::from(combinations),
+        }
+    }
+}
+
+impl From<KeyBinding> for KeyBindingFormat {
+    /// stick to the old single combination format whenever possible, so configs stay readable
+    fn from(mut key_binding: KeyBinding) -> Self {
+        match key_binding.combinations.len() {
+            0 => KeyBindingFormat::Single(Vec::new()),
+            1 => KeyBindingFormat::Single(key_binding.combinations.pop().unwrap()),
+            _ => KeyBindingFormat::Alternatives(key_binding.combinations),
+        }
+    }
+}
 
 // serde defaults for new keybinds
 fn default_cycle_monitor_keybind() -> KeyBinding {
@@ -31,8 +99,52 @@ fn default_toggle_color_picker_keybind() -> KeyBinding {
     KeyBindings::default().toggle_color_picker
 }
 
+fn default_scale_increase_fixed_keybind() -> KeyBinding {
+    KeyBindings::default().scale_increase_fixed
+}
+
+fn default_scale_decrease_fixed_keybind() -> KeyBinding {
+    KeyBindings::default().scale_decrease_fixed
+}
+
+fn default_toggle_magnifier_keybind() -> KeyBinding {
+    KeyBindings::default().toggle_magnifier
+}
+
+fn default_toggle_hotkeys_keybind() -> KeyBinding {
+    KeyBindings::default().toggle_hotkeys
+}
+
+fn default_confirm_color_keybind() -> KeyBinding {
+    KeyBindings::default().confirm_color
+}
+
+/// How the scale speed ramps up while a scale hotkey is held down
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScaleEasing {
+    /// The original ramp, which jumps between a handful of fixed speeds
+    #[default]
+    Stepped,
+    /// Speed increases at a constant rate
+    Linear,
+    /// Speed starts slow and accelerates
+    Quadratic,
+    /// Speed doubles at a fixed interval
+    Exponential,
+}
+
+impl ScaleEasing {
+    /// calculate the scale speed based on how many frames the scale keys have been held
+    fn ramp(self, frames: u32) -> u32 {
+        match self {
+            ScaleEasing::Stepped => scale_ramp(frames),
+            easing => eased_scale_ramp(easing, frames),
+        }
+    }
+}
+
 /// format user can specify keybindings with
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct KeyBindings {
     up: KeyBinding,
     down: KeyBinding,
@@ -46,40 +158,196 @@ pub struct KeyBindings {
     toggle_adjust: KeyBinding,
     #[serde(default = "default_toggle_color_picker_keybind")]
     toggle_color_picker: KeyBinding,
+    #[serde(default = "default_scale_increase_fixed_keybind")]
+    scale_increase_fixed: KeyBinding,
+    #[serde(default = "default_scale_decrease_fixed_keybind")]
+    scale_decrease_fixed: KeyBinding,
+    #[serde(default = "default_toggle_magnifier_keybind")]
+    toggle_magnifier: KeyBinding,
+    /// the only hotkey that still works while hotkeys are disabled
+    #[serde(default = "default_toggle_hotkeys_keybind")]
+    toggle_hotkeys: KeyBinding,
+    /// picks the color under the color picker's keyboard cursor
+    #[serde(default = "default_confirm_color_keybind")]
+    confirm_color: KeyBinding,
+    /// unbound by default
+    #[serde(default)]
+    spotlight: KeyBinding,
+    /// switches between the loaded image and the generated crosshair. Unbound by default.
+    #[serde(default)]
+    toggle_image: KeyBinding,
+    /// one binding per save slot, starting at slot 1. None by default.
+    #[serde(default)]
+    save_slots: Vec<KeyBinding>,
+    /// one binding per save slot, starting at slot 1. None by default.
+    #[serde(default)]
+    load_slots: Vec<KeyBinding>,
+    /// switches to the next profile. Unbound by default.
+    #[serde(default)]
+    next_profile: KeyBinding,
+    /// switches to the previous profile. Unbound by default.
+    #[serde(default)]
+    prev_profile: KeyBinding,
+    /// makes the whole overlay more opaque. Unbound by default.
+    #[serde(default)]
+    opacity_increase: KeyBinding,
+    /// makes the whole overlay more transparent. Unbound by default.
+    #[serde(default)]
+    opacity_decrease: KeyBinding,
+    /// moves the crosshair back to the middle without touching anything else. Unbound by default.
+    #[serde(default)]
+    reset_position: KeyBinding,
+    /// reads the last loaded image from disk again, for when it's been edited since. Unbound by default.
+    #[serde(default)]
+    reload_image: KeyBinding,
+    /// while held, the movement keys always go one pixel at a time no matter how long they're held. Unbound by
+    /// default.
+    #[serde(default)]
+    fine_adjust: KeyBinding,
+    /// makes the whole overlay clickable so it can be dragged around with the mouse. Unbound by default.
+    #[serde(default)]
+    toggle_clickthrough: KeyBinding,
+    /// locks the overlay so only "toggle_hidden" and this still work. Unbound by default.
+    #[serde(default)]
+    toggle_lock: KeyBinding,
+    /// widens the generated crosshair while `aspect_locked` is off. Unbound by default.
+    #[serde(default)]
+    width_increase: KeyBinding,
+    /// narrows the generated crosshair while `aspect_locked` is off. Unbound by default.
+    #[serde(default)]
+    width_decrease: KeyBinding,
+    /// makes the generated crosshair taller while `aspect_locked` is off. Unbound by default.
+    #[serde(default)]
+    height_increase: KeyBinding,
+    /// makes the generated crosshair shorter while `aspect_locked` is off. Unbound by default.
+    #[serde(default)]
+    height_decrease: KeyBinding,
+    /// switches the overlay between always-on-top and a normal window. Unbound by default.
+    #[serde(default)]
+    toggle_window_level: KeyBinding,
+    /// raises the fps. Unbound by default.
+    #[serde(default)]
+    fps_increase: KeyBinding,
+    /// lowers the fps. Unbound by default.
+    #[serde(default)]
+    fps_decrease: KeyBinding,
+    /// steps back through recent changes to the crosshair's position, size, color, and style. Unbound by default.
+    #[serde(default)]
+    undo: KeyBinding,
+    /// steps forward again after an "undo". Unbound by default.
+    #[serde(default)]
+    redo: KeyBinding,
 }
 
 impl Default for KeyBindings {
     fn default() -> Self {
         KeyBindings {
-            up: vec![Keycode::Up],
-            down: vec![Keycode::Down],
-            left: vec![Keycode::Left],
-            right: vec![Keycode::Right],
-            cycle_monitor: vec![Keycode::LControl, Keycode::M],
-            scale_increase: vec![Keycode::PageUp],
-            scale_decrease: vec![Keycode::PageDown],
-            toggle_hidden: vec![Keycode::LControl, Keycode::H],
-            toggle_adjust: vec![Keycode::LControl, Keycode::J],
-            toggle_color_picker: vec![Keycode::LControl, Keycode::K],
+            up: [Keycode::Up].into(),
+            down: [Keycode::Down].into(),
+            left: [Keycode::Left].into(),
+            right: [Keycode::Right].into(),
+            cycle_monitor: [Keycode::LControl, Keycode::M].into(),
+            scale_increase: [Keycode::PageUp].into(),
+            scale_decrease: [Keycode::PageDown].into(),
+            toggle_hidden: [Keycode::LControl, Keycode::H].into(),
+            toggle_adjust: [Keycode::LControl, Keycode::J].into(),
+            toggle_color_picker: [Keycode::LControl, Keycode::K].into(),
+            scale_increase_fixed: [Keycode::LShift, Keycode::PageUp].into(),
+            scale_decrease_fixed: [Keycode::LShift, Keycode::PageDown].into(),
+            toggle_magnifier: [Keycode::LControl, Keycode::L].into(),
+            toggle_hotkeys: [Keycode::LControl, Keycode::P].into(),
+            confirm_color: [Keycode::Enter].into(),
+            spotlight: KeyBinding::default(),
+            toggle_image: KeyBinding::default(),
+            save_slots: Vec::new(),
+            load_slots: Vec::new(),
+            next_profile: KeyBinding::default(),
+            prev_profile: KeyBinding::default(),
+            opacity_increase: KeyBinding::default(),
+            opacity_decrease: KeyBinding::default(),
+            reset_position: KeyBinding::default(),
+            reload_image: KeyBinding::default(),
+            fine_adjust: KeyBinding::default(),
+            toggle_clickthrough: KeyBinding::default(),
+            toggle_lock: KeyBinding::default(),
+            width_increase: KeyBinding::default(),
+            width_decrease: KeyBinding::default(),
+            height_increase: KeyBinding::default(),
+            height_decrease: KeyBinding::default(),
+            toggle_window_level: KeyBinding::default(),
+            fps_increase: KeyBinding::default(),
+            fps_decrease: KeyBinding::default(),
+            undo: KeyBinding::default(),
+            redo: KeyBinding::default(),
         }
     }
 }
 
+impl KeyBindings {
+    /// A copy of these bindings with the one called `name` in the config bound to just `combination` instead. An empty
+    /// combination leaves it unbound.
+    pub fn with_binding(
+        &self,
+        name: &str,
+        combination: Vec<Keycode>,
+    ) -> Result<KeyBindings, String> {
+        let mut table = match toml::Value::try_from(self).map_err(|e| e.to_string())? {
+            toml::Value::Table(table) => table,
+            _ => unreachable!("keybindings always serialize to a table"),
+        };
+        let binding = table
+            .get_mut(name)
+            .ok_or_else(|| format!("Unknown keybinding \"{name}\""))?;
+        *binding =
+            toml::Value::try_from(KeyBinding::from(combination)).map_err(|e| e.to_string())?;
+        toml::Value::Table(table)
+            .try_into()
+            .map_err(|e: toml::de::Error| e.to_string())
+    }
+}
+
 struct KeyBuffer<K>
 where
     K: KeycodeType,
 {
     lookup_table: Vec<Bitmask>,
-    up_mask: Bitmask,
-    down_mask: Bitmask,
-    left_mask: Bitmask,
-    right_mask: Bitmask,
-    cycle_monitor_mask: Bitmask,
-    scale_increase_mask: Bitmask,
-    scale_decrease_mask: Bitmask,
-    toggle_hidden_mask: Bitmask,
-    toggle_adjust_mask: Bitmask,
-    toggle_color_picker_mask: Bitmask,
+    up_masks: Masks,
+    down_masks: Masks,
+    left_masks: Masks,
+    right_masks: Masks,
+    cycle_monitor_masks: Masks,
+    scale_increase_masks: Masks,
+    scale_decrease_masks: Masks,
+    toggle_hidden_masks: Masks,
+    toggle_adjust_masks: Masks,
+    toggle_color_picker_masks: Masks,
+    scale_increase_fixed_masks: Masks,
+    scale_decrease_fixed_masks: Masks,
+    toggle_magnifier_masks: Masks,
+    toggle_hotkeys_masks: Masks,
+    confirm_color_masks: Masks,
+    spotlight_masks: Masks,
+    toggle_image_masks: Masks,
+    save_slot_masks: Vec<Masks>,
+    load_slot_masks: Vec<Masks>,
+    next_profile_masks: Masks,
+    prev_profile_masks: Masks,
+    opacity_increase_masks: Masks,
+    opacity_decrease_masks: Masks,
+    reset_position_masks: Masks,
+    reload_image_masks: Masks,
+    fine_adjust_masks: Masks,
+    toggle_clickthrough_masks: Masks,
+    toggle_lock_masks: Masks,
+    width_increase_masks: Masks,
+    width_decrease_masks: Masks,
+    height_increase_masks: Masks,
+    height_decrease_masks: Masks,
+    toggle_window_level_masks: Masks,
+    fps_increase_masks: Masks,
+    fps_decrease_masks: Masks,
+    undo_masks: Masks,
+    redo_masks: Masks,
     any_movement_mask: Bitmask,
     any_scale_mask: Bitmask,
     _keycode_type_marker: PhantomData<K>,
@@ -93,59 +361,139 @@ where
         // build the lookup table and compute each hotkeys bitmask combination
         let mut bit = 1;
         let mut lookup_table = vec![0; K::num_variants()];
-        let up_mask =
-            Self::update_key_buffer_values(&key_bindings.up, &mut bit, &mut lookup_table)?;
-        let down_mask =
-            Self::update_key_buffer_values(&key_bindings.down, &mut bit, &mut lookup_table)?;
-        let left_mask =
-            Self::update_key_buffer_values(&key_bindings.left, &mut bit, &mut lookup_table)?;
-        let right_mask =
-            Self::update_key_buffer_values(&key_bindings.right, &mut bit, &mut lookup_table)?;
-        let cycle_monitor_mask = Self::update_key_buffer_values(
-            &key_bindings.cycle_monitor,
-            &mut bit,
-            &mut lookup_table,
-        )?;
-        let scale_increase_mask = Self::update_key_buffer_values(
-            &key_bindings.scale_increase,
+        let up_masks = Self::binding_masks(&key_bindings.up, &mut bit, &mut lookup_table)?;
+        let down_masks = Self::binding_masks(&key_bindings.down, &mut bit, &mut lookup_table)?;
+        let left_masks = Self::binding_masks(&key_bindings.left, &mut bit, &mut lookup_table)?;
+        let right_masks = Self::binding_masks(&key_bindings.right, &mut bit, &mut lookup_table)?;
+        let cycle_monitor_masks =
+            Self::binding_masks(&key_bindings.cycle_monitor, &mut bit, &mut lookup_table)?;
+        let scale_increase_masks =
+            Self::binding_masks(&key_bindings.scale_increase, &mut bit, &mut lookup_table)?;
+        let scale_decrease_masks =
+            Self::binding_masks(&key_bindings.scale_decrease, &mut bit, &mut lookup_table)?;
+        let toggle_hidden_masks =
+            Self::binding_masks(&key_bindings.toggle_hidden, &mut bit, &mut lookup_table)?;
+        let toggle_adjust_masks =
+            Self::binding_masks(&key_bindings.toggle_adjust, &mut bit, &mut lookup_table)?;
+        let toggle_color_picker_masks = Self::binding_masks(
+            &key_bindings.toggle_color_picker,
             &mut bit,
             &mut lookup_table,
         )?;
-        let scale_decrease_mask = Self::update_key_buffer_values(
-            &key_bindings.scale_decrease,
+        let scale_increase_fixed_masks = Self::binding_masks(
+            &key_bindings.scale_increase_fixed,
             &mut bit,
             &mut lookup_table,
         )?;
-        let toggle_hidden_mask = Self::update_key_buffer_values(
-            &key_bindings.toggle_hidden,
+        let scale_decrease_fixed_masks = Self::binding_masks(
+            &key_bindings.scale_decrease_fixed,
             &mut bit,
             &mut lookup_table,
         )?;
-        let toggle_adjust_mask = Self::update_key_buffer_values(
-            &key_bindings.toggle_adjust,
+        let toggle_magnifier_masks =
+            Self::binding_masks(&key_bindings.toggle_magnifier, &mut bit, &mut lookup_table)?;
+        let toggle_hotkeys_masks =
+            Self::binding_masks(&key_bindings.toggle_hotkeys, &mut bit, &mut lookup_table)?;
+        let confirm_color_masks =
+            Self::binding_masks(&key_bindings.confirm_color, &mut bit, &mut lookup_table)?;
+        let spotlight_masks =
+            Self::binding_masks(&key_bindings.spotlight, &mut bit, &mut lookup_table)?;
+        let toggle_image_masks =
+            Self::binding_masks(&key_bindings.toggle_image, &mut bit, &mut lookup_table)?;
+        let save_slot_masks = key_bindings
+            .save_slots
+            .iter()
+            .map(|binding| Self::binding_masks(binding, &mut bit, &mut lookup_table))
+            .collect::<Result<Vec<_>, _>>()?;
+        let load_slot_masks = key_bindings
+            .load_slots
+            .iter()
+            .map(|binding| Self::binding_masks(binding, &mut bit, &mut lookup_table))
+            .collect::<Result<Vec<_>, _>>()?;
+        let next_profile_masks =
+            Self::binding_masks(&key_bindings.next_profile, &mut bit, &mut lookup_table)?;
+        let prev_profile_masks =
+            Self::binding_masks(&key_bindings.prev_profile, &mut bit, &mut lookup_table)?;
+        let opacity_increase_masks =
+            Self::binding_masks(&key_bindings.opacity_increase, &mut bit, &mut lookup_table)?;
+        let opacity_decrease_masks =
+            Self::binding_masks(&key_bindings.opacity_decrease, &mut bit, &mut lookup_table)?;
+        let reset_position_masks =
+            Self::binding_masks(&key_bindings.reset_position, &mut bit, &mut lookup_table)?;
+        let reload_image_masks =
+            Self::binding_masks(&key_bindings.reload_image, &mut bit, &mut lookup_table)?;
+        let fine_adjust_masks =
+            Self::binding_masks(&key_bindings.fine_adjust, &mut bit, &mut lookup_table)?;
+        let toggle_clickthrough_masks = Self::binding_masks(
+            &key_bindings.toggle_clickthrough,
             &mut bit,
             &mut lookup_table,
         )?;
-        let toggle_color_picker_mask = Self::update_key_buffer_values(
-            &key_bindings.toggle_color_picker,
+        let toggle_lock_masks =
+            Self::binding_masks(&key_bindings.toggle_lock, &mut bit, &mut lookup_table)?;
+        let width_increase_masks =
+            Self::binding_masks(&key_bindings.width_increase, &mut bit, &mut lookup_table)?;
+        let width_decrease_masks =
+            Self::binding_masks(&key_bindings.width_decrease, &mut bit, &mut lookup_table)?;
+        let height_increase_masks =
+            Self::binding_masks(&key_bindings.height_increase, &mut bit, &mut lookup_table)?;
+        let height_decrease_masks =
+            Self::binding_masks(&key_bindings.height_decrease, &mut bit, &mut lookup_table)?;
+        let toggle_window_level_masks = Self::binding_masks(
+            &key_bindings.toggle_window_level,
             &mut bit,
             &mut lookup_table,
         )?;
-        let any_movement_mask = up_mask | down_mask | left_mask | right_mask;
-        let any_scale_mask = scale_increase_mask | scale_decrease_mask;
+        let fps_increase_masks =
+            Self::binding_masks(&key_bindings.fps_increase, &mut bit, &mut lookup_table)?;
+        let fps_decrease_masks =
+            Self::binding_masks(&key_bindings.fps_decrease, &mut bit, &mut lookup_table)?;
+        let undo_masks = Self::binding_masks(&key_bindings.undo, &mut bit, &mut lookup_table)?;
+        let redo_masks = Self::binding_masks(&key_bindings.redo, &mut bit, &mut lookup_table)?;
+        let any_movement_mask = [&up_masks, &down_masks, &left_masks, &right_masks]
+            .into_iter()
+            .fold(0, |mask, masks| mask | union(masks));
+        let any_scale_mask = union(&scale_increase_masks) | union(&scale_decrease_masks);
 
         Ok(KeyBuffer {
             lookup_table,
-            up_mask,
-            down_mask,
-            left_mask,
-            right_mask,
-            cycle_monitor_mask,
-            scale_increase_mask,
-            scale_decrease_mask,
-            toggle_hidden_mask,
-            toggle_adjust_mask,
-            toggle_color_picker_mask,
+            up_masks,
+            down_masks,
+            left_masks,
+            right_masks,
+            cycle_monitor_masks,
+            scale_increase_masks,
+            scale_decrease_masks,
+            toggle_hidden_masks,
+            toggle_adjust_masks,
+            toggle_color_picker_masks,
+            scale_increase_fixed_masks,
+            scale_decrease_fixed_masks,
+            toggle_magnifier_masks,
+            toggle_hotkeys_masks,
+            confirm_color_masks,
+            spotlight_masks,
+            toggle_image_masks,
+            save_slot_masks,
+            load_slot_masks,
+            next_profile_masks,
+            prev_profile_masks,
+            opacity_increase_masks,
+            opacity_decrease_masks,
+            reset_position_masks,
+            reload_image_masks,
+            fine_adjust_masks,
+            toggle_clickthrough_masks,
+            toggle_lock_masks,
+            width_increase_masks,
+            width_decrease_masks,
+            height_increase_masks,
+            height_decrease_masks,
+            toggle_window_level_masks,
+            fps_increase_masks,
+            fps_decrease_masks,
+            undo_masks,
+            redo_masks,
             any_movement_mask,
             any_scale_mask,
             _keycode_type_marker: Default::default(),
@@ -185,6 +533,20 @@ where
         Ok(mask)
     }
 
+    /// Compute the bitmask for each of a hotkey's alternative key combinations, as [`Self::update_key_buffer_values`]
+    /// does for a single one.
+    fn binding_masks(
+        key_binding: &KeyBinding,
+        bit: &mut Bitmask,
+        lookup_table: &mut [Bitmask],
+    ) -> Result<Masks, &'static str> {
+        key_binding
+            .combinations
+            .iter()
+            .map(|combination| Self::update_key_buffer_values(combination, bit, lookup_table))
+            .collect()
+    }
+
     /// Get the bitmask that corresponds to this specific key. This returns a mask with a single bit
     /// set for keys used in any hotkey, and returns zero for keys not used in any hotkey.
     #[inline(always)]
@@ -202,52 +564,192 @@ where
 
     /// Check if the currently pressed keys contain the "up" key combination
     fn up(&self, buf: Bitmask) -> bool {
-        buf & self.up_mask == self.up_mask
+        binding_held(buf, &self.up_masks)
     }
 
     /// Check if the currently pressed keys contain the "down" key combination
     fn down(&self, buf: Bitmask) -> bool {
-        buf & self.down_mask == self.down_mask
+        binding_held(buf, &self.down_masks)
     }
 
     /// Check if the currently pressed keys contain the "left" key combination
     fn left(&self, buf: Bitmask) -> bool {
-        buf & self.left_mask == self.left_mask
+        binding_held(buf, &self.left_masks)
     }
 
     /// Check if the currently pressed keys contain the "right" key combination
     fn right(&self, buf: Bitmask) -> bool {
-        buf & self.right_mask == self.right_mask
+        binding_held(buf, &self.right_masks)
     }
 
     /// Check if the currently pressed keys contain the "cycle_monitor" key combination
     fn cycle_monitor(&self, buf: Bitmask) -> bool {
-        buf & self.cycle_monitor_mask == self.cycle_monitor_mask
+        binding_held(buf, &self.cycle_monitor_masks)
     }
 
     /// Check if the currently pressed keys contain the "scale_increase" key combination
     fn scale_increase(&self, buf: Bitmask) -> bool {
-        buf & self.scale_increase_mask == self.scale_increase_mask
+        binding_held(buf, &self.scale_increase_masks)
     }
 
     /// Check if the currently pressed keys contain the "scale_decrease" key combination
     fn scale_decrease(&self, buf: Bitmask) -> bool {
-        buf & self.scale_decrease_mask == self.scale_decrease_mask
+        binding_held(buf, &self.scale_decrease_masks)
     }
 
     /// Check if the currently pressed keys contain the "toggle_hidden" key combination
     fn toggle_hidden(&self, buf: Bitmask) -> bool {
-        buf & self.toggle_hidden_mask == self.toggle_hidden_mask
+        binding_held(buf, &self.toggle_hidden_masks)
     }
 
     /// Check if the currently pressed keys contain the "toggle_adjust" key combination
     fn toggle_adjust(&self, buf: Bitmask) -> bool {
-        buf & self.toggle_adjust_mask == self.toggle_adjust_mask
+        binding_held(buf, &self.toggle_adjust_masks)
     }
 
     /// Check if the currently pressed keys contain the "toggle_color_picker" key combination
     fn toggle_color_picker(&self, buf: Bitmask) -> bool {
-        buf & self.toggle_color_picker_mask == self.toggle_color_picker_mask
+        binding_held(buf, &self.toggle_color_picker_masks)
+    }
+
+    /// Check if the currently pressed keys contain the "scale_increase_fixed" key combination
+    fn scale_increase_fixed(&self, buf: Bitmask) -> bool {
+        binding_held(buf, &self.scale_increase_fixed_masks)
+    }
+
+    /// Check if the currently pressed keys contain the "scale_decrease_fixed" key combination
+    fn scale_decrease_fixed(&self, buf: Bitmask) -> bool {
+        binding_held(buf, &self.scale_decrease_fixed_masks)
+    }
+
+    /// Check if the currently pressed keys contain the "toggle_magnifier" key combination
+    fn toggle_magnifier(&self, buf: Bitmask) -> bool {
+        binding_held(buf, &self.toggle_magnifier_masks)
+    }
+
+    /// Check if the currently pressed keys contain the "toggle_hotkeys" key combination
+    fn toggle_hotkeys(&self, buf: Bitmask) -> bool {
+        binding_held(buf, &self.toggle_hotkeys_masks)
+    }
+
+    /// Check if the currently pressed keys contain the "confirm_color" key combination
+    fn confirm_color(&self, buf: Bitmask) -> bool {
+        binding_held(buf, &self.confirm_color_masks)
+    }
+
+    /// Check if the currently pressed keys contain the "spotlight" key combination
+    fn spotlight(&self, buf: Bitmask) -> bool {
+        binding_held(buf, &self.spotlight_masks)
+    }
+
+    /// Check if the currently pressed keys contain the "toggle_image" key combination
+    fn toggle_image(&self, buf: Bitmask) -> bool {
+        binding_held(buf, &self.toggle_image_masks)
+    }
+
+    /// Check if the currently pressed keys contain the "next_profile" key combination
+    fn next_profile(&self, buf: Bitmask) -> bool {
+        binding_held(buf, &self.next_profile_masks)
+    }
+
+    /// Check if the currently pressed keys contain the "prev_profile" key combination
+    fn prev_profile(&self, buf: Bitmask) -> bool {
+        binding_held(buf, &self.prev_profile_masks)
+    }
+
+    /// Check if the currently pressed keys contain the "opacity_increase" key combination
+    fn opacity_increase(&self, buf: Bitmask) -> bool {
+        binding_held(buf, &self.opacity_increase_masks)
+    }
+
+    /// Check if the currently pressed keys contain the "opacity_decrease" key combination
+    fn opacity_decrease(&self, buf: Bitmask) -> bool {
+        binding_held(buf, &self.opacity_decrease_masks)
+    }
+
+    /// Check if the currently pressed keys contain the "reset_position" key combination
+    fn reset_position(&self, buf: Bitmask) -> bool {
+        binding_held(buf, &self.reset_position_masks)
+    }
+
+    /// Check if the currently pressed keys contain the "reload_image" key combination
+    fn reload_image(&self, buf: Bitmask) -> bool {
+        binding_held(buf, &self.reload_image_masks)
+    }
+
+    /// Check if the currently pressed keys contain the "fine_adjust" key combination
+    fn fine_adjust(&self, buf: Bitmask) -> bool {
+        binding_held(buf, &self.fine_adjust_masks)
+    }
+
+    /// Check if the currently pressed keys contain the "toggle_clickthrough" key combination
+    fn toggle_clickthrough(&self, buf: Bitmask) -> bool {
+        binding_held(buf, &self.toggle_clickthrough_masks)
+    }
+
+    /// Check if the currently pressed keys contain the "toggle_lock" key combination
+    fn toggle_lock(&self, buf: Bitmask) -> bool {
+        binding_held(buf, &self.toggle_lock_masks)
+    }
+
+    /// Check if the currently pressed keys contain the "width_increase" key combination
+    fn width_increase(&self, buf: Bitmask) -> bool {
+        binding_held(buf, &self.width_increase_masks)
+    }
+
+    /// Check if the currently pressed keys contain the "width_decrease" key combination
+    fn width_decrease(&self, buf: Bitmask) -> bool {
+        binding_held(buf, &self.width_decrease_masks)
+    }
+
+    /// Check if the currently pressed keys contain the "height_increase" key combination
+    fn height_increase(&self, buf: Bitmask) -> bool {
+        binding_held(buf, &self.height_increase_masks)
+    }
+
+    /// Check if the currently pressed keys contain the "height_decrease" key combination
+    fn height_decrease(&self, buf: Bitmask) -> bool {
+        binding_held(buf, &self.height_decrease_masks)
+    }
+
+    /// Check if the currently pressed keys contain the "toggle_window_level" key combination
+    fn toggle_window_level(&self, buf: Bitmask) -> bool {
+        binding_held(buf, &self.toggle_window_level_masks)
+    }
+
+    /// Check if the currently pressed keys contain the "fps_increase" key combination
+    fn fps_increase(&self, buf: Bitmask) -> bool {
+        binding_held(buf, &self.fps_increase_masks)
+    }
+
+    /// Check if the currently pressed keys contain the "fps_decrease" key combination
+    fn fps_decrease(&self, buf: Bitmask) -> bool {
+        binding_held(buf, &self.fps_decrease_masks)
+    }
+
+    /// Check if the currently pressed keys contain the "undo" key combination
+    fn undo(&self, buf: Bitmask) -> bool {
+        binding_held(buf, &self.undo_masks)
+    }
+
+    /// Check if the currently pressed keys contain the "redo" key combination
+    fn redo(&self, buf: Bitmask) -> bool {
+        binding_held(buf, &self.redo_masks)
+    }
+
+    /// The keys that can still do anything. Disabling hotkeys leaves only "toggle_hotkeys", and locking leaves only
+    /// the hotkeys that don't adjust the crosshair.
+    fn live_keys_mask(&self, enabled: bool, locked: bool) -> Bitmask {
+        if !enabled {
+            union(&self.toggle_hotkeys_masks)
+        } else if locked {
+            union(&self.toggle_hotkeys_masks)
+                | union(&self.toggle_lock_masks)
+                | union(&self.toggle_hidden_masks)
+                | union(&self.spotlight_masks)
+        } else {
+            Bitmask::MAX
+        }
     }
 
     //TODO: this is not strictly correct: if a movement keybind uses multiple keys it breaks, as it will return `true` for partial binding presses
@@ -263,6 +765,26 @@ where
     }
 }
 
+/// Check if the currently pressed keys contain any of a hotkey's key combinations. Unlike a plain mask check, an empty
+/// (unbound) key combination is never considered held.
+#[inline(always)]
+fn binding_held(buf: Bitmask, masks: &[Bitmask]) -> bool {
+    masks.iter().any(|&mask| mask != 0 && buf & mask == mask)
+}
+
+/// every key used by any of a hotkey's key combinations
+fn union(masks: &[Bitmask]) -> Bitmask {
+    masks.iter().fold(0, |union, mask| union | mask)
+}
+
+/// find the first 1-indexed slot whose key combination was just pressed
+fn just_pressed_slot(masks: &[Masks], previous: Bitmask, current: Bitmask) -> Option<usize> {
+    masks
+        .iter()
+        .position(|masks| !binding_held(previous, masks) && binding_held(current, masks))
+        .map(|index| index + 1)
+}
+
 pub struct HotkeyManager<KS, K>
 where
     KS: KeyboardState<K>,
@@ -271,7 +793,18 @@ where
     previous_state: Bitmask,
     current_state: Bitmask,
     movement_key_held_frames: u32,
+    /// movement keys held for this many frames or less don't move anything
+    move_deadzone_frames: u32,
     scale_key_held_frames: u32,
+    /// `true` while "fine_adjust" is held, which takes the movement ramp out of the picture
+    fine_adjust: bool,
+    scale_easing: ScaleEasing,
+    /// how long each frame lasts, so the ramps can move the same distance over the same time at any fps
+    tick_interval: Duration,
+    /// when `false`, every hotkey except "toggle_hotkeys" is ignored
+    enabled: bool,
+    /// when `true`, every hotkey that adjusts the crosshair is ignored
+    locked: bool,
     key_buffer: KeyBuffer<K>,
     keyboard_state: KS,
 }
@@ -283,12 +816,19 @@ where
 {
     pub(crate) fn new_generic(
         key_bindings: &KeyBindings,
+        scale_easing: ScaleEasing,
     ) -> Result<HotkeyManager<KS, K>, &'static str> {
         Ok(HotkeyManager {
             previous_state: 0,
             current_state: 0,
             movement_key_held_frames: 0,
+            move_deadzone_frames: 0,
             scale_key_held_frames: 0,
+            fine_adjust: false,
+            scale_easing,
+            tick_interval: RAMP_REFERENCE_TICK,
+            enabled: true,
+            locked: false,
             key_buffer: KeyBuffer::new(key_bindings)?,
             keyboard_state: KS::default(),
         })
@@ -305,6 +845,7 @@ where
         // calculate state
         let key_buffer = &self.key_buffer;
         key_buffer.update(&mut self.current_state, self.keyboard_state.get_state());
+        self.current_state &= key_buffer.live_keys_mask(self.enabled, self.locked);
 
         self.movement_key_held_frames = if key_buffer.any_movement(self.current_state) {
             self.movement_key_held_frames + 1
@@ -312,11 +853,96 @@ where
             0
         };
 
-        self.scale_key_held_frames = if key_buffer.any_scale(self.current_state) {
+        // the fixed step bindings contain the ramped ones, but holding them shouldn't wind up the ramp
+        self.scale_key_held_frames = if key_buffer.scale_increase_fixed(self.current_state)
+            || key_buffer.scale_decrease_fixed(self.current_state)
+        {
+            0
+        } else if key_buffer.any_scale(self.current_state) {
             self.scale_key_held_frames + 1
         } else {
             0
         };
+
+        self.fine_adjust = key_buffer.fine_adjust(self.current_state);
+    }
+
+    /// Every key and mouse button held as of the last poll, whether or not it's part of a hotkey
+    pub fn pressed_keys(&self) -> Vec<Keycode> {
+        self.keyboard_state
+            .get_state()
+            .iter()
+            .filter_map(|&key| key.try_into().ok())
+            .collect()
+    }
+
+    /// Swap in new keybindings. Nothing counts as held afterwards, so no hotkey fires until its keys are pressed again.
+    pub fn set_key_bindings(&mut self, key_bindings: &KeyBindings) -> Result<(), &'static str> {
+        self.key_buffer = KeyBuffer::new(key_bindings)?;
+        self.previous_state = 0;
+        self.current_state = 0;
+        self.movement_key_held_frames = 0;
+        self.scale_key_held_frames = 0;
+        self.fine_adjust = false;
+        Ok(())
+    }
+
+    /// `true` if any key that's part of a hotkey is held right now
+    pub fn any_key_held(&self) -> bool {
+        self.current_state != 0
+    }
+
+    /// Enable or disable all hotkeys other than "toggle_hotkeys". Takes effect immediately, so nothing else fires
+    /// on the same frame hotkeys get disabled.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.drop_dead_keys();
+    }
+
+    /// Lock or unlock every hotkey that adjusts the crosshair. Like [`Self::set_enabled`], this takes effect
+    /// immediately.
+    pub fn set_locked(&mut self, locked: bool) {
+        self.locked = locked;
+        self.drop_dead_keys();
+    }
+
+    /// forget any held keys that can't do anything anymore, so they don't fire on this frame
+    fn drop_dead_keys(&mut self) {
+        let live_keys_mask = self.key_buffer.live_keys_mask(self.enabled, self.locked);
+        if live_keys_mask != Bitmask::MAX {
+            self.current_state &= live_keys_mask;
+            self.movement_key_held_frames = 0;
+            self.scale_key_held_frames = 0;
+            self.fine_adjust = self.key_buffer.fine_adjust(self.current_state);
+        }
+    }
+
+    /// `true` while the "fine_adjust" key combination is held
+    pub fn fine_adjust_active(&self) -> bool {
+        self.fine_adjust
+    }
+
+    /// Let the ramps know how long a frame is now
+    pub fn set_tick_interval(&mut self, tick_interval: Duration) {
+        self.tick_interval = tick_interval;
+    }
+
+    /// Ignore movement keys until they've been held for more than `frames` frames. 0 moves right away.
+    pub fn set_move_deadzone_frames(&mut self, frames: u32) {
+        self.move_deadzone_frames = frames;
+    }
+
+    /// check if "toggle_hotkeys" key combination was just pressed. This works even while hotkeys are disabled.
+    pub fn toggle_hotkeys(&self) -> bool {
+        let key_buffer = &self.key_buffer;
+        !key_buffer.toggle_hotkeys(self.previous_state)
+            && key_buffer.toggle_hotkeys(self.current_state)
+    }
+
+    /// check if "toggle_lock" key combination was just pressed. This works even while locked.
+    pub fn toggle_lock(&self) -> bool {
+        let key_buffer = &self.key_buffer;
+        !key_buffer.toggle_lock(self.previous_state) && key_buffer.toggle_lock(self.current_state)
     }
 
     /// check if "toggle_hidden" key combination was just pressed
@@ -340,6 +966,160 @@ where
             && key_buffer.toggle_color_picker(self.current_state)
     }
 
+    /// check if "toggle_magnifier" key combination was just pressed
+    pub fn toggle_magnifier(&self) -> bool {
+        let key_buffer = &self.key_buffer;
+        !key_buffer.toggle_magnifier(self.previous_state)
+            && key_buffer.toggle_magnifier(self.current_state)
+    }
+
+    /// check if "confirm_color" key combination was just pressed
+    pub fn confirm_color(&self) -> bool {
+        let key_buffer = &self.key_buffer;
+        !key_buffer.confirm_color(self.previous_state)
+            && key_buffer.confirm_color(self.current_state)
+    }
+
+    /// check if "toggle_image" key combination was just pressed
+    pub fn toggle_image(&self) -> bool {
+        let key_buffer = &self.key_buffer;
+        !key_buffer.toggle_image(self.previous_state) && key_buffer.toggle_image(self.current_state)
+    }
+
+    /// check if "next_profile" key combination was just pressed
+    pub fn next_profile(&self) -> bool {
+        let key_buffer = &self.key_buffer;
+        !key_buffer.next_profile(self.previous_state) && key_buffer.next_profile(self.current_state)
+    }
+
+    /// check if "prev_profile" key combination was just pressed
+    pub fn prev_profile(&self) -> bool {
+        let key_buffer = &self.key_buffer;
+        !key_buffer.prev_profile(self.previous_state) && key_buffer.prev_profile(self.current_state)
+    }
+
+    /// check if "opacity_increase" key combination was just pressed
+    pub fn opacity_increase(&self) -> bool {
+        let key_buffer = &self.key_buffer;
+        !key_buffer.opacity_increase(self.previous_state)
+            && key_buffer.opacity_increase(self.current_state)
+    }
+
+    /// check if "opacity_decrease" key combination was just pressed
+    pub fn opacity_decrease(&self) -> bool {
+        let key_buffer = &self.key_buffer;
+        !key_buffer.opacity_decrease(self.previous_state)
+            && key_buffer.opacity_decrease(self.current_state)
+    }
+
+    /// check if "width_increase" key combination was just pressed
+    pub fn width_increase(&self) -> bool {
+        let key_buffer = &self.key_buffer;
+        !key_buffer.width_increase(self.previous_state)
+            && key_buffer.width_increase(self.current_state)
+    }
+
+    /// check if "width_decrease" key combination was just pressed
+    pub fn width_decrease(&self) -> bool {
+        let key_buffer = &self.key_buffer;
+        !key_buffer.width_decrease(self.previous_state)
+            && key_buffer.width_decrease(self.current_state)
+    }
+
+    /// check if "height_increase" key combination was just pressed
+    pub fn height_increase(&self) -> bool {
+        let key_buffer = &self.key_buffer;
+        !key_buffer.height_increase(self.previous_state)
+            && key_buffer.height_increase(self.current_state)
+    }
+
+    /// check if "height_decrease" key combination was just pressed
+    pub fn height_decrease(&self) -> bool {
+        let key_buffer = &self.key_buffer;
+        !key_buffer.height_decrease(self.previous_state)
+            && key_buffer.height_decrease(self.current_state)
+    }
+
+    /// check if "toggle_window_level" key combination was just pressed
+    pub fn toggle_window_level(&self) -> bool {
+        let key_buffer = &self.key_buffer;
+        !key_buffer.toggle_window_level(self.previous_state)
+            && key_buffer.toggle_window_level(self.current_state)
+    }
+
+    /// check if "fps_increase" key combination was just pressed
+    pub fn fps_increase(&self) -> bool {
+        let key_buffer = &self.key_buffer;
+        !key_buffer.fps_increase(self.previous_state) && key_buffer.fps_increase(self.current_state)
+    }
+
+    /// check if "fps_decrease" key combination was just pressed
+    pub fn fps_decrease(&self) -> bool {
+        let key_buffer = &self.key_buffer;
+        !key_buffer.fps_decrease(self.previous_state) && key_buffer.fps_decrease(self.current_state)
+    }
+
+    /// check if "undo" key combination was just pressed
+    pub fn undo(&self) -> bool {
+        let key_buffer = &self.key_buffer;
+        !key_buffer.undo(self.previous_state) && key_buffer.undo(self.current_state)
+    }
+
+    /// check if "redo" key combination was just pressed
+    pub fn redo(&self) -> bool {
+        let key_buffer = &self.key_buffer;
+        !key_buffer.redo(self.previous_state) && key_buffer.redo(self.current_state)
+    }
+
+    /// check if "reset_position" key combination was just pressed
+    pub fn reset_position(&self) -> bool {
+        let key_buffer = &self.key_buffer;
+        !key_buffer.reset_position(self.previous_state)
+            && key_buffer.reset_position(self.current_state)
+    }
+
+    /// check if "reload_image" key combination was just pressed
+    pub fn reload_image(&self) -> bool {
+        let key_buffer = &self.key_buffer;
+        !key_buffer.reload_image(self.previous_state) && key_buffer.reload_image(self.current_state)
+    }
+
+    /// check if "toggle_clickthrough" key combination was just pressed
+    pub fn toggle_clickthrough(&self) -> bool {
+        let key_buffer = &self.key_buffer;
+        !key_buffer.toggle_clickthrough(self.previous_state)
+            && key_buffer.toggle_clickthrough(self.current_state)
+    }
+
+    /// check if "spotlight" key combination is currently held. Unlike the toggles this stays `true` until release.
+    pub fn spotlight(&self) -> bool {
+        self.key_buffer.spotlight(self.current_state)
+    }
+
+    /// check if "spotlight" key combination was just pressed or released
+    pub fn spotlight_changed(&self) -> bool {
+        let key_buffer = &self.key_buffer;
+        key_buffer.spotlight(self.previous_state) != key_buffer.spotlight(self.current_state)
+    }
+
+    /// check if any "save_slots" key combination was just pressed, returning the 1-indexed slot
+    pub fn save_slot(&self) -> Option<usize> {
+        just_pressed_slot(
+            &self.key_buffer.save_slot_masks,
+            self.previous_state,
+            self.current_state,
+        )
+    }
+
+    /// check if any "load_slots" key combination was just pressed, returning the 1-indexed slot
+    pub fn load_slot(&self) -> Option<usize> {
+        just_pressed_slot(
+            &self.key_buffer.load_slot_masks,
+            self.previous_state,
+            self.current_state,
+        )
+    }
+
     /// check if "cycle_monitor" key combination was just pressed
     pub fn cycle_monitor(&self) -> bool {
         let key_buffer = &self.key_buffer;
@@ -350,7 +1130,7 @@ where
     /// calculate the move up speed based on how long movement keys have been held
     pub fn move_up(&self) -> u32 {
         if self.key_buffer.up(self.current_state) {
-            move_ramp(self.movement_key_held_frames)
+            self.move_speed()
         } else {
             0
         }
@@ -359,7 +1139,7 @@ where
     /// calculate the move down speed based on how long movement keys have been held
     pub fn move_down(&self) -> u32 {
         if self.key_buffer.down(self.current_state) {
-            move_ramp(self.movement_key_held_frames)
+            self.move_speed()
         } else {
             0
         }
@@ -368,7 +1148,7 @@ where
     /// calculate the move left speed based on how long movement keys have been held
     pub fn move_left(&self) -> u32 {
         if self.key_buffer.left(self.current_state) {
-            move_ramp(self.movement_key_held_frames)
+            self.move_speed()
         } else {
             0
         }
@@ -377,16 +1157,46 @@ where
     /// calculate the move right speed based on how long movement keys have been held
     pub fn move_right(&self) -> u32 {
         if self.key_buffer.right(self.current_state) {
-            move_ramp(self.movement_key_held_frames)
+            self.move_speed()
         } else {
             0
         }
     }
 
+    /// movement speed for how long the movement keys have been held, once they're past the deadzone
+    fn move_speed(&self) -> u32 {
+        if self.movement_key_held_frames <= self.move_deadzone_frames {
+            0
+        } else if self.fine_adjust_active() {
+            // one pixel per frame whatever the frame rate, so no ramp scaling
+            1
+        } else {
+            let frames = self.movement_key_held_frames - self.move_deadzone_frames;
+            ramp_step(move_ramp, frames, self.ramp_ratio())
+        }
+    }
+
+    /// scale speed for how long the scale keys have been held
+    fn scale_speed(&self) -> u32 {
+        ramp_step(
+            |frames| self.scale_easing.ramp(frames),
+            self.scale_key_held_frames,
+            self.ramp_ratio(),
+        )
+    }
+
+    /// how many of the ramps' reference frames fit in one of our frames
+    fn ramp_ratio(&self) -> f64 {
+        self.tick_interval.as_nanos() as f64 / RAMP_REFERENCE_TICK.as_nanos() as f64
+    }
+
     /// calculate the scale increase speed based on how long scaling keys have been held
     pub fn scale_increase(&self) -> u32 {
-        if self.key_buffer.scale_increase(self.current_state) {
-            scale_ramp(self.scale_key_held_frames)
+        // the fixed step binding usually contains the ramped binding, and shouldn't also trigger it
+        if self.key_buffer.scale_increase(self.current_state)
+            && !self.key_buffer.scale_increase_fixed(self.current_state)
+        {
+            self.scale_speed()
         } else {
             0
         }
@@ -394,15 +1204,68 @@ where
 
     /// calculate the scale decrease speed based on how long scaling keys have been held
     pub fn scale_decrease(&self) -> u32 {
-        if self.key_buffer.scale_decrease(self.current_state) {
-            scale_ramp(self.scale_key_held_frames)
+        // the fixed step binding usually contains the ramped binding, and shouldn't also trigger it
+        if self.key_buffer.scale_decrease(self.current_state)
+            && !self.key_buffer.scale_decrease_fixed(self.current_state)
+        {
+            self.scale_speed()
         } else {
             0
         }
     }
+
+    /// check if "scale_increase_fixed" key combination was just pressed
+    pub fn scale_increase_fixed(&self) -> bool {
+        let key_buffer = &self.key_buffer;
+        !key_buffer.scale_increase_fixed(self.previous_state)
+            && key_buffer.scale_increase_fixed(self.current_state)
+    }
+
+    /// check if "scale_decrease_fixed" key combination was just pressed
+    pub fn scale_decrease_fixed(&self) -> bool {
+        let key_buffer = &self.key_buffer;
+        !key_buffer.scale_decrease_fixed(self.previous_state)
+            && key_buffer.scale_decrease_fixed(self.current_state)
+    }
+}
+
+/// after this many reference frames every ramp is at its top speed
+const RAMP_FULL_SPEED_FRAMES: u32 = 75;
+
+/// How far a ramp has gone after being held for `frames` of its reference frames, where part of a frame goes part of
+/// that frame's distance. The first frame's step happens right away, so a quick tap always moves exactly once.
+fn ramp_distance(ramp: impl Fn(u32) -> u32, frames: f64) -> f64 {
+    if frames <= 0.0 {
+        return 0.0;
+    }
+    let mut distance = ramp(1) as f64;
+    let whole_frames = frames.floor() as u32;
+    if whole_frames == 0 {
+        return distance;
+    }
+    for frame in 2..=whole_frames.min(RAMP_FULL_SPEED_FRAMES) {
+        distance += ramp(frame) as f64;
+    }
+    // every ramp is flat from here on, so skip the loop
+    if whole_frames > RAMP_FULL_SPEED_FRAMES {
+        distance +=
+            (whole_frames - RAMP_FULL_SPEED_FRAMES) as f64 * ramp(RAMP_FULL_SPEED_FRAMES) as f64;
+    }
+    distance + (frames - whole_frames as f64) * ramp(whole_frames.saturating_add(1)) as f64
 }
 
-// TODO: this should probably be fps-aware
+/// How far to go on the `frames`th frame a ramp has been held. Each of our frames covers `ratio` of the ramp's
+/// reference frames, and only whole pixels are handed out, so the leftovers carry over to later frames.
+fn ramp_step(ramp: impl Fn(u32) -> u32, frames: u32, ratio: f64) -> u32 {
+    if frames == 0 {
+        return 0;
+    }
+    // the tiny nudge keeps floating point error from dropping a pixel at exact frame boundaries
+    let pixels = |frames: u32| (ramp_distance(&ramp, frames as f64 * ratio) + 1e-6).floor() as u64;
+    (pixels(frames) - pixels(frames - 1)) as u32
+}
+
+/// movement speed in pixels per reference frame, for how many reference frames the keys have been held
 fn move_ramp(frames: u32) -> u32 {
     if frames < 2 {
         1
@@ -421,7 +1284,7 @@ fn move_ramp(frames: u32) -> u32 {
     }
 }
 
-// TODO: this should probably be fps-aware
+/// scale speed in pixels per reference frame, for how many reference frames the keys have been held
 fn scale_ramp(frames: u32) -> u32 {
     if frames < 2 {
         1
@@ -439,3 +1302,999 @@ fn scale_ramp(frames: u32) -> u32 {
         64
     }
 }
+
+/// frames the scale keys must be held before the eased ramps start moving. Matches the stepped ramp's pause.
+const SCALE_RAMP_DELAY_FRAMES: u32 = 10;
+/// frames the scale keys must be held before the eased ramps reach full speed. Matches the stepped ramp.
+const SCALE_RAMP_FULL_SPEED_FRAMES: u32 = RAMP_FULL_SPEED_FRAMES;
+/// top speed of all scale ramps
+const SCALE_RAMP_MAX_SPEED: u32 = 64;
+
+/// Smooth alternative to [`scale_ramp`]. The first couple of frames and the following pause are
+/// identical to the stepped ramp so that a single tap still scales by exactly one pixel.
+fn eased_scale_ramp(easing: ScaleEasing, frames: u32) -> u32 {
+    if frames < 2 {
+        1
+    } else if frames < SCALE_RAMP_DELAY_FRAMES {
+        0
+    } else {
+        let progress = (frames - SCALE_RAMP_DELAY_FRAMES) as f32
+            / (SCALE_RAMP_FULL_SPEED_FRAMES - SCALE_RAMP_DELAY_FRAMES) as f32;
+        let progress = progress.min(1.0);
+        let max_speed = SCALE_RAMP_MAX_SPEED as f32;
+        let speed = match easing {
+            ScaleEasing::Stepped | ScaleEasing::Linear => 1.0 + (max_speed - 1.0) * progress,
+            ScaleEasing::Quadratic => 1.0 + (max_speed - 1.0) * progress * progress,
+            ScaleEasing::Exponential => max_speed.powf(progress),
+        };
+        (speed.round() as u32).clamp(1, SCALE_RAMP_MAX_SPEED)
+    }
+}
+
+#[cfg(test)]
+mod test_scale_easing {
+    use super::*;
+
+    const EASINGS: [ScaleEasing; 4] = [
+        ScaleEasing::Stepped,
+        ScaleEasing::Linear,
+        ScaleEasing::Quadratic,
+        ScaleEasing::Exponential,
+    ];
+
+    /// total size change after holding the scale key for `frames` frames
+    fn integrated_change(easing: ScaleEasing, frames: u32) -> u32 {
+        (0..frames).map(|frame| easing.ramp(frame)).sum()
+    }
+
+    /// a single tap must always scale by exactly one pixel, no matter the easing
+    #[test]
+    fn test_single_tap() {
+        for easing in EASINGS {
+            assert_eq!(integrated_change(easing, 1), 1, "{easing:?}");
+            assert_eq!(
+                integrated_change(easing, SCALE_RAMP_DELAY_FRAMES),
+                2,
+                "{easing:?}"
+            );
+        }
+    }
+
+    /// once the initial pause is over the speed must never decrease
+    #[test]
+    fn test_monotonic() {
+        for easing in EASINGS {
+            let mut previous_speed = 0;
+            for frame in SCALE_RAMP_DELAY_FRAMES..200 {
+                let speed = easing.ramp(frame);
+                assert!(
+                    speed >= previous_speed,
+                    "{easing:?} slowed down at frame {frame}"
+                );
+                previous_speed = speed;
+            }
+        }
+    }
+
+    /// every easing must stay within the same speed limit and eventually reach it
+    #[test]
+    fn test_bounded() {
+        const HOLD_FRAMES: u32 = 120;
+        for easing in EASINGS {
+            for frame in 0..HOLD_FRAMES {
+                assert!(easing.ramp(frame) <= SCALE_RAMP_MAX_SPEED, "{easing:?}");
+            }
+            assert_eq!(
+                easing.ramp(SCALE_RAMP_FULL_SPEED_FRAMES),
+                SCALE_RAMP_MAX_SPEED,
+                "{easing:?}"
+            );
+            assert!(integrated_change(easing, HOLD_FRAMES) <= SCALE_RAMP_MAX_SPEED * HOLD_FRAMES);
+        }
+    }
+
+    /// the curves that start slower should cover less distance over the same hold duration
+    #[test]
+    fn test_relative_integrated_change() {
+        const HOLD_FRAMES: u32 = 60;
+        let linear = integrated_change(ScaleEasing::Linear, HOLD_FRAMES);
+        let quadratic = integrated_change(ScaleEasing::Quadratic, HOLD_FRAMES);
+        let exponential = integrated_change(ScaleEasing::Exponential, HOLD_FRAMES);
+        assert!(quadratic < linear, "quadratic={quadratic} linear={linear}");
+        assert!(
+            exponential < linear,
+            "exponential={exponential} linear={linear}"
+        );
+    }
+}
+
+/// A fake keyboard so hotkey logic can be tested without actually pressing keys
+#[cfg(test)]
+pub(crate) mod test_keyboard {
+    use super::*;
+
+    #[derive(Default)]
+    pub(crate) struct TestKeyboardState {
+        pub(crate) pressed: Vec<Keycode>,
+    }
+
+    impl KeyboardState<Keycode> for TestKeyboardState {
+        fn poll(&mut self) {}
+
+        fn get_state(&self) -> &[Keycode] {
+            &self.pressed
+        }
+    }
+
+    pub(crate) type TestHotkeyManager = HotkeyManager<TestKeyboardState, Keycode>;
+
+    impl TestHotkeyManager {
+        pub(crate) fn new_test(key_bindings: &KeyBindings) -> Self {
+            HotkeyManager::new_generic(key_bindings, ScaleEasing::default()).unwrap()
+        }
+
+        /// simulate a single frame with the given keys held
+        pub(crate) fn frame(&mut self, pressed: &[Keycode]) {
+            self.set_pressed(pressed);
+            self.process_keys();
+        }
+
+        /// hold the given keys, without processing them yet
+        pub(crate) fn set_pressed(&mut self, pressed: &[Keycode]) {
+            self.keyboard_state.pressed = pressed.to_vec();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_fixed_scale_step {
+    use super::test_keyboard::TestHotkeyManager;
+    use super::*;
+
+    const FIXED_INCREASE: [Keycode; 2] = [Keycode::LShift, Keycode::PageUp];
+    const FIXED_DECREASE: [Keycode; 2] = [Keycode::LShift, Keycode::PageDown];
+
+    /// holding the fixed binding for a long time should still only trigger once
+    #[test]
+    fn test_edge_triggered() {
+        let mut manager = TestHotkeyManager::new_test(&KeyBindings::default());
+        manager.frame(&FIXED_INCREASE);
+        assert!(manager.scale_increase_fixed());
+        for _ in 0..100 {
+            manager.frame(&FIXED_INCREASE);
+            assert!(!manager.scale_increase_fixed());
+        }
+        manager.frame(&[]);
+        assert!(!manager.scale_increase_fixed());
+        manager.frame(&FIXED_INCREASE);
+        assert!(manager.scale_increase_fixed());
+    }
+
+    /// the fixed binding contains the ramped binding, but must not trigger the ramp
+    #[test]
+    fn test_bypasses_ramp() {
+        let mut manager = TestHotkeyManager::new_test(&KeyBindings::default());
+        for _ in 0..100 {
+            manager.frame(&FIXED_DECREASE);
+            assert_eq!(manager.scale_decrease(), 0);
+            assert_eq!(manager.scale_increase(), 0);
+        }
+    }
+
+    /// each press applies exactly the configured step, regardless of how long it's held
+    #[test]
+    fn test_each_press_applies_step() {
+        const STEP: u32 = 3;
+        let mut manager = TestHotkeyManager::new_test(&KeyBindings::default());
+        let mut size: u32 = 16;
+        for press in 1..=5 {
+            for _ in 0..press * 10 {
+                manager.frame(&FIXED_INCREASE);
+                if manager.scale_increase_fixed() {
+                    size += STEP;
+                }
+                size += manager.scale_increase();
+            }
+            manager.frame(&[]);
+            assert_eq!(size, 16 + press * STEP);
+        }
+    }
+
+    /// letting go of Shift after holding the fixed binding starts the ramp from the beginning
+    #[test]
+    fn test_ramp_starts_fresh() {
+        let mut manager = TestHotkeyManager::new_test(&KeyBindings::default());
+        for _ in 0..100 {
+            manager.frame(&FIXED_INCREASE);
+        }
+
+        let mut fresh = TestHotkeyManager::new_test(&KeyBindings::default());
+        for _ in 0..50 {
+            manager.frame(&[Keycode::PageUp]);
+            fresh.frame(&[Keycode::PageUp]);
+            assert_eq!(manager.scale_increase(), fresh.scale_increase());
+        }
+    }
+
+    #[test]
+    fn test_unbound() {
+        let key_bindings = KeyBindings {
+            scale_increase_fixed: KeyBinding::default(),
+            ..Default::default()
+        };
+        let mut manager = TestHotkeyManager::new_test(&key_bindings);
+        manager.frame(&[]);
+        assert!(!manager.scale_increase_fixed());
+        manager.frame(&[Keycode::PageUp]);
+        assert!(!manager.scale_increase_fixed());
+        assert_eq!(manager.scale_increase(), 1);
+    }
+}
+
+#[cfg(test)]
+mod test_move_deadzone {
+    use super::test_keyboard::TestHotkeyManager;
+    use super::*;
+
+    /// total distance moved up while holding the up key for `frames` frames, then releasing it
+    fn hold_up(manager: &mut TestHotkeyManager, frames: u32) -> u32 {
+        let mut distance = 0;
+        for _ in 0..frames {
+            manager.frame(&[Keycode::Up]);
+            distance += manager.move_up();
+        }
+        manager.frame(&[]);
+        distance
+    }
+
+    #[test]
+    fn test_no_deadzone() {
+        let mut manager = TestHotkeyManager::new_test(&KeyBindings::default());
+        assert_eq!(hold_up(&mut manager, 1), 1);
+    }
+
+    #[test]
+    fn test_deadzone() {
+        let mut manager = TestHotkeyManager::new_test(&KeyBindings::default());
+        manager.set_move_deadzone_frames(3);
+        // a quick tap is ignored
+        assert_eq!(hold_up(&mut manager, 3), 0);
+        // holding just past the deadzone moves a single pixel, same as a tap without a deadzone
+        assert_eq!(hold_up(&mut manager, 4), 1);
+        // the ramp starts once the deadzone is over
+        assert_eq!(hold_up(&mut manager, 30), hold_up_without_deadzone(27));
+    }
+
+    fn hold_up_without_deadzone(frames: u32) -> u32 {
+        let mut manager = TestHotkeyManager::new_test(&KeyBindings::default());
+        hold_up(&mut manager, frames)
+    }
+}
+
+#[cfg(test)]
+mod test_fps_aware_ramps {
+    use super::test_keyboard::TestHotkeyManager;
+    use super::*;
+    use crate::private::util::numeric::fps_to_tick_interval;
+
+    fn manager(fps: u32) -> TestHotkeyManager {
+        let mut manager = TestHotkeyManager::new_test(&KeyBindings::default());
+        manager.set_tick_interval(fps_to_tick_interval(fps));
+        manager
+    }
+
+    /// running total of the distance moved after each frame of holding `key`
+    fn hold(manager: &mut TestHotkeyManager, key: Keycode, frames: u32) -> Vec<u32> {
+        let mut distance = 0;
+        (0..frames)
+            .map(|_| {
+                manager.frame(&[key]);
+                distance += manager.move_up() + manager.scale_increase();
+                distance
+            })
+            .collect()
+    }
+
+    /// 30 FPS frames are exactly twice as long as 60 FPS frames, so they must cover the same distance
+    #[test]
+    fn test_half_frame_rate() {
+        for key in [Keycode::Up, Keycode::PageUp] {
+            let fast = hold(&mut manager(60), key, 400);
+            let slow = hold(&mut manager(30), key, 200);
+            for (frame, distance) in slow.iter().enumerate() {
+                assert_eq!(*distance, fast[frame * 2 + 1], "{key:?} frame {frame}");
+            }
+        }
+    }
+
+    /// the default tick interval keeps the ramps exactly as they were
+    #[test]
+    fn test_reference_frame_rate() {
+        for frames in 1..200 {
+            assert_eq!(ramp_step(move_ramp, frames, 1.0), move_ramp(frames));
+        }
+    }
+
+    /// a quick tap must still move exactly one pixel at high frame rates
+    #[test]
+    fn test_tap_at_high_frame_rate() {
+        let mut manager = manager(144);
+        assert_eq!(hold(&mut manager, Keycode::Up, 1), [1]);
+    }
+
+    /// the eased scale ramps get the same treatment
+    #[test]
+    fn test_eased_scale() {
+        for easing in [
+            ScaleEasing::Linear,
+            ScaleEasing::Quadratic,
+            ScaleEasing::Exponential,
+        ] {
+            let ramp = |frames| easing.ramp(frames);
+            let fast: u32 = (1..=300).map(|frame| ramp_step(ramp, frame, 0.5)).sum();
+            let slow: u32 = (1..=150).map(|frame| ramp_step(ramp, frame, 1.0)).sum();
+            assert_eq!(fast, slow, "{easing:?}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_spotlight {
+    use super::test_keyboard::TestHotkeyManager;
+    use super::*;
+
+    #[test]
+    fn test_unbound_by_default() {
+        let mut manager = TestHotkeyManager::new_test(&KeyBindings::default());
+        manager.frame(&[]);
+        assert!(!manager.spotlight());
+        assert!(!manager.spotlight_changed());
+    }
+
+    #[test]
+    fn test_press_and_release() {
+        let key_bindings = KeyBindings {
+            spotlight: [Keycode::LAlt].into(),
+            ..Default::default()
+        };
+        let mut manager = TestHotkeyManager::new_test(&key_bindings);
+        manager.frame(&[]);
+        assert!(!manager.spotlight());
+
+        // press
+        manager.frame(&[Keycode::LAlt]);
+        assert!(manager.spotlight());
+        assert!(manager.spotlight_changed());
+
+        // hold
+        for _ in 0..10 {
+            manager.frame(&[Keycode::LAlt, Keycode::Up]);
+            assert!(manager.spotlight());
+            assert!(!manager.spotlight_changed());
+        }
+
+        // release
+        manager.frame(&[Keycode::Up]);
+        assert!(!manager.spotlight());
+        assert!(manager.spotlight_changed());
+        manager.frame(&[]);
+        assert!(!manager.spotlight_changed());
+    }
+}
+
+#[cfg(test)]
+mod test_toggle_hotkeys {
+    use super::test_keyboard::TestHotkeyManager;
+    use super::*;
+
+    const TOGGLE: [Keycode; 2] = [Keycode::LControl, Keycode::P];
+
+    #[test]
+    fn test_disabled_ignores_other_hotkeys() {
+        let mut manager = TestHotkeyManager::new_test(&KeyBindings::default());
+        manager.set_enabled(false);
+
+        for _ in 0..100 {
+            manager.frame(&[Keycode::Up, Keycode::PageUp]);
+            assert_eq!(manager.move_up(), 0);
+            assert_eq!(manager.scale_increase(), 0);
+        }
+        manager.frame(&[Keycode::LControl, Keycode::H]);
+        assert!(!manager.toggle_hidden());
+        manager.frame(&[Keycode::LControl, Keycode::J]);
+        assert!(!manager.toggle_adjust());
+
+        // the toggle itself still works
+        manager.frame(&TOGGLE);
+        assert!(manager.toggle_hotkeys());
+        manager.set_enabled(true);
+
+        manager.frame(&[]);
+        manager.frame(&[Keycode::Up]);
+        assert_ne!(manager.move_up(), 0);
+    }
+
+    /// disabling mid-frame must drop whatever else was held, so it can't fire as the toggle is pressed
+    #[test]
+    fn test_disable_is_immediate() {
+        let mut manager = TestHotkeyManager::new_test(&KeyBindings::default());
+        manager.frame(&[Keycode::LControl, Keycode::P, Keycode::Up]);
+        assert!(manager.toggle_hotkeys());
+        manager.set_enabled(false);
+        assert_eq!(manager.move_up(), 0);
+
+        // holding the toggle doesn't re-trigger it
+        manager.frame(&TOGGLE);
+        assert!(!manager.toggle_hotkeys());
+    }
+}
+
+#[cfg(test)]
+mod test_lock {
+    use super::test_keyboard::TestHotkeyManager;
+    use super::*;
+
+    const LOCK: [Keycode; 2] = [Keycode::LControl, Keycode::O];
+
+    fn lockable_manager() -> TestHotkeyManager {
+        let key_bindings = KeyBindings {
+            toggle_lock: LOCK.into(),
+            spotlight: [Keycode::LAlt].into(),
+            ..KeyBindings::default()
+        };
+        TestHotkeyManager::new_test(&key_bindings)
+    }
+
+    #[test]
+    fn test_live_keys() {
+        let manager = lockable_manager();
+        let key_buffer = &manager.key_buffer;
+        let locked = key_buffer.live_keys_mask(true, true);
+        assert_eq!(key_buffer.live_keys_mask(true, false), Bitmask::MAX);
+        assert!(key_buffer.toggle_lock(locked));
+        assert!(key_buffer.toggle_hidden(locked));
+        assert!(key_buffer.toggle_hotkeys(locked));
+        assert!(key_buffer.spotlight(locked));
+        assert!(!key_buffer.up(locked));
+        assert!(!key_buffer.scale_increase(locked));
+        assert!(!key_buffer.cycle_monitor(locked));
+        assert!(!key_buffer.toggle_adjust(locked));
+
+        // disabled hotkeys win over the lock
+        let disabled = key_buffer.live_keys_mask(false, true);
+        assert!(!key_buffer.toggle_lock(disabled));
+        assert!(!key_buffer.toggle_hidden(disabled));
+    }
+
+    #[test]
+    fn test_locked_ignores_adjustments() {
+        let mut manager = lockable_manager();
+        manager.set_locked(true);
+
+        for _ in 0..100 {
+            manager.frame(&[Keycode::Up, Keycode::PageUp]);
+            assert_eq!(manager.move_up(), 0);
+            assert_eq!(manager.scale_increase(), 0);
+        }
+        manager.frame(&[Keycode::LControl, Keycode::M]);
+        assert!(!manager.cycle_monitor());
+
+        // hiding and unlocking still work
+        manager.frame(&[Keycode::LControl, Keycode::H]);
+        assert!(manager.toggle_hidden());
+        manager.frame(&LOCK);
+        assert!(manager.toggle_lock());
+        manager.set_locked(false);
+
+        manager.frame(&[]);
+        manager.frame(&[Keycode::Up]);
+        assert_ne!(manager.move_up(), 0);
+    }
+
+    #[test]
+    fn test_unbound_by_default() {
+        let mut manager = TestHotkeyManager::new_test(&KeyBindings::default());
+        manager.frame(&[]);
+        assert!(!manager.toggle_lock());
+    }
+}
+
+#[cfg(test)]
+mod test_empty_bindings {
+    use super::test_keyboard::TestHotkeyManager;
+    use super::*;
+
+    #[test]
+    fn test_empty_toggle_hidden_never_fires() {
+        let key_bindings = KeyBindings {
+            toggle_hidden: KeyBinding::default(),
+            ..KeyBindings::default()
+        };
+        let mut manager = TestHotkeyManager::new_test(&key_bindings);
+        assert!(!manager.key_buffer.toggle_hidden(0));
+        assert!(!manager.key_buffer.toggle_hidden(Bitmask::MAX));
+
+        for pressed in [
+            &[][..],
+            &[Keycode::LControl, Keycode::H],
+            &[],
+            &[Keycode::Up, Keycode::PageUp],
+            &[Keycode::LControl, Keycode::H, Keycode::J],
+        ] {
+            manager.frame(pressed);
+            assert!(!manager.toggle_hidden());
+        }
+    }
+
+    /// an unbound held action, like movement, must not act like it's held down forever
+    #[test]
+    fn test_empty_movement_never_moves() {
+        let key_bindings = KeyBindings {
+            up: KeyBinding::default(),
+            cycle_monitor: KeyBinding::default(),
+            ..KeyBindings::default()
+        };
+        let mut manager = TestHotkeyManager::new_test(&key_bindings);
+        for _ in 0..100 {
+            manager.frame(&[]);
+            assert_eq!(manager.move_up(), 0);
+            assert!(!manager.cycle_monitor());
+        }
+        manager.frame(&[Keycode::Down]);
+        assert_ne!(manager.move_down(), 0);
+    }
+}
+
+#[cfg(test)]
+mod test_alternatives {
+    use super::test_keyboard::TestHotkeyManager;
+    use super::*;
+
+    /// a config with every required binding, with `scale_increase` written as given
+    fn config(scale_increase: &str) -> String {
+        format!(
+            r#"
+up = ["Up"]
+down = ["Down"]
+left = ["Left"]
+right = ["Right"]
+scale_increase = {scale_increase}
+scale_decrease = ["PageDown"]
+toggle_hidden = ["LControl", "H"]
+toggle_adjust = ["LControl", "J"]
+"#
+        )
+    }
+
+    #[test]
+    fn test_either_alternative_triggers() {
+        let key_bindings: KeyBindings =
+            toml::from_str(&config(r#"[["PageUp"], ["NumpadAdd"]]"#)).unwrap();
+        let mut manager = TestHotkeyManager::new_test(&key_bindings);
+        for pressed in [Keycode::PageUp, Keycode::NumpadAdd] {
+            manager.frame(&[]);
+            manager.frame(&[pressed]);
+            assert_eq!(manager.scale_increase(), 1, "{pressed:?}");
+        }
+        manager.frame(&[Keycode::PageDown]);
+        assert_eq!(manager.scale_increase(), 0);
+    }
+
+    #[test]
+    fn test_alternative_combinations() {
+        let key_bindings = KeyBindings {
+            toggle_hidden: vec![vec![Keycode::LControl, Keycode::H], vec![Keycode::MouseX2]].into(),
+            ..KeyBindings::default()
+        };
+        let mut manager = TestHotkeyManager::new_test(&key_bindings);
+        manager.frame(&[Keycode::LControl, Keycode::H]);
+        assert!(manager.toggle_hidden());
+        manager.frame(&[]);
+        manager.frame(&[Keycode::MouseX2]);
+        assert!(manager.toggle_hidden());
+
+        // half of a combination isn't enough
+        manager.frame(&[]);
+        manager.frame(&[Keycode::H]);
+        assert!(!manager.toggle_hidden());
+    }
+
+    #[test]
+    fn test_old_single_combination_config() {
+        let key_bindings: KeyBindings = toml::from_str(&config(r#"["LShift", "Up"]"#)).unwrap();
+        assert_eq!(
+            key_bindings.scale_increase,
+            KeyBinding::from([Keycode::LShift, Keycode::Up])
+        );
+        let key_bindings: KeyBindings = toml::from_str(&config("[]")).unwrap();
+        assert_eq!(key_bindings.scale_increase, KeyBinding::default());
+    }
+
+    /// single combinations are still saved the old way, so older versions can read the config
+    #[test]
+    fn test_round_trip() {
+        let key_bindings = KeyBindings {
+            scale_increase: vec![vec![Keycode::PageUp], vec![Keycode::NumpadAdd]].into(),
+            ..KeyBindings::default()
+        };
+        let text = toml::to_string(&key_bindings).unwrap();
+        assert!(
+            text.contains(r#"toggle_hidden = ["LControl", "H"]"#),
+            "{text}"
+        );
+        assert!(text.contains("spotlight = []"), "{text}");
+
+        let parsed: KeyBindings = toml::from_str(&text).unwrap();
+        assert_eq!(parsed.scale_increase, key_bindings.scale_increase);
+        assert_eq!(parsed.toggle_hidden, key_bindings.toggle_hidden);
+    }
+}
+
+#[cfg(test)]
+mod test_slots {
+    use super::test_keyboard::TestHotkeyManager;
+    use super::*;
+
+    #[test]
+    fn test_slots() {
+        let key_bindings = KeyBindings {
+            save_slots: vec![
+                [Keycode::LControl, Keycode::LShift, Keycode::Key1].into(),
+                [Keycode::LControl, Keycode::LShift, Keycode::Key2].into(),
+            ],
+            load_slots: vec![
+                [Keycode::LControl, Keycode::Key1].into(),
+                [Keycode::LControl, Keycode::Key2].into(),
+            ],
+            ..Default::default()
+        };
+        let mut manager = TestHotkeyManager::new_test(&key_bindings);
+        manager.frame(&[]);
+        assert_eq!(manager.save_slot(), None);
+        assert_eq!(manager.load_slot(), None);
+
+        manager.frame(&[Keycode::LControl, Keycode::Key2]);
+        assert_eq!(manager.save_slot(), None);
+        assert_eq!(manager.load_slot(), Some(2));
+
+        // held, so no new press
+        manager.frame(&[Keycode::LControl, Keycode::Key2]);
+        assert_eq!(manager.load_slot(), None);
+
+        manager.frame(&[Keycode::LControl, Keycode::LShift, Keycode::Key1]);
+        assert_eq!(manager.save_slot(), Some(1));
+    }
+}
+
+#[cfg(test)]
+mod test_undo {
+    use super::test_keyboard::TestHotkeyManager;
+    use super::*;
+
+    /// undoing is an adjustment, so locking blocks it
+    #[test]
+    fn test_locked() {
+        let key_bindings = KeyBindings {
+            undo: [Keycode::LControl, Keycode::Z].into(),
+            ..Default::default()
+        };
+        let mut manager = TestHotkeyManager::new_test(&key_bindings);
+        manager.set_locked(true);
+        manager.frame(&[]);
+        manager.frame(&[Keycode::LControl, Keycode::Z]);
+        assert!(!manager.undo());
+    }
+}
+
+#[cfg(test)]
+mod test_fine_adjust {
+    use super::test_keyboard::TestHotkeyManager;
+    use super::*;
+    use crate::private::util::numeric::fps_to_tick_interval;
+
+    fn manager() -> TestHotkeyManager {
+        let key_bindings = KeyBindings {
+            fine_adjust: [Keycode::LAlt].into(),
+            ..Default::default()
+        };
+        TestHotkeyManager::new_test(&key_bindings)
+    }
+
+    /// the plain ramp would be going 64 pixels a frame by the end of this
+    #[test]
+    fn test_stays_at_one() {
+        let mut manager = manager();
+        for _ in 0..200 {
+            manager.frame(&[Keycode::LAlt, Keycode::Right]);
+            assert!(manager.fine_adjust_active());
+            assert_eq!(manager.move_right(), 1);
+            assert_eq!(manager.move_left(), 0);
+        }
+    }
+
+    /// letting go of the modifier mid-move goes right back to the ramp
+    #[test]
+    fn test_release() {
+        let mut manager = manager();
+        for _ in 0..100 {
+            manager.frame(&[Keycode::LAlt, Keycode::Up]);
+        }
+        assert_eq!(manager.move_up(), 1);
+        manager.frame(&[Keycode::Up]);
+        assert!(!manager.fine_adjust_active());
+        assert_eq!(manager.move_up(), move_ramp(101));
+    }
+
+    #[test]
+    fn test_deadzone() {
+        let mut manager = manager();
+        manager.set_move_deadzone_frames(3);
+        for _ in 0..3 {
+            manager.frame(&[Keycode::LAlt, Keycode::Down]);
+            assert_eq!(manager.move_down(), 0);
+        }
+        manager.frame(&[Keycode::LAlt, Keycode::Down]);
+        assert_eq!(manager.move_down(), 1);
+    }
+
+    /// one pixel per frame holds at frame rates on either side of the ramps' reference rate
+    #[test]
+    fn test_frame_rates() {
+        for fps in [30, 144] {
+            let mut manager = manager();
+            manager.set_tick_interval(fps_to_tick_interval(fps));
+            for _ in 0..200 {
+                manager.frame(&[Keycode::LAlt, Keycode::Left]);
+                assert_eq!(manager.move_left(), 1, "at {fps} FPS");
+            }
+        }
+    }
+
+    /// unbound by default, so the ramp always applies
+    #[test]
+    fn test_unbound() {
+        let mut manager = TestHotkeyManager::new_test(&KeyBindings::default());
+        for _ in 0..100 {
+            manager.frame(&[Keycode::LAlt, Keycode::Right]);
+        }
+        assert!(!manager.fine_adjust_active());
+        assert_eq!(manager.move_right(), move_ramp(100));
+    }
+}
+
+#[cfg(test)]
+mod test_one_shot_bindings {
+    use super::test_keyboard::TestHotkeyManager;
+    use super::*;
+
+    type Field = fn(&mut KeyBindings) -> &mut KeyBinding;
+    type Fired = fn(&TestHotkeyManager) -> bool;
+
+    /// the hotkeys that fire once per press and are unbound by default, by their name in the config
+    const BINDINGS: [(&str, Field, Fired); 17] = [
+        (
+            "toggle_image",
+            |b| &mut b.toggle_image,
+            TestHotkeyManager::toggle_image,
+        ),
+        (
+            "next_profile",
+            |b| &mut b.next_profile,
+            TestHotkeyManager::next_profile,
+        ),
+        (
+            "prev_profile",
+            |b| &mut b.prev_profile,
+            TestHotkeyManager::prev_profile,
+        ),
+        (
+            "opacity_increase",
+            |b| &mut b.opacity_increase,
+            TestHotkeyManager::opacity_increase,
+        ),
+        (
+            "opacity_decrease",
+            |b| &mut b.opacity_decrease,
+            TestHotkeyManager::opacity_decrease,
+        ),
+        (
+            "reload_image",
+            |b| &mut b.reload_image,
+            TestHotkeyManager::reload_image,
+        ),
+        (
+            "reset_position",
+            |b| &mut b.reset_position,
+            TestHotkeyManager::reset_position,
+        ),
+        (
+            "toggle_clickthrough",
+            |b| &mut b.toggle_clickthrough,
+            TestHotkeyManager::toggle_clickthrough,
+        ),
+        (
+            "width_increase",
+            |b| &mut b.width_increase,
+            TestHotkeyManager::width_increase,
+        ),
+        (
+            "width_decrease",
+            |b| &mut b.width_decrease,
+            TestHotkeyManager::width_decrease,
+        ),
+        (
+            "height_increase",
+            |b| &mut b.height_increase,
+            TestHotkeyManager::height_increase,
+        ),
+        (
+            "height_decrease",
+            |b| &mut b.height_decrease,
+            TestHotkeyManager::height_decrease,
+        ),
+        (
+            "toggle_window_level",
+            |b| &mut b.toggle_window_level,
+            TestHotkeyManager::toggle_window_level,
+        ),
+        (
+            "fps_increase",
+            |b| &mut b.fps_increase,
+            TestHotkeyManager::fps_increase,
+        ),
+        (
+            "fps_decrease",
+            |b| &mut b.fps_decrease,
+            TestHotkeyManager::fps_decrease,
+        ),
+        ("undo", |b| &mut b.undo, TestHotkeyManager::undo),
+        ("redo", |b| &mut b.redo, TestHotkeyManager::redo),
+    ];
+
+    const COMBINATION: [Keycode; 2] = [Keycode::LAlt, Keycode::F9];
+
+    /// fires on the frame the whole combination goes down, and not again until it's pressed again
+    #[test]
+    fn test_press() {
+        for (name, field, fired) in BINDINGS {
+            let mut key_bindings = KeyBindings::default();
+            *field(&mut key_bindings) = COMBINATION.into();
+            let mut manager = TestHotkeyManager::new_test(&key_bindings);
+            manager.frame(&[Keycode::F9]);
+            assert!(!fired(&manager), "{name} fired on part of its combination");
+            manager.frame(&COMBINATION);
+            assert!(fired(&manager), "{name} didn't fire");
+            manager.frame(&COMBINATION);
+            assert!(!fired(&manager), "{name} fired again while held");
+            manager.frame(&[]);
+            manager.frame(&COMBINATION);
+            assert!(fired(&manager), "{name} didn't fire on the second press");
+        }
+    }
+
+    /// pressing one binding doesn't set off any of the others
+    #[test]
+    fn test_independent() {
+        for (name, field, _) in BINDINGS {
+            let mut key_bindings = KeyBindings::default();
+            *field(&mut key_bindings) = COMBINATION.into();
+            let mut manager = TestHotkeyManager::new_test(&key_bindings);
+            manager.frame(&COMBINATION);
+            for (other, _, fired) in BINDINGS {
+                assert_eq!(fired(&manager), other == name, "{other} fired for {name}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_unbound_by_default() {
+        let mut manager = TestHotkeyManager::new_test(&KeyBindings::default());
+        for pressed in [
+            &[][..],
+            &COMBINATION,
+            &[Keycode::LControl, Keycode::Home],
+            &[],
+        ] {
+            manager.frame(pressed);
+            for (name, _, fired) in BINDINGS {
+                assert!(!fired(&manager), "{name} fired while unbound");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_mouse_buttons {
+    use super::test_keyboard::TestHotkeyManager;
+    use super::*;
+
+    #[test]
+    fn test_mouse_button_binding() {
+        let key_bindings: KeyBindings = toml::from_str(
+            &toml::to_string(&KeyBindings {
+                toggle_hidden: [Keycode::MouseX1].into(),
+                ..Default::default()
+            })
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            key_bindings.toggle_hidden,
+            KeyBinding::from([Keycode::MouseX1])
+        );
+
+        let mut manager = TestHotkeyManager::new_test(&key_bindings);
+        manager.frame(&[Keycode::MouseLeft]);
+        assert!(!manager.toggle_hidden());
+        manager.frame(&[Keycode::MouseX1]);
+        assert!(manager.toggle_hidden());
+    }
+
+    /// mouse buttons mix with keys like any other key
+    #[test]
+    fn test_mixed_binding() {
+        let key_bindings = KeyBindings {
+            toggle_hidden: [Keycode::LControl, Keycode::MouseMiddle].into(),
+            ..Default::default()
+        };
+        let mut manager = TestHotkeyManager::new_test(&key_bindings);
+        manager.frame(&[Keycode::MouseMiddle]);
+        assert!(!manager.toggle_hidden());
+        manager.frame(&[Keycode::LControl, Keycode::MouseMiddle]);
+        assert!(manager.toggle_hidden());
+    }
+}
+
+#[cfg(test)]
+mod test_rebind {
+    use super::test_keyboard::TestHotkeyManager;
+    use super::*;
+
+    #[test]
+    fn test_with_binding() {
+        let key_bindings = KeyBindings::default()
+            .with_binding("toggle_hidden", vec![Keycode::F9])
+            .unwrap();
+        assert_eq!(key_bindings.toggle_hidden, [Keycode::F9].into());
+        assert_eq!(
+            key_bindings.toggle_adjust,
+            KeyBindings::default().toggle_adjust
+        );
+
+        let key_bindings = key_bindings.with_binding("up", Vec::new()).unwrap();
+        assert_eq!(key_bindings.up, KeyBinding::default());
+    }
+
+    #[test]
+    fn test_with_unknown_binding() {
+        assert!(KeyBindings::default()
+            .with_binding("toggle_everything", vec![Keycode::F9])
+            .is_err());
+        // the slots are a list of bindings, not a single one
+        assert!(KeyBindings::default()
+            .with_binding("save_slots", vec![Keycode::F9])
+            .is_err());
+    }
+
+    /// the new binding works straight away and the old one is gone
+    #[test]
+    fn test_set_key_bindings() {
+        let mut manager = TestHotkeyManager::new_test(&KeyBindings::default());
+        let key_bindings = KeyBindings::default()
+            .with_binding("toggle_hidden", vec![Keycode::F9])
+            .unwrap();
+        manager.set_key_bindings(&key_bindings).unwrap();
+        manager.frame(&[Keycode::LControl, Keycode::H]);
+        assert!(!manager.toggle_hidden());
+        manager.frame(&[Keycode::F9]);
+        assert!(manager.toggle_hidden());
+    }
+
+    /// keys that aren't part of any hotkey still show up, so they can be recorded as a new binding
+    #[test]
+    fn test_pressed_keys() {
+        let mut manager = TestHotkeyManager::new_test(&KeyBindings::default());
+        manager.frame(&[Keycode::F9, Keycode::MouseX1]);
+        assert_eq!(manager.pressed_keys(), [Keycode::F9, Keycode::MouseX1]);
+    }
+}
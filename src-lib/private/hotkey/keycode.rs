@@ -4,13 +4,19 @@
 
 //! Listing of all keycodes and conversions between other keycode types
 
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize};
 
-/// Our own Keycode type, which *should* be a 1:1 mapping with `device_query::Keycode`.
+use crate::private::platform::KeycodeType;
+
+/// Our own Keycode type, which *should* be a 1:1 mapping with `device_query::Keycode`, plus the mouse buttons.
+/// device_query reports those separately, so they get folded in when polling.
 /// You may be wondering why I don't just use `device_query::Keycode`. Well, I can't
 /// `#[derive(Serialize, Deserialize)]` for a type I don't own, so alas I had to make this
 /// incredibly verbose file to allow serde to handle the Keycode enum.
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+///
+/// Keycodes are saved by their variant name, but loading also accepts any capitalization and a handful of friendlier
+/// names, see [`parse_keycode`].
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Keycode {
     Key0,
     Key1,
@@ -68,7 +74,6 @@ pub enum Keycode {
     RShift,
     LAlt,
     RAlt,
-    #[serde(alias = "Meta")] // for backwards compatibility
     LMeta,
     RMeta,
     Enter,
@@ -124,4 +129,287 @@ pub enum Keycode {
     NumpadEquals,
     NumpadEnter,
     NumpadDecimal,
+    MouseLeft,
+    MouseRight,
+    MouseMiddle,
+    /// the "back" side button
+    MouseX1,
+    /// the "forward" side button. This MUST remain the last variant, see [`Keycode::num_variants`].
+    MouseX2,
+}
+
+impl KeycodeType for Keycode {
+    #[inline(always)]
+    fn num_variants() -> usize {
+        Keycode::MouseX2 as usize + 1
+    }
+
+    #[inline(always)]
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// every keycode, in variant order
+const KEYCODES: [Keycode; 116] = [
+    Keycode::Key0,
+    Keycode::Key1,
+    Keycode::Key2,
+    Keycode::Key3,
+    Keycode::Key4,
+    Keycode::Key5,
+    Keycode::Key6,
+    Keycode::Key7,
+    Keycode::Key8,
+    Keycode::Key9,
+    Keycode::A,
+    Keycode::B,
+    Keycode::C,
+    Keycode::D,
+    Keycode::E,
+    Keycode::F,
+    Keycode::G,
+    Keycode::H,
+    Keycode::I,
+    Keycode::J,
+    Keycode::K,
+    Keycode::L,
+    Keycode::M,
+    Keycode::N,
+    Keycode::O,
+    Keycode::P,
+    Keycode::Q,
+    Keycode::R,
+    Keycode::S,
+    Keycode::T,
+    Keycode::U,
+    Keycode::V,
+    Keycode::W,
+    Keycode::X,
+    Keycode::Y,
+    Keycode::Z,
+    Keycode::F1,
+    Keycode::F2,
+    Keycode::F3,
+    Keycode::F4,
+    Keycode::F5,
+    Keycode::F6,
+    Keycode::F7,
+    Keycode::F8,
+    Keycode::F9,
+    Keycode::F10,
+    Keycode::F11,
+    Keycode::F12,
+    Keycode::Escape,
+    Keycode::Space,
+    Keycode::LControl,
+    Keycode::RControl,
+    Keycode::LShift,
+    Keycode::RShift,
+    Keycode::LAlt,
+    Keycode::RAlt,
+    Keycode::LMeta,
+    Keycode::RMeta,
+    Keycode::Enter,
+    Keycode::Up,
+    Keycode::Down,
+    Keycode::Left,
+    Keycode::Right,
+    Keycode::Backspace,
+    Keycode::CapsLock,
+    Keycode::Tab,
+    Keycode::Home,
+    Keycode::End,
+    Keycode::PageUp,
+    Keycode::PageDown,
+    Keycode::Insert,
+    Keycode::Delete,
+    Keycode::Numpad0,
+    Keycode::Numpad1,
+    Keycode::Numpad2,
+    Keycode::Numpad3,
+    Keycode::Numpad4,
+    Keycode::Numpad5,
+    Keycode::Numpad6,
+    Keycode::Numpad7,
+    Keycode::Numpad8,
+    Keycode::Numpad9,
+    Keycode::NumpadSubtract,
+    Keycode::NumpadAdd,
+    Keycode::NumpadDivide,
+    Keycode::NumpadMultiply,
+    Keycode::Grave,
+    Keycode::Minus,
+    Keycode::Equal,
+    Keycode::LeftBracket,
+    Keycode::RightBracket,
+    Keycode::BackSlash,
+    Keycode::Semicolon,
+    Keycode::Apostrophe,
+    Keycode::Comma,
+    Keycode::Dot,
+    Keycode::Slash,
+    Keycode::F13,
+    Keycode::F14,
+    Keycode::F15,
+    Keycode::F16,
+    Keycode::F17,
+    Keycode::F18,
+    Keycode::F19,
+    Keycode::F20,
+    Keycode::Command,
+    Keycode::LOption,
+    Keycode::ROption,
+    Keycode::NumpadEquals,
+    Keycode::NumpadEnter,
+    Keycode::NumpadDecimal,
+    Keycode::MouseLeft,
+    Keycode::MouseRight,
+    Keycode::MouseMiddle,
+    Keycode::MouseX1,
+    Keycode::MouseX2,
+];
+
+/// Friendlier names for keys, on top of the variant names. Matched without caring about capitalization.
+const ALIASES: &[(&str, Keycode)] = &[
+    ("Meta", Keycode::LMeta), // for backwards compatibility
+    ("Ctrl", Keycode::LControl),
+    ("Control", Keycode::LControl),
+    ("LCtrl", Keycode::LControl),
+    ("RCtrl", Keycode::RControl),
+    ("Shift", Keycode::LShift),
+    ("Alt", Keycode::LAlt),
+    ("Win", Keycode::LMeta),
+    ("Windows", Keycode::LMeta),
+    ("Super", Keycode::LMeta),
+    ("LWin", Keycode::LMeta),
+    ("RWin", Keycode::RMeta),
+    ("Cmd", Keycode::Command),
+    ("Option", Keycode::LOption),
+    ("Esc", Keycode::Escape),
+    ("Return", Keycode::Enter),
+    ("Spacebar", Keycode::Space),
+    ("Caps", Keycode::CapsLock),
+    ("Ins", Keycode::Insert),
+    ("Del", Keycode::Delete),
+    ("PgUp", Keycode::PageUp),
+    ("PgDn", Keycode::PageDown),
+    ("PgDown", Keycode::PageDown),
+    ("0", Keycode::Key0),
+    ("1", Keycode::Key1),
+    ("2", Keycode::Key2),
+    ("3", Keycode::Key3),
+    ("4", Keycode::Key4),
+    ("5", Keycode::Key5),
+    ("6", Keycode::Key6),
+    ("7", Keycode::Key7),
+    ("8", Keycode::Key8),
+    ("9", Keycode::Key9),
+    ("Backtick", Keycode::Grave),
+    ("Tilde", Keycode::Grave),
+    ("Period", Keycode::Dot),
+    ("NumpadPlus", Keycode::NumpadAdd),
+    ("NumpadMinus", Keycode::NumpadSubtract),
+    ("LMB", Keycode::MouseLeft),
+    ("RMB", Keycode::MouseRight),
+    ("MMB", Keycode::MouseMiddle),
+    ("Mouse4", Keycode::MouseX1),
+    ("Mouse5", Keycode::MouseX2),
+];
+
+/// Look up a keycode by its variant name or one of the [`ALIASES`], ignoring capitalization and surrounding whitespace
+pub fn parse_keycode(name: &str) -> Option<Keycode> {
+    let name = name.trim();
+    KEYCODES
+        .into_iter()
+        .find(|keycode| format!("{keycode:?}").eq_ignore_ascii_case(name))
+        .or_else(|| {
+            ALIASES
+                .iter()
+                .find(|(alias, _)| alias.eq_ignore_ascii_case(name))
+                .map(|&(_, keycode)| keycode)
+        })
+}
+
+impl<'de> Deserialize<'de> for Keycode {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+        parse_keycode(&name).ok_or_else(|| de::Error::custom(format!("unknown key \"{name}\"")))
+    }
+}
+
+#[cfg(test)]
+mod test_parse_keycode {
+    use super::*;
+
+    #[test]
+    fn test_canonical() {
+        assert_eq!(parse_keycode("LControl"), Some(Keycode::LControl));
+        assert_eq!(parse_keycode("PageUp"), Some(Keycode::PageUp));
+        assert_eq!(parse_keycode("MouseX2"), Some(Keycode::MouseX2));
+    }
+
+    #[test]
+    fn test_case_insensitive() {
+        assert_eq!(parse_keycode("lcontrol"), Some(Keycode::LControl));
+        assert_eq!(parse_keycode("PAGEUP"), Some(Keycode::PageUp));
+        assert_eq!(parse_keycode(" h "), Some(Keycode::H));
+    }
+
+    #[test]
+    fn test_aliases() {
+        assert_eq!(parse_keycode("Ctrl"), Some(Keycode::LControl));
+        assert_eq!(parse_keycode("ctrl"), Some(Keycode::LControl));
+        assert_eq!(parse_keycode("PgUp"), Some(Keycode::PageUp));
+        assert_eq!(parse_keycode("Esc"), Some(Keycode::Escape));
+        assert_eq!(parse_keycode("Meta"), Some(Keycode::LMeta));
+        assert_eq!(parse_keycode("5"), Some(Keycode::Key5));
+        assert_eq!(parse_keycode("mouse4"), Some(Keycode::MouseX1));
+    }
+
+    #[test]
+    fn test_unknown() {
+        assert_eq!(parse_keycode("Hyper"), None);
+        assert_eq!(parse_keycode(""), None);
+        // `+` is shifted `=` on some layouts but its own key on others, so there's no good guess for what "Plus" means
+        assert_eq!(parse_keycode("Plus"), None);
+    }
+
+    /// every keycode's own name has to parse back to it, and none of the aliases may shadow one
+    #[test]
+    fn test_every_keycode() {
+        assert_eq!(KEYCODES.len(), Keycode::num_variants());
+        for (index, keycode) in KEYCODES.into_iter().enumerate() {
+            assert_eq!(keycode.index(), index);
+            assert_eq!(parse_keycode(&format!("{keycode:?}")), Some(keycode));
+        }
+        for (alias, _) in ALIASES {
+            assert!(
+                !KEYCODES
+                    .iter()
+                    .any(|keycode| format!("{keycode:?}").eq_ignore_ascii_case(alias)),
+                "{alias} shadows a keycode"
+            );
+        }
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Wrapper {
+        keys: Vec<Keycode>,
+    }
+
+    /// friendly names load fine, but always get saved as the variant name
+    #[test]
+    fn test_serde() {
+        let wrapper: Wrapper = toml::from_str(r#"keys = ["ctrl", "PgUp"]"#).unwrap();
+        assert_eq!(wrapper.keys, [Keycode::LControl, Keycode::PageUp]);
+        assert_eq!(
+            toml::to_string(&wrapper).unwrap().trim(),
+            r#"keys = ["LControl", "PageUp"]"#
+        );
+        assert!(toml::from_str::<Wrapper>(r#"keys = ["Hyper"]"#).is_err());
+    }
 }
@@ -4,8 +4,11 @@
 
 //! Keyboard reading system built to read hotkeys without a focused window.
 
+#[cfg(test)]
+pub(crate) use hotkey_manager::test_keyboard;
 pub use hotkey_manager::HotkeyManager;
 pub use hotkey_manager::KeyBindings;
+pub use hotkey_manager::ScaleEasing;
 pub(crate) use keycode::Keycode; // needs to be pub(crate) so the platform-specific implementations can implement From conversions
 
 mod hotkey_manager;
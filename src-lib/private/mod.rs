@@ -1,3 +1,6 @@
+pub mod controller;
+#[cfg(feature = "gui")]
+pub mod gui;
 pub mod hotkey;
 pub mod platform;
 pub mod settings;
@@ -0,0 +1,164 @@
+// This file is part of simple-crosshair-overlay and is licenced under the GNU GPL v3.0.
+// See LICENSE file for full text.
+// Copyright © 2023 Michael Ripley
+
+//! Linux implementations, for the few things [`super::generic`] can't do.
+//!
+//! Window focus goes through X11's `_NET_ACTIVE_WINDOW`, and screen pixels are read with `GetImage`. Wayland
+//! deliberately doesn't let clients look at other clients' windows, so on Wayland these fall back to the same no-ops as
+//! [`super::generic`].
+
+use winit::dpi::PhysicalPosition;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{
+    Atom, AtomEnum, ClientMessageEvent, ConnectionExt, EventMask, ImageFormat, ImageOrder, Window,
+};
+use x11rb::rust_connection::RustConnection;
+
+use crate::private::util::image::Color;
+
+/// source indication for `_NET_ACTIVE_WINDOW` requests from pagers and taskbars, which window managers trust more than
+/// regular applications and so don't block with focus stealing prevention
+const SOURCE_PAGER: u32 = 2;
+
+/// X11 window id
+#[derive(Copy, Clone, Debug)]
+pub struct WindowHandle {
+    window: Window,
+}
+
+/// A fresh connection to the X server, along with its root window. `None` on Wayland or if there's no X server.
+///
+/// These get made on demand instead of kept around, as they're only needed when the color picker opens and closes.
+fn connect() -> Option<(RustConnection, Window)> {
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        return None;
+    }
+    let (connection, screen) = x11rb::connect(None).ok()?;
+    let root = connection.setup().roots.get(screen)?.root;
+    Some((connection, root))
+}
+
+fn active_window_atom(connection: &RustConnection) -> Option<Atom> {
+    connection
+        .intern_atom(false, b"_NET_ACTIVE_WINDOW")
+        .ok()?
+        .reply()
+        .ok()
+        .map(|reply| reply.atom)
+}
+
+/// Read `_NET_ACTIVE_WINDOW` off the root window.
+///
+/// Always `None` on Wayland, which doesn't allow this.
+pub fn get_foreground_window() -> Option<WindowHandle> {
+    let (connection, root) = connect()?;
+    let atom = active_window_atom(&connection)?;
+    let reply = connection
+        .get_property(false, root, atom, AtomEnum::WINDOW, 0, 1)
+        .ok()?
+        .reply()
+        .ok()?;
+    let window = reply.value32()?.next()?;
+    (window != x11rb::NONE).then_some(WindowHandle { window })
+}
+
+/// Ask the window manager to activate the window with a `_NET_ACTIVE_WINDOW` client message.
+///
+/// `true` is returned if the request was sent, which doesn't guarantee the window manager honored it. Always no-ops and
+/// returns `false` on Wayland, which doesn't allow this.
+pub fn set_foreground_window(window_handle: WindowHandle) -> bool {
+    let Some((connection, root)) = connect() else {
+        return false;
+    };
+    let Some(atom) = active_window_atom(&connection) else {
+        return false;
+    };
+    let event = ClientMessageEvent::new(
+        32,
+        window_handle.window,
+        atom,
+        [SOURCE_PAGER, x11rb::CURRENT_TIME, 0, 0, 0],
+    );
+    connection
+        .send_event(
+            false,
+            root,
+            EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+            event,
+        )
+        .is_ok()
+        && connection.flush().is_ok()
+}
+
+/// Single screen pixels can be read from the X server. This still fails at runtime on Wayland.
+pub const SCREEN_SAMPLE_SUPPORTED: bool = true;
+
+/// Read the color of one pixel of the root window with `GetImage`.
+///
+/// Returns `None` on Wayland, if the position is off screen, or if the screen isn't a true color 32 bits per pixel
+/// visual, which is what every desktop made this century uses.
+pub fn sample_screen_pixel(position: PhysicalPosition<i32>) -> Option<Color> {
+    let (connection, root) = connect()?;
+    let x = i16::try_from(position.x).ok()?;
+    let y = i16::try_from(position.y).ok()?;
+    let reply = connection
+        .get_image(ImageFormat::Z_PIXMAP, root, x, y, 1, 1, !0)
+        .ok()?
+        .reply()
+        .ok()?;
+
+    let setup = connection.setup();
+    let visual = setup
+        .roots
+        .iter()
+        .filter(|screen| screen.root == root)
+        .flat_map(|screen| &screen.allowed_depths)
+        .flat_map(|depth| &depth.visuals)
+        .find(|visual| visual.visual_id == reply.visual)?;
+    let bytes: [u8; 4] = reply.data.get(..4)?.try_into().ok()?;
+    let pixel = if setup.image_byte_order == ImageOrder::MSB_FIRST {
+        u32::from_be_bytes(bytes)
+    } else {
+        u32::from_le_bytes(bytes)
+    };
+    Some(Color::from_rgb(
+        mask_channel(pixel, visual.red_mask),
+        mask_channel(pixel, visual.green_mask),
+        mask_channel(pixel, visual.blue_mask),
+    ))
+}
+
+/// Pull one channel out of a pixel using its visual's mask, scaled to 8 bits
+fn mask_channel(pixel: u32, mask: u32) -> u8 {
+    if mask == 0 {
+        return 0;
+    }
+    let shift = mask.trailing_zeros();
+    let bits = (mask >> shift).count_ones();
+    let value = (pixel & mask) >> shift;
+    if bits >= 8 {
+        (value >> (bits - 8)) as u8
+    } else {
+        (value * 255 / ((1 << bits) - 1)) as u8
+    }
+}
+
+#[cfg(test)]
+mod test_sample_screen_pixel {
+    use super::*;
+
+    #[test]
+    fn test_mask_channel() {
+        // the usual 24 bit true color layout
+        let pixel = 0x00123456;
+        assert_eq!(mask_channel(pixel, 0xFF0000), 0x12);
+        assert_eq!(mask_channel(pixel, 0x00FF00), 0x34);
+        assert_eq!(mask_channel(pixel, 0x0000FF), 0x56);
+        // 30 bit deep color gets its extra bits dropped
+        assert_eq!(mask_channel(0x3FF << 20, 0x3FF << 20), 0xFF);
+        // 5 bit channels get stretched to fill 8 bits
+        assert_eq!(mask_channel(0b11111, 0b11111), 0xFF);
+        assert_eq!(mask_channel(0, 0), 0);
+    }
+}
@@ -0,0 +1,34 @@
+// This file is part of simple-crosshair-overlay and is licenced under the GNU GPL v3.0.
+// See LICENSE file for full text.
+// Copyright © 2023 Michael Ripley
+
+//! macOS implementations, for the few things [`super::generic`] can't do.
+//!
+//! macOS focuses whole applications rather than single windows, so a "window" here is really the frontmost app. This
+//! goes through `NSWorkspace`, which unlike the Accessibility API doesn't need any permissions from the user.
+
+use objc2_app_kit::{NSApplicationActivationOptions, NSRunningApplication, NSWorkspace};
+
+/// process id of an application. The app itself can't be kept around, as handles must be `Copy`.
+#[derive(Copy, Clone, Debug)]
+pub struct WindowHandle {
+    pid: i32,
+}
+
+/// The frontmost application, if there is one and it has a process id
+pub fn get_foreground_window() -> Option<WindowHandle> {
+    let application = NSWorkspace::sharedWorkspace().frontmostApplication()?;
+    // apps without a pid report -1, and couldn't be looked up again anyways
+    let pid = application.processIdentifier();
+    (pid > 0).then_some(WindowHandle { pid })
+}
+
+/// Bring the application back to the front. This only works while we're the active app, which is the case after
+/// color picking as we focus ourselves for it.
+///
+/// `true` is returned if the request was sent. `false` means the app has quit in the meantime.
+pub fn set_foreground_window(window_handle: WindowHandle) -> bool {
+    NSRunningApplication::runningApplicationWithProcessIdentifier(window_handle.pid).is_some_and(
+        |application| application.activateWithOptions(NSApplicationActivationOptions::empty()),
+    )
+}
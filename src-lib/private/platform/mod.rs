@@ -6,16 +6,43 @@
 
 use std::fmt::Debug;
 
-pub use generic::HotkeyManager;
 #[cfg(not(target_os = "windows"))]
+pub use generic::{
+    attach_parent_console, capture_screen_region, REASSERT_TOPMOST_SUPPORTED,
+    SCREEN_CAPTURE_SUPPORTED,
+};
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
 pub use generic::{get_foreground_window, set_foreground_window, WindowHandle};
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub use generic::{sample_screen_pixel, SCREEN_SAMPLE_SUPPORTED};
+pub use generic::{Controller, HotkeyManager};
+pub use generic::{MouseReader, MouseSnapshot};
+#[cfg(target_os = "linux")]
+pub use linux::{
+    get_foreground_window, sample_screen_pixel, set_foreground_window, WindowHandle,
+    SCREEN_SAMPLE_SUPPORTED,
+};
+#[cfg(target_os = "macos")]
+pub use macos::{get_foreground_window, set_foreground_window, WindowHandle};
+#[cfg(unix)]
+pub use unix::IpcListener;
 #[cfg(target_os = "windows")]
-pub use windows::{get_foreground_window, set_foreground_window, WindowHandle};
+pub use windows::{
+    attach_parent_console, capture_screen_region, get_foreground_window, sample_screen_pixel,
+    set_foreground_window, IpcListener, WindowHandle, REASSERT_TOPMOST_SUPPORTED,
+    SCREEN_CAPTURE_SUPPORTED, SCREEN_SAMPLE_SUPPORTED,
+};
 
 use crate::private::hotkey::Keycode;
 
 pub mod generic; // pub so benchmarking can access
 
+#[cfg(target_os = "linux")]
+pub mod linux;
+#[cfg(target_os = "macos")]
+pub mod macos;
+#[cfg(unix)]
+pub mod unix;
 #[cfg(target_os = "windows")]
 pub mod windows; // pub so benchmarking can access
 
@@ -30,7 +57,7 @@ where
     fn get_state(&self) -> &[T];
 }
 
-pub trait KeycodeType: From<Keycode> + TryInto<Keycode> + Debug {
+pub trait KeycodeType: From<Keycode> + TryInto<Keycode> + Copy + Debug {
     /// maximum possible number of distinct keycode variants
     fn num_variants() -> usize;
 
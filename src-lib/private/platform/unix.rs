@@ -0,0 +1,111 @@
+// This file is part of simple-crosshair-overlay and is licenced under the GNU GPL v3.0.
+// See LICENSE file for full text.
+// Copyright © 2023 Michael Ripley
+
+//! Implementations shared by every Unix, whichever of [`super::linux`], [`super::macos`], or [`super::generic`] covers
+//! the rest.
+
+use std::fs;
+use std::io::{self, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::private::util::ipc;
+
+/// how long a write to an IPC client may block before we give up on that client
+const IPC_WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Accepts local IPC connections on a Unix domain socket next to the config file
+pub struct IpcListener {
+    listener: UnixListener,
+}
+
+impl IpcListener {
+    /// Start listening on the socket for the config at `config_path`. A socket left behind by a run that didn't shut
+    /// down cleanly gets replaced, but one that another instance is still listening on is left alone.
+    pub fn bind(config_path: &Path) -> io::Result<IpcListener> {
+        let path = ipc::socket_path(config_path);
+        match UnixStream::connect(&path) {
+            Ok(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::AddrInUse,
+                    format!(
+                        "Another instance is already listening on {}",
+                        path.display()
+                    ),
+                ))
+            }
+            // nobody's listening, so whatever's there is stale
+            Err(e) if e.kind() == io::ErrorKind::ConnectionRefused => fs::remove_file(&path)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        Ok(IpcListener {
+            listener: UnixListener::bind(path)?,
+        })
+    }
+
+    /// Block until the next client connects
+    pub fn accept(&self) -> io::Result<Box<dyn Write + Send>> {
+        let (stream, _) = self.listener.accept()?;
+        // a client that stops reading shouldn't tie up its writer thread forever
+        stream.set_write_timeout(Some(IPC_WRITE_TIMEOUT))?;
+        Ok(Box::new(stream))
+    }
+}
+
+#[cfg(test)]
+mod test_ipc_listener {
+    use std::io::Read;
+
+    use super::*;
+
+    /// a fresh directory to put a config path in, so tests don't fight over sockets
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let directory = std::env::temp_dir().join(format!(
+            "DELETEME_simple-crosshair-overlay-test-ipc-{name}-{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    #[test]
+    fn test_accept() {
+        let directory = temp_dir("accept");
+        let config_path = directory.join("config.toml");
+        let listener = IpcListener::bind(&config_path).unwrap();
+        let mut client = UnixStream::connect(ipc::socket_path(&config_path)).unwrap();
+        listener.accept().unwrap().write_all(b"hi\n").unwrap();
+        let mut buf = [0; 3];
+        client.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"hi\n");
+        drop(listener);
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    /// a second instance on the same config doesn't take the socket away from the first
+    #[test]
+    fn test_in_use() {
+        let directory = temp_dir("in-use");
+        let config_path = directory.join("config.toml");
+        let _listener = IpcListener::bind(&config_path).unwrap();
+        let error = IpcListener::bind(&config_path).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::AddrInUse);
+        assert!(UnixStream::connect(ipc::socket_path(&config_path)).is_ok());
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    /// a socket nobody's listening on gets replaced
+    #[test]
+    fn test_stale() {
+        let directory = temp_dir("stale");
+        let config_path = directory.join("config.toml");
+        drop(UnixListener::bind(ipc::socket_path(&config_path)).unwrap());
+        assert!(ipc::socket_path(&config_path).exists());
+        let _listener = IpcListener::bind(&config_path).unwrap();
+        assert!(UnixStream::connect(ipc::socket_path(&config_path)).is_ok());
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
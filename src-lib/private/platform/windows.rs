@@ -5,8 +5,22 @@
 //! Windows-specific implementations.
 //! This is only in the module tree on Windows targets.
 
-use winapi::shared::windef::HWND;
-use winapi::um::winuser;
+use std::ffi::OsStr;
+use std::fs::File;
+use std::io::{self, Write};
+use std::iter;
+use std::os::windows::ffi::OsStrExt;
+use std::os::windows::io::{FromRawHandle, RawHandle};
+use std::path::Path;
+use std::{mem, ptr};
+
+use winapi::shared::windef::{HGDIOBJ, HWND};
+use winapi::shared::winerror;
+use winapi::um::{handleapi, namedpipeapi, winbase, wincon, wingdi, winuser};
+use winit::dpi::PhysicalPosition;
+
+use crate::private::util::image::Color;
+use crate::private::util::ipc;
 
 /// null-safe window handle
 #[derive(Copy, Clone, Debug)]
@@ -28,6 +42,54 @@ impl WindowHandle {
     }
 }
 
+/// Accepts local IPC connections on a named pipe. Each client gets its own pipe instance.
+pub struct IpcListener {
+    /// null-terminated UTF-16 pipe name
+    name: Vec<u16>,
+}
+
+impl IpcListener {
+    /// Listen on the pipe for the config at `config_path`. Named pipes don't exist until someone waits on one, so this
+    /// can't fail.
+    pub fn bind(config_path: &Path) -> io::Result<IpcListener> {
+        let name = OsStr::new(&ipc::pipe_name(config_path))
+            .encode_wide()
+            .chain(iter::once(0))
+            .collect();
+        Ok(IpcListener { name })
+    }
+
+    /// Block until the next client connects.
+    /// Wraps https://learn.microsoft.com/en-us/windows/win32/api/namedpipeapi/nf-namedpipeapi-connectnamedpipe
+    pub fn accept(&self) -> io::Result<Box<dyn Write + Send>> {
+        unsafe {
+            let handle = namedpipeapi::CreateNamedPipeW(
+                self.name.as_ptr(),
+                winbase::PIPE_ACCESS_OUTBOUND,
+                winbase::PIPE_TYPE_BYTE | winbase::PIPE_WAIT | winbase::PIPE_REJECT_REMOTE_CLIENTS,
+                winbase::PIPE_UNLIMITED_INSTANCES,
+                4096,
+                0,
+                0,
+                ptr::null_mut(),
+            );
+            if handle == handleapi::INVALID_HANDLE_VALUE {
+                return Err(io::Error::last_os_error());
+            }
+            // the File owns the handle from here on, so it gets closed on every path out
+            let pipe = File::from_raw_handle(handle as RawHandle);
+            if namedpipeapi::ConnectNamedPipe(handle, ptr::null_mut()) == 0 {
+                // a client that connected between the two calls is still a success
+                let error = io::Error::last_os_error();
+                if error.raw_os_error() != Some(winerror::ERROR_PIPE_CONNECTED as i32) {
+                    return Err(error);
+                }
+            }
+            Ok(Box::new(pipe))
+        }
+    }
+}
+
 /// wrapper around https://learn.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-getforegroundwindow
 ///
 /// this converts null pointers into None
@@ -47,3 +109,103 @@ pub fn get_foreground_window() -> Option<WindowHandle> {
 pub fn set_foreground_window(window_handle: WindowHandle) -> bool {
     unsafe { winuser::SetForegroundWindow(window_handle.hwnd()) != 0 }
 }
+
+/// We're a GUI subsystem app, so we don't get a console by default. This hooks stdout up to the console we were launched
+/// from, if there is one. Failure just means there's nowhere for output to go.
+pub fn attach_parent_console() {
+    unsafe {
+        wincon::AttachConsole(wincon::ATTACH_PARENT_PROCESS);
+    }
+}
+
+/// Screen capture is implemented with GDI on Windows
+pub const SCREEN_CAPTURE_SUPPORTED: bool = true;
+
+/// Other topmost windows can cover ours on Windows, so it's worth periodically taking the top spot back
+pub const REASSERT_TOPMOST_SUPPORTED: bool = true;
+
+/// Single screen pixels are read with GDI on Windows
+pub const SCREEN_SAMPLE_SUPPORTED: bool = true;
+
+/// Read the color of one pixel of the screen. Returns `None` if the position is off screen or the read fails.
+pub fn sample_screen_pixel(position: PhysicalPosition<i32>) -> Option<Color> {
+    unsafe {
+        let screen_dc = winuser::GetDC(ptr::null_mut());
+        if screen_dc.is_null() {
+            return None;
+        }
+        let pixel = wingdi::GetPixel(screen_dc, position.x, position.y);
+        winuser::ReleaseDC(ptr::null_mut(), screen_dc);
+        (pixel != wingdi::CLR_INVALID).then(|| {
+            Color::from_rgb(
+                wingdi::GetRValue(pixel),
+                wingdi::GetGValue(pixel),
+                wingdi::GetBValue(pixel),
+            )
+        })
+    }
+}
+
+/// Copy a `size` by `size` square of the screen starting at `origin` into a buffer of opaque ARGB pixels.
+///
+/// This does not use `CAPTUREBLT`, so layered windows (like our own overlay) are left out of the capture.
+/// Returns `None` if any part of the capture fails.
+pub fn capture_screen_region(origin: PhysicalPosition<i32>, size: u32) -> Option<Vec<u32>> {
+    let side = i32::try_from(size).ok()?;
+    unsafe {
+        let screen_dc = winuser::GetDC(ptr::null_mut());
+        if screen_dc.is_null() {
+            return None;
+        }
+        let memory_dc = wingdi::CreateCompatibleDC(screen_dc);
+        let bitmap = wingdi::CreateCompatibleBitmap(screen_dc, side, side);
+
+        let previous_object = wingdi::SelectObject(memory_dc, bitmap as HGDIOBJ);
+        let copied = wingdi::BitBlt(
+            memory_dc,
+            0,
+            0,
+            side,
+            side,
+            screen_dc,
+            origin.x,
+            origin.y,
+            wingdi::SRCCOPY,
+        ) != 0;
+        // the bitmap must not be selected into a DC when calling GetDIBits
+        wingdi::SelectObject(memory_dc, previous_object);
+
+        let mut info: wingdi::BITMAPINFO = mem::zeroed();
+        info.bmiHeader.biSize = mem::size_of::<wingdi::BITMAPINFOHEADER>() as u32;
+        info.bmiHeader.biWidth = side;
+        info.bmiHeader.biHeight = -side; // negative height means top-down rows, like our buffers
+        info.bmiHeader.biPlanes = 1;
+        info.bmiHeader.biBitCount = 32;
+        info.bmiHeader.biCompression = wingdi::BI_RGB;
+
+        let mut pixels = vec![0u32; size as usize * size as usize];
+        let lines = wingdi::GetDIBits(
+            memory_dc,
+            bitmap,
+            0,
+            size,
+            pixels.as_mut_ptr().cast(),
+            &mut info,
+            wingdi::DIB_RGB_COLORS,
+        );
+
+        wingdi::DeleteObject(bitmap as HGDIOBJ);
+        wingdi::DeleteDC(memory_dc);
+        winuser::ReleaseDC(ptr::null_mut(), screen_dc);
+
+        if copied && lines == side {
+            // GDI leaves the alpha byte zeroed, and the screen is opaque
+            for pixel in pixels.iter_mut() {
+                *pixel |= 0xFF000000;
+            }
+            Some(pixels)
+        } else {
+            None
+        }
+    }
+}
@@ -4,28 +4,75 @@
 
 //! Relating to the settings file loaded on app start and persisted on app close
 
+use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::{fs, io};
 
 use debug_print::debug_println;
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 use winit::dpi::{PhysicalPosition, PhysicalSize};
-use winit::window::Window;
+use winit::window::{Window, WindowLevel};
 
-use crate::private::hotkey::KeyBindings;
+use crate::private::hotkey::{KeyBindings, ScaleEasing};
 use crate::private::util::dialog::show_warning;
-use crate::private::util::image::{self, Image};
-use crate::private::util::numeric::fps_to_tick_interval;
+use crate::private::util::ghost::{self, Ghost};
+use crate::private::util::image::animation::{self, Animation};
+use crate::private::util::image::{self, Color, Image};
+use crate::private::util::magnifier;
+use crate::private::util::monitor::{self, MonitorInfo, MonitorSelector, SnapAnchor};
+use crate::private::util::numeric::{fps_to_tick_interval, step_hue};
+use crate::private::util::osd::{self, Osd};
 
+/// Bump this and add a step to [`migrate`] whenever an old config needs more than serde defaults to load correctly
+const CONFIG_VERSION: u32 = 1;
 const DEFAULT_OFFSET_X: i32 = 0;
 const DEFAULT_OFFSET_Y: i32 = 0;
 const DEFAULT_SIZE: u32 = 16;
 const DEFAULT_FPS: u32 = 60;
 const DEFAULT_MONITOR_INDEX: usize = 0;
 const DEFAULT_MONITOR: u32 = (DEFAULT_MONITOR_INDEX as u32) + 1;
-const DEFAULT_COLOR: u32 = 0xB2FF0000; // 70% alpha red;
+const DEFAULT_COLOR: Color = Color::from_argb(0xB2FF0000); // 70% alpha red;
+const DEFAULT_BATCH_WARNINGS: bool = true;
+const DEFAULT_ASPECT_RATIO: f32 = 1.0;
+const DEFAULT_ASPECT_LOCKED: bool = true;
+const DEFAULT_FIXED_SCALE_STEP: u32 = 1;
+const DEFAULT_RING_RADIUS: u32 = 8;
+const DEFAULT_RING_THICKNESS: u32 = 1;
+const DEFAULT_DOT_RADIUS: u32 = 1;
+const DEFAULT_INNER_RADIUS: u32 = 4;
+const DEFAULT_OUTER_RADIUS: u32 = 8;
+const DEFAULT_CIRCLE_THICKNESS: u32 = 1;
+const DEFAULT_HOTKEYS_ENABLED: bool = true;
+const DEFAULT_RESET_CLEARS_IMAGE: bool = true;
+const DEFAULT_LINE_THICKNESS: u32 = 1;
+const DEFAULT_OUTLINE_THICKNESS: u32 = 0;
+const DEFAULT_OUTLINE_COLOR: Color = Color::from_argb(0xFF000000); // opaque black
+const DEFAULT_IMAGE_SCALE: f32 = 1.0;
+/// loaded images can't be scaled down any further than this
+const MIN_IMAGE_SCALE: f32 = 0.1;
+const DEFAULT_OPACITY: u8 = u8::MAX;
+/// crosshair colors more transparent than this are practically invisible
+const MIN_VISIBLE_ALPHA: u8 = 16;
+const DEFAULT_PREVENT_INVISIBLE: bool = false;
+/// degrees of hue per second that `color_cycle` goes through, so a full rainbow takes 6 seconds
+const DEFAULT_CYCLE_SPEED: f32 = 60.0;
+/// how much the opacity hotkeys change the opacity by per press
+const OPACITY_STEP: u8 = 16;
+const MIN_FPS: u32 = 1;
+/// going any faster than this just burns CPU
+const MAX_FPS: u32 = 240;
+/// how much the fps hotkeys change the fps by per press
+const FPS_STEP: u32 = 10;
+/// [`Units::Percent`] values are in hundredths of a percent, so this is 100%
+const PERCENT_SCALE: i64 = 100 * 100;
+/// window sizes get converted to i32 for positioning math, so they can't go any bigger than this
+const MAX_SIZE: u32 = i32::MAX as u32;
+/// Sizes in the config file get clamped to this. It's bigger than any monitor, but small enough that the window buffer
+/// still fits in memory.
+const MAX_CONFIG_SIZE: u32 = 16384;
 
 // needed for serde, as it can't read constants directly
 const fn default_fps() -> u32 {
@@ -36,36 +83,478 @@ const fn default_monitor() -> u32 {
     DEFAULT_MONITOR
 }
 
+const fn default_batch_warnings() -> bool {
+    DEFAULT_BATCH_WARNINGS
+}
+
+const fn default_aspect_ratio() -> f32 {
+    DEFAULT_ASPECT_RATIO
+}
+
+const fn default_aspect_locked() -> bool {
+    DEFAULT_ASPECT_LOCKED
+}
+
+const fn default_fixed_scale_step() -> u32 {
+    DEFAULT_FIXED_SCALE_STEP
+}
+
+const fn default_ring_radius() -> u32 {
+    DEFAULT_RING_RADIUS
+}
+
+const fn default_ring_thickness() -> u32 {
+    DEFAULT_RING_THICKNESS
+}
+
+const fn default_dot_radius() -> u32 {
+    DEFAULT_DOT_RADIUS
+}
+
+const fn default_inner_radius() -> u32 {
+    DEFAULT_INNER_RADIUS
+}
+
+const fn default_outer_radius() -> u32 {
+    DEFAULT_OUTER_RADIUS
+}
+
+const fn default_circle_thickness() -> u32 {
+    DEFAULT_CIRCLE_THICKNESS
+}
+
+const fn default_hotkeys_enabled() -> bool {
+    DEFAULT_HOTKEYS_ENABLED
+}
+
+const fn default_line_thickness() -> u32 {
+    DEFAULT_LINE_THICKNESS
+}
+
+const fn default_outline_thickness() -> u32 {
+    DEFAULT_OUTLINE_THICKNESS
+}
+
+const fn default_outline_color() -> Color {
+    DEFAULT_OUTLINE_COLOR
+}
+
+const fn default_reset_clears_image() -> bool {
+    DEFAULT_RESET_CLEARS_IMAGE
+}
+
+const fn default_prevent_invisible() -> bool {
+    DEFAULT_PREVENT_INVISIBLE
+}
+
+const fn default_premultiply_alpha() -> bool {
+    image::PREMULTIPLY_ALPHA_DEFAULT
+}
+
+const fn default_image_scale() -> f32 {
+    DEFAULT_IMAGE_SCALE
+}
+
+const fn default_cycle_speed() -> f32 {
+    DEFAULT_CYCLE_SPEED
+}
+
+const fn default_opacity() -> u8 {
+    DEFAULT_OPACITY
+}
+
 lazy_static! {
     pub static ref CONFIG_PATH: PathBuf =
         directories::ProjectDirs::from("dev.zkxs", "", "simple-crosshair-overlay")
             .unwrap()
             .config_dir()
             .join("config.toml");
+    /// directory holding the save slot snapshots
+    static ref SLOT_DIRECTORY: PathBuf = CONFIG_PATH.parent().unwrap().join("slots");
+}
+
+/// The config file from `--config <path>` or `--config=<path>`, if that was passed. Otherwise [`CONFIG_PATH`] is used.
+pub fn config_path_arg<I>(args: I) -> Option<PathBuf>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--config" {
+            return args.next().map(PathBuf::from);
+        } else if let Some(path) = arg.strip_prefix("--config=") {
+            return Some(path.into());
+        }
+    }
+    None
+}
+
+/// What a config file is written in, going by its extension
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ConfigFormat {
+    Toml,
+    /// for configs generated by scripts, as JSON is easier to produce than TOML
+    Json,
+}
+
+impl ConfigFormat {
+    /// `.json` files are JSON, and everything else is TOML
+    fn of(path: &Path) -> ConfigFormat {
+        match path.extension() {
+            Some(extension) if extension.eq_ignore_ascii_case("json") => ConfigFormat::Json,
+            _ => ConfigFormat::Toml,
+        }
+    }
+}
+
+/// Load an image file, along with all of its frames if it's an animated PNG
+fn load_image_file(
+    path: &Path,
+    premultiply: bool,
+    gamma_correct: bool,
+    strict: bool,
+) -> io::Result<(Box<Image>, Option<Animation>)> {
+    let image = image::load_image(path, premultiply, gamma_correct, strict)?;
+    let animation = animation::load_animation(path, premultiply, gamma_correct)?;
+    Ok((image, animation))
 }
 
 /// The actual persisted settings struct
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Clone)]
 pub struct PersistedSettings {
+    /// the [`CONFIG_VERSION`] this config was migrated to. Configs from before versioning count as version 0.
+    #[serde(default)]
+    config_version: u32,
     pub window_dx: i32,
     pub window_dy: i32,
     pub window_width: u32,
     pub window_height: u32,
     #[serde(with = "crate::private::util::custom_serializer::argb_color")]
-    color: u32,
+    color: Color,
     #[serde(default = "default_fps")]
     fps: u32,
+    /// fps to drop down to after a couple of seconds without anything happening. Unset always ticks at `fps`.
+    #[serde(default)]
+    pub idle_fps: Option<u32>,
     image_path: Option<PathBuf>,
     #[serde(default)]
     pub key_bindings: KeyBindings,
     /// 1-indexed monitor to render the overlay to
     #[serde(default = "default_monitor")]
     monitor: u32,
+    /// name of the monitor last picked. While a monitor by this name is connected it wins over `monitor`, which can
+    /// point at a different screen once the OS reorders them.
+    #[serde(default)]
+    monitor_name: Option<String>,
+    /// 1-indexed monitor last picked for each monitor setup, keyed by [`monitor::layout_key`]. Setups that aren't in
+    /// here use `monitor`.
+    #[serde(default)]
+    monitor_layouts: BTreeMap<String, u32>,
+    /// how the scale speed ramps up while a scale hotkey is held
+    #[serde(default)]
+    pub scale_easing: ScaleEasing,
+    /// side-length of the interactive region in the top-left of the window that can be used to drag
+    /// the overlay around while in adjust mode. 0 keeps the overlay fully click-through.
+    #[serde(default)]
+    pub drag_handle_size: u32,
+    /// keep the overlay centered on the mouse cursor instead of the monitor. The offset is ignored while this is on.
+    #[serde(default)]
+    pub follow_cursor: bool,
+    /// monitors to show the overlay on, as 1-indexed numbers or monitor names. When empty, only `monitor` is used.
+    #[serde(default)]
+    pub monitors: Vec<MonitorSelector>,
+    /// which of the `monitors` overlays to keep above the others. When unset, later overlays in `monitors` end up on top.
+    #[serde(default)]
+    pub top_overlay: Option<MonitorSelector>,
+    /// periodically put the overlay back on top, in case another always-on-top window covered it. Windows only.
+    #[serde(default)]
+    pub reassert_topmost: bool,
+    /// how the overlay stacks against other windows. `Normal` is an escape hatch for setups where always-on-top
+    /// flickers or fights with fullscreen-exclusive games.
+    #[serde(default)]
+    pub window_level: OverlayLevel,
+    /// combine warnings that show up at about the same time into a single dialog
+    #[serde(default = "default_batch_warnings")]
+    pub batch_warnings: bool,
+    /// width / height of the generated crosshair. Scaling changes the height and derives the width from this.
+    #[serde(default = "default_aspect_ratio")]
+    pub aspect_ratio: f32,
+    /// when `false`, the generated crosshair's width and height are set independently and `aspect_ratio` is ignored
+    #[serde(default = "default_aspect_locked")]
+    pub aspect_locked: bool,
+    /// hide the overlay after it's been shown this many seconds without any interaction
+    #[serde(
+        default,
+        with = "crate::private::util::custom_serializer::optional_seconds"
+    )]
+    pub auto_hide_after: Option<Duration>,
+    /// how many pixels of height the fixed scale hotkeys change the crosshair by per press
+    #[serde(default = "default_fixed_scale_step")]
+    pub fixed_scale_step: u32,
+    /// bump crosshair colors that are too transparent to see up to [`MIN_VISIBLE_ALPHA`]
+    #[serde(default = "default_prevent_invisible")]
+    pub prevent_invisible: bool,
+    /// premultiply alpha in the window buffer. Some capture software wants straight alpha, even on Windows.
+    #[serde(default = "default_premultiply_alpha")]
+    pub premultiply_alpha: bool,
+    /// premultiply in linear light instead of directly on the sRGB values. Slower, but more accurate.
+    #[serde(default)]
+    pub gamma_correct: bool,
+    /// color to use while the spotlight key is held. When unset the crosshair color is used at full opacity.
+    #[serde(
+        default,
+        with = "crate::private::util::custom_serializer::optional_argb_color"
+    )]
+    spotlight_color: Option<Color>,
+    /// which generated crosshair to show when no image is loaded
+    #[serde(default)]
+    pub crosshair_mode: RenderMode,
+    /// outside radius of the [`RenderMode::CircleDot`] ring
+    #[serde(default = "default_ring_radius")]
+    pub ring_radius: u32,
+    /// thickness of the [`RenderMode::CircleDot`] ring
+    #[serde(default = "default_ring_thickness")]
+    pub ring_thickness: u32,
+    /// radius of the [`RenderMode::CircleDot`] center dot
+    #[serde(default = "default_dot_radius")]
+    pub dot_radius: u32,
+    /// radius of the hole in the [`RenderMode::Donut`]. 0 means no hole.
+    #[serde(default = "default_inner_radius")]
+    pub inner_radius: u32,
+    /// outside radius of the [`RenderMode::Donut`]
+    #[serde(default = "default_outer_radius")]
+    pub outer_radius: u32,
+    /// thickness of the [`RenderMode::Circle`] ring
+    #[serde(default = "default_circle_thickness")]
+    pub circle_thickness: u32,
+    /// when `false`, every hotkey except the one that toggles this is ignored
+    #[serde(default = "default_hotkeys_enabled")]
+    pub hotkeys_enabled: bool,
+    /// what `window_dx`, `window_dy`, and the crosshair size are measured in. Configs from before this setting existed
+    /// are in pixels, so that's the default.
+    #[serde(default)]
+    pub units: Units,
+    /// which way to round when the monitor's center falls between two pixels
+    #[serde(default)]
+    pub center_rounding: CenterRounding,
+    /// what to draw at the exact center of the generated crosshairs, on top of everything else
+    #[serde(default)]
+    pub center_marker: CenterMarker,
+    /// automatically adjust the generated crosshair's color to stand out against the game's background
+    #[serde(default)]
+    pub contrast_mode: ContrastMode,
+    /// fill the background with this color instead of leaving it transparent, so capture software can chroma-key the
+    /// overlay out. The window is opaque while this is set.
+    #[serde(
+        default,
+        with = "crate::private::util::custom_serializer::optional_argb_color"
+    )]
+    pub transparency_key: Option<Color>,
+    /// when `false`, resetting keeps the loaded image and only resets the position, size, and color
+    #[serde(default = "default_reset_clears_image")]
+    pub reset_clears_image: bool,
+    /// thickness of the [`RenderMode::Crosshair`] lines. Lines get an extra pixel when needed to stay centered.
+    #[serde(default = "default_line_thickness", alias = "crosshair_thickness")]
+    pub line_thickness: u32,
+    /// how far the [`RenderMode::Crosshair`] outline sticks out on each side of the lines. 0 means no outline.
+    #[serde(default = "default_outline_thickness")]
+    pub outline_thickness: u32,
+    #[serde(
+        default = "default_outline_color",
+        with = "crate::private::util::custom_serializer::argb_color"
+    )]
+    outline_color: Color,
+    /// how far right and down the [`RenderMode::Crosshair`] drop shadow is drawn
+    #[serde(default)]
+    pub shadow_offset: (i32, i32),
+    /// color of the [`RenderMode::Crosshair`] drop shadow. Unset means no shadow.
+    #[serde(
+        default,
+        with = "crate::private::util::custom_serializer::optional_argb_color"
+    )]
+    shadow_color: Option<Color>,
+    /// how many pixels of the [`RenderMode::Crosshair`] lines to cut away on each side of the center. 0 means no gap.
+    #[serde(default)]
+    pub center_gap: u32,
+    /// draw a small dot where each arm starts, at the edge of the `center_gap`
+    #[serde(default)]
+    pub gap_dots: bool,
+    /// how much the [`RenderMode::Crosshair`] arms thin out, from -1 to 1. Positive values thin toward the tips,
+    /// negative values thin toward the center, and 0 keeps the arms uniform.
+    #[serde(default)]
+    pub taper: f32,
+    /// only accept RGBA PNGs instead of converting other color types
+    #[serde(default)]
+    pub strict_png: bool,
+    /// when set, images loaded from the tray that are bigger than this fraction of the monitor get shrunk to fit
+    #[serde(default)]
+    pub auto_fit_image: Option<f32>,
+    /// briefly show a message under the crosshair when a hotkey action fires
+    #[serde(default)]
+    pub osd: bool,
+    /// a second, smaller crosshair drawn at a fixed offset from the main one
+    #[serde(default)]
+    pub ghost: Option<Ghost>,
+    /// start in adjust mode if the overlay was in adjust mode when it last exited
+    #[serde(default)]
+    pub persist_adjust_mode: bool,
+    /// whether adjust mode was on at the last exit. Only saved while `persist_adjust_mode` is on.
+    #[serde(default)]
+    adjust_mode: bool,
+    /// start locked if the overlay was locked when it last exited
+    #[serde(default)]
+    pub persist_lock: bool,
+    /// whether the overlay was locked at the last exit. Only saved while `persist_lock` is on.
+    #[serde(default)]
+    locked: bool,
+    /// frames the movement keys must be held before the crosshair starts moving, so accidental taps are ignored
+    #[serde(default)]
+    pub move_deadzone_frames: u32,
+    /// start with the overlay hidden, because it was hidden when it last exited
+    #[serde(default)]
+    start_hidden: bool,
+    /// the most recently loaded image, which is remembered even after the image gets unloaded
+    #[serde(default)]
+    last_image_path: Option<PathBuf>,
+    /// how much bigger than its native size the loaded image is drawn. The scale hotkeys change this while an image is
+    /// shown.
+    #[serde(default = "default_image_scale")]
+    pub image_scale: f32,
+    /// dims the whole overlay, images included, on top of any per-pixel alpha. 255 is fully opaque.
+    #[serde(default = "default_opacity")]
+    pub opacity: u8,
+    /// crosshairs to switch between with the "next_profile" and "prev_profile" hotkeys. The active one lives in the
+    /// settings above, and gets written back here on a switch or save.
+    #[serde(default)]
+    profiles: Vec<ProfileSettings>,
+    /// 0-indexed profile that's currently active
+    #[serde(default)]
+    active_profile: usize,
+    /// share the overlay's state with other local programs over a Unix socket or named pipe
+    #[serde(default)]
+    pub ipc_enabled: bool,
+    /// slowly cycle the generated crosshair through every hue, keeping the alpha of `color`
+    #[serde(default)]
+    pub color_cycle: bool,
+    /// how fast `color_cycle` goes, in degrees of hue per second. Negative values cycle backwards.
+    #[serde(default = "default_cycle_speed")]
+    pub cycle_speed: f32,
 }
 
 impl PersistedSettings {
-    fn load(self) -> Settings {
-        let color = image::premultiply_alpha(self.color);
+    fn load(mut self) -> Settings {
+        for (name, size) in [
+            ("window_width", &mut self.window_width),
+            ("window_height", &mut self.window_height),
+        ] {
+            let clamped = (*size).clamp(1, MAX_CONFIG_SIZE);
+            if clamped != *size {
+                show_warning(format!(
+                    "Invalid {name} {size} in config. It must be between 1 and {MAX_CONFIG_SIZE}. Using {clamped} instead."
+                ));
+                *size = clamped;
+            }
+        }
+
+        let clamped = self.fps.clamp(MIN_FPS, MAX_FPS);
+        if clamped != self.fps {
+            show_warning(format!(
+                "Invalid fps {} in config. It must be between {MIN_FPS} and {MAX_FPS}. Using {clamped} instead.",
+                self.fps
+            ));
+            self.fps = clamped;
+        }
+
+        if !(self.aspect_ratio.is_finite() && self.aspect_ratio > 0.0) {
+            show_warning(format!(
+                "Invalid aspect_ratio {} in config. Using {DEFAULT_ASPECT_RATIO} instead.",
+                self.aspect_ratio
+            ));
+            self.aspect_ratio = DEFAULT_ASPECT_RATIO;
+        }
+
+        if !self.crosshair_mode.is_generated() {
+            show_warning(format!(
+                "crosshair_mode {:?} in config is not a generated crosshair. Using {:?} instead.",
+                self.crosshair_mode,
+                RenderMode::default()
+            ));
+            self.crosshair_mode = RenderMode::default();
+        }
+
+        if self.inner_radius > self.outer_radius {
+            show_warning(format!(
+                "inner_radius {} in config is bigger than outer_radius {}. Drawing a filled disc instead.",
+                self.inner_radius, self.outer_radius
+            ));
+            self.inner_radius = 0;
+        }
+
+        if self.line_thickness == 0 {
+            show_warning(format!(
+                "Invalid line_thickness 0 in config. Using {DEFAULT_LINE_THICKNESS} instead."
+            ));
+            self.line_thickness = DEFAULT_LINE_THICKNESS;
+        }
+
+        if !(self.taper.is_finite() && (-1.0..=1.0).contains(&self.taper)) {
+            show_warning(format!(
+                "Invalid taper {} in config. It must be between -1 and 1. Using uniform arms instead.",
+                self.taper
+            ));
+            self.taper = 0.0;
+        }
+
+        if let Some(fraction) = self
+            .auto_fit_image
+            .filter(|fraction| !(fraction.is_finite() && *fraction > 0.0 && *fraction <= 1.0))
+        {
+            show_warning(format!(
+                "Invalid auto_fit_image {fraction} in config. It must be above 0 and at most 1. Loading images at native size instead."
+            ));
+            self.auto_fit_image = None;
+        }
+
+        if !(self.image_scale.is_finite() && self.image_scale >= MIN_IMAGE_SCALE) {
+            show_warning(format!(
+                "Invalid image_scale {} in config. It must be at least {MIN_IMAGE_SCALE}. Using {DEFAULT_IMAGE_SCALE} instead.",
+                self.image_scale
+            ));
+            self.image_scale = DEFAULT_IMAGE_SCALE;
+        }
+
+        if !self.cycle_speed.is_finite() {
+            show_warning(format!(
+                "Invalid cycle_speed {} in config. Using {DEFAULT_CYCLE_SPEED} instead.",
+                self.cycle_speed
+            ));
+            self.cycle_speed = DEFAULT_CYCLE_SPEED;
+        }
+
+        if !self.profiles.is_empty() && self.active_profile >= self.profiles.len() {
+            show_warning(format!(
+                "Invalid active_profile {} in config. There are only {} profiles. Using the first one instead.",
+                self.active_profile,
+                self.profiles.len()
+            ));
+            self.active_profile = 0;
+        }
+
+        let visible = visible_color(self.color);
+        let invisible_color_warned = visible != self.color;
+        if invisible_color_warned {
+            show_warning(invisible_color_warning(
+                self.color,
+                self.prevent_invisible.then_some(visible),
+            ));
+            if self.prevent_invisible {
+                self.color = visible;
+            }
+        }
+
+        let color = self
+            .color
+            .for_buffer(self.premultiply_alpha, self.gamma_correct);
 
         // make sure that if the user manually put an empty string in their config we don't explode
         let filtered_image_path = self
@@ -73,35 +562,52 @@ impl PersistedSettings {
             .as_ref()
             .filter(|path| !path.as_os_str().is_empty());
 
-        let image = if let Some(image_path) = filtered_image_path {
-            match image::load_png(image_path.as_path()) {
-                Ok(image) => Some(image),
+        let (image, animation) = if let Some(image_path) = filtered_image_path {
+            match load_image_file(
+                image_path,
+                self.premultiply_alpha,
+                self.gamma_correct,
+                self.strict_png,
+            ) {
+                Ok((image, animation)) => (Some(image), animation),
                 Err(e) => {
                     show_warning(format!(
                         "Failed loading saved image_path \"{}\".\n\n{}",
                         image_path.display(),
                         e
                     ));
-                    None
+                    (None, None)
                 }
             }
         } else {
-            None
+            (None, None)
         };
 
         let tick_interval = fps_to_tick_interval(self.fps);
         let monitor_index = usize::try_from(self.monitor.checked_sub(1).unwrap()).unwrap();
-        let render_mode = RenderMode::from(&image);
+        let render_mode = match (&image, &animation) {
+            (_, Some(_)) => RenderMode::Animated,
+            (Some(_), None) => RenderMode::Image,
+            (None, None) => self.crosshair_mode,
+        };
 
         Settings {
             persisted: self,
             color,
             image,
+            animation,
             tick_interval,
             monitor_index,
-            desired_window_position: PhysicalPosition::default(),
             desired_window_size: PhysicalSize::default(),
             render_mode,
+            magnifier: false,
+            spotlight: false,
+            monitor_size: PhysicalSize::default(),
+            picker_saturation: u8::MAX,
+            osd: Osd::default(),
+            image_hidden: false,
+            cycle_hue: None,
+            invisible_color_warned,
         }
     }
 }
@@ -109,41 +615,246 @@ impl PersistedSettings {
 impl Default for PersistedSettings {
     fn default() -> Self {
         PersistedSettings {
+            config_version: CONFIG_VERSION,
             window_dx: DEFAULT_OFFSET_X,
             window_dy: DEFAULT_OFFSET_Y,
             window_width: DEFAULT_SIZE,
             window_height: DEFAULT_SIZE,
             color: DEFAULT_COLOR,
             fps: DEFAULT_FPS,
+            idle_fps: None,
             image_path: None,
             key_bindings: KeyBindings::default(),
             monitor: DEFAULT_MONITOR,
+            monitor_name: None,
+            monitor_layouts: BTreeMap::new(),
+            scale_easing: ScaleEasing::default(),
+            drag_handle_size: 0,
+            follow_cursor: false,
+            monitors: Vec::new(),
+            top_overlay: None,
+            reassert_topmost: false,
+            window_level: OverlayLevel::default(),
+            batch_warnings: DEFAULT_BATCH_WARNINGS,
+            aspect_ratio: DEFAULT_ASPECT_RATIO,
+            aspect_locked: DEFAULT_ASPECT_LOCKED,
+            auto_hide_after: None,
+            fixed_scale_step: DEFAULT_FIXED_SCALE_STEP,
+            prevent_invisible: DEFAULT_PREVENT_INVISIBLE,
+            premultiply_alpha: image::PREMULTIPLY_ALPHA_DEFAULT,
+            gamma_correct: false,
+            spotlight_color: None,
+            crosshair_mode: RenderMode::default(),
+            ring_radius: DEFAULT_RING_RADIUS,
+            ring_thickness: DEFAULT_RING_THICKNESS,
+            dot_radius: DEFAULT_DOT_RADIUS,
+            inner_radius: DEFAULT_INNER_RADIUS,
+            outer_radius: DEFAULT_OUTER_RADIUS,
+            circle_thickness: DEFAULT_CIRCLE_THICKNESS,
+            hotkeys_enabled: DEFAULT_HOTKEYS_ENABLED,
+            units: Units::default(),
+            center_rounding: CenterRounding::default(),
+            center_marker: CenterMarker::default(),
+            contrast_mode: ContrastMode::default(),
+            transparency_key: None,
+            reset_clears_image: DEFAULT_RESET_CLEARS_IMAGE,
+            line_thickness: DEFAULT_LINE_THICKNESS,
+            outline_thickness: DEFAULT_OUTLINE_THICKNESS,
+            outline_color: DEFAULT_OUTLINE_COLOR,
+            shadow_offset: (0, 0),
+            shadow_color: None,
+            center_gap: 0,
+            gap_dots: false,
+            taper: 0.0,
+            strict_png: false,
+            auto_fit_image: None,
+            osd: false,
+            ghost: None,
+            persist_adjust_mode: false,
+            adjust_mode: false,
+            persist_lock: false,
+            locked: false,
+            start_hidden: false,
+            move_deadzone_frames: 0,
+            last_image_path: None,
+            image_scale: DEFAULT_IMAGE_SCALE,
+            opacity: DEFAULT_OPACITY,
+            profiles: Vec::new(),
+            active_profile: 0,
+            ipc_enabled: false,
+            color_cycle: false,
+            cycle_speed: DEFAULT_CYCLE_SPEED,
+        }
+    }
+}
+
+/// The parts of the settings that differ between profiles
+#[derive(Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct ProfileSettings {
+    pub window_dx: i32,
+    pub window_dy: i32,
+    pub window_width: u32,
+    pub window_height: u32,
+    #[serde(with = "crate::private::util::custom_serializer::argb_color")]
+    pub color: Color,
+    pub image_path: Option<PathBuf>,
+}
+
+impl Default for ProfileSettings {
+    fn default() -> Self {
+        ProfileSettings {
+            window_dx: DEFAULT_OFFSET_X,
+            window_dy: DEFAULT_OFFSET_Y,
+            window_width: DEFAULT_SIZE,
+            window_height: DEFAULT_SIZE,
+            color: DEFAULT_COLOR,
+            image_path: None,
         }
     }
 }
 
+/// The parts of the settings that undo and redo step through
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Adjustment {
+    window_dx: i32,
+    window_dy: i32,
+    window_width: u32,
+    window_height: u32,
+    color: Color,
+    crosshair_mode: RenderMode,
+}
+
 /// A wrapper around the persisted settings providing additional derived values
 pub struct Settings {
     pub persisted: PersistedSettings,
-    pub color: u32,
+    pub color: Color,
     image: Option<Box<Image>>,
+    /// every frame of the loaded image, if it's animated. `image` holds the first frame.
+    animation: Option<Animation>,
     pub tick_interval: Duration,
     /// 0-indexed monitor to render the overlay to
     pub monitor_index: usize,
-    pub desired_window_position: PhysicalPosition<i32>,
     pub desired_window_size: PhysicalSize<u32>,
     pub render_mode: RenderMode,
+    /// `true` while the magnifier view is being shown next to the crosshair
+    pub magnifier: bool,
+    /// `true` while the spotlight key is held
+    pub spotlight: bool,
+    /// size of the monitor that [`Units::Percent`] crosshair sizes are relative to
+    pub monitor_size: PhysicalSize<u32>,
+    /// the on-screen display message, if one is up
+    pub osd: Osd,
+    /// `true` while the generated crosshair is shown in place of a loaded image
+    image_hidden: bool,
+    /// current hue in degrees while `color_cycle` is running
+    cycle_hue: Option<f32>,
+    /// saturation of the colors in the color picker, changed with the scroll wheel. 255 is fully saturated.
+    pub picker_saturation: u8,
+    /// `true` once the user has been told their color is too transparent to see, so dragging through the color picker
+    /// doesn't bury them in warnings
+    invisible_color_warned: bool,
 }
 
 impl Settings {
+    /// The window size, which includes room for the magnifier and on-screen display if they're showing
     pub fn size(&self) -> PhysicalSize<u32> {
+        let inner_size = self.inner_size();
+        match self.osd_message() {
+            Some(message) => osd::padded_size(inner_size, osd::text_size(message)),
+            None => inner_size,
+        }
+    }
+
+    /// The window size without room for the on-screen display
+    pub fn inner_size(&self) -> PhysicalSize<u32> {
+        let content_size = self.content_size();
+        if self.magnifier_visible() {
+            magnifier::padded_size(content_size, magnifier::MAGNIFIER_VIEW_SIZE)
+        } else {
+            content_size
+        }
+    }
+
+    /// The on-screen display message to draw, if any. Never shown over the color picker, which needs its window to
+    /// itself.
+    pub fn osd_message(&self) -> Option<&str> {
+        self.osd
+            .message()
+            .filter(|_| self.persisted.osd && self.render_mode != RenderMode::ColorPicker)
+    }
+
+    /// The crosshair color at full opacity, faded along with the on-screen display. Has premultiplied alpha if
+    /// required.
+    pub fn osd_color(&self) -> Color {
+        let [r, g, b, _] = self.persisted.color.to_rgba_bytes();
+        Color::from_rgba_bytes([r, g, b, self.osd.alpha()]).for_buffer(
+            self.persisted.premultiply_alpha,
+            self.persisted.gamma_correct,
+        )
+    }
+
+    /// The size of the crosshair, image, or color picker, plus room for the ghost crosshair if there is one
+    pub fn content_size(&self) -> PhysicalSize<u32> {
+        let crosshair_size = self.crosshair_size();
+        match self.ghost() {
+            Some(ghost) => ghost::padded_size(crosshair_size, ghost),
+            None => crosshair_size,
+        }
+    }
+
+    /// The ghost crosshair to draw, if any. Never shown over the color picker.
+    pub fn ghost(&self) -> Option<&Ghost> {
+        self.persisted
+            .ghost
+            .as_ref()
+            .filter(|_| self.render_mode != RenderMode::ColorPicker)
+    }
+
+    /// Color of the ghost crosshair. Has premultiplied alpha if required.
+    pub fn ghost_color(&self) -> Color {
+        self.persisted
+            .ghost
+            .and_then(|ghost| ghost.color)
+            .unwrap_or(self.persisted.color)
+            .for_buffer(
+                self.persisted.premultiply_alpha,
+                self.persisted.gamma_correct,
+            )
+    }
+
+    /// The size of the crosshair, image, or color picker itself
+    pub fn crosshair_size(&self) -> PhysicalSize<u32> {
         match self.render_mode {
-            RenderMode::Image => {
-                let image = self.image.as_ref().unwrap();
-                PhysicalSize::new(image.width, image.height)
+            RenderMode::Image | RenderMode::Animated => self.image_size(),
+            RenderMode::Crosshair | RenderMode::Circle | RenderMode::TShape | RenderMode::Cross => {
+                let height = self
+                    .persisted
+                    .units
+                    .size_to_pixels(self.persisted.window_height, self.monitor_size.height);
+                let width = if self.persisted.aspect_locked {
+                    self.width_for_height(height)
+                } else {
+                    self.persisted
+                        .units
+                        .size_to_pixels(self.persisted.window_width, self.monitor_size.height)
+                };
+                PhysicalSize::new(width, height)
+            }
+            RenderMode::CircleDot => {
+                let radius = self.persisted.ring_radius.max(self.persisted.dot_radius);
+                let side = radius.saturating_mul(2).clamp(1, MAX_SIZE);
+                PhysicalSize::new(side, side)
             }
-            RenderMode::Crosshair => {
-                PhysicalSize::new(self.persisted.window_width, self.persisted.window_height)
+            RenderMode::Donut => {
+                // odd, so there's a center pixel for the donut to be centered on
+                let side = self
+                    .persisted
+                    .outer_radius
+                    .saturating_mul(2)
+                    .saturating_add(1)
+                    .min(MAX_SIZE);
+                PhysicalSize::new(side, side)
             }
             RenderMode::ColorPicker => PhysicalSize::new(
                 image::COLOR_PICKER_SIZE as u32,
@@ -152,14 +863,99 @@ impl Settings {
         }
     }
 
+    /// What to render when not picking a color: the image if there is one, otherwise the generated crosshair
+    fn default_render_mode(&self) -> RenderMode {
+        if self.image_shown() {
+            self.image_render_mode()
+        } else {
+            self.persisted.crosshair_mode
+        }
+    }
+
+    /// how to draw the loaded image, which depends on whether it's animated
+    fn image_render_mode(&self) -> RenderMode {
+        if self.animation.is_some() {
+            RenderMode::Animated
+        } else {
+            RenderMode::Image
+        }
+    }
+
+    /// `true` if there's a loaded image and it hasn't been swapped out for the generated crosshair
+    pub fn image_shown(&self) -> bool {
+        self.image.is_some() && !self.image_hidden
+    }
+
+    /// Switch between the loaded image and the generated crosshair without unloading the image. Does nothing while
+    /// picking a color or if there's no image. Returns `true` if the render mode changed.
+    pub fn toggle_image(&mut self) -> bool {
+        if self.image.is_none() || self.render_mode == RenderMode::ColorPicker {
+            return false;
+        }
+        self.image_hidden = !self.image_hidden;
+        self.render_mode = self.default_render_mode();
+        true
+    }
+
+    /// the magnifier gets out of the way of the color picker
+    pub fn magnifier_visible(&self) -> bool {
+        self.magnifier && self.render_mode != RenderMode::ColorPicker
+    }
+
+    /// the loaded image, or the current frame of it if it's animated
     pub fn image(&self) -> Option<&Image> {
-        self.image.as_ref().map(|b| b.as_ref())
+        match &self.animation {
+            Some(animation) => Some(animation.current()),
+            None => self.image.as_ref().map(|b| b.as_ref()),
+        }
+    }
+
+    /// Move an animated image on to whichever frame should be showing at `now`. Returns `true` if it needs redrawing.
+    pub fn advance_animation(&mut self, now: Instant) -> bool {
+        match &mut self.animation {
+            Some(animation) if self.render_mode == RenderMode::Animated => animation.advance(now),
+            _ => false,
+        }
+    }
+
+    /// Move the `color_cycle` hue along by a tick. Once cycling gets turned off the static color comes back. Returns
+    /// `true` if the color changed.
+    pub fn advance_color_cycle(&mut self) -> bool {
+        let premultiply = self.persisted.premultiply_alpha;
+        let gamma_correct = self.persisted.gamma_correct;
+        match (self.persisted.color_cycle, self.cycle_hue) {
+            (true, hue) => {
+                let hue = step_hue(
+                    hue.unwrap_or(0.0),
+                    self.persisted.cycle_speed,
+                    self.tick_interval,
+                );
+                self.cycle_hue = Some(hue);
+                // hue is under 360, so this always fits in a u8
+                let hue_byte = (hue / 360.0 * 256.0) as u8;
+                let alpha = self.persisted.color.to_rgba_bytes()[3];
+                self.color = Color::from_argb(image::hue_alpha_to_argb(hue_byte, alpha))
+                    .for_buffer(premultiply, gamma_correct);
+                true
+            }
+            (false, Some(_)) => {
+                self.cycle_hue = None;
+                self.color = self.persisted.color.for_buffer(premultiply, gamma_correct);
+                true
+            }
+            (false, None) => false,
+        }
+    }
+
+    fn unload_image(&mut self) {
+        self.image = None;
+        self.animation = None;
     }
 
     /// Toggle color picker mode on or off. Returns `true` if color picker mode is now enabled, `false` otherwise.
     pub fn toggle_pick_color(&mut self) -> bool {
         let (render_mode, enabled) = if self.render_mode == RenderMode::ColorPicker {
-            (RenderMode::from(&self.image), false)
+            (self.default_render_mode(), false)
         } else {
             (RenderMode::ColorPicker, true)
         };
@@ -171,7 +967,7 @@ impl Settings {
         self.render_mode = if pick_color {
             RenderMode::ColorPicker
         } else {
-            RenderMode::from(&self.image)
+            self.default_render_mode()
         }
     }
 
@@ -180,197 +976,1193 @@ impl Settings {
         self.render_mode == RenderMode::ColorPicker
     }
 
+    /// The color to actually draw the generated crosshair with, which may be overridden by the spotlight.
+    /// The result has premultiplied alpha if required.
+    pub fn render_color(&self) -> Color {
+        if self.spotlight {
+            // a fully opaque color is the same whether it's premultiplied or not
+            self.persisted
+                .spotlight_color
+                .unwrap_or(self.persisted.color)
+                .opaque()
+        } else {
+            match self.persisted.contrast_mode {
+                ContrastMode::Off => self.color,
+                ContrastMode::DarkBackground => image::high_contrast(self.persisted.color, true),
+                ContrastMode::LightBackground => image::high_contrast(self.persisted.color, false),
+            }
+        }
+    }
+
+    /// The color of the [`RenderMode::Crosshair`] outline, with premultiplied alpha if required
+    pub fn outline_color(&self) -> Color {
+        self.persisted.outline_color.for_buffer(
+            self.persisted.premultiply_alpha,
+            self.persisted.gamma_correct,
+        )
+    }
+
+    /// The color of the [`RenderMode::Crosshair`] drop shadow with premultiplied alpha if required, or `None` if there's
+    /// no shadow to draw
+    pub fn shadow_color(&self) -> Option<Color> {
+        self.persisted
+            .shadow_color
+            .filter(|_| self.persisted.shadow_offset != (0, 0))
+            .map(|color| {
+                color.for_buffer(
+                    self.persisted.premultiply_alpha,
+                    self.persisted.gamma_correct,
+                )
+            })
+    }
+
+    /// What the generated crosshairs draw behind themselves: the transparency key if one is set, otherwise nothing.
+    /// The key is always drawn fully opaque so it can be matched exactly.
+    pub fn background_color(&self) -> Color {
+        self.persisted
+            .transparency_key
+            .map_or(Color::TRANSPARENT, Color::opaque)
+    }
+
     /// Set the color of the generated crosshair. The provided `color` must not have premultiplied alpha (yet)
-    pub fn set_color(&mut self, color: u32) {
+    pub fn set_color(&mut self, color: Color) {
+        let visible = visible_color(color);
+        let prevent_invisible = self.persisted.prevent_invisible;
+        if visible != color && !self.invisible_color_warned {
+            show_warning(invisible_color_warning(
+                color,
+                prevent_invisible.then_some(visible),
+            ));
+            self.invisible_color_warned = true;
+        }
+        let color = if prevent_invisible { visible } else { color };
         debug_println!("set color to {color:08X}");
         self.persisted.color = color;
-        self.color = image::premultiply_alpha(color);
-        self.image = None; // unload image
+        self.color = color.for_buffer(
+            self.persisted.premultiply_alpha,
+            self.persisted.gamma_correct,
+        );
+        self.unload_image();
         self.persisted.image_path = None;
-        self.render_mode = RenderMode::Crosshair;
+        self.render_mode = self.persisted.crosshair_mode;
     }
 
+    /// Images, the generated `+` crosshair, and the shapes sized like it can be scaled. The generated crosshair's width
+    /// follows from the height and the aspect ratio unless `aspect_locked` is off, and images keep their own aspect
+    /// ratio. Other generated crosshairs are sized by their own settings.
     pub fn is_scalable(&self) -> bool {
-        self.image.is_none()
+        self.image_shown() || (self.image.is_none() && self.persisted.crosshair_mode.is_scalable())
     }
 
-    /// only reset the settings the user can actually edit in-app. If they've manually edited "secret settings" in their config that should stick.
-    pub fn reset(&mut self) {
-        self.persisted.window_dx = DEFAULT_OFFSET_X;
-        self.persisted.window_dy = DEFAULT_OFFSET_Y;
-        self.persisted.window_width = DEFAULT_SIZE;
-        self.persisted.window_height = DEFAULT_SIZE;
-        self.persisted.color = DEFAULT_COLOR;
-        self.color = image::premultiply_alpha(DEFAULT_COLOR);
-        self.persisted.image_path = None;
-        if self.render_mode == RenderMode::Image {
-            self.render_mode = RenderMode::Crosshair;
-        }
-        self.image = None;
+    /// The size the loaded image is drawn at, which is its native size times `image_scale`
+    fn image_size(&self) -> PhysicalSize<u32> {
+        let image = self.image.as_ref().unwrap();
+        let scaled = |length: u32| {
+            ((length as f64 * self.persisted.image_scale as f64).round() as u32).clamp(1, MAX_SIZE)
+        };
+        PhysicalSize::new(scaled(image.width), scaled(image.height))
     }
 
-    /// load a new PNG at runtime
-    pub fn load_png(&mut self, path: PathBuf) -> io::Result<()> {
-        let image = image::load_png(path.as_path())?;
-        self.persisted.image_path = Some(path);
-        self.image = Some(image);
-        self.render_mode = RenderMode::Image;
-        Ok(())
+    /// pick the image scale that makes the image `height` pixels tall
+    fn set_image_height(&mut self, height: u32) {
+        let native_height = self.image.as_ref().unwrap().height;
+        self.persisted.image_scale = (height as f32 / native_height as f32).max(MIN_IMAGE_SCALE);
     }
 
-    pub fn load() -> io::Result<Settings> {
-        fs::create_dir_all(CONFIG_PATH.as_path().parent().unwrap())?;
-        Settings::load_from_path(CONFIG_PATH.as_path())
+    fn width_for_height(&self, height: u32) -> u32 {
+        ((height as f32 * self.persisted.aspect_ratio).round() as u32).clamp(1, MAX_SIZE)
     }
 
-    #[inline(always)]
-    fn load_from_path<T>(path: T) -> io::Result<Settings>
-    where
-        T: AsRef<Path>,
-    {
-        fs::read_to_string(path)
-            .and_then(|string| {
-                toml::from_str::<PersistedSettings>(&string)
-                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
-            })
-            .map(|settings| settings.load())
+    /// set the generated crosshair's size. While the aspect ratio is locked, `width` is ignored and derived from the
+    /// height instead.
+    fn set_crosshair_size(&mut self, width: u32, height: u32) {
+        let height = height.clamp(1, MAX_SIZE);
+        self.persisted.window_height = height;
+        self.persisted.window_width = if self.persisted.aspect_locked {
+            self.width_for_height(height)
+        } else {
+            width.clamp(1, MAX_SIZE)
+        };
     }
 
-    pub fn save(&self) -> Result<(), String> {
-        self.save_to_path(CONFIG_PATH.as_path())
+    /// grow the image or generated crosshair by `amount` pixels of height, preserving the aspect ratio. With the
+    /// aspect ratio unlocked the generated crosshair grows by `amount` in both directions instead.
+    pub fn scale_up(&mut self, amount: u32) {
+        if self.image_shown() {
+            self.set_image_height(self.image_size().height.saturating_add(amount));
+        } else {
+            self.set_crosshair_size(
+                self.persisted.window_width.saturating_add(amount),
+                self.persisted.window_height.saturating_add(amount),
+            );
+        }
     }
 
-    #[inline(always)]
-    fn save_to_path<T>(&self, path: T) -> Result<(), String>
-    where
-        T: AsRef<Path>,
-    {
-        let serialized_config =
-            toml::to_string(&self.persisted).expect("failed to serialize settings");
-        fs::write(path, serialized_config).map_err(|e| format!("{e:?}"))
+    /// shrink the image or generated crosshair by `amount` pixels of height, preserving the aspect ratio. With the
+    /// aspect ratio unlocked the generated crosshair shrinks by `amount` in both directions instead.
+    pub fn scale_down(&mut self, amount: u32) {
+        if self.image_shown() {
+            self.set_image_height(self.image_size().height.saturating_sub(amount));
+        } else {
+            self.set_crosshair_size(
+                self.persisted.window_width.saturating_sub(amount),
+                self.persisted.window_height.saturating_sub(amount),
+            );
+        }
     }
 
-    pub fn set_window_position(&mut self, window: &Window) {
-        let position = self.compute_window_coordinates(window);
-        self.desired_window_position = position;
-        window.set_outer_position(position);
+    /// whether the generated crosshair's width and height can be changed separately right now
+    pub fn is_resizable(&self) -> bool {
+        !self.persisted.aspect_locked && !self.image_shown() && self.is_scalable()
     }
 
-    fn reset_window_position(&self, window: &Window) {
-        window.set_outer_position(self.desired_window_position);
+    /// Grow or shrink just the generated crosshair's width by `amount` pixels. Returns `false` without changing
+    /// anything unless [`Settings::is_resizable`].
+    pub fn resize_width(&mut self, increase: bool, amount: u32) -> bool {
+        if !self.is_resizable() {
+            return false;
+        }
+        let width = self.persisted.window_width;
+        let width = if increase {
+            width.saturating_add(amount)
+        } else {
+            width.saturating_sub(amount)
+        };
+        self.set_crosshair_size(width, self.persisted.window_height);
+        true
     }
 
-    pub fn validate_window_position(&self, window: &Window, position: PhysicalPosition<i32>) {
-        if position != self.desired_window_position {
-            debug_println!("resetting window position");
-            self.reset_window_position(window);
+    /// Grow or shrink just the generated crosshair's height by `amount` pixels. Returns `false` without changing
+    /// anything unless [`Settings::is_resizable`].
+    pub fn resize_height(&mut self, increase: bool, amount: u32) -> bool {
+        if !self.is_resizable() {
+            return false;
         }
+        let height = self.persisted.window_height;
+        let height = if increase {
+            height.saturating_add(amount)
+        } else {
+            height.saturating_sub(amount)
+        };
+        self.set_crosshair_size(self.persisted.window_width, height);
+        true
     }
 
-    pub fn set_window_size(&self, window: &Window) {
-        let _ = window.request_inner_size(self.size());
+    /// Step the overall opacity up or down. Returns `true` if it changed.
+    pub fn change_opacity(&mut self, increase: bool) -> bool {
+        let opacity = self.persisted.opacity;
+        self.persisted.opacity = if increase {
+            opacity.saturating_add(OPACITY_STEP)
+        } else {
+            opacity.saturating_sub(OPACITY_STEP)
+        };
+        self.persisted.opacity != opacity
     }
 
-    pub fn validate_window_size(&self, window: &Window, size: PhysicalSize<u32>) {
-        if size != self.size() {
-            debug_println!("resetting window size");
-            self.set_window_size(window);
-        }
+    /// The color of the generated crosshair as configured, without premultiplied alpha
+    pub fn straight_color(&self) -> Color {
+        self.persisted.color
     }
 
-    /// Compute the correct coordinates of the top-left of the window in order to center the crosshair in the selected monitor
-    fn compute_window_coordinates(&self, window: &Window) -> PhysicalPosition<i32> {
-        // fall back to primary monitor if the desired monitor index is invalid
-        let monitor = window
-            .available_monitors()
-            .nth(self.monitor_index)
-            .unwrap_or_else(|| window.primary_monitor().unwrap());
+    pub fn fps(&self) -> u32 {
+        self.persisted.fps
+    }
 
-        // grab a bunch of coordinates/sizes and convert them to i32s, as we have some signed math to do
-        let PhysicalPosition {
-            x: monitor_x,
-            y: monitor_y,
-        } = monitor.position();
-        let PhysicalSize {
-            width: monitor_width,
-            height: monitor_height,
-        } = monitor.size();
-        let monitor_width = i32::try_from(monitor_width).unwrap();
-        let monitor_height = i32::try_from(monitor_height).unwrap();
-        let PhysicalSize {
+    /// How long to wait between ticks while idle, or `None` if ticking shouldn't slow down. An `idle_fps` that isn't
+    /// actually slower than `fps` is ignored.
+    pub fn idle_tick_interval(&self) -> Option<Duration> {
+        self.persisted
+            .idle_fps
+            .filter(|&idle_fps| idle_fps > 0)
+            .map(fps_to_tick_interval)
+            .filter(|&interval| interval > self.tick_interval)
+    }
+
+    /// change how often the overlay ticks. This gets clamped to between 1 and 240 fps.
+    pub fn set_fps(&mut self, fps: u32) {
+        let fps = fps.clamp(MIN_FPS, MAX_FPS);
+        self.persisted.fps = fps;
+        self.tick_interval = fps_to_tick_interval(fps);
+    }
+
+    /// Step the fps up or down. Returns `true` if it changed.
+    pub fn change_fps(&mut self, increase: bool) -> bool {
+        let fps = self.persisted.fps;
+        self.set_fps(if increase {
+            fps.saturating_add(FPS_STEP)
+        } else {
+            fps.saturating_sub(FPS_STEP)
+        });
+        self.persisted.fps != fps
+    }
+
+    /// Switch to a different generated crosshair, and remember it for next time. It only shows up right away if the
+    /// generated crosshair is what's currently on screen.
+    pub fn set_crosshair_mode(&mut self, mode: RenderMode) {
+        if !mode.is_generated() {
+            return;
+        }
+        self.persisted.crosshair_mode = mode;
+        if self.render_mode.is_generated() {
+            self.render_mode = mode;
+        }
+    }
+
+    /// switch to a different 0-indexed monitor, and remember it for next time
+    pub fn set_monitor_index(&mut self, monitor_index: usize) {
+        self.monitor_index = monitor_index;
+        self.persisted.monitor = u32::try_from(monitor_index + 1).unwrap();
+    }
+
+    /// Remember the current monitor's name, so it can still be found if the monitors get reordered
+    pub fn remember_monitor_name(&mut self, monitors: &[MonitorInfo]) {
+        self.persisted.monitor_name = monitors
+            .get(self.monitor_index)
+            .and_then(|monitor| monitor.name.clone());
+    }
+
+    /// Remember the current monitor as the one to use whenever this exact set of monitors is connected
+    pub fn remember_monitor_layout(&mut self, monitors: &[MonitorInfo]) {
+        if !monitors.is_empty() {
+            self.persisted
+                .monitor_layouts
+                .insert(monitor::layout_key(monitors), self.persisted.monitor);
+        }
+    }
+
+    /// The 0-indexed monitor to use with this set of monitors: whichever was last picked for it. If this setup hasn't
+    /// been seen before, or the monitors are in a different order than when it was, use the monitor named
+    /// `monitor_name`, and failing that `monitor_index`.
+    pub fn layout_monitor_index(&self, monitors: &[MonitorInfo]) -> usize {
+        self.persisted
+            .monitor_layouts
+            .get(&monitor::layout_key(monitors))
+            .and_then(|&monitor| usize::try_from(monitor.checked_sub(1)?).ok())
+            .filter(|&index| index < monitors.len())
+            .unwrap_or_else(|| {
+                monitor::resolve_named_monitor(
+                    self.persisted.monitor_name.as_deref(),
+                    self.monitor_index,
+                    monitors,
+                )
+            })
+    }
+
+    /// Move the crosshair to one of the common spots on a monitor of `monitor_size`. With several overlays that's the
+    /// one being snapped, which isn't necessarily the one [`Settings::monitor_size`] comes from.
+    pub fn snap_to(&mut self, anchor: SnapAnchor, monitor_size: PhysicalSize<u32>) {
+        let (dx, dy) = anchor.offset(monitor_size, self.size(), self.persisted.center_rounding);
+        let units = self.persisted.units;
+        self.persisted.window_dx = units.pixels_to_offset(dx, monitor_size.width);
+        self.persisted.window_dy = units.pixels_to_offset(dy, monitor_size.height);
+    }
+
+    /// only reset the settings the user can actually edit in-app. If they've manually edited "secret settings" in their config that should stick.
+    pub fn reset(&mut self) {
+        self.persisted.window_dx = DEFAULT_OFFSET_X;
+        self.persisted.window_dy = DEFAULT_OFFSET_Y;
+        self.set_crosshair_size(DEFAULT_SIZE, DEFAULT_SIZE);
+        self.persisted.image_scale = DEFAULT_IMAGE_SCALE;
+        self.persisted.color = DEFAULT_COLOR;
+        self.color = DEFAULT_COLOR.for_buffer(
+            self.persisted.premultiply_alpha,
+            self.persisted.gamma_correct,
+        );
+        if self.persisted.reset_clears_image {
+            self.persisted.image_path = None;
+            if self.render_mode.is_image() {
+                self.render_mode = self.persisted.crosshair_mode;
+            }
+            self.unload_image();
+        }
+    }
+
+    /// move the crosshair back to its default spot, leaving everything else alone
+    pub fn reset_position(&mut self) {
+        self.persisted.window_dx = DEFAULT_OFFSET_X;
+        self.persisted.window_dy = DEFAULT_OFFSET_Y;
+    }
+
+    /// load a new image at runtime
+    pub fn load_image(&mut self, path: PathBuf) -> io::Result<()> {
+        let (image, animation) = load_image_file(
+            &path,
+            self.persisted.premultiply_alpha,
+            self.persisted.gamma_correct,
+            self.persisted.strict_png,
+        )?;
+        self.persisted.last_image_path = Some(path.clone());
+        self.persisted.image_path = Some(path);
+        self.image = Some(image);
+        self.animation = animation;
+        self.fit_image();
+        self.image_hidden = false;
+        self.render_mode = self.image_render_mode();
+        Ok(())
+    }
+
+    /// Shrink the loaded image to fit the monitor if `auto_fit_image` is set. The image from the config gets loaded
+    /// before the monitor size is known, so this has to run again once it is.
+    pub fn fit_image(&mut self) {
+        let (Some(fraction), Some(image)) = (self.persisted.auto_fit_image, self.image.as_mut())
+        else {
+            return;
+        };
+        let native_size = PhysicalSize::new(image.width, image.height);
+        if let Some(size) = image::fit_size(native_size, self.monitor_size, fraction) {
+            show_warning(format!(
+                "This {}×{} image is too big for your monitor, so it was shrunk to {}×{}.",
+                native_size.width, native_size.height, size.width, size.height
+            ));
+            **image = image.resize(size.width, size.height);
+            self.animation = self
+                .animation
+                .take()
+                .map(|animation| animation.resize(size.width, size.height));
+        }
+    }
+
+    /// Save whatever's being drawn right now, be it the generated crosshair, the loaded image, or the color picker, as
+    /// a PNG. The magnifier and on-screen display are left out.
+    pub fn export_png<T>(&self, path: T) -> io::Result<()>
+    where
+        T: AsRef<Path>,
+    {
+        let PhysicalSize { width, height } = self.content_size();
+        let mut buffer = vec![0; width as usize * height as usize];
+        image::draw::render_content(&mut buffer, width as usize, height as usize, self);
+        image::save_png(
+            path,
+            width,
+            height,
+            &buffer,
+            self.persisted.premultiply_alpha,
+            self.persisted.gamma_correct,
+        )
+    }
+
+    /// The image to bring back with [`Settings::reload_last_image`]. Configs from before this was tracked only know
+    /// about the current image.
+    pub fn last_image_path(&self) -> Option<&Path> {
+        self.persisted
+            .last_image_path
+            .as_deref()
+            .or(self.persisted.image_path.as_deref())
+    }
+
+    /// load the most recently loaded image again, e.g. after picking a color unloaded it
+    pub fn reload_last_image(&mut self) -> io::Result<()> {
+        let path = self
+            .last_image_path()
+            .map(Path::to_path_buf)
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::NotFound, "No image has been loaded yet.")
+            })?;
+        self.load_image(path)
+    }
+
+    /// load the config file at `path`, which is usually [`CONFIG_PATH`]. Its directory gets created if it's missing.
+    pub fn load<T>(path: T) -> io::Result<Settings>
+    where
+        T: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        Settings::load_from_path(path)
+    }
+
+    #[inline(always)]
+    fn load_from_path<T>(path: T) -> io::Result<Settings>
+    where
+        T: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let string = fs::read_to_string(path)?;
+        match ConfigFormat::of(path) {
+            ConfigFormat::Toml => Settings::from_toml(&string),
+            ConfigFormat::Json => Settings::from_json(&string),
+        }
+    }
+
+    /// parse settings from a TOML string, with the same validation as loading them from disk
+    pub fn from_toml(string: &str) -> io::Result<Settings> {
+        let table = string
+            .parse::<toml::Table>()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Settings::from_table(table)
+    }
+
+    /// Parse settings from a JSON string, with the same validation as loading them from disk. `null` works the same as
+    /// leaving a key out.
+    pub fn from_json(string: &str) -> io::Result<Settings> {
+        let invalid_data = |e| io::Error::new(io::ErrorKind::InvalidData, e);
+        let mut value = serde_json::from_str::<serde_json::Value>(string).map_err(invalid_data)?;
+        strip_nulls(&mut value);
+        // going through TOML's types lets JSON configs be migrated and validated just like TOML ones
+        let table = toml::Table::deserialize(value).map_err(invalid_data)?;
+        Settings::from_table(table)
+    }
+
+    fn from_table(mut table: toml::Table) -> io::Result<Settings> {
+        let invalid_data = |e| io::Error::new(io::ErrorKind::InvalidData, e);
+        migrate(&mut table);
+        toml::Value::Table(table)
+            .try_into::<PersistedSettings>()
+            .map_err(invalid_data)
+            .map(|settings| settings.load())
+    }
+
+    /// whether to start up with the overlay shown
+    pub fn startup_visible(&self) -> bool {
+        !self.persisted.start_hidden
+    }
+
+    /// Remember whether the user left the overlay shown or hidden, for the next launch
+    pub fn remember_visible(&mut self, visible: bool) {
+        self.persisted.start_hidden = !visible;
+    }
+
+    /// whether to start up in adjust mode
+    pub fn startup_adjust_mode(&self) -> bool {
+        self.persisted.persist_adjust_mode && self.persisted.adjust_mode
+    }
+
+    /// Note the current adjust mode so the next launch can restore it. Does nothing useful unless
+    /// `persist_adjust_mode` is on.
+    pub fn remember_adjust_mode(&mut self, adjust_mode: bool) {
+        self.persisted.adjust_mode = self.persisted.persist_adjust_mode && adjust_mode;
+    }
+
+    /// whether to start up locked
+    pub fn startup_locked(&self) -> bool {
+        self.persisted.persist_lock && self.persisted.locked
+    }
+
+    /// Note whether the overlay is locked so the next launch can restore it. Does nothing useful unless `persist_lock`
+    /// is on.
+    pub fn remember_locked(&mut self, locked: bool) {
+        self.persisted.locked = self.persisted.persist_lock && locked;
+    }
+
+    /// the settings exactly as they'd be saved to the config file
+    pub fn to_toml(&self) -> String {
+        toml::to_string(&self.to_persist()).expect("failed to serialize settings")
+    }
+
+    /// The settings as they'd be saved to a JSON config file. Unset options are left out rather than written as
+    /// `null`, same as in TOML.
+    pub fn to_json(&self) -> String {
+        let table = toml::Table::try_from(self.to_persist()).expect("failed to serialize settings");
+        serde_json::to_string_pretty(&table).expect("failed to serialize settings")
+    }
+
+    /// what gets written to the config file
+    fn to_persist(&self) -> Cow<'_, PersistedSettings> {
+        if self.persisted.profiles.is_empty() {
+            Cow::Borrowed(&self.persisted)
+        } else {
+            // the active profile may have been adjusted since it was switched to
+            let mut persisted = self.persisted.clone();
+            persisted.profiles[persisted.active_profile] = self.current_profile();
+            Cow::Owned(persisted)
+        }
+    }
+
+    /// the crosshair currently on screen, as it would be stored in a profile
+    fn current_profile(&self) -> ProfileSettings {
+        ProfileSettings {
+            window_dx: self.persisted.window_dx,
+            window_dy: self.persisted.window_dy,
+            window_width: self.persisted.window_width,
+            window_height: self.persisted.window_height,
+            color: self.persisted.color,
+            image_path: self.persisted.image_path.clone(),
+        }
+    }
+
+    /// A short summary of the current crosshair, one setting per line, for the About dialog and bug reports
+    pub fn describe(&self) -> String {
+        let size = self.crosshair_size();
+        let image = self
+            .persisted
+            .image_path
+            .as_ref()
+            .map_or_else(|| "none".to_string(), |path| path.display().to_string());
+        format!(
+            "size: {}x{}\noffset: ({}, {})\ncolor: {:08X}\nmonitor: {}\nrender mode: {:?}\nimage: {}",
+            size.width,
+            size.height,
+            self.persisted.window_dx,
+            self.persisted.window_dy,
+            self.persisted.color,
+            self.monitor_index + 1,
+            self.render_mode,
+            image
+        )
+    }
+
+    /// the crosshair's current position, size, color, and style, for the undo history
+    pub fn adjustment(&self) -> Adjustment {
+        Adjustment {
+            window_dx: self.persisted.window_dx,
+            window_dy: self.persisted.window_dy,
+            window_width: self.persisted.window_width,
+            window_height: self.persisted.window_height,
+            color: self.persisted.color,
+            crosshair_mode: self.persisted.crosshair_mode,
+        }
+    }
+
+    /// Put back a snapshot from [`Settings::adjustment`]. Unlike [`Settings::set_color`], this leaves a loaded image
+    /// alone.
+    pub fn apply_adjustment(&mut self, adjustment: Adjustment) {
+        self.persisted.window_dx = adjustment.window_dx;
+        self.persisted.window_dy = adjustment.window_dy;
+        self.persisted.window_width = adjustment.window_width;
+        self.persisted.window_height = adjustment.window_height;
+        self.persisted.color = adjustment.color;
+        self.color = adjustment.color.for_buffer(
+            self.persisted.premultiply_alpha,
+            self.persisted.gamma_correct,
+        );
+        self.set_crosshair_mode(adjustment.crosshair_mode);
+    }
+
+    /// Switch to the next profile, or the previous one if `forward` is `false`, wrapping around at the ends. The
+    /// current crosshair gets stored in the profile being left. Returns the new 1-indexed profile, or `None` if there
+    /// are no profiles.
+    pub fn switch_profile(&mut self, forward: bool) -> Option<usize> {
+        let count = self.persisted.profiles.len();
+        if count == 0 {
+            return None;
+        }
+        let active = self.persisted.active_profile;
+        self.persisted.profiles[active] = self.current_profile();
+        let active = if forward {
+            (active + 1) % count
+        } else {
+            (active + count - 1) % count
+        };
+        self.persisted.active_profile = active;
+        self.apply_profile(self.persisted.profiles[active].clone());
+        Some(active + 1)
+    }
+
+    fn apply_profile(&mut self, profile: ProfileSettings) {
+        self.persisted.window_dx = profile.window_dx;
+        self.persisted.window_dy = profile.window_dy;
+        self.persisted.window_width = profile.window_width.clamp(1, MAX_SIZE);
+        self.persisted.window_height = profile.window_height.clamp(1, MAX_SIZE);
+        self.persisted.color = profile.color;
+        self.color = profile.color.for_buffer(
+            self.persisted.premultiply_alpha,
+            self.persisted.gamma_correct,
+        );
+
+        // the color picker stays up if it was up
+        let pick_color = self.get_pick_color();
+        let image_path = profile
+            .image_path
+            .filter(|path| !path.as_os_str().is_empty());
+        let loaded = match image_path {
+            Some(path) => match self.load_image(path.clone()) {
+                Ok(()) => true,
+                Err(e) => {
+                    show_warning(format!(
+                        "Failed loading profile image_path \"{}\".\n\n{}",
+                        path.display(),
+                        e
+                    ));
+                    false
+                }
+            },
+            None => false,
+        };
+        if !loaded {
+            self.unload_image();
+            self.persisted.image_path = None;
+            self.image_hidden = false;
+        }
+        self.set_pick_color(pick_color);
+    }
+
+    /// save to the config file at `path`, which is usually [`CONFIG_PATH`]
+    pub fn save<T>(&self, path: T) -> Result<(), String>
+    where
+        T: AsRef<Path>,
+    {
+        self.save_to_path(path)
+    }
+
+    /// where a 1-indexed save slot lives on disk
+    pub fn slot_path(slot: usize) -> PathBuf {
+        SLOT_DIRECTORY.join(format!("slot_{slot}.toml"))
+    }
+
+    /// snapshot the current settings into a 1-indexed save slot
+    pub fn save_slot(&self, slot: usize) -> Result<(), String> {
+        fs::create_dir_all(SLOT_DIRECTORY.as_path()).map_err(|e| format!("{e:?}"))?;
+        self.save_to_path(Settings::slot_path(slot))
+    }
+
+    /// replace the current settings with a snapshot from a 1-indexed save slot
+    pub fn load_slot(&mut self, slot: usize) -> io::Result<()> {
+        self.load_slot_from_path(Settings::slot_path(slot))
+    }
+
+    /// Replace the current settings with a snapshot from any file. Settings that only get read at startup, like the key
+    /// bindings and which monitors have overlays, are kept as they are, since nothing would pick up the snapshot's.
+    pub fn load_slot_from_path<T>(&mut self, path: T) -> io::Result<()>
+    where
+        T: AsRef<Path>,
+    {
+        let mut loaded = Settings::load_from_path(path)?;
+
+        // the hotkey manager, windows, and dialog worker were all built from these
+        let persisted = &mut loaded.persisted;
+        persisted.key_bindings = self.persisted.key_bindings.clone();
+        persisted.scale_easing = self.persisted.scale_easing;
+        persisted.move_deadzone_frames = self.persisted.move_deadzone_frames;
+        persisted.hotkeys_enabled = self.persisted.hotkeys_enabled;
+        persisted.monitor = self.persisted.monitor;
+        persisted.monitor_name = self.persisted.monitor_name.clone();
+        persisted.monitor_layouts = self.persisted.monitor_layouts.clone();
+        persisted.monitors = self.persisted.monitors.clone();
+        persisted.top_overlay = self.persisted.top_overlay.clone();
+        persisted.transparency_key = self.persisted.transparency_key;
+        persisted.batch_warnings = self.persisted.batch_warnings;
+        persisted.ipc_enabled = self.persisted.ipc_enabled;
+        loaded.monitor_index = self.monitor_index;
+
+        // temporary UI state isn't part of a snapshot, so carry it over
+        loaded.magnifier = self.magnifier;
+        loaded.spotlight = self.spotlight;
+        loaded.picker_saturation = self.picker_saturation;
+        loaded.monitor_size = self.monitor_size;
+        loaded.osd = std::mem::take(&mut self.osd);
+        if self.get_pick_color() {
+            loaded.render_mode = RenderMode::ColorPicker;
+        }
+
+        *self = loaded;
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn save_to_path<T>(&self, path: T) -> Result<(), String>
+    where
+        T: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let string = match ConfigFormat::of(path) {
+            ConfigFormat::Toml => self.to_toml(),
+            ConfigFormat::Json => self.to_json(),
+        };
+        fs::write(path, string).map_err(|e| format!("{e:?}"))
+    }
+
+    pub fn set_window_size(&self, window: &Window) {
+        let _ = window.request_inner_size(self.size());
+    }
+
+    pub fn validate_window_size(&self, window: &Window, size: PhysicalSize<u32>) {
+        if size != self.size() {
+            debug_println!("resetting window size");
+            self.set_window_size(window);
+        }
+    }
+
+    /// Compute the correct coordinates of the top-left of the window in order to center the crosshair in the given
+    /// monitor. `None` means whichever monitor was last picked for the currently connected monitors.
+    pub fn compute_window_coordinates(
+        &self,
+        window: &Window,
+        monitor_index: Option<usize>,
+    ) -> PhysicalPosition<i32> {
+        let monitors: Vec<MonitorInfo> = window
+            .available_monitors()
+            .map(|monitor| MonitorInfo::from(&monitor))
+            .collect();
+        let monitor_index = monitor_index.unwrap_or_else(|| self.layout_monitor_index(&monitors));
+
+        // fall back to primary monitor if the desired monitor index is invalid
+        let primary = window
+            .primary_monitor()
+            .map(|monitor| MonitorInfo::from(&monitor));
+        let Some(monitor) = monitor::pick_monitor(monitors.iter().cloned(), monitor_index, primary)
+        else {
+            // this runs on every move and scale tick, so a popup here would just keep coming back
+            debug_println!("warning: no monitors found, placing window at 0, 0");
+            return PhysicalPosition::new(0, 0);
+        };
+
+        // grab a bunch of coordinates/sizes and convert them to i32s, as we have some signed math to do
+        let PhysicalPosition {
+            x: monitor_x,
+            y: monitor_y,
+        } = monitor.position;
+        let PhysicalSize {
+            width: monitor_width,
+            height: monitor_height,
+        } = monitor.size;
+        let monitor_width = i32::try_from(monitor_width).unwrap();
+        let monitor_height = i32::try_from(monitor_height).unwrap();
+        let PhysicalSize {
             width: window_width,
             height: window_height,
         } = self.size();
         let window_width = i32::try_from(window_width).unwrap();
         let window_height = i32::try_from(window_height).unwrap();
 
-        // calculate the coordinates of the center of the monitor, rounding down
-        let (monitor_center_x, monitor_center_y) =
-            image::rectangle_center(monitor_x, monitor_y, monitor_width, monitor_height);
+        // calculate the coordinates of the center of the monitor, rounding however the user asked
+        let (monitor_center_x, monitor_center_y) = image::rounded_rectangle_center(
+            monitor_x,
+            monitor_y,
+            monitor_width,
+            monitor_height,
+            self.persisted.center_rounding,
+        );
 
         // adjust by half our window size, as we want the coordinates at which to place the top-left corner of the window
-        let window_x = monitor_center_x - (window_width / 2) + self.persisted.window_dx;
-        let window_y = monitor_center_y - (window_height / 2) + self.persisted.window_dy;
+        let units = self.persisted.units;
+        let window_x = monitor_center_x - (window_width / 2)
+            + units.offset_to_pixels(self.persisted.window_dx, monitor_width);
+        let window_y = monitor_center_y - (window_height / 2)
+            + units.offset_to_pixels(self.persisted.window_dy, monitor_height);
+
+        let position = PhysicalPosition::new(window_x, window_y);
 
-        debug_println!("placing window at {}, {}", window_x, window_y);
-        PhysicalPosition::new(window_x, window_y)
+        // big offsets can push the window off of every monitor, where it may not show up at all
+        let position = match monitor::keep_on_desktop(position, self.size(), &monitors) {
+            Some(nudged) => {
+                debug_println!(
+                    "window at {}, {} would be off-screen. Nudging it to {}, {}",
+                    position.x,
+                    position.y,
+                    nudged.x,
+                    nudged.y
+                );
+                nudged
+            }
+            None => position,
+        };
+
+        debug_println!("placing window at {}, {}", position.x, position.y);
+        position
     }
 }
 
 impl Default for Settings {
     fn default() -> Self {
         let savable = PersistedSettings::default();
-        let color = image::premultiply_alpha(savable.color);
+        let color = savable
+            .color
+            .for_buffer(savable.premultiply_alpha, savable.gamma_correct);
         Settings {
             persisted: savable,
             color,
             image: None,
+            animation: None,
             tick_interval: fps_to_tick_interval(DEFAULT_FPS),
             monitor_index: DEFAULT_MONITOR_INDEX,
-            desired_window_position: PhysicalPosition::default(),
             desired_window_size: PhysicalSize::default(),
             render_mode: RenderMode::Crosshair,
+            magnifier: false,
+            spotlight: false,
+            monitor_size: PhysicalSize::default(),
+            picker_saturation: u8::MAX,
+            osd: Osd::default(),
+            image_hidden: false,
+            cycle_hue: None,
+            invisible_color_warned: false,
+        }
+    }
+}
+
+/// Which way to round a center that falls between two pixels, which happens for odd-sized monitors
+#[derive(Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub enum CenterRounding {
+    /// toward -Infinity, so up and to the left
+    #[default]
+    Floor,
+    /// away from 0, so monitors on either side of the primary monitor round the same way
+    Round,
+    /// toward +Infinity, so down and to the right
+    Ceil,
+}
+
+/// What the crosshair position and size settings are measured in
+#[derive(Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub enum Units {
+    /// physical pixels
+    #[default]
+    Pixels,
+    /// hundredths of a percent of the monitor's dimensions, so `5000` is half the monitor. Offsets are relative to the
+    /// monitor's width or height, and sizes are relative to its height. This keeps a crosshair in proportionally the
+    /// same place across monitors of different resolutions.
+    Percent,
+}
+
+impl Units {
+    /// convert an offset along a monitor dimension into pixels, rounding to the nearest pixel
+    fn offset_to_pixels(self, offset: i32, monitor_dimension: i32) -> i32 {
+        match self {
+            Units::Pixels => offset,
+            Units::Percent => {
+                let pixels =
+                    (offset as i64 * monitor_dimension as i64) as f64 / PERCENT_SCALE as f64;
+                pixels.round() as i32
+            }
+        }
+    }
+
+    /// the inverse of [`Units::offset_to_pixels`]
+    fn pixels_to_offset(self, pixels: i32, monitor_dimension: u32) -> i32 {
+        match self {
+            Units::Pixels => pixels,
+            Units::Percent if monitor_dimension == 0 => 0,
+            Units::Percent => {
+                let offset = pixels as f64 * PERCENT_SCALE as f64 / monitor_dimension as f64;
+                offset.round() as i32
+            }
+        }
+    }
+
+    /// convert a size into pixels, rounding to the nearest pixel. Never returns less than one pixel.
+    fn size_to_pixels(self, size: u32, monitor_dimension: u32) -> u32 {
+        let pixels = match self {
+            Units::Pixels => size,
+            Units::Percent => {
+                let pixels = (size as u64 * monitor_dimension as u64) as f64 / PERCENT_SCALE as f64;
+                pixels.round().min(MAX_SIZE as f64) as u32
+            }
+        };
+        pixels.clamp(1, MAX_SIZE)
+    }
+}
+
+/// A hint about what the game mostly looks like, used to pick a crosshair color that stands out against it
+#[derive(Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub enum ContrastMode {
+    /// use the crosshair color as-is
+    #[default]
+    Off,
+    /// the game is mostly dark, so use a near-white version of the crosshair color
+    DarkBackground,
+    /// the game is mostly light, so use a near-black version of the crosshair color
+    LightBackground,
+}
+
+/// What gets drawn at the exact center of a generated crosshair. On even sizes the center is a 2x2 block of pixels.
+#[derive(Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub enum CenterMarker {
+    /// leave the center however the rest of the crosshair drew it
+    #[default]
+    None,
+    /// fill in the center pixel(s)
+    Dot,
+    /// clear the center pixel(s) and outline them
+    HollowDot,
+    /// a tiny `+` one pixel out from the center pixel(s)
+    CrossPixel,
+}
+
+/// Bump the alpha of a straight `color` up to [`MIN_VISIBLE_ALPHA`] if it's any lower, so the crosshair can't vanish
+fn visible_color(color: Color) -> Color {
+    color.with_alpha(color.alpha().max(MIN_VISIBLE_ALPHA))
+}
+
+/// The warning for a `color` too transparent to see, where `replacement` is what `prevent_invisible` swapped it for
+fn invisible_color_warning(color: Color, replacement: Option<Color>) -> String {
+    match replacement {
+        Some(visible) => format!(
+            "Color {color:08X} is so transparent the crosshair would be invisible. Using {visible:08X} instead. \
+             Set prevent_invisible = false in the config to allow this."
+        ),
+        None => format!(
+            "Color {color:08X} is so transparent the crosshair is invisible. \
+             Set prevent_invisible = true in the config to keep it visible."
+        ),
+    }
+}
+
+/// Remove every `null` from the objects in a JSON config, as TOML has no such thing
+fn strip_nulls(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            map.retain(|_, value| !value.is_null());
+            map.values_mut().for_each(strip_nulls);
+        }
+        serde_json::Value::Array(values) => values.iter_mut().for_each(strip_nulls),
+        _ => {}
+    }
+}
+
+/// Bring a config from an older version up to [`CONFIG_VERSION`] one version at a time, then stamp it with the current
+/// version. This works on the raw TOML so keys can be renamed before serde ever sees them. Configs from a newer version
+/// are left alone.
+fn migrate(table: &mut toml::Table) {
+    let version = table
+        .get("config_version")
+        .and_then(toml::Value::as_integer)
+        .unwrap_or(0);
+    if version >= CONFIG_VERSION as i64 {
+        return;
+    }
+
+    if version < 1 {
+        // `crosshair_thickness` became `line_thickness` once there were other crosshairs with their own thicknesses.
+        // The serde alias still accepts the old name, but this way the saved config only ever has the new one.
+        if let Some(thickness) = table.remove("crosshair_thickness") {
+            table.entry("line_thickness").or_insert(thickness);
+        }
+        // the `DotRing` mode got renamed to `CircleDot`
+        if table.get("crosshair_mode").and_then(toml::Value::as_str) == Some("DotRing") {
+            table.insert("crosshair_mode".to_string(), "CircleDot".into());
+        }
+    }
+
+    table.insert("config_version".to_string(), (CONFIG_VERSION as i64).into());
+}
+
+/// How the overlay windows stack against other windows
+#[derive(Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub enum OverlayLevel {
+    /// above everything else
+    #[default]
+    AlwaysOnTop,
+    /// a plain window, which anything focused (such as a fullscreen game) can cover
+    Normal,
+}
+
+impl OverlayLevel {
+    pub fn window_level(self) -> WindowLevel {
+        match self {
+            OverlayLevel::AlwaysOnTop => WindowLevel::AlwaysOnTop,
+            OverlayLevel::Normal => WindowLevel::Normal,
+        }
+    }
+
+    pub fn toggled(self) -> OverlayLevel {
+        match self {
+            OverlayLevel::AlwaysOnTop => OverlayLevel::Normal,
+            OverlayLevel::Normal => OverlayLevel::AlwaysOnTop,
         }
     }
 }
 
-#[derive(Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Debug, Default)]
 pub enum RenderMode {
+    /// a user-provided PNG
     Image,
+    /// a user-provided animated PNG
+    Animated,
+    /// the generated `+` crosshair
+    #[default]
     Crosshair,
+    /// the generated hollow ring with a dot in the middle. `DotRing` is accepted as another name for it.
+    #[serde(alias = "DotRing")]
+    CircleDot,
+    /// the generated thick ring between `inner_radius` and `outer_radius`
+    Donut,
+    /// the generated hollow ring, sized like the `+` crosshair
+    Circle,
+    /// the generated `+` crosshair without its top arm
+    TShape,
+    /// the generated diagonal `X` crosshair, sized like the `+` crosshair
+    Cross,
     ColorPicker,
 }
 
-impl<T> From<&Option<T>> for RenderMode
-where
-    T: AsRef<Image>,
-{
-    fn from(value: &Option<T>) -> Self {
-        if value.is_some() {
-            RenderMode::Image
-        } else {
-            RenderMode::Crosshair
+impl RenderMode {
+    /// the modes that can be picked with the `crosshair_mode` setting, in the order they get cycled through
+    pub const GENERATED: [RenderMode; 6] = [
+        RenderMode::Crosshair,
+        RenderMode::CircleDot,
+        RenderMode::Donut,
+        RenderMode::Circle,
+        RenderMode::Cross,
+        RenderMode::TShape,
+    ];
+
+    /// `true` for the modes that draw a user-provided image
+    pub fn is_image(self) -> bool {
+        matches!(self, RenderMode::Image | RenderMode::Animated)
+    }
+
+    /// `true` for the modes that can be picked with the `crosshair_mode` setting
+    pub fn is_generated(self) -> bool {
+        RenderMode::GENERATED.contains(&self)
+    }
+
+    /// The generated mode after this one in [`RenderMode::GENERATED`], wrapping around at the end. Anything that isn't
+    /// generated is followed by the first generated mode.
+    pub fn next_generated(self) -> RenderMode {
+        let generated = RenderMode::GENERATED;
+        match generated.iter().position(|&mode| mode == self) {
+            Some(index) => generated[(index + 1) % generated.len()],
+            None => generated[0],
+        }
+    }
+
+    /// The generated mode before this one in [`RenderMode::GENERATED`], wrapping around at the start. Anything that
+    /// isn't generated is preceded by the first generated mode.
+    pub fn previous_generated(self) -> RenderMode {
+        let generated = RenderMode::GENERATED;
+        match generated.iter().position(|&mode| mode == self) {
+            Some(index) => generated[(index + generated.len() - 1) % generated.len()],
+            None => generated[0],
         }
     }
+
+    /// `true` for the generated crosshairs whose size follows `window_width` and `window_height`
+    fn is_scalable(self) -> bool {
+        matches!(
+            self,
+            RenderMode::Crosshair | RenderMode::Circle | RenderMode::TShape | RenderMode::Cross
+        )
+    }
 }
 
 #[cfg(test)]
 mod test_config_load {
     use super::*;
 
-    /// typical config
+    /// load a config with the given crosshair size from disk
+    fn load_size(name: &str, width: u32, height: u32) -> Settings {
+        let mut path = std::env::temp_dir();
+        path.push(format!(
+            "DELETEME_simple-crosshair-overlay-test-{name}.toml"
+        ));
+        let config = format!(
+            "window_dx = 0\nwindow_dy = 0\nwindow_width = {width}\nwindow_height = {height}\ncolor = \"FFFF0000\"\n"
+        );
+        fs::write(&path, config).expect("write failed");
+        let settings = Settings::load_from_path(&path).expect("load failed");
+        fs::remove_file(&path).expect("cleanup failed");
+        settings
+    }
+
     #[test]
-    fn test_load_settings() {
-        Settings::load_from_path("tests/resources/test_config.toml").unwrap();
+    fn test_zero_size_clamped() {
+        let settings = load_size("zero-size", 0, 0);
+        assert_eq!(settings.persisted.window_width, 1);
+        assert_eq!(settings.persisted.window_height, 1);
+        assert_eq!(settings.size(), PhysicalSize::new(1, 1));
     }
 
-    /// config with an image set
     #[test]
-    fn test_load_settings_with_image() {
-        Settings::load_from_path("tests/resources/test_config_image.toml").unwrap();
+    fn test_huge_size_clamped() {
+        let settings = load_size("huge-size", u32::MAX, 4_000_000_000);
+        assert_eq!(settings.persisted.window_width, MAX_CONFIG_SIZE);
+        assert_eq!(settings.persisted.window_height, MAX_CONFIG_SIZE);
+        assert_eq!(
+            settings.size(),
+            PhysicalSize::new(MAX_CONFIG_SIZE, MAX_CONFIG_SIZE)
+        );
     }
 
-    /// config with minimum possible values set
+    /// save a slot to disk and load it back over different settings
+    #[test]
+    fn test_slot_round_trip() {
+        let mut settings = Settings::load_from_path("tests/resources/test_config.toml").unwrap();
+        settings.persisted.window_dx = 12;
+        settings.persisted.window_dy = -34;
+        settings.scale_up(5);
+        settings.set_color(Color::from_argb(0xFF00FF00));
+
+        let mut path = std::env::temp_dir();
+        path.push("DELETEME_simple-crosshair-overlay-test-slot.toml");
+        settings.save_to_path(&path).expect("save failed");
+
+        let mut loaded = Settings {
+            spotlight: true,
+            ..Default::default()
+        };
+        loaded.load_slot_from_path(&path).expect("load failed");
+        fs::remove_file(&path).expect("cleanup failed");
+
+        assert_eq!(loaded.persisted.window_dx, 12);
+        assert_eq!(loaded.persisted.window_dy, -34);
+        assert_eq!(loaded.size(), settings.size());
+        assert_eq!(loaded.persisted.color, Color::from_argb(0xFF00FF00));
+        assert_eq!(loaded.color, settings.color);
+        assert_eq!(loaded.render_mode, RenderMode::Crosshair);
+        assert!(
+            loaded.spotlight,
+            "temporary state should survive a slot load"
+        );
+    }
+
+    /// a `--config` path in a directory that doesn't exist yet works just like the default path
+    #[test]
+    fn test_custom_config_path() {
+        let directory = std::env::temp_dir().join("DELETEME_simple-crosshair-overlay-test-config");
+        let _ = fs::remove_dir_all(&directory);
+        let path = directory.join("nested").join("config.toml");
+
+        // nothing to load yet, but the directory is ready for the save on exit
+        let error = Settings::load(&path)
+            .err()
+            .expect("nothing should have loaded");
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        assert!(path.parent().unwrap().is_dir());
+
+        let mut settings = Settings::default();
+        settings.persisted.window_dx = 21;
+        settings.save(&path).expect("save failed");
+        let loaded = Settings::load(&path).expect("load failed");
+        fs::remove_dir_all(&directory).expect("cleanup failed");
+
+        assert_eq!(loaded.persisted.window_dx, 21);
+    }
+
+    #[test]
+    fn test_config_path_arg() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        assert_eq!(config_path_arg(args(&["app"])), None);
+        assert_eq!(
+            config_path_arg(args(&["app", "--config", "other.toml"])),
+            Some(PathBuf::from("other.toml"))
+        );
+        assert_eq!(
+            config_path_arg(args(&["app", "--config=other.toml"])),
+            Some(PathBuf::from("other.toml"))
+        );
+        assert_eq!(config_path_arg(args(&["app", "--config"])), None);
+        // not to be confused with the remote config
+        assert_eq!(
+            config_path_arg(args(&["app", "--config-url", "https://example.com"])),
+            None
+        );
+    }
+
+    /// loading a slot that was never saved should fail without touching the current settings
+    #[test]
+    fn test_missing_slot() {
+        let mut settings = Settings::default();
+        settings.persisted.window_dx = 7;
+        assert!(settings
+            .load_slot_from_path("tests/resources/DOES_NOT_EXIST.toml")
+            .is_err());
+        assert_eq!(settings.persisted.window_dx, 7);
+    }
+
+    /// typical config
+    #[test]
+    fn test_load_settings() {
+        Settings::load_from_path("tests/resources/test_config.toml").unwrap();
+    }
+
+    /// config with an image set
+    #[test]
+    fn test_load_settings_with_image() {
+        Settings::load_from_path("tests/resources/test_config_image.toml").unwrap();
+    }
+
+    /// config with minimum possible values set
     #[test]
     fn test_load_settings_old() {
         Settings::load_from_path("tests/resources/test_config_old.toml").unwrap();
@@ -381,8 +2173,67 @@ mod test_config_load {
     fn test_load_png() {
         let mut settings = Settings::load_from_path("tests/resources/test_config.toml").unwrap();
         settings
-            .load_png("tests/resources/test.png".into())
+            .load_image("tests/resources/test.png".into())
+            .unwrap();
+    }
+
+    /// RGB PNGs are only turned away in strict mode
+    #[test]
+    fn test_load_png_strict() {
+        let mut settings = Settings::default();
+        settings
+            .load_image("tests/resources/test_rgb.png".into())
+            .unwrap();
+        assert_eq!(settings.render_mode, RenderMode::Image);
+
+        let mut settings = Settings::default();
+        settings.persisted.strict_png = true;
+        assert!(settings
+            .load_image("tests/resources/test_rgb.png".into())
+            .is_err());
+        assert!(settings.image().is_none());
+        assert_eq!(settings.render_mode, RenderMode::Crosshair);
+    }
+
+    /// oversized PNGs get shrunk to fit the monitor only when asked to
+    #[test]
+    fn test_load_png_auto_fit() {
+        let mut settings = Settings {
+            monitor_size: PhysicalSize::new(100, 100),
+            ..Default::default()
+        };
+        settings
+            .load_image("tests/resources/test.png".into())
+            .unwrap();
+        assert_eq!(settings.image().unwrap().width, 128);
+
+        settings.persisted.auto_fit_image = Some(0.5);
+        settings
+            .load_image("tests/resources/test.png".into())
             .unwrap();
+        let image = settings.image().unwrap();
+        assert_eq!((image.width, image.height), (50, 50));
+        assert_eq!(image.data.len(), 50 * 50);
+    }
+
+    /// the image from the config is loaded before the monitor size is known, and gets fit once it is
+    #[test]
+    fn test_auto_fit_startup() {
+        let mut settings = Settings::from_toml(
+            "window_dx = 0\nwindow_dy = 0\nwindow_width = 16\nwindow_height = 16\ncolor = \"FFFF0005\"\n\
+             image_path = \"tests/resources/test.png\"\nauto_fit_image = 0.5\n",
+        )
+        .unwrap();
+        assert_eq!(settings.image().unwrap().width, 128);
+
+        settings.monitor_size = PhysicalSize::new(100, 100);
+        settings.fit_image();
+        let image = settings.image().unwrap();
+        assert_eq!((image.width, image.height), (50, 50));
+
+        // fitting again once it already fits leaves it alone
+        settings.fit_image();
+        assert_eq!(settings.image().unwrap().width, 50);
     }
 
     /// save config to disk
@@ -396,4 +2247,1717 @@ mod test_config_load {
         settings.save_to_path(&path).expect("save failed");
         fs::remove_file(&path).expect("cleanup failed");
     }
+
+    /// `crosshair_thickness` is accepted as another name for `line_thickness`
+    #[test]
+    fn test_crosshair_thickness_alias() {
+        let settings = Settings::from_toml(
+            "window_dx = 0\nwindow_dy = 0\nwindow_width = 16\nwindow_height = 16\ncolor = \"FFFF0000\"\n\
+             crosshair_thickness = 3\n",
+        )
+        .unwrap();
+        assert_eq!(settings.persisted.line_thickness, 3);
+    }
+}
+
+#[cfg(test)]
+mod test_image_scale {
+    use super::*;
+
+    /// the 128x128 test image
+    fn image_settings() -> Settings {
+        let mut settings = Settings::default();
+        settings
+            .load_image("tests/resources/test.png".into())
+            .unwrap();
+        settings
+    }
+
+    #[test]
+    fn test_size_follows_scale() {
+        let mut settings = image_settings();
+        assert!(settings.is_scalable());
+        assert_eq!(settings.size(), PhysicalSize::new(128, 128));
+
+        settings.persisted.image_scale = 2.0;
+        assert_eq!(settings.size(), PhysicalSize::new(256, 256));
+        settings.persisted.image_scale = 0.25;
+        assert_eq!(settings.size(), PhysicalSize::new(32, 32));
+    }
+
+    /// the scale hotkeys change the image's height by whole pixels, leaving the generated crosshair alone
+    #[test]
+    fn test_scale_hotkeys() {
+        let mut settings = image_settings();
+        settings.scale_up(32);
+        assert_eq!(settings.size(), PhysicalSize::new(160, 160));
+        settings.scale_down(96);
+        assert_eq!(settings.size(), PhysicalSize::new(64, 64));
+        assert_eq!(settings.persisted.window_height, DEFAULT_SIZE);
+
+        // never smaller than the minimum scale
+        settings.scale_down(u32::MAX);
+        assert_eq!(settings.persisted.image_scale, MIN_IMAGE_SCALE);
+        assert_eq!(settings.size(), PhysicalSize::new(13, 13));
+
+        settings.reset();
+        assert_eq!(settings.persisted.image_scale, DEFAULT_IMAGE_SCALE);
+    }
+
+    /// a hidden image leaves the scale hotkeys to the generated crosshair
+    #[test]
+    fn test_hidden_image() {
+        let mut settings = image_settings();
+        settings.toggle_image();
+        settings.scale_up(4);
+        assert_eq!(settings.persisted.image_scale, DEFAULT_IMAGE_SCALE);
+    }
+
+    #[test]
+    fn test_render_scaled() {
+        let mut settings = image_settings();
+        settings.persisted.image_scale = 2.0;
+        let size = settings.content_size();
+        let (width, height) = (size.width as usize, size.height as usize);
+        let mut buffer = vec![0; width * height];
+        image::draw::render_content(&mut buffer, width, height, &settings);
+        let native = settings.image().unwrap();
+        for (x, y) in [(0, 0), (37, 90), (127, 127)] {
+            let expected = native.data[y * native.width as usize + x];
+            for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                assert_eq!(buffer[(y * 2 + dy) * width + x * 2 + dx], expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        for image_scale in [0.0, -1.0, f32::NAN] {
+            let settings = PersistedSettings {
+                image_scale,
+                ..Default::default()
+            }
+            .load();
+            assert_eq!(settings.persisted.image_scale, DEFAULT_IMAGE_SCALE);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_opacity {
+    use super::*;
+
+    #[test]
+    fn test_change_opacity() {
+        let mut settings = Settings::default();
+        assert_eq!(settings.persisted.opacity, u8::MAX);
+        assert!(!settings.change_opacity(true));
+        assert!(settings.change_opacity(false));
+        assert_eq!(settings.persisted.opacity, u8::MAX - OPACITY_STEP);
+
+        for _ in 0..u8::MAX / OPACITY_STEP {
+            settings.change_opacity(false);
+        }
+        assert_eq!(settings.persisted.opacity, 0);
+        assert!(!settings.change_opacity(false));
+    }
+}
+
+#[cfg(test)]
+mod test_shadow {
+    use super::*;
+
+    #[test]
+    fn test_shadow_settings() {
+        let mut settings = Settings::default();
+        settings.persisted.shadow_offset = (1, 1);
+        assert_eq!(settings.shadow_color(), None);
+
+        settings.persisted.premultiply_alpha = true;
+        settings.persisted.shadow_color = Some(Color::from_argb(0x80FFFFFF));
+        assert_eq!(settings.shadow_color(), Some(Color::from_argb(0x80808080)));
+
+        // no offset means the shadow would be hidden anyway
+        settings.persisted.shadow_offset = (0, 0);
+        assert_eq!(settings.shadow_color(), None);
+    }
+}
+
+#[cfg(test)]
+mod test_describe {
+    use super::*;
+
+    #[test]
+    fn test_describe() {
+        let mut settings = Settings::default();
+        // the width follows the height and aspect ratio
+        settings.persisted.window_height = 24;
+        settings.persisted.window_dx = -5;
+        settings.set_color(Color::from_argb(0x80FF8040));
+
+        let summary = settings.describe();
+        assert!(summary.contains("size: 24x24"), "{summary}");
+        assert!(summary.contains("offset: (-5, 0)"), "{summary}");
+        assert!(summary.contains("color: 80FF8040"), "{summary}");
+        assert!(summary.contains("monitor: 1"), "{summary}");
+        assert!(summary.contains("render mode: Crosshair"), "{summary}");
+        assert!(summary.contains("image: none"), "{summary}");
+    }
+}
+
+#[cfg(test)]
+mod test_adjustment {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut settings = Settings::default();
+        let before = settings.adjustment();
+
+        settings.persisted.window_dx = 12;
+        settings.persisted.window_height = 99;
+        settings.set_color(Color::from_argb(0x8000FF00));
+        settings.set_crosshair_mode(RenderMode::Donut);
+        assert_ne!(settings.adjustment(), before);
+
+        settings.apply_adjustment(before);
+        assert_eq!(settings.adjustment(), before);
+        assert_eq!(settings.render_mode, RenderMode::Crosshair);
+        assert_eq!(
+            settings.color,
+            before.color.for_buffer(
+                settings.persisted.premultiply_alpha,
+                settings.persisted.gamma_correct
+            )
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_fps_clamp {
+    use super::*;
+
+    #[test]
+    fn test_set_fps_bounds() {
+        let mut settings = Settings::default();
+        settings.set_fps(0);
+        assert_eq!(settings.fps(), MIN_FPS);
+        assert_eq!(settings.tick_interval, Duration::from_secs(1));
+
+        settings.set_fps(1000);
+        assert_eq!(settings.fps(), MAX_FPS);
+        // 1000 / 240 rounds up
+        assert_eq!(settings.tick_interval, Duration::from_millis(5));
+    }
+
+    #[test]
+    fn test_change_fps() {
+        let mut settings = Settings::default();
+        assert!(settings.change_fps(true));
+        assert_eq!(settings.fps(), DEFAULT_FPS + FPS_STEP);
+        assert_eq!(
+            settings.tick_interval,
+            fps_to_tick_interval(DEFAULT_FPS + FPS_STEP)
+        );
+
+        settings.set_fps(MAX_FPS);
+        assert!(!settings.change_fps(true));
+        assert_eq!(settings.fps(), MAX_FPS);
+
+        settings.set_fps(5);
+        assert!(settings.change_fps(false));
+        assert_eq!(settings.fps(), MIN_FPS);
+        assert!(!settings.change_fps(false));
+    }
+
+    #[test]
+    fn test_load_clamps_fps() {
+        let persisted = PersistedSettings {
+            fps: 0,
+            ..Default::default()
+        };
+        let settings = persisted.load();
+        assert_eq!(settings.fps(), MIN_FPS);
+        assert_eq!(settings.tick_interval, Duration::from_secs(1));
+    }
+}
+
+#[cfg(test)]
+mod test_export_png {
+    use super::*;
+
+    fn export(settings: &Settings, name: &str) -> Box<Image> {
+        let mut path = std::env::temp_dir();
+        path.push(format!("DELETEME_simple-crosshair-overlay-test-{name}.png"));
+        settings.export_png(&path).expect("export failed");
+        let exported = image::load_png(&path, false, false, true).expect("load failed");
+        fs::remove_file(&path).expect("cleanup failed");
+        exported
+    }
+
+    #[test]
+    fn test_generated_crosshair() {
+        let mut settings = Settings::default();
+        settings.set_color(Color::from_argb(0x80FF0000));
+        let exported = export(&settings, "export-crosshair");
+        assert_eq!(
+            PhysicalSize::new(exported.width, exported.height),
+            settings.content_size()
+        );
+        let center = (exported.height / 2 * exported.width + exported.width / 2) as usize;
+        // saved with straight alpha, even though the window buffer may be premultiplied
+        assert_eq!(exported.data[center], 0x80FF0000);
+        assert_eq!(exported.data[0], 0);
+    }
+
+    #[test]
+    fn test_image() {
+        let mut settings = Settings::default();
+        settings
+            .load_image("tests/resources/test.png".into())
+            .unwrap();
+        let exported = export(&settings, "export-image");
+        let image = image::load_png("tests/resources/test.png", false, false, true).unwrap();
+        assert_eq!(
+            (exported.width, exported.height),
+            (image.width, image.height)
+        );
+        assert_eq!(exported.data, image.data);
+    }
+}
+
+#[cfg(test)]
+mod test_profiles {
+    use super::*;
+
+    const CONFIG: &str = r#"
+window_dx = 0
+window_dy = 0
+window_width = 16
+window_height = 16
+color = "FFFF0000"
+active_profile = 0
+
+[[profiles]]
+window_dx = 0
+window_dy = 0
+window_width = 16
+window_height = 16
+color = "FFFF0000"
+
+[[profiles]]
+window_dx = 10
+window_dy = -20
+window_width = 32
+window_height = 40
+color = "8000FF00"
+image_path = "tests/resources/test.png"
+"#;
+
+    #[test]
+    fn test_switch() {
+        let mut settings = Settings::from_toml(CONFIG).unwrap();
+        assert_eq!(settings.persisted.profiles.len(), 2);
+
+        // tweak the first profile before leaving it
+        settings.persisted.window_dx = 5;
+
+        assert_eq!(settings.switch_profile(true), Some(2));
+        assert_eq!(settings.persisted.window_dx, 10);
+        assert_eq!(settings.persisted.window_dy, -20);
+        assert_eq!(settings.persisted.window_width, 32);
+        assert_eq!(settings.persisted.window_height, 40);
+        assert_eq!(settings.straight_color(), Color::from_argb(0x8000FF00));
+        assert_eq!(settings.render_mode, RenderMode::Image);
+        assert!(settings.image().is_some());
+
+        // wraps back around to the first, which kept the tweak
+        assert_eq!(settings.switch_profile(true), Some(1));
+        assert_eq!(settings.persisted.window_dx, 5);
+        assert_eq!(settings.persisted.window_width, 16);
+        assert_eq!(settings.straight_color(), Color::from_argb(0xFFFF0000));
+        assert_eq!(settings.render_mode, RenderMode::Crosshair);
+        assert!(settings.image().is_none());
+        assert!(settings.persisted.image_path.is_none());
+
+        assert_eq!(settings.switch_profile(false), Some(2));
+        assert_eq!(settings.persisted.window_dx, 10);
+    }
+
+    #[test]
+    fn test_no_profiles() {
+        let mut settings = Settings::default();
+        settings.persisted.window_dx = 5;
+        assert_eq!(settings.switch_profile(true), None);
+        assert_eq!(settings.switch_profile(false), None);
+        assert_eq!(settings.persisted.window_dx, 5);
+    }
+
+    /// the color picker stays up across a switch
+    #[test]
+    fn test_switch_while_picking() {
+        let mut settings = Settings::from_toml(CONFIG).unwrap();
+        settings.set_pick_color(true);
+        settings.switch_profile(true);
+        assert_eq!(settings.render_mode, RenderMode::ColorPicker);
+        settings.set_pick_color(false);
+        assert_eq!(settings.render_mode, RenderMode::Image);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut settings = Settings::from_toml(CONFIG).unwrap();
+        settings.switch_profile(true);
+        // adjusting the active profile is saved without switching away first
+        settings.persisted.window_dy = 7;
+
+        let loaded = Settings::from_toml(&settings.to_toml()).unwrap();
+        assert_eq!(loaded.persisted.active_profile, 1);
+        assert_eq!(loaded.persisted.profiles.len(), 2);
+        let first = &loaded.persisted.profiles[0];
+        assert_eq!(first.window_width, 16);
+        assert_eq!(first.color, Color::from_argb(0xFFFF0000));
+        assert_eq!(first.image_path, None);
+        let second = &loaded.persisted.profiles[1];
+        assert_eq!(second.window_dx, 10);
+        assert_eq!(second.window_dy, 7);
+        assert_eq!(second.window_width, 32);
+        assert_eq!(second.color, Color::from_argb(0x8000FF00));
+        assert_eq!(
+            second.image_path.as_deref(),
+            Some(Path::new("tests/resources/test.png"))
+        );
+        assert_eq!(loaded.persisted.window_dy, 7);
+        assert!(loaded.image().is_some());
+    }
+
+    /// an out of range active profile falls back to the first one
+    #[test]
+    fn test_invalid_active_profile() {
+        let config = CONFIG.replace("active_profile = 0", "active_profile = 5");
+        let settings = Settings::from_toml(&config).unwrap();
+        assert_eq!(settings.persisted.active_profile, 0);
+    }
+}
+
+#[cfg(test)]
+mod test_aspect_locked {
+    use super::*;
+
+    fn unlocked() -> Settings {
+        let mut settings = Settings::default();
+        settings.persisted.aspect_locked = false;
+        settings
+    }
+
+    #[test]
+    fn test_independent() {
+        let mut settings = unlocked();
+        assert!(settings.resize_width(true, 10));
+        assert_eq!(settings.size(), PhysicalSize::new(26, 16));
+        assert!(settings.resize_height(false, 6));
+        assert_eq!(settings.size(), PhysicalSize::new(26, 10));
+        assert_eq!(settings.persisted.window_width, 26);
+        assert_eq!(settings.persisted.window_height, 10);
+    }
+
+    /// scaling moves both dimensions by the same amount, so a rectangle stays a rectangle
+    #[test]
+    fn test_scale() {
+        let mut settings = unlocked();
+        settings.resize_width(true, 10);
+        settings.scale_up(4);
+        assert_eq!(settings.size(), PhysicalSize::new(30, 20));
+        settings.scale_down(u32::MAX);
+        assert_eq!(settings.size(), PhysicalSize::new(1, 1));
+    }
+
+    #[test]
+    fn test_aspect_ratio_ignored() {
+        let mut settings = unlocked();
+        settings.persisted.aspect_ratio = 2.0;
+        assert_eq!(
+            settings.size(),
+            PhysicalSize::new(DEFAULT_SIZE, DEFAULT_SIZE)
+        );
+    }
+
+    /// the locked path doesn't let the width wander off on its own
+    #[test]
+    fn test_locked() {
+        let mut settings = Settings::default();
+        assert!(!settings.resize_width(true, 10));
+        assert!(!settings.resize_height(true, 10));
+        assert_eq!(
+            settings.size(),
+            PhysicalSize::new(DEFAULT_SIZE, DEFAULT_SIZE)
+        );
+    }
+
+    #[test]
+    fn test_reset_is_square() {
+        let mut settings = unlocked();
+        settings.resize_width(true, 10);
+        settings.reset();
+        assert_eq!(
+            settings.size(),
+            PhysicalSize::new(DEFAULT_SIZE, DEFAULT_SIZE)
+        );
+    }
+
+    #[test]
+    fn test_default_locked() {
+        let settings = Settings::from_toml(
+            "window_dx = 0\nwindow_dy = 0\nwindow_width = 32\nwindow_height = 16\ncolor = \"FFFF0000\"\n",
+        )
+        .unwrap();
+        assert!(settings.persisted.aspect_locked);
+        assert_eq!(settings.size(), PhysicalSize::new(16, 16));
+    }
+}
+
+#[cfg(test)]
+mod test_aspect_ratio {
+    use super::*;
+
+    fn settings_with_ratio(aspect_ratio: f32) -> Settings {
+        let mut settings = Settings::default();
+        settings.persisted.aspect_ratio = aspect_ratio;
+        settings
+    }
+
+    #[test]
+    fn test_default_is_square() {
+        let settings = Settings::default();
+        assert_eq!(
+            settings.size(),
+            PhysicalSize::new(DEFAULT_SIZE, DEFAULT_SIZE)
+        );
+    }
+
+    #[test]
+    fn test_size_reflects_ratio() {
+        let settings = settings_with_ratio(2.0);
+        assert_eq!(
+            settings.size(),
+            PhysicalSize::new(DEFAULT_SIZE * 2, DEFAULT_SIZE)
+        );
+    }
+
+    #[test]
+    fn test_scaling_preserves_ratio() {
+        let mut settings = settings_with_ratio(2.0);
+        settings.scale_up(5);
+        assert_eq!(settings.size(), PhysicalSize::new(42, 21));
+        settings.scale_down(11);
+        assert_eq!(settings.size(), PhysicalSize::new(20, 10));
+        assert_eq!(settings.persisted.window_width, 20);
+        assert_eq!(settings.persisted.window_height, 10);
+    }
+
+    #[test]
+    fn test_tall_ratio_rounds() {
+        let mut settings = settings_with_ratio(0.5);
+        settings.scale_down(DEFAULT_SIZE - 3);
+        assert_eq!(settings.size(), PhysicalSize::new(2, 3));
+    }
+
+    #[test]
+    fn test_scale_down_clamps() {
+        let mut settings = settings_with_ratio(0.25);
+        settings.scale_down(u32::MAX);
+        assert_eq!(settings.size(), PhysicalSize::new(1, 1));
+    }
+
+    #[test]
+    fn test_scale_up_clamps() {
+        let mut settings = settings_with_ratio(1.0);
+        settings.scale_up(u32::MAX);
+        assert_eq!(settings.size(), PhysicalSize::new(MAX_SIZE, MAX_SIZE));
+    }
+
+    #[test]
+    fn test_fixed_step() {
+        let mut settings = settings_with_ratio(1.0);
+        settings.persisted.fixed_scale_step = 4;
+        for press in 1..=3 {
+            settings.scale_up(settings.persisted.fixed_scale_step);
+            assert_eq!(settings.persisted.window_height, DEFAULT_SIZE + press * 4);
+        }
+        settings.scale_down(settings.persisted.fixed_scale_step);
+        assert_eq!(settings.persisted.window_height, DEFAULT_SIZE + 8);
+    }
+
+    #[test]
+    fn test_invalid_ratio_falls_back() {
+        let persisted = PersistedSettings {
+            aspect_ratio: -1.0,
+            ..Default::default()
+        };
+        let settings = persisted.load();
+        assert_eq!(settings.persisted.aspect_ratio, DEFAULT_ASPECT_RATIO);
+    }
+}
+
+#[cfg(test)]
+mod test_premultiply_setting {
+    use super::*;
+
+    const COLOR: Color = Color::from_argb(0x80FF0000);
+
+    fn settings(premultiply_alpha: bool) -> Settings {
+        PersistedSettings {
+            color: COLOR,
+            premultiply_alpha,
+            ..Default::default()
+        }
+        .load()
+    }
+
+    #[test]
+    fn test_straight() {
+        let mut settings = settings(false);
+        assert_eq!(settings.color, COLOR);
+        settings.set_color(Color::from_argb(0x40FFFFFF));
+        assert_eq!(settings.color, Color::from_argb(0x40FFFFFF));
+    }
+
+    #[test]
+    fn test_premultiplied() {
+        let mut settings = settings(true);
+        assert_eq!(settings.color, Color::from_argb(0x80800000));
+        settings.set_color(Color::from_argb(0x40FFFFFF));
+        assert_eq!(settings.color, Color::from_argb(0x40404040));
+    }
+
+    #[test]
+    fn test_image() {
+        let mut straight = settings(false);
+        let mut premultiplied = settings(true);
+        straight
+            .load_image("tests/resources/test.png".into())
+            .unwrap();
+        premultiplied
+            .load_image("tests/resources/test.png".into())
+            .unwrap();
+        for (&straight, &premultiplied) in straight
+            .image()
+            .unwrap()
+            .data
+            .iter()
+            .zip(premultiplied.image().unwrap().data.iter())
+        {
+            assert_eq!(
+                image::premultiply_alpha(straight, true, false),
+                premultiplied
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_spotlight {
+    use super::*;
+
+    #[test]
+    fn test_spotlight_override() {
+        let mut settings = Settings::default();
+        settings.persisted.premultiply_alpha = true;
+        settings.set_color(Color::from_argb(0x40FF0000));
+        assert_eq!(settings.render_color(), Color::from_argb(0x40400000));
+
+        settings.spotlight = true;
+        assert_eq!(settings.render_color(), Color::from_argb(0xFFFF0000));
+
+        settings.persisted.spotlight_color = Some(Color::from_argb(0x8000FF00));
+        assert_eq!(settings.render_color(), Color::from_argb(0xFF00FF00));
+
+        settings.spotlight = false;
+        assert_eq!(settings.render_color(), Color::from_argb(0x40400000));
+    }
+}
+
+#[cfg(test)]
+mod test_contrast_mode {
+    use super::*;
+
+    #[test]
+    fn test_render_color() {
+        let mut settings = Settings::default();
+        settings.set_color(Color::from_argb(0x80808080));
+
+        settings.persisted.contrast_mode = ContrastMode::DarkBackground;
+        assert_eq!(settings.render_color(), Color::from_argb(0xFFFFFFFF));
+        settings.persisted.contrast_mode = ContrastMode::LightBackground;
+        assert_eq!(settings.render_color(), Color::from_argb(0xFF282828));
+        settings.persisted.contrast_mode = ContrastMode::Off;
+        assert_eq!(settings.render_color(), settings.color);
+    }
+
+    /// the spotlight color wins over the contrast adjustment
+    #[test]
+    fn test_spotlight_wins() {
+        let mut settings = Settings::default();
+        settings.set_color(Color::from_argb(0x80808080));
+        settings.persisted.contrast_mode = ContrastMode::LightBackground;
+        settings.persisted.spotlight_color = Some(Color::from_argb(0xFF00FF00));
+        settings.spotlight = true;
+        assert_eq!(settings.render_color(), Color::from_argb(0xFF00FF00));
+    }
+}
+
+#[cfg(test)]
+mod test_circle_dot {
+    use super::*;
+
+    fn circle_dot_settings() -> Settings {
+        PersistedSettings {
+            crosshair_mode: RenderMode::CircleDot,
+            ring_radius: 10,
+            dot_radius: 2,
+            ..Default::default()
+        }
+        .load()
+    }
+
+    #[test]
+    fn test_render_mode() {
+        let mut settings = circle_dot_settings();
+        assert_eq!(settings.render_mode, RenderMode::CircleDot);
+        assert!(!settings.is_scalable());
+
+        // color picking goes back to the configured crosshair
+        settings.set_pick_color(true);
+        settings.set_color(Color::from_argb(0xFF00FF00));
+        assert_eq!(settings.render_mode, RenderMode::CircleDot);
+    }
+
+    #[test]
+    fn test_size_fits_ring() {
+        let mut settings = circle_dot_settings();
+        assert_eq!(settings.size(), PhysicalSize::new(20, 20));
+        settings.persisted.dot_radius = 15;
+        assert_eq!(settings.size(), PhysicalSize::new(30, 30));
+    }
+
+    #[test]
+    fn test_dot_ring_alias() {
+        let settings = Settings::from_toml(
+            "window_dx = 0\nwindow_dy = 0\nwindow_width = 16\nwindow_height = 16\ncolor = \"FFFF0000\"\n\
+             crosshair_mode = \"DotRing\"\n",
+        )
+        .unwrap();
+        assert_eq!(settings.render_mode, RenderMode::CircleDot);
+    }
+
+    /// the dot in the middle is filled, and there's a transparent gap between it and the ring
+    #[test]
+    fn test_render() {
+        let settings = circle_dot_settings();
+        let PhysicalSize { width, height } = settings.content_size();
+        let (width, height) = (width as usize, height as usize);
+        let mut buffer = vec![0; width * height];
+        image::draw::render_content(&mut buffer, width, height, &settings);
+        let center_row = height / 2 * width;
+        assert_eq!(buffer[center_row + width / 2], settings.color.argb());
+        assert_eq!(buffer[center_row + width / 2 - 5], 0);
+        assert_eq!(buffer[center_row], settings.color.argb());
+    }
+
+    #[test]
+    fn test_invalid_mode_falls_back() {
+        let settings = PersistedSettings {
+            crosshair_mode: RenderMode::ColorPicker,
+            ..Default::default()
+        }
+        .load();
+        assert_eq!(settings.render_mode, RenderMode::Crosshair);
+    }
+}
+
+#[cfg(test)]
+mod test_circle {
+    use super::*;
+
+    /// the circle is sized and scaled just like the `+` crosshair
+    #[test]
+    fn test_scalable() {
+        let mut settings = PersistedSettings {
+            crosshair_mode: RenderMode::Circle,
+            ..Default::default()
+        }
+        .load();
+        assert_eq!(settings.render_mode, RenderMode::Circle);
+        assert!(settings.is_scalable());
+        assert_eq!(
+            settings.size(),
+            PhysicalSize::new(DEFAULT_SIZE, DEFAULT_SIZE)
+        );
+        settings.scale_up(5);
+        assert_eq!(
+            settings.size(),
+            PhysicalSize::new(DEFAULT_SIZE + 5, DEFAULT_SIZE + 5)
+        );
+    }
+
+    #[test]
+    fn test_config() {
+        let settings = Settings::from_toml(
+            "window_dx = 0\nwindow_dy = 0\nwindow_width = 16\nwindow_height = 16\ncolor = \"FFFF0000\"\n\
+             crosshair_mode = \"Circle\"\ncircle_thickness = 3\n",
+        )
+        .unwrap();
+        assert_eq!(settings.render_mode, RenderMode::Circle);
+        assert_eq!(settings.persisted.circle_thickness, 3);
+    }
+}
+
+#[cfg(test)]
+mod test_t_shape {
+    use super::*;
+
+    #[test]
+    fn test_scalable() {
+        let settings = PersistedSettings {
+            crosshair_mode: RenderMode::TShape,
+            ..Default::default()
+        }
+        .load();
+        assert_eq!(settings.render_mode, RenderMode::TShape);
+        assert!(settings.is_scalable());
+        assert_eq!(
+            settings.size(),
+            PhysicalSize::new(DEFAULT_SIZE, DEFAULT_SIZE)
+        );
+    }
+
+    /// cycling visits every generated mode once and never lands on the others
+    #[test]
+    fn test_cycle() {
+        let mut mode = RenderMode::Crosshair;
+        let mut visited = Vec::new();
+        for _ in 0..RenderMode::GENERATED.len() {
+            mode = mode.next_generated();
+            visited.push(mode);
+            assert_eq!(mode.previous_generated().next_generated(), mode);
+        }
+        assert_eq!(mode, RenderMode::Crosshair);
+        assert!(visited.contains(&RenderMode::TShape));
+        assert!(visited.iter().all(|mode| mode.is_generated()));
+        assert_eq!(RenderMode::Image.next_generated(), RenderMode::Crosshair);
+    }
+}
+
+#[cfg(test)]
+mod test_donut {
+    use super::*;
+
+    #[test]
+    fn test_size_fits_donut() {
+        let settings = PersistedSettings {
+            crosshair_mode: RenderMode::Donut,
+            inner_radius: 3,
+            outer_radius: 6,
+            ..Default::default()
+        }
+        .load();
+        assert_eq!(settings.render_mode, RenderMode::Donut);
+        assert!(!settings.is_scalable());
+        assert_eq!(settings.size(), PhysicalSize::new(13, 13));
+    }
+
+    #[test]
+    fn test_inverted_radii_fill_in() {
+        let settings = PersistedSettings {
+            crosshair_mode: RenderMode::Donut,
+            inner_radius: 9,
+            outer_radius: 6,
+            ..Default::default()
+        }
+        .load();
+        assert_eq!(settings.persisted.inner_radius, 0);
+        assert_eq!(settings.persisted.outer_radius, 6);
+    }
+}
+
+#[cfg(test)]
+mod test_prevent_invisible {
+    use super::*;
+
+    #[test]
+    fn test_visible_color() {
+        assert_eq!(
+            visible_color(Color::from_argb(0x00FF0000)),
+            Color::from_argb(0x10FF0000)
+        );
+        assert_eq!(
+            visible_color(Color::from_argb(0x01FF0000)),
+            Color::from_argb(0x10FF0000)
+        );
+        assert_eq!(
+            visible_color(Color::from_argb(0xFFFF0000)),
+            Color::from_argb(0xFFFF0000)
+        );
+        assert_eq!(
+            visible_color(Color::from_argb(0x10FF0000)),
+            Color::from_argb(0x10FF0000)
+        );
+    }
+
+    #[test]
+    fn test_set_color() {
+        let mut settings = Settings::default();
+        assert!(!settings.persisted.prevent_invisible);
+        settings.set_color(Color::from_argb(0x00FF0000));
+        assert_eq!(settings.straight_color(), Color::from_argb(0x00FF0000));
+
+        settings.persisted.prevent_invisible = true;
+        settings.set_color(Color::from_argb(0x00FF0000));
+        assert_eq!(settings.straight_color(), Color::from_argb(0x10FF0000));
+    }
+
+    /// the warning only shows the first time, so dragging through the color picker doesn't spam it
+    #[test]
+    fn test_warn_once() {
+        let mut settings = Settings::default();
+        settings.set_color(Color::from_argb(0xFFFF0000));
+        assert!(!settings.invisible_color_warned);
+        settings.set_color(Color::from_argb(0x00FF0000));
+        assert!(settings.invisible_color_warned);
+
+        // a config that already warned on load counts too
+        let settings = Settings::from_toml(
+            "window_dx = 0\nwindow_dy = 0\nwindow_width = 16\nwindow_height = 16\ncolor = \"00FF0000\"\n",
+        )
+        .unwrap();
+        assert!(settings.invisible_color_warned);
+    }
+
+    #[test]
+    fn test_warning() {
+        let color = Color::from_argb(0x00FF0000);
+        assert!(invisible_color_warning(color, None).contains("prevent_invisible = true"));
+        let warning = invisible_color_warning(color, Some(visible_color(color)));
+        assert!(warning.contains("Using 10FF0000 instead."));
+        assert!(warning.contains("prevent_invisible = false"));
+    }
+
+    #[test]
+    fn test_load() {
+        let config = |prevent_invisible: bool| {
+            format!(
+                "window_dx = 0\nwindow_dy = 0\nwindow_width = 16\nwindow_height = 16\ncolor = \"00FF0000\"\n\
+                 prevent_invisible = {prevent_invisible}\n"
+            )
+        };
+        let settings = Settings::from_toml(&config(true)).unwrap();
+        assert_eq!(settings.straight_color(), Color::from_argb(0x10FF0000));
+        let settings = Settings::from_toml(&config(false)).unwrap();
+        assert_eq!(settings.straight_color(), Color::from_argb(0x00FF0000));
+    }
+}
+
+#[cfg(test)]
+mod test_migrate {
+    use super::*;
+
+    #[test]
+    fn test_v0_fixture() {
+        let settings = Settings::load_from_path("tests/resources/test_config_v0.toml").unwrap();
+        assert_eq!(settings.persisted.config_version, CONFIG_VERSION);
+        assert_eq!(settings.persisted.line_thickness, 3);
+        assert_eq!(settings.persisted.crosshair_mode, RenderMode::CircleDot);
+
+        // it gets saved with the canonical names
+        let toml = settings.to_toml();
+        assert!(toml.contains(&format!("config_version = {CONFIG_VERSION}")));
+        assert!(toml.contains("line_thickness = 3"));
+        assert!(toml.contains("crosshair_mode = \"CircleDot\""));
+        assert!(!toml.contains("crosshair_thickness"));
+    }
+
+    /// the version 0 step renames keys on the raw table, before serde's aliases ever come into it
+    #[test]
+    fn test_v0_table() {
+        let mut table: toml::Table = "crosshair_thickness = 3\ncrosshair_mode = \"DotRing\"\n"
+            .parse()
+            .unwrap();
+        migrate(&mut table);
+        let expected: toml::Table = format!(
+            "line_thickness = 3\ncrosshair_mode = \"CircleDot\"\nconfig_version = {CONFIG_VERSION}\n"
+        )
+        .parse()
+        .unwrap();
+        assert_eq!(table, expected);
+    }
+
+    /// if both names are somehow present, the new one wins
+    #[test]
+    fn test_new_name_wins() {
+        let mut table: toml::Table = "crosshair_thickness = 3\nline_thickness = 5\n"
+            .parse()
+            .unwrap();
+        migrate(&mut table);
+        assert_eq!(table.get("line_thickness"), Some(&toml::Value::Integer(5)));
+        assert!(!table.contains_key("crosshair_thickness"));
+    }
+
+    /// migrations only run for versions older than the one they migrate from
+    #[test]
+    fn test_current_untouched() {
+        let mut table: toml::Table =
+            format!("config_version = {CONFIG_VERSION}\ncrosshair_thickness = 3\n")
+                .parse()
+                .unwrap();
+        let expected = table.clone();
+        migrate(&mut table);
+        assert_eq!(table, expected);
+    }
+
+    /// a config from a newer version doesn't get downgraded
+    #[test]
+    fn test_newer_untouched() {
+        let mut table: toml::Table = "config_version = 1000\n".parse().unwrap();
+        migrate(&mut table);
+        assert_eq!(
+            table.get("config_version"),
+            Some(&toml::Value::Integer(1000))
+        );
+    }
+
+    #[test]
+    fn test_default_is_current() {
+        assert_eq!(PersistedSettings::default().config_version, CONFIG_VERSION);
+    }
+}
+
+#[cfg(test)]
+mod test_json_config {
+    use super::*;
+
+    /// the keys every config needs, as a JSON object missing its closing brace
+    const REQUIRED: &str = r#"{"window_dx": 0, "window_dy": 0, "window_width": 16, "window_height": 16, "color": "FF00FF00""#;
+
+    #[test]
+    fn test_format() {
+        assert_eq!(
+            ConfigFormat::of(Path::new("config.json")),
+            ConfigFormat::Json
+        );
+        assert_eq!(
+            ConfigFormat::of(Path::new("CONFIG.JSON")),
+            ConfigFormat::Json
+        );
+        assert_eq!(
+            ConfigFormat::of(Path::new("config.toml")),
+            ConfigFormat::Toml
+        );
+        assert_eq!(ConfigFormat::of(Path::new("config")), ConfigFormat::Toml);
+        assert_eq!(ConfigFormat::of(Path::new("json")), ConfigFormat::Toml);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let settings = Settings::load_from_path("tests/resources/test_config.toml").unwrap();
+        let mut path = std::env::temp_dir();
+        path.push("DELETEME_simple-crosshair-overlay-test-config.json");
+        settings.save_to_path(&path).unwrap();
+        let json = fs::read_to_string(&path).expect("read failed");
+        let loaded = Settings::load_from_path(&path).unwrap();
+        fs::remove_file(&path).expect("cleanup failed");
+
+        // the custom color serializer still makes a hex string, not a number
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["color"], "FFFF0005");
+        assert_eq!(
+            value["key_bindings"]["cycle_monitor"],
+            serde_json::json!(["LControl", "M"])
+        );
+        // unset options are left out instead of being null
+        assert!(!json.contains("null"));
+
+        assert_eq!(loaded.to_toml(), settings.to_toml());
+        assert_eq!(loaded.persisted.color, Color::from_argb(0xFFFF0005));
+    }
+
+    #[test]
+    fn test_null() {
+        let settings = Settings::from_json(&format!(
+            r#"{REQUIRED}, "image_path": null, "monitor_name": null}}"#
+        ))
+        .unwrap();
+        assert_eq!(settings.persisted.color, Color::from_argb(0xFF00FF00));
+        assert!(settings.persisted.image_path.is_none());
+        assert!(settings.persisted.monitor_name.is_none());
+    }
+
+    /// JSON configs get migrated just like TOML ones, aliases included
+    #[test]
+    fn test_migrate() {
+        let settings =
+            Settings::from_json(&format!(r#"{REQUIRED}, "crosshair_thickness": 3}}"#)).unwrap();
+        assert_eq!(settings.persisted.config_version, CONFIG_VERSION);
+        assert_eq!(settings.persisted.line_thickness, 3);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let error = Settings::from_json("color = \"FF00FF00\"").err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        let error = Settings::from_json(&format!(r#"{REQUIRED}, "fps": "fast"}}"#))
+            .err()
+            .unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
+
+#[cfg(test)]
+mod test_overlay_level {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        for level in [OverlayLevel::AlwaysOnTop, OverlayLevel::Normal] {
+            let mut settings = Settings::default();
+            settings.persisted.window_level = level;
+            let loaded = Settings::from_toml(&settings.to_toml()).unwrap();
+            assert_eq!(loaded.persisted.window_level, level);
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let settings = Settings::from_toml(&Settings::default().to_toml()).unwrap();
+        assert_eq!(settings.persisted.window_level, OverlayLevel::AlwaysOnTop);
+        let toml = Settings::default().to_toml().replace(
+            "window_level = \"AlwaysOnTop\"",
+            "window_level = \"Normal\"",
+        );
+        let settings = Settings::from_toml(&toml).unwrap();
+        assert_eq!(settings.persisted.window_level, OverlayLevel::Normal);
+    }
+
+    #[test]
+    fn test_window_level() {
+        assert_eq!(
+            OverlayLevel::AlwaysOnTop.window_level(),
+            WindowLevel::AlwaysOnTop
+        );
+        assert_eq!(OverlayLevel::Normal.window_level(), WindowLevel::Normal);
+        assert_eq!(OverlayLevel::AlwaysOnTop.toggled(), OverlayLevel::Normal);
+        assert_eq!(OverlayLevel::Normal.toggled(), OverlayLevel::AlwaysOnTop);
+    }
+}
+
+#[cfg(test)]
+mod test_units {
+    use super::*;
+
+    const HD: PhysicalSize<u32> = PhysicalSize::new(1920, 1080);
+    const UHD: PhysicalSize<u32> = PhysicalSize::new(3840, 2160);
+
+    #[test]
+    fn test_pixels_unchanged() {
+        assert_eq!(Units::Pixels.offset_to_pixels(-37, 1920), -37);
+        assert_eq!(Units::Pixels.size_to_pixels(16, 1080), 16);
+    }
+
+    #[test]
+    fn test_percent_offset() {
+        // 10% of the width to the right, 5% of the height up
+        assert_eq!(Units::Percent.offset_to_pixels(1000, HD.width as i32), 192);
+        assert_eq!(Units::Percent.offset_to_pixels(-500, HD.height as i32), -54);
+        assert_eq!(Units::Percent.offset_to_pixels(1000, UHD.width as i32), 384);
+        assert_eq!(
+            Units::Percent.offset_to_pixels(-500, UHD.height as i32),
+            -108
+        );
+
+        // rounds to the nearest pixel: 0.01% of 1080 is 0.108
+        assert_eq!(Units::Percent.offset_to_pixels(1, 1080), 0);
+        assert_eq!(Units::Percent.offset_to_pixels(5, 1080), 1);
+        assert_eq!(Units::Percent.offset_to_pixels(-5, 1080), -1);
+    }
+
+    #[test]
+    fn test_percent_size() {
+        assert_eq!(Units::Percent.size_to_pixels(10000, HD.height), 1080);
+        assert_eq!(Units::Percent.size_to_pixels(150, HD.height), 16);
+        assert_eq!(Units::Percent.size_to_pixels(150, UHD.height), 32);
+
+        // never vanishes entirely
+        assert_eq!(Units::Percent.size_to_pixels(0, HD.height), 1);
+        assert_eq!(Units::Percent.size_to_pixels(150, 0), 1);
+        assert_eq!(Units::Percent.size_to_pixels(u32::MAX, u32::MAX), MAX_SIZE);
+    }
+
+    /// the same percent crosshair should be proportionally the same size on different monitors
+    #[test]
+    fn test_size_follows_monitor() {
+        let mut settings = Settings::default();
+        settings.persisted.units = Units::Percent;
+        settings.persisted.aspect_ratio = 2.0;
+        settings.persisted.window_height = 150;
+
+        settings.monitor_size = HD;
+        assert_eq!(settings.size(), PhysicalSize::new(32, 16));
+        settings.monitor_size = UHD;
+        assert_eq!(settings.size(), PhysicalSize::new(64, 32));
+    }
+
+    #[test]
+    fn test_snap_to() {
+        let mut settings = Settings {
+            monitor_size: HD,
+            ..Default::default()
+        };
+        let size = settings.size();
+        settings.snap_to(SnapAnchor::BottomRight, HD);
+        assert_eq!(
+            settings.persisted.window_dx,
+            (HD.width as i32 - size.width as i32) / 2
+        );
+        // a second overlay on a bigger monitor
+        settings.snap_to(SnapAnchor::BottomRight, UHD);
+        assert_eq!(
+            settings.persisted.window_dx,
+            (UHD.width as i32 - size.width as i32) / 2
+        );
+        settings.snap_to(SnapAnchor::Center, HD);
+        assert_eq!(
+            (settings.persisted.window_dx, settings.persisted.window_dy),
+            (0, 0)
+        );
+
+        // snapping in percent lands on the same pixel
+        settings.persisted.units = Units::Percent;
+        settings.snap_to(SnapAnchor::Top, HD);
+        let (_, dy) = SnapAnchor::Top.offset(HD, settings.size(), CenterRounding::Floor);
+        assert_eq!(
+            Units::Percent.offset_to_pixels(settings.persisted.window_dy, HD.height as i32),
+            dy
+        );
+    }
+
+    /// configs from before `units` existed must keep meaning pixels
+    #[test]
+    fn test_old_configs_are_pixels() {
+        let settings = Settings::load_from_path("tests/resources/test_config_old.toml").unwrap();
+        assert_eq!(settings.persisted.units, Units::Pixels);
+    }
+}
+
+#[cfg(test)]
+mod test_toggle_image {
+    use super::*;
+
+    #[test]
+    fn test_toggle_keeps_image() {
+        let mut settings = Settings::default();
+        settings
+            .load_image("tests/resources/test.png".into())
+            .unwrap();
+        assert_eq!(settings.render_mode, RenderMode::Image);
+
+        assert!(settings.toggle_image());
+        assert_eq!(settings.render_mode, RenderMode::Crosshair);
+        assert!(settings.image().is_some());
+        assert!(settings.persisted.image_path.is_some());
+
+        assert!(settings.toggle_image());
+        assert_eq!(settings.render_mode, RenderMode::Image);
+    }
+
+    /// closing the color picker goes back to whichever was showing before
+    #[test]
+    fn test_toggle_survives_color_pick() {
+        let mut settings = Settings::default();
+        settings
+            .load_image("tests/resources/test.png".into())
+            .unwrap();
+        settings.toggle_image();
+        settings.set_pick_color(true);
+        assert!(!settings.toggle_image());
+        settings.set_pick_color(false);
+        assert_eq!(settings.render_mode, RenderMode::Crosshair);
+    }
+
+    #[test]
+    fn test_toggle_without_image() {
+        let mut settings = Settings::default();
+        assert!(!settings.toggle_image());
+        assert_eq!(settings.render_mode, RenderMode::Crosshair);
+    }
+}
+
+#[cfg(test)]
+mod test_reload_last_image {
+    use super::*;
+
+    #[test]
+    fn test_reload_after_set_color() {
+        let mut settings = Settings::default();
+        settings
+            .load_image("tests/resources/test.png".into())
+            .unwrap();
+        settings.set_color(Color::from_argb(0xFF00FF00));
+        assert!(settings.image().is_none());
+        assert!(settings.persisted.image_path.is_none());
+        assert_eq!(
+            settings.last_image_path(),
+            Some(Path::new("tests/resources/test.png"))
+        );
+
+        // survives a restart
+        let mut settings = Settings::from_toml(&settings.to_toml()).unwrap();
+        assert!(settings.image().is_none());
+        settings.reload_last_image().unwrap();
+        assert_eq!(settings.render_mode, RenderMode::Image);
+        assert!(settings.image().is_some());
+        assert!(settings.persisted.image_path.is_some());
+    }
+
+    #[test]
+    fn test_nothing_to_reload() {
+        let mut settings = Settings::default();
+        assert_eq!(settings.last_image_path(), None);
+        let error = settings.reload_last_image().err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        assert_eq!(settings.render_mode, RenderMode::Crosshair);
+    }
+
+    /// the image got edited on disk since it was loaded
+    #[test]
+    fn test_modified_on_disk() {
+        let mut path = std::env::temp_dir();
+        path.push("DELETEME_simple-crosshair-overlay-test-reload.png");
+        fs::copy("tests/resources/test.png", &path).expect("copy failed");
+
+        let mut settings = Settings::default();
+        settings.load_image(path.clone()).unwrap();
+        let image = settings.image().unwrap();
+        assert_eq!((image.width, image.height), (128, 128));
+
+        fs::copy("tests/resources/test_rgb.png", &path).expect("copy failed");
+        settings.reload_last_image().unwrap();
+        let image = settings.image().unwrap();
+        assert_eq!((image.width, image.height), (2, 2));
+
+        // a missing file keeps the image that's already loaded
+        fs::remove_file(&path).expect("cleanup failed");
+        assert!(settings.reload_last_image().is_err());
+        let image = settings.image().unwrap();
+        assert_eq!((image.width, image.height), (2, 2));
+        assert_eq!(settings.render_mode, RenderMode::Image);
+    }
+
+    /// configs from before the last image was tracked fall back to the current image
+    #[test]
+    fn test_old_config() {
+        let mut settings = Settings::default();
+        settings.persisted.image_path = Some("tests/resources/test.png".into());
+        assert_eq!(
+            settings.last_image_path(),
+            Some(Path::new("tests/resources/test.png"))
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_reset {
+    use super::*;
+
+    /// a settings with a loaded image and everything else moved away from the defaults
+    fn settings(reset_clears_image: bool) -> Settings {
+        let mut settings = Settings::default();
+        settings.persisted.reset_clears_image = reset_clears_image;
+        settings.persisted.window_dx = 10;
+        settings.persisted.window_dy = -10;
+        settings.set_color(Color::from_argb(0xFF00FF00));
+        settings
+            .load_image("tests/resources/test.png".into())
+            .unwrap();
+        settings
+    }
+
+    fn assert_geometry_reset(settings: &Settings) {
+        assert_eq!(settings.persisted.window_dx, DEFAULT_OFFSET_X);
+        assert_eq!(settings.persisted.window_dy, DEFAULT_OFFSET_Y);
+        assert_eq!(settings.persisted.color, DEFAULT_COLOR);
+    }
+
+    #[test]
+    fn test_reset_clears_image() {
+        let mut settings = settings(true);
+        settings.reset();
+        assert_geometry_reset(&settings);
+        assert!(settings.image().is_none());
+        assert!(settings.persisted.image_path.is_none());
+        assert_eq!(settings.render_mode, RenderMode::Crosshair);
+    }
+
+    #[test]
+    fn test_reset_keeps_image() {
+        let mut settings = settings(false);
+        settings.reset();
+        assert_geometry_reset(&settings);
+        assert!(settings.image().is_some());
+        assert!(settings.persisted.image_path.is_some());
+        assert_eq!(settings.render_mode, RenderMode::Image);
+    }
+
+    #[test]
+    fn test_reset_position() {
+        let mut settings = settings(true);
+        let height = settings.persisted.window_height;
+        settings.reset_position();
+        assert_eq!(settings.persisted.window_dx, DEFAULT_OFFSET_X);
+        assert_eq!(settings.persisted.window_dy, DEFAULT_OFFSET_Y);
+        assert_eq!(settings.persisted.window_height, height);
+        assert_eq!(settings.persisted.color, Color::from_argb(0xFF00FF00));
+        assert!(settings.image().is_some());
+    }
+}
+#[cfg(test)]
+mod test_persist_adjust_mode {
+    use super::*;
+
+    /// what the next launch would start with, after exiting with `adjust_mode` on or off
+    fn relaunch(persist_adjust_mode: bool, adjust_mode: bool) -> bool {
+        let mut settings = Settings::default();
+        settings.persisted.persist_adjust_mode = persist_adjust_mode;
+        settings.remember_adjust_mode(adjust_mode);
+        Settings::from_toml(&settings.to_toml())
+            .unwrap()
+            .startup_adjust_mode()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        assert!(relaunch(true, true));
+        assert!(!relaunch(true, false));
+    }
+
+    #[test]
+    fn test_off_by_default() {
+        assert!(!Settings::default().startup_adjust_mode());
+        assert!(!relaunch(false, true));
+    }
+
+    /// turning the option off later doesn't resurrect a stale adjust mode
+    #[test]
+    fn test_opt_out_ignores_saved_state() {
+        let mut settings = Settings::default();
+        settings.persisted.persist_adjust_mode = true;
+        settings.remember_adjust_mode(true);
+        settings.persisted.persist_adjust_mode = false;
+        assert!(!settings.startup_adjust_mode());
+    }
+}
+
+#[cfg(test)]
+mod test_color_cycle {
+    use super::*;
+
+    fn cycling_settings() -> Settings {
+        let mut settings = Settings::default();
+        settings.persisted.premultiply_alpha = true;
+        settings.set_color(Color::from_argb(0x800000FF));
+        settings.persisted.color_cycle = true;
+        settings.persisted.cycle_speed = 90.0;
+        settings.tick_interval = Duration::from_secs(1);
+        settings
+    }
+
+    #[test]
+    fn test_off_by_default() {
+        let mut settings = Settings::default();
+        let color = settings.color;
+        assert!(!settings.advance_color_cycle());
+        assert_eq!(settings.color, color);
+    }
+
+    #[test]
+    fn test_cycles_with_premultiplied_alpha() {
+        let mut settings = cycling_settings();
+        assert!(settings.advance_color_cycle());
+        // 90 degrees in is yellow-green, and the alpha stays the same
+        let [r, g, b, a] = settings.color.to_rgba_bytes();
+        assert_eq!(a, 0x80);
+        assert!(g == a && r < g && b == 0, "{:08X}", settings.color);
+
+        assert!(settings.advance_color_cycle());
+        assert_ne!(settings.color.to_rgba_bytes(), [r, g, b, a]);
+        // the saved color is never touched
+        assert_eq!(settings.persisted.color, Color::from_argb(0x800000FF));
+    }
+
+    #[test]
+    fn test_disabling_restores_color() {
+        let mut settings = cycling_settings();
+        let color = settings.color;
+        settings.advance_color_cycle();
+        assert_ne!(settings.color, color);
+
+        settings.persisted.color_cycle = false;
+        assert!(settings.advance_color_cycle());
+        assert_eq!(settings.color, color);
+        assert!(!settings.advance_color_cycle());
+    }
+}
+
+#[cfg(test)]
+mod test_persist_lock {
+    use super::*;
+
+    /// whether the next launch would start locked, after locking or unlocking
+    fn relaunch(persist_lock: bool, locked: bool) -> bool {
+        let mut settings = Settings::default();
+        settings.persisted.persist_lock = persist_lock;
+        settings.remember_locked(locked);
+        Settings::from_toml(&settings.to_toml())
+            .unwrap()
+            .startup_locked()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        assert!(relaunch(true, true));
+        assert!(!relaunch(true, false));
+    }
+
+    #[test]
+    fn test_off_by_default() {
+        assert!(!Settings::default().startup_locked());
+        assert!(!relaunch(false, true));
+    }
+}
+
+#[cfg(test)]
+mod test_start_hidden {
+    use super::*;
+
+    /// whether the next launch would show the overlay, after exiting with it shown or hidden
+    fn relaunch(visible: bool) -> bool {
+        let mut settings = Settings::default();
+        settings.remember_visible(visible);
+        Settings::from_toml(&settings.to_toml())
+            .unwrap()
+            .startup_visible()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        assert!(relaunch(true));
+        assert!(!relaunch(false));
+    }
+
+    #[test]
+    fn test_serialized() {
+        let mut settings = Settings::default();
+        settings.remember_visible(false);
+        assert!(settings.to_toml().contains("start_hidden = true"));
+    }
+
+    /// configs from before this setting existed start visible
+    #[test]
+    fn test_visible_by_default() {
+        assert!(Settings::default().startup_visible());
+    }
+}
+
+#[cfg(test)]
+mod test_monitor_layouts {
+    use super::*;
+
+    fn monitor(name: &str, x: i32) -> MonitorInfo {
+        MonitorInfo {
+            name: Some(name.to_string()),
+            position: PhysicalPosition::new(x, 0),
+            size: PhysicalSize::new(1920, 1080),
+        }
+    }
+
+    fn laptop() -> Vec<MonitorInfo> {
+        vec![monitor("LAPTOP", 0)]
+    }
+
+    fn docked() -> Vec<MonitorInfo> {
+        vec![monitor("LAPTOP", 0), monitor("DESK", 1920)]
+    }
+
+    /// pick the 2nd monitor while docked, then the 1st while undocked
+    fn remembered() -> Settings {
+        let mut settings = Settings::default();
+        settings.set_monitor_index(1);
+        settings.remember_monitor_layout(&docked());
+        settings.set_monitor_index(0);
+        settings.remember_monitor_layout(&laptop());
+        settings
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let toml = remembered().to_toml();
+        assert!(toml.contains("[monitor_layouts]"));
+        let settings = Settings::from_toml(&toml).unwrap();
+        assert_eq!(
+            settings.persisted.monitor_layouts,
+            remembered().persisted.monitor_layouts
+        );
+        assert_eq!(settings.persisted.monitor_layouts.len(), 2);
+    }
+
+    #[test]
+    fn test_known_layout() {
+        let settings = remembered();
+        assert_eq!(settings.layout_monitor_index(&docked()), 1);
+        assert_eq!(settings.layout_monitor_index(&laptop()), 0);
+    }
+
+    #[test]
+    fn test_unseen_layout() {
+        let mut settings = remembered();
+        settings.set_monitor_index(2);
+        let triple = vec![
+            monitor("LAPTOP", 0),
+            monitor("DESK", 1920),
+            monitor("TV", 3840),
+        ];
+        assert_eq!(settings.layout_monitor_index(&triple), 2);
+    }
+
+    /// hand-edited configs could remember a monitor the layout doesn't have
+    #[test]
+    fn test_out_of_range() {
+        let mut settings = Settings::default();
+        settings
+            .persisted
+            .monitor_layouts
+            .insert(monitor::layout_key(&laptop()), 5);
+        assert_eq!(settings.layout_monitor_index(&laptop()), 0);
+    }
+
+    /// the OS swapped the monitors around, which is a layout that hasn't been seen before
+    #[test]
+    fn test_monitor_name() {
+        let mut settings = Settings::default();
+        settings.set_monitor_index(1);
+        settings.remember_monitor_name(&docked());
+        assert_eq!(settings.persisted.monitor_name.as_deref(), Some("DESK"));
+
+        let swapped = vec![monitor("DESK", 1920), monitor("LAPTOP", 0)];
+        assert_eq!(settings.layout_monitor_index(&swapped), 0);
+        // without the named monitor, the index is all there is to go on
+        assert_eq!(settings.layout_monitor_index(&laptop()), 1);
+    }
+
+    /// a remembered layout still wins when it's seen again
+    #[test]
+    fn test_layout_beats_name() {
+        let mut settings = remembered();
+        settings.persisted.monitor_name = Some("DESK".to_string());
+        assert_eq!(settings.layout_monitor_index(&docked()), 1);
+        settings.persisted.monitor_name = Some("LAPTOP".to_string());
+        assert_eq!(settings.layout_monitor_index(&docked()), 1);
+    }
+
+    /// configs from before this setting existed have no remembered layouts
+    #[test]
+    fn test_default() {
+        let settings = Settings::from_toml(
+            "window_dx = 0\nwindow_dy = 0\nwindow_width = 16\nwindow_height = 16\ncolor = \"FFFF0000\"\n",
+        )
+        .unwrap();
+        assert!(settings.persisted.monitor_layouts.is_empty());
+        assert_eq!(settings.layout_monitor_index(&docked()), 0);
+    }
+}
+
+#[cfg(test)]
+mod test_animated_image {
+    use super::*;
+
+    /// 2x1, a red frame then a blue frame, 100ms each
+    const ANIMATED_IMAGE: &str = "tests/resources/test_animated.png";
+
+    #[test]
+    fn test_load() {
+        let mut settings = Settings::default();
+        settings.load_image(PathBuf::from(ANIMATED_IMAGE)).unwrap();
+        assert_eq!(settings.render_mode, RenderMode::Animated);
+        assert_eq!(settings.crosshair_size(), PhysicalSize::new(2, 1));
+        assert_eq!(settings.image().unwrap().data[0], 0xFFFF0000);
+
+        let start = Instant::now();
+        assert!(!settings.advance_animation(start));
+        assert!(settings.advance_animation(start + Duration::from_millis(100)));
+        assert_eq!(settings.image().unwrap().data[0], 0xFF0000FF);
+    }
+
+    #[test]
+    fn test_static_image() {
+        let mut settings = Settings::default();
+        settings
+            .load_image(PathBuf::from("tests/resources/test.png"))
+            .unwrap();
+        assert_eq!(settings.render_mode, RenderMode::Image);
+        assert!(!settings.advance_animation(Instant::now() + Duration::from_secs(1)));
+    }
+
+    /// showing the generated crosshair instead pauses the animation
+    #[test]
+    fn test_hidden() {
+        let mut settings = Settings::default();
+        settings.load_image(PathBuf::from(ANIMATED_IMAGE)).unwrap();
+        assert!(settings.toggle_image());
+        let start = Instant::now();
+        settings.advance_animation(start);
+        assert!(!settings.advance_animation(start + Duration::from_secs(1)));
+        assert!(settings.toggle_image());
+        assert_eq!(settings.render_mode, RenderMode::Animated);
+    }
+
+    #[test]
+    fn test_set_color_unloads() {
+        let mut settings = Settings::default();
+        settings.load_image(PathBuf::from(ANIMATED_IMAGE)).unwrap();
+        settings.set_color(Color::from_argb(0xFFFFFFFF));
+        assert!(settings.image().is_none());
+        assert_eq!(settings.render_mode, settings.persisted.crosshair_mode);
+    }
+}
+
+#[cfg(test)]
+mod test_idle_fps {
+    use super::*;
+
+    fn settings(fps: u32, idle_fps: Option<u32>) -> Settings {
+        let mut settings = Settings::default();
+        settings.set_fps(fps);
+        settings.persisted.idle_fps = idle_fps;
+        settings
+    }
+
+    #[test]
+    fn test_idle_interval() {
+        assert_eq!(
+            settings(60, Some(10)).idle_tick_interval(),
+            Some(Duration::from_millis(100))
+        );
+    }
+
+    #[test]
+    fn test_unset() {
+        assert_eq!(settings(60, None).idle_tick_interval(), None);
+        assert_eq!(Settings::default().idle_tick_interval(), None);
+    }
+
+    /// an idle rate that wouldn't actually slow anything down is ignored
+    #[test]
+    fn test_not_slower() {
+        assert_eq!(settings(10, Some(10)).idle_tick_interval(), None);
+        assert_eq!(settings(10, Some(60)).idle_tick_interval(), None);
+        assert_eq!(settings(60, Some(0)).idle_tick_interval(), None);
+    }
 }
@@ -2,22 +2,166 @@
 // See LICENSE file for full text.
 // Copyright © 2023 Michael Ripley
 
-/// Serialize a u32-packed ARGB color as a hex string, because editing a decimal u32 by hand is fucked.
+/// Parse a color typed in by the user. Unlike the config file, this insists on all 8 `AARRGGBB` digits, so a
+/// forgotten alpha doesn't silently give you an invisible crosshair. A leading `#` and surrounding whitespace are fine.
+pub fn parse_argb_hex(s: &str) -> Result<u32, String> {
+    let hex = s.trim();
+    let hex = hex.strip_prefix('#').unwrap_or(hex);
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!(
+            "\"{hex}\" isn't a hex color. Only the digits 0-9 and A-F are allowed."
+        ));
+    }
+    // all ASCII by now, so bytes are characters
+    if hex.len() != 8 {
+        return Err(format!(
+            "\"{hex}\" is {} digits long, but an AARRGGBB color needs exactly 8.",
+            hex.len()
+        ));
+    }
+    argb_color::parse(hex)
+        .map(|color| color.argb())
+        .map_err(|e| e.to_string())
+}
+
+/// Serialize a color as an `AARRGGBB` hex string, because editing a decimal u32 by hand is fucked.
 pub mod argb_color {
     use serde::{Deserialize, Deserializer, Serializer};
 
-    pub fn serialize<S>(color: &u32, serializer: S) -> Result<S::Ok, S::Error>
+    use crate::private::util::image::Color;
+
+    pub fn serialize<S>(color: &Color, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
         serializer.serialize_str(&format!("{color:08X}"))
     }
 
-    pub fn deserialize<'de, D>(deserializer: D) -> Result<u32, D::Error>
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Color, D::Error>
     where
         D: Deserializer<'de>,
     {
         let s = String::deserialize(deserializer)?;
-        u32::from_str_radix(&s, 16).map_err(serde::de::Error::custom)
+        parse(&s).map_err(serde::de::Error::custom)
+    }
+
+    pub(super) fn parse(s: &str) -> Result<Color, std::num::ParseIntError> {
+        u32::from_str_radix(s, 16).map(Color::from_argb)
+    }
+}
+
+/// Same as [`argb_color`], but for a color that can be left unset
+pub mod optional_argb_color {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::private::util::image::Color;
+
+    pub fn serialize<S>(color: &Option<Color>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match color {
+            Some(color) => super::argb_color::serialize(color, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Color>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<String>::deserialize(deserializer)?
+            .map(|s| super::argb_color::parse(&s).map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
+
+/// Serialize an optional duration as a plain number of seconds, because nobody wants to hand-edit `{ secs = 5, nanos = 0 }`
+pub mod optional_seconds {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(duration: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match duration {
+            Some(duration) => serializer.serialize_f64(duration.as_secs_f64()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<f64>::deserialize(deserializer)?
+            .map(|seconds| Duration::try_from_secs_f64(seconds).map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod test_parse_argb_hex {
+    use super::parse_argb_hex;
+
+    #[test]
+    fn test_valid() {
+        assert_eq!(parse_argb_hex("FFFF0000"), Ok(0xFFFF0000));
+        assert_eq!(parse_argb_hex("80ff00ff"), Ok(0x80FF00FF));
+        assert_eq!(parse_argb_hex(" #00000000\n"), Ok(0x00000000));
+    }
+
+    #[test]
+    fn test_wrong_length() {
+        assert!(parse_argb_hex("").is_err());
+        assert!(parse_argb_hex("FF0000").is_err());
+        assert!(parse_argb_hex("FFFF00000").is_err());
+    }
+
+    #[test]
+    fn test_not_hex() {
+        assert!(parse_argb_hex("FFFF00GG").is_err());
+        assert!(parse_argb_hex("+FFF0000").is_err());
+        assert!(parse_argb_hex("FF FF 00").is_err());
+        assert!(parse_argb_hex("FFFF00é").is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_argb_color {
+    use serde::{Deserialize, Serialize};
+
+    use crate::private::util::image::Color;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Wrapper {
+        #[serde(with = "super::argb_color")]
+        color: Color,
+        #[serde(default, with = "super::optional_argb_color")]
+        optional_color: Option<Color>,
+    }
+
+    /// the config file format must not change
+    #[test]
+    fn test_hex_format() {
+        let wrapper = Wrapper {
+            color: Color::from_argb(0xB2FF0000),
+            optional_color: Some(Color::from_argb(0x0000FF0A)),
+        };
+        let serialized = toml::to_string(&wrapper).unwrap();
+        assert_eq!(
+            serialized,
+            "color = \"B2FF0000\"\noptional_color = \"0000FF0A\"\n"
+        );
+        assert_eq!(toml::from_str::<Wrapper>(&serialized).unwrap(), wrapper);
+    }
+
+    #[test]
+    fn test_lowercase_and_missing() {
+        let wrapper: Wrapper = toml::from_str("color = \"b2ff0000\"").unwrap();
+        assert_eq!(wrapper.color, Color::from_argb(0xB2FF0000));
+        assert_eq!(wrapper.optional_color, None);
     }
 }
@@ -0,0 +1,172 @@
+// This file is part of simple-crosshair-overlay and is licenced under the GNU GPL v3.0.
+// See LICENSE file for full text.
+// Copyright © 2023 Michael Ripley
+
+//! Diagnostic information for bug reports
+
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::private::settings::RenderMode;
+use crate::private::util::image;
+use crate::private::util::monitor::MonitorInfo;
+
+/// Everything we want to know about a user's setup when they report a bug
+pub struct Diagnostics<'a> {
+    pub application_name: &'a str,
+    pub version: &'a str,
+    pub git_commit_hash: &'a str,
+    pub os: &'a str,
+    pub arch: &'a str,
+    pub monitors: &'a [MonitorInfo],
+    pub render_mode: &'a RenderMode,
+    pub config_path: &'a Path,
+}
+
+impl Diagnostics<'_> {
+    /// Format the diagnostics into something that can be pasted straight into an issue
+    pub fn format(&self) -> String {
+        let mut text = String::new();
+        // writing to a String can't fail, so the results are safe to ignore
+        let _ = writeln!(text, "{}", self.application_name);
+        let _ = writeln!(text, "version: {}", self.version);
+        let _ = writeln!(text, "commit: {}", self.git_commit_hash.trim());
+        let _ = writeln!(text, "os: {} {}", self.os, self.arch);
+        let _ = writeln!(text, "render mode: {:?}", self.render_mode);
+        let _ = writeln!(text, "config: {}", self.config_path.display());
+        let _ = write!(text, "monitors: {}", self.monitors.len());
+        for (index, monitor) in self.monitors.iter().enumerate() {
+            let _ = write!(
+                text,
+                "\n  {}: {} {}x{} at ({}, {})",
+                index + 1,
+                monitor.name.as_deref().unwrap_or("<unnamed>"),
+                monitor.size.width,
+                monitor.size.height,
+                monitor.position.x,
+                monitor.position.y
+            );
+        }
+        text
+    }
+}
+
+/// Show what a gradient of alpha values looks like on its way to the window buffer. If semi-transparent pixels look
+/// wrong, this tells whether the bytes or the OS is to blame.
+pub fn alpha_test_report(premultiply: bool, gamma_correct: bool) -> String {
+    let mut text = String::new();
+    let _ = writeln!(
+        text,
+        "premultiply_alpha = {premultiply} (default on this platform: {}), gamma_correct = {gamma_correct}",
+        image::PREMULTIPLY_ALPHA_DEFAULT
+    );
+    let _ = writeln!(text, "PNG RGBA -> buffer ARGB = bytes in memory");
+    for ([r, g, b, a], argb) in image::alpha_test_gradient(premultiply, gamma_correct) {
+        let [b0, b1, b2, b3] = argb.to_le_bytes();
+        let _ = writeln!(
+            text,
+            "{r:02X}{g:02X}{b:02X}{a:02X} -> {argb:08X} = [{b0:02X}, {b1:02X}, {b2:02X}, {b3:02X}]"
+        );
+    }
+    text
+}
+
+#[cfg(test)]
+mod test_alpha_test {
+    use super::*;
+
+    #[test]
+    fn test_straight() {
+        let report = alpha_test_report(false, false);
+        let samples: Vec<&str> = report.lines().skip(2).collect();
+        assert_eq!(
+            samples,
+            [
+                "FF800000 -> 00FF8000 = [00, 80, FF, 00]",
+                "FF800040 -> 40FF8000 = [00, 80, FF, 40]",
+                "FF800080 -> 80FF8000 = [00, 80, FF, 80]",
+                "FF8000C0 -> C0FF8000 = [00, 80, FF, C0]",
+                "FF8000FF -> FFFF8000 = [00, 80, FF, FF]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_premultiplied() {
+        let report = alpha_test_report(true, false);
+        assert!(report.starts_with("premultiply_alpha = true"));
+        let samples: Vec<&str> = report.lines().skip(2).collect();
+        assert_eq!(
+            samples,
+            [
+                "FF800000 -> 00000000 = [00, 00, 00, 00]",
+                "FF800040 -> 40402000 = [00, 20, 40, 40]",
+                "FF800080 -> 80804000 = [00, 40, 80, 80]",
+                "FF8000C0 -> C0C06000 = [00, 60, C0, C0]",
+                "FF8000FF -> FFFF8000 = [00, 80, FF, FF]",
+            ]
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_diagnostics {
+    use winit::dpi::{PhysicalPosition, PhysicalSize};
+
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        let monitors = [
+            MonitorInfo {
+                name: Some("DELL U2412M".to_string()),
+                position: PhysicalPosition::new(0, 0),
+                size: PhysicalSize::new(1920, 1200),
+            },
+            MonitorInfo {
+                name: None,
+                position: PhysicalPosition::new(-1920, 120),
+                size: PhysicalSize::new(1920, 1080),
+            },
+        ];
+        let diagnostics = Diagnostics {
+            application_name: "Simple Crosshair Overlay",
+            version: "1.2.0",
+            git_commit_hash: "0123abcd\n",
+            os: "windows",
+            arch: "x86_64",
+            monitors: &monitors,
+            render_mode: &RenderMode::Crosshair,
+            config_path: Path::new("config.toml"),
+        };
+
+        assert_eq!(
+            diagnostics.format(),
+            "Simple Crosshair Overlay\n\
+            version: 1.2.0\n\
+            commit: 0123abcd\n\
+            os: windows x86_64\n\
+            render mode: Crosshair\n\
+            config: config.toml\n\
+            monitors: 2\n  \
+            1: DELL U2412M 1920x1200 at (0, 0)\n  \
+            2: <unnamed> 1920x1080 at (-1920, 120)"
+        );
+    }
+
+    #[test]
+    fn test_format_no_monitors() {
+        let diagnostics = Diagnostics {
+            application_name: "Simple Crosshair Overlay",
+            version: "1.2.0",
+            git_commit_hash: "",
+            os: "linux",
+            arch: "aarch64",
+            monitors: &[],
+            render_mode: &RenderMode::ColorPicker,
+            config_path: Path::new("config.toml"),
+        };
+
+        assert!(diagnostics.format().ends_with("monitors: 0"));
+    }
+}
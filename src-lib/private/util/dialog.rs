@@ -2,7 +2,9 @@ use std::path::PathBuf;
 use std::sync::mpsc;
 use std::sync::Mutex;
 use std::thread::JoinHandle;
+use std::time::Duration;
 
+use arboard::Clipboard;
 use lazy_static::lazy_static;
 use native_dialog::{FileDialog, MessageDialog, MessageType};
 
@@ -19,6 +21,9 @@ lazy_static! {
     };
 }
 
+/// How long to wait for more warnings to show up before showing a batched warning dialog
+const WARNING_BATCH_WINDOW: Duration = Duration::from_millis(100);
+
 thread_local! {
     // We only need one of these per thread. As we don't use any thread pools this should be a one-time cost on application startup.
     static DIALOG_REQUEST_SENDER: mpsc::Sender<DialogRequest> = DIALOG_REQUEST_CHANNEL.0.lock().unwrap().clone();
@@ -26,12 +31,18 @@ thread_local! {
 
 /// The different types of requests the dialog worker thread can process
 enum DialogRequest {
-    /// Show a file browser for the user to select a PNG image
-    PngPath,
+    /// Show a file browser for the user to select an image
+    ImagePath,
+    /// Show a file browser for the user to pick where to save a PNG image
+    SavePngPath,
     /// Show an informational popup with the provided text
     Info(String),
     /// Show a warning popup with the provided text
     Warning(String),
+    /// Place the provided text on the clipboard
+    CopyToClipboard(String),
+    /// Ask the user to copy a hex color, then read it off the clipboard
+    HexColor,
     /// Stop the dialog worker thread
     Terminate,
 }
@@ -39,6 +50,8 @@ enum DialogRequest {
 pub struct DialogWorker {
     join_handle: Option<JoinHandle<()>>,
     file_path_receiver: mpsc::Receiver<Option<PathBuf>>,
+    save_path_receiver: mpsc::Receiver<Option<PathBuf>>,
+    hex_color_receiver: mpsc::Receiver<Option<String>>,
 }
 
 impl DialogWorker {
@@ -47,6 +60,16 @@ impl DialogWorker {
         self.file_path_receiver.try_recv()
     }
 
+    /// try to get a path to save a PNG to from the dialog worker's internal queue
+    pub fn try_recv_save_path(&self) -> Result<Option<PathBuf>, mpsc::TryRecvError> {
+        self.save_path_receiver.try_recv()
+    }
+
+    /// try to get the text of a hex color from the dialog worker's internal queue
+    pub fn try_recv_hex_color(&self) -> Result<Option<String>, mpsc::TryRecvError> {
+        self.hex_color_receiver.try_recv()
+    }
+
     /// signal the dialog worker thread to shut down once it's done processing its queue
     pub fn shutdown(&mut self) -> Option<()> {
         let _ = DIALOG_REQUEST_SENDER.with(|sender| sender.send(DialogRequest::Terminate));
@@ -64,13 +87,52 @@ pub fn show_warning(text: String) {
     let _ = DIALOG_REQUEST_SENDER.with(|sender| sender.send(DialogRequest::Warning(text)));
 }
 
-/// show a native popup requesting a path to a PNG
-pub fn request_png() {
-    let _ = DIALOG_REQUEST_SENDER.with(|sender| sender.send(DialogRequest::PngPath));
+/// place text on the clipboard, then let the user know it worked with a native popup
+pub fn copy_to_clipboard(text: String) {
+    let _ = DIALOG_REQUEST_SENDER.with(|sender| sender.send(DialogRequest::CopyToClipboard(text)));
+}
+
+/// show a native popup requesting a path to an image
+pub fn request_image() {
+    let _ = DIALOG_REQUEST_SENDER.with(|sender| sender.send(DialogRequest::ImagePath));
+}
+
+/// show a native popup requesting a path to save a PNG to
+pub fn request_save_png() {
+    let _ = DIALOG_REQUEST_SENDER.with(|sender| sender.send(DialogRequest::SavePngPath));
+}
+
+/// Ask the user for a hex color. There's no native text input popup, so this goes through the clipboard.
+pub fn request_hex_color() {
+    let _ = DIALOG_REQUEST_SENDER.with(|sender| sender.send(DialogRequest::HexColor));
 }
 
-pub fn spawn_worker() -> DialogWorker {
+/// Combine the given warning with any more warnings that arrive within `window` of each other. Returns the combined
+/// text, as well as the first non-warning request encountered (if any) so that the caller can still process it.
+fn coalesce_warnings(
+    first: String,
+    receiver: &mpsc::Receiver<DialogRequest>,
+    window: Duration,
+) -> (String, Option<DialogRequest>) {
+    let mut text = first;
+    loop {
+        match receiver.recv_timeout(window) {
+            Ok(DialogRequest::Warning(next)) => {
+                text.push_str("\n\n");
+                text.push_str(&next);
+            }
+            Ok(other) => return (text, Some(other)),
+            Err(_) => return (text, None),
+        }
+    }
+}
+
+/// Spawn the dialog worker thread. If `batch_warnings` is set, warnings that arrive in quick succession are
+/// combined into a single dialog.
+pub fn spawn_worker(batch_warnings: bool) -> DialogWorker {
     let (file_path_sender, file_path_receiver) = mpsc::channel();
+    let (save_path_sender, save_path_receiver) = mpsc::channel();
+    let (hex_color_sender, hex_color_receiver) = mpsc::channel();
     let dialog_request_receiver = DIALOG_REQUEST_CHANNEL.1.lock().unwrap().take().unwrap();
 
     // native dialogs block a thread, so we'll spin up a single thread to loop through queued dialogs.
@@ -78,18 +140,46 @@ pub fn spawn_worker() -> DialogWorker {
     let join_handle = std::thread::Builder::new()
         .name("dialog-worker".to_string())
         .spawn(move || {
+            // On some platforms the clipboard contents only live as long as the clipboard handle, so keep it around
+            let mut clipboard: Option<Clipboard> = None;
+
+            // a request we pulled off the queue early while batching warnings
+            let mut pending: Option<DialogRequest> = None;
+
             loop {
                 // block waiting for a file read request
-                match dialog_request_receiver.recv().unwrap() {
-                    DialogRequest::PngPath => {
+                let request = match pending.take() {
+                    Some(request) => request,
+                    None => dialog_request_receiver.recv().unwrap(),
+                };
+                match request {
+                    DialogRequest::ImagePath => {
                         let path = FileDialog::new()
-                            .add_filter("PNG Image", &["png"])
+                            .add_filter("Image", &["png", "jpg", "jpeg", "bmp"])
                             .show_open_single_file()
                             .ok()
                             .flatten();
 
                         let _ = file_path_sender.send(path);
                     }
+                    DialogRequest::SavePngPath => {
+                        let path = FileDialog::new()
+                            .add_filter("PNG Image", &["png"])
+                            .set_filename("crosshair.png")
+                            .show_save_single_file()
+                            .ok()
+                            .flatten()
+                            .map(|path| {
+                                // not every platform's dialog adds the extension for you
+                                if path.extension().is_none() {
+                                    path.with_extension("png")
+                                } else {
+                                    path
+                                }
+                            });
+
+                        let _ = save_path_sender.send(path);
+                    }
                     DialogRequest::Info(text) => {
                         MessageDialog::new()
                             .set_type(MessageType::Info)
@@ -99,6 +189,17 @@ pub fn spawn_worker() -> DialogWorker {
                             .unwrap();
                     }
                     DialogRequest::Warning(text) => {
+                        let text = if batch_warnings {
+                            let (text, next) = coalesce_warnings(
+                                text,
+                                &dialog_request_receiver,
+                                WARNING_BATCH_WINDOW,
+                            );
+                            pending = next;
+                            text
+                        } else {
+                            text
+                        };
                         MessageDialog::new()
                             .set_type(MessageType::Warning)
                             .set_title("Simple Crosshair Overlay")
@@ -106,6 +207,59 @@ pub fn spawn_worker() -> DialogWorker {
                             .show_alert()
                             .unwrap();
                     }
+                    DialogRequest::CopyToClipboard(text) => {
+                        let result = match clipboard.as_mut() {
+                            Some(clipboard) => clipboard.set_text(text),
+                            None => Clipboard::new().and_then(|new_clipboard| {
+                                clipboard.insert(new_clipboard).set_text(text)
+                            }),
+                        };
+                        let (message_type, message) = match result {
+                            Ok(()) => (MessageType::Info, "Copied to clipboard.".to_string()),
+                            Err(e) => (
+                                MessageType::Warning,
+                                format!("Failed to copy to clipboard.\n\n{e}"),
+                            ),
+                        };
+                        MessageDialog::new()
+                            .set_type(message_type)
+                            .set_title("Simple Crosshair Overlay")
+                            .set_text(&message)
+                            .show_alert()
+                            .unwrap();
+                    }
+                    DialogRequest::HexColor => {
+                        let copied = MessageDialog::new()
+                            .set_type(MessageType::Info)
+                            .set_title("Simple Crosshair Overlay")
+                            .set_text("Copy an AARRGGBB hex color to your clipboard, e.g. FFFF0000 for solid red, then press OK to use it.")
+                            .show_confirm()
+                            .unwrap_or(false);
+                        let text = if copied {
+                            let result = match clipboard.as_mut() {
+                                Some(clipboard) => clipboard.get_text(),
+                                None => Clipboard::new().and_then(|new_clipboard| {
+                                    clipboard.insert(new_clipboard).get_text()
+                                }),
+                            };
+                            match result {
+                                Ok(text) => Some(text),
+                                Err(e) => {
+                                    MessageDialog::new()
+                                        .set_type(MessageType::Warning)
+                                        .set_title("Simple Crosshair Overlay")
+                                        .set_text(&format!("Failed to read the clipboard.\n\n{e}"))
+                                        .show_alert()
+                                        .unwrap();
+                                    None
+                                }
+                            }
+                        } else {
+                            None
+                        };
+
+                        let _ = hex_color_sender.send(text);
+                    }
                     DialogRequest::Terminate => break,
                 }
             }
@@ -115,5 +269,50 @@ pub fn spawn_worker() -> DialogWorker {
     DialogWorker {
         join_handle: Some(join_handle), // we take() from this later
         file_path_receiver,
+        save_path_receiver,
+        hex_color_receiver,
+    }
+}
+
+#[cfg(test)]
+mod test_warning_batching {
+    use super::*;
+
+    #[test]
+    fn test_queued_warnings_combine() {
+        let (sender, receiver) = mpsc::channel();
+        sender
+            .send(DialogRequest::Warning("second".to_string()))
+            .unwrap();
+        sender
+            .send(DialogRequest::Warning("third".to_string()))
+            .unwrap();
+
+        let (text, next) = coalesce_warnings("first".to_string(), &receiver, Duration::ZERO);
+        assert_eq!(text, "first\n\nsecond\n\nthird");
+        assert!(next.is_none());
+    }
+
+    #[test]
+    fn test_other_requests_stop_batching() {
+        let (sender, receiver) = mpsc::channel();
+        sender
+            .send(DialogRequest::Warning("second".to_string()))
+            .unwrap();
+        sender
+            .send(DialogRequest::Info("info".to_string()))
+            .unwrap();
+        sender
+            .send(DialogRequest::Warning("third".to_string()))
+            .unwrap();
+
+        let (text, next) = coalesce_warnings("first".to_string(), &receiver, Duration::ZERO);
+        assert_eq!(text, "first\n\nsecond");
+        assert!(matches!(next, Some(DialogRequest::Info(info)) if info == "info"));
+
+        // the warning after the info request is left for the next batch
+        assert!(
+            matches!(receiver.try_recv(), Ok(DialogRequest::Warning(warning)) if warning == "third")
+        );
     }
 }
@@ -0,0 +1,157 @@
+// This file is part of simple-crosshair-overlay and is licenced under the GNU GPL v3.0.
+// See LICENSE file for full text.
+// Copyright © 2023 Michael Ripley
+
+//! Screen-space geometry utilities
+
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+
+/// Check if a cursor at the given screen coordinates is over the drag handle, which is the top-left
+/// `handle_size`×`handle_size` pixels of the window. The handle never extends past the window itself,
+/// and a `handle_size` of zero means there is no handle at all.
+pub fn drag_handle_hit(
+    cursor: PhysicalPosition<i32>,
+    window_position: PhysicalPosition<i32>,
+    window_size: PhysicalSize<u32>,
+    handle_size: u32,
+) -> bool {
+    // do the math in i64 so that wild coordinates on huge virtual desktops can't overflow
+    let x = cursor.x as i64 - window_position.x as i64;
+    let y = cursor.y as i64 - window_position.y as i64;
+    let handle_width = handle_size.min(window_size.width) as i64;
+    let handle_height = handle_size.min(window_size.height) as i64;
+    (0..handle_width).contains(&x) && (0..handle_height).contains(&y)
+}
+
+/// Compute the new window offset while dragging. `start_offset` is the offset the window had when
+/// the drag began, and `start_cursor` is where the cursor was at that time.
+pub fn drag_offset(
+    start_offset: (i32, i32),
+    start_cursor: PhysicalPosition<i32>,
+    cursor: PhysicalPosition<i32>,
+) -> (i32, i32) {
+    let (dx, dy) = start_offset;
+    (
+        dx.saturating_add(cursor.x.saturating_sub(start_cursor.x)),
+        dy.saturating_add(cursor.y.saturating_sub(start_cursor.y)),
+    )
+}
+
+/// Where to put the top-left corner of a `window_size` window so it's centered on the `cursor`. With an even size the
+/// window's center falls between pixels, and the cursor's pixel ends up just down and to the right of it.
+pub fn centered_on_cursor(
+    cursor: PhysicalPosition<i32>,
+    window_size: PhysicalSize<u32>,
+) -> PhysicalPosition<i32> {
+    // half of a u32 always fits in an i32
+    PhysicalPosition::new(
+        cursor.x.saturating_sub((window_size.width / 2) as i32),
+        cursor.y.saturating_sub((window_size.height / 2) as i32),
+    )
+}
+
+#[cfg(test)]
+mod test_drag_handle {
+    use super::*;
+
+    const WINDOW_POSITION: PhysicalPosition<i32> = PhysicalPosition::new(100, 200);
+    const WINDOW_SIZE: PhysicalSize<u32> = PhysicalSize::new(32, 32);
+
+    fn hit(x: i32, y: i32, handle_size: u32) -> bool {
+        drag_handle_hit(
+            PhysicalPosition::new(x, y),
+            WINDOW_POSITION,
+            WINDOW_SIZE,
+            handle_size,
+        )
+    }
+
+    #[test]
+    fn test_handle_corners() {
+        assert!(hit(100, 200, 8));
+        assert!(hit(107, 200, 8));
+        assert!(hit(100, 207, 8));
+        assert!(hit(107, 207, 8));
+    }
+
+    #[test]
+    fn test_outside_handle() {
+        assert!(!hit(108, 200, 8), "right of handle");
+        assert!(!hit(100, 208, 8), "below handle");
+        assert!(!hit(99, 200, 8), "left of window");
+        assert!(!hit(100, 199, 8), "above window");
+    }
+
+    #[test]
+    fn test_disabled_handle() {
+        assert!(!hit(100, 200, 0));
+    }
+
+    /// the handle must not grow past the edges of the window
+    #[test]
+    fn test_handle_clamped_to_window() {
+        assert!(hit(131, 231, 64));
+        assert!(!hit(132, 200, 64));
+        assert!(!hit(100, 232, 64));
+    }
+
+    /// with click-through off the whole window is the handle
+    #[test]
+    fn test_whole_window_handle() {
+        assert!(hit(100, 200, u32::MAX));
+        assert!(hit(131, 231, u32::MAX));
+        assert!(!hit(132, 231, u32::MAX));
+    }
+
+    #[test]
+    fn test_drag_offset() {
+        let start_cursor = PhysicalPosition::new(10, 10);
+        assert_eq!(
+            drag_offset((5, -5), start_cursor, PhysicalPosition::new(13, 6)),
+            (8, -9)
+        );
+        assert_eq!(drag_offset((5, -5), start_cursor, start_cursor), (5, -5));
+    }
+}
+
+#[cfg(test)]
+mod test_centered_on_cursor {
+    use super::*;
+
+    #[test]
+    fn test_odd() {
+        // the middle pixel of a 15 pixel window is 7 in from the edge
+        assert_eq!(
+            centered_on_cursor(PhysicalPosition::new(100, 200), PhysicalSize::new(15, 15)),
+            PhysicalPosition::new(93, 193)
+        );
+    }
+
+    #[test]
+    fn test_even() {
+        assert_eq!(
+            centered_on_cursor(PhysicalPosition::new(100, 200), PhysicalSize::new(16, 32)),
+            PhysicalPosition::new(92, 184)
+        );
+    }
+
+    /// cursors on monitors left of or above the primary monitor have negative coordinates
+    #[test]
+    fn test_negative() {
+        assert_eq!(
+            centered_on_cursor(PhysicalPosition::new(-1920, -5), PhysicalSize::new(16, 16)),
+            PhysicalPosition::new(-1928, -13)
+        );
+    }
+
+    #[test]
+    fn test_saturates() {
+        assert_eq!(
+            centered_on_cursor(
+                PhysicalPosition::new(i32::MIN, 0),
+                PhysicalSize::new(u32::MAX, 1)
+            ),
+            PhysicalPosition::new(i32::MIN, 0)
+        );
+    }
+}
@@ -0,0 +1,145 @@
+// This file is part of simple-crosshair-overlay and is licenced under the GNU GPL v3.0.
+// See LICENSE file for full text.
+// Copyright © 2023 Michael Ripley
+
+//! A secondary "ghost" crosshair at a fixed offset from the main one, e.g. as a bullet drop reference point.
+//!
+//! The ghost shares the main crosshair's window. To make room the window is padded equally on opposite sides, which
+//! keeps the main crosshair exactly where it was.
+
+use serde::{Deserialize, Serialize};
+use winit::dpi::PhysicalSize;
+
+use crate::private::util::image::Color;
+
+const DEFAULT_GHOST_SIZE: u32 = 5;
+
+const fn default_ghost_size() -> u32 {
+    DEFAULT_GHOST_SIZE
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Ghost {
+    /// pixels from the main crosshair's center to the ghost's center. Positive is to the right.
+    #[serde(default)]
+    pub dx: i32,
+    /// pixels from the main crosshair's center to the ghost's center. Positive is down.
+    #[serde(default)]
+    pub dy: i32,
+    /// width and height of the ghost's `+`
+    #[serde(default = "default_ghost_size")]
+    pub size: u32,
+    /// when unset the main crosshair's color is used
+    #[serde(
+        default,
+        with = "crate::private::util::custom_serializer::optional_argb_color"
+    )]
+    pub color: Option<Color>,
+}
+
+impl Ghost {
+    /// the ghost's size, which is never less than 1 pixel
+    fn side(&self) -> u32 {
+        self.size.max(1)
+    }
+}
+
+/// Size needed to fit both the main crosshair and the ghost without moving the main crosshair's center
+pub fn padded_size(main: PhysicalSize<u32>, ghost: &Ghost) -> PhysicalSize<u32> {
+    let (pad_x, pad_y) = padding(main, ghost);
+    PhysicalSize::new(
+        main.width.saturating_add(pad_x.saturating_mul(2)),
+        main.height.saturating_add(pad_y.saturating_mul(2)),
+    )
+}
+
+/// where the top-left corner of the main crosshair goes in a buffer of [`padded_size`]
+pub fn main_origin(main: PhysicalSize<u32>, ghost: &Ghost) -> (usize, usize) {
+    let (pad_x, pad_y) = padding(main, ghost);
+    (pad_x as usize, pad_y as usize)
+}
+
+/// where the top-left corner of the ghost goes in a buffer of [`padded_size`]
+pub fn ghost_origin(main: PhysicalSize<u32>, ghost: &Ghost) -> (usize, usize) {
+    let (pad_x, pad_y) = padding(main, ghost);
+    let x = pad_x as i64 + ghost_start(main.width, ghost.dx, ghost.side());
+    let y = pad_y as i64 + ghost_start(main.height, ghost.dy, ghost.side());
+    (x as usize, y as usize)
+}
+
+fn padding(main: PhysicalSize<u32>, ghost: &Ghost) -> (u32, u32) {
+    (
+        padding_along(main.width, ghost.dx, ghost.side()),
+        padding_along(main.height, ghost.dy, ghost.side()),
+    )
+}
+
+/// Where the ghost starts relative to the start of the main crosshair. When the sizes have different parities the
+/// ghost can't be centered exactly, so it rounds up and to the left.
+fn ghost_start(main_length: u32, offset: i32, ghost_length: u32) -> i64 {
+    let difference = main_length as i64 - ghost_length as i64;
+    offset as i64 + difference.div_euclid(2)
+}
+
+/// padding needed on each side so the ghost doesn't hang off either end
+fn padding_along(main_length: u32, offset: i32, ghost_length: u32) -> u32 {
+    let start = ghost_start(main_length, offset, ghost_length);
+    let end = start + ghost_length as i64;
+    let padding = (-start).max(end - main_length as i64).max(0);
+    u32::try_from(padding).unwrap_or(u32::MAX)
+}
+
+#[cfg(test)]
+mod test_ghost {
+    use super::*;
+
+    fn ghost(dx: i32, dy: i32, size: u32) -> Ghost {
+        Ghost {
+            dx,
+            dy,
+            size,
+            color: None,
+        }
+    }
+
+    /// a ghost that fits inside the main crosshair doesn't need any padding
+    #[test]
+    fn test_inside() {
+        let main = PhysicalSize::new(9, 9);
+        let ghost = ghost(0, 2, 3);
+        assert_eq!(padded_size(main, &ghost), main);
+        assert_eq!(main_origin(main, &ghost), (0, 0));
+        assert_eq!(ghost_origin(main, &ghost), (3, 5));
+    }
+
+    #[test]
+    fn test_below() {
+        let main = PhysicalSize::new(5, 5);
+        let ghost = ghost(0, 6, 3);
+        assert_eq!(padded_size(main, &ghost), PhysicalSize::new(5, 15));
+        assert_eq!(main_origin(main, &ghost), (0, 5));
+        // the ghost's center pixel is 6 below the main crosshair's center pixel at (2, 7)
+        assert_eq!(ghost_origin(main, &ghost), (1, 12));
+    }
+
+    /// padding is symmetric, so a ghost up and to the left pads the bottom and right too
+    #[test]
+    fn test_up_left() {
+        let main = PhysicalSize::new(5, 5);
+        let ghost = ghost(-4, -4, 3);
+        assert_eq!(padded_size(main, &ghost), PhysicalSize::new(11, 11));
+        assert_eq!(main_origin(main, &ghost), (3, 3));
+        assert_eq!(ghost_origin(main, &ghost), (0, 0));
+    }
+
+    #[test]
+    fn test_mismatched_parity() {
+        let main = PhysicalSize::new(4, 4);
+        let ghost = ghost(0, 4, 3);
+        let padded = padded_size(main, &ghost);
+        let (x, y) = ghost_origin(main, &ghost);
+        assert_eq!((x, y), (0, 7));
+        assert!(y + 3 <= padded.height as usize);
+        assert!(x + 3 <= padded.width as usize);
+    }
+}
@@ -0,0 +1,118 @@
+// This file is part of simple-crosshair-overlay and is licenced under the GNU GPL v3.0.
+// See LICENSE file for full text.
+// Copyright © 2023 Michael Ripley
+
+//! Bounded undo/redo history. Nothing gets pushed explicitly: the current state is handed to [`History::commit`] every
+//! so often, and a new step is recorded whenever it's changed since last time.
+
+use std::collections::VecDeque;
+use std::mem;
+
+/// how many steps back undo can go
+pub const HISTORY_CAPACITY: usize = 32;
+
+pub struct History<T> {
+    /// the state as of the last commit, undo, or redo
+    committed: T,
+    /// older states, oldest first
+    undo_stack: VecDeque<T>,
+    /// states that were undone, most recently undone last
+    redo_stack: Vec<T>,
+}
+
+impl<T> History<T>
+where
+    T: Clone + PartialEq,
+{
+    pub fn new(initial: T) -> Self {
+        History {
+            committed: initial,
+            undo_stack: VecDeque::new(),
+            redo_stack: Vec::new(),
+        }
+    }
+
+    /// Record `current` as a new step if it differs from the last committed state. This throws away anything that
+    /// could have been redone. Returns `true` if a step was recorded.
+    pub fn commit(&mut self, current: T) -> bool {
+        if current == self.committed {
+            return false;
+        }
+        let previous = mem::replace(&mut self.committed, current);
+        if self.undo_stack.len() == HISTORY_CAPACITY {
+            self.undo_stack.pop_front();
+        }
+        self.undo_stack.push_back(previous);
+        self.redo_stack.clear();
+        true
+    }
+
+    /// Step back, returning the state to restore. Anything not yet committed should be committed first, or it's lost.
+    pub fn undo(&mut self) -> Option<T> {
+        let previous = self.undo_stack.pop_back()?;
+        let current = mem::replace(&mut self.committed, previous.clone());
+        self.redo_stack.push(current);
+        Some(previous)
+    }
+
+    /// Step forward again after an [undo](Self::undo), returning the state to restore
+    pub fn redo(&mut self) -> Option<T> {
+        let next = self.redo_stack.pop()?;
+        let current = mem::replace(&mut self.committed, next.clone());
+        self.undo_stack.push_back(current);
+        Some(next)
+    }
+}
+
+#[cfg(test)]
+mod test_history {
+    use super::*;
+
+    #[test]
+    fn test_unchanged_isnt_recorded() {
+        let mut history = History::new(1);
+        assert!(!history.commit(1));
+        assert_eq!(history.undo(), None);
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut history = History::new(1);
+        assert!(history.commit(2));
+        assert!(history.commit(3));
+
+        assert_eq!(history.undo(), Some(2));
+        assert_eq!(history.undo(), Some(1));
+        assert_eq!(history.undo(), None);
+
+        assert_eq!(history.redo(), Some(2));
+        assert_eq!(history.redo(), Some(3));
+        assert_eq!(history.redo(), None);
+        assert_eq!(history.undo(), Some(2));
+    }
+
+    /// a new change after an undo starts a new branch, so the undone steps can't be redone anymore
+    #[test]
+    fn test_commit_clears_redo() {
+        let mut history = History::new(1);
+        history.commit(2);
+        history.undo();
+        assert!(!history.commit(1));
+        assert!(history.commit(5));
+        assert_eq!(history.redo(), None);
+        assert_eq!(history.undo(), Some(1));
+    }
+
+    #[test]
+    fn test_capacity() {
+        let mut history = History::new(0);
+        for value in 1..=HISTORY_CAPACITY + 10 {
+            history.commit(value);
+        }
+        let mut undone = 0;
+        while history.undo().is_some() {
+            undone += 1;
+        }
+        assert_eq!(undone, HISTORY_CAPACITY);
+    }
+}
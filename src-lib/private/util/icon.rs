@@ -0,0 +1,83 @@
+// This file is part of simple-crosshair-overlay and is licenced under the GNU GPL v3.0.
+// See LICENSE file for full text.
+// Copyright © 2023 Michael Ripley
+
+//! Generated icon graphics. This is also pulled into build.rs to bake the default icons, so it can't depend on anything
+//! else in the crate.
+
+// TODO: stop doing absurd buffer math to generate icons and just freaking bake an SVG
+/// Generate a simple icon. Just a red circle with a little green/blue gradient stuff going on to spice it up.
+/// This outputs series of 8-bit color depth RGBA values.
+pub fn generate_icon_rgba(size: u32) -> Vec<u8> {
+    generate_icon(size, |x, y| {
+        [0xFF, (x * 128 / size) as u8, (y * 128 / size) as u8]
+    })
+}
+
+/// Generate the same circle as [`generate_icon_rgba`], but filled with a single opaque `rgb` color, so the tray can
+/// show which color the crosshair is.
+pub fn generate_icon_rgba_colored(size: u32, rgb: [u8; 3]) -> Vec<u8> {
+    generate_icon(size, |_, _| rgb)
+}
+
+/// some silly math to make a colored circle. `color` picks the RGB of each pixel inside the circle.
+fn generate_icon<F>(size: u32, color: F) -> Vec<u8>
+where
+    F: Fn(u32, u32) -> [u8; 3],
+{
+    let icon_size_squared = size * size;
+    let mut icon_rgba = vec![0; (icon_size_squared * 4) as usize];
+    for x in 0..size {
+        for y in 0..size {
+            let x_term = ((x as i32) * 2 - (size as i32) + 1) / 2;
+            let y_term = ((y as i32) * 2 - (size as i32) + 1) / 2;
+            let distance_squared = x_term * x_term + y_term * y_term;
+            if distance_squared < icon_size_squared as i32 / 4 {
+                let [r, g, b] = color(x, y);
+                let icon_offset = (x as usize * size as usize + y as usize) * 4;
+                icon_rgba[icon_offset..icon_offset + 4].copy_from_slice(&[r, g, b, 0xFF]);
+            }
+        }
+    }
+    icon_rgba
+}
+
+#[cfg(test)]
+mod test_icon {
+    use super::*;
+
+    const SIZE: u32 = 32;
+
+    fn pixel(icon: &[u8], x: u32, y: u32) -> &[u8] {
+        let offset = (x * SIZE + y) as usize * 4;
+        &icon[offset..offset + 4]
+    }
+
+    #[test]
+    fn test_colored() {
+        let icon = generate_icon_rgba_colored(SIZE, [0x12, 0x34, 0x56]);
+        assert_eq!(icon.len(), (SIZE * SIZE * 4) as usize);
+        for (x, y) in [(SIZE / 2, SIZE / 2), (SIZE / 2, 2), (4, SIZE / 2), (10, 10)] {
+            assert_eq!(
+                pixel(&icon, x, y),
+                [0x12, 0x34, 0x56, 0xFF],
+                "at ({x}, {y})"
+            );
+        }
+        // the corners are outside the circle
+        assert_eq!(pixel(&icon, 0, 0), [0, 0, 0, 0]);
+        assert_eq!(pixel(&icon, SIZE - 1, SIZE - 1), [0, 0, 0, 0]);
+    }
+
+    /// the default icon is the same shape, just with a gradient on top of the red
+    #[test]
+    fn test_default() {
+        let icon = generate_icon_rgba(SIZE);
+        let colored = generate_icon_rgba_colored(SIZE, [0xFF, 0, 0]);
+        for (default, colored) in icon.chunks(4).zip(colored.chunks(4)) {
+            assert_eq!(default[0], colored[0]);
+            assert_eq!(default[3], colored[3]);
+        }
+        assert_eq!(pixel(&icon, SIZE / 2, SIZE / 2), [0xFF, 64, 64, 0xFF]);
+    }
+}
@@ -0,0 +1,380 @@
+// This file is part of simple-crosshair-overlay and is licenced under the GNU GPL v3.0.
+// See LICENSE file for full text.
+// Copyright © 2023 Michael Ripley
+
+//! Animated PNG (APNG) crosshairs. Each frame gets composited up front, so playing the animation back is just a
+//! matter of picking which finished frame to draw.
+
+use std::ffi::OsStr;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::mem;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use png::{BlendOp, DisposeOp, FrameControl};
+
+use super::{multiply_color_channels_u8, png_pixel_to_rgba, premultiply_alpha, Color, Image};
+
+/// Browsers bump delays this short up to 100ms, as they're almost always a mistake. We just make sure a zero delay
+/// can't spin forever.
+const MIN_FRAME_DELAY: Duration = Duration::from_millis(10);
+
+/// A finished frame of an animation, ready to be drawn
+pub struct Frame {
+    pub image: Image,
+    /// how long to show this frame for
+    pub delay: Duration,
+}
+
+/// An animated crosshair, along with which of its frames is showing right now
+pub struct Animation {
+    pub frames: Vec<Frame>,
+    current: usize,
+    /// when the current frame started showing, or `None` if playback hasn't started
+    frame_started: Option<Instant>,
+}
+
+impl Animation {
+    fn new(frames: Vec<Frame>) -> Animation {
+        Animation {
+            frames,
+            current: 0,
+            frame_started: None,
+        }
+    }
+
+    /// the frame that should be drawn right now
+    pub fn current(&self) -> &Image {
+        &self.frames[self.current].image
+    }
+
+    /// `true` if there's more than one frame to flip through
+    pub fn is_animated(&self) -> bool {
+        self.frames.len() > 1
+    }
+
+    /// Move on to whichever frame should be showing at `now`. Returns `true` if that's a different frame than before.
+    pub fn advance(&mut self, now: Instant) -> bool {
+        if !self.is_animated() {
+            return false;
+        }
+        let Some(mut frame_started) = self.frame_started else {
+            self.frame_started = Some(now);
+            return false;
+        };
+
+        let mut changed = false;
+        // going around more than once means we stalled, e.g. the system slept. There's no point in catching up.
+        for _ in 0..self.frames.len() {
+            let delay = self.frames[self.current].delay.max(MIN_FRAME_DELAY);
+            if now.saturating_duration_since(frame_started) < delay {
+                self.frame_started = Some(frame_started);
+                return changed;
+            }
+            frame_started += delay;
+            self.current = (self.current + 1) % self.frames.len();
+            changed = true;
+        }
+        self.frame_started = Some(now);
+        changed
+    }
+
+    /// Scale every frame to a new size, keeping playback where it was
+    pub fn resize(&self, width: u32, height: u32) -> Animation {
+        Animation {
+            frames: self
+                .frames
+                .iter()
+                .map(|frame| Frame {
+                    image: frame.image.resize(width, height),
+                    delay: frame.delay,
+                })
+                .collect(),
+            current: self.current,
+            frame_started: self.frame_started,
+        }
+    }
+}
+
+/// Load every frame of an animated PNG, premultiplying alpha if `premultiply` is set. Returns `None` for anything
+/// that isn't an animated PNG, as [`super::load_image`] already covers those.
+pub fn load_animation<T>(
+    path: T,
+    premultiply: bool,
+    gamma_correct: bool,
+) -> io::Result<Option<Animation>>
+where
+    T: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let extension = path
+        .extension()
+        .and_then(OsStr::to_str)
+        .map(str::to_ascii_lowercase);
+    if matches!(extension.as_deref(), Some("jpg" | "jpeg" | "bmp")) {
+        return Ok(None);
+    }
+    let animation = decode_apng(
+        BufReader::new(File::open(path)?),
+        premultiply,
+        gamma_correct,
+    )?;
+    Ok(animation.filter(Animation::is_animated))
+}
+
+/// The APNG delay fraction in seconds as a [`Duration`]. A denominator of 0 means hundredths of a second.
+fn frame_delay(control: &FrameControl) -> Duration {
+    let denominator = match control.delay_den {
+        0 => 100,
+        denominator => denominator,
+    };
+    Duration::from_nanos(control.delay_num as u64 * 1_000_000_000 / denominator as u64)
+}
+
+/// Draw a straight `source` pixel over a straight `destination` pixel
+fn blend_over(destination: Color, source: Color) -> Color {
+    let [sr, sg, sb, sa] = source.to_rgba_bytes();
+    let [dr, dg, db, da] = destination.to_rgba_bytes();
+    match sa {
+        u8::MAX => source,
+        0 => destination,
+        _ => {
+            // do the math on premultiplied channels, where "over" is just a weighted sum
+            let remaining = u8::MAX - sa;
+            let alpha = sa + multiply_color_channels_u8(da, remaining);
+            let channel = |s: u8, d: u8| {
+                let premultiplied = multiply_color_channels_u8(s, sa)
+                    + multiply_color_channels_u8(multiply_color_channels_u8(d, da), remaining);
+                super::divide_color_channels_u8(premultiplied, alpha)
+            };
+            Color::from_rgba_bytes([channel(sr, dr), channel(sg, dg), channel(sb, db), alpha])
+        }
+    }
+}
+
+/// Every frame gets composited into a full-size image up front. Animations that would take more memory than the PNG
+/// decoder allows a single image are rejected the same way an oversized image is.
+fn check_frame_budget(frame_count: u32, width: u32, height: u32) -> io::Result<()> {
+    let frame_bytes = (width as usize)
+        .checked_mul(height as usize)
+        .and_then(|pixels| pixels.checked_mul(mem::size_of::<u32>()))
+        .and_then(|bytes| bytes.checked_add(mem::size_of::<Frame>()));
+    let total_bytes = frame_bytes.and_then(|bytes| bytes.checked_mul(frame_count as usize));
+    match total_bytes {
+        Some(bytes) if bytes <= png::Limits::default().bytes => Ok(()),
+        _ => Err(png::DecodingError::LimitsExceeded.into()),
+    }
+}
+
+/// Decode and composite every frame of an animated PNG. Returns `None` without decoding any image data if the PNG has
+/// no animation control chunk, as that's a plain PNG.
+fn decode_apng<R: Read>(
+    read: R,
+    premultiply: bool,
+    gamma_correct: bool,
+) -> io::Result<Option<Animation>> {
+    let mut decoder = png::Decoder::new(read);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+    let (width, height) = reader.info().size();
+    let Some(animation_control) = reader.info().animation_control else {
+        return Ok(None);
+    };
+    let frame_count = animation_control.num_frames;
+    check_frame_budget(frame_count, width, height)?;
+
+    let mut buf = vec![0; reader.output_buffer_size()];
+    // the default image is only part of the animation if it has its own frame control
+    if reader.info().frame_control.is_none() {
+        reader.next_frame(&mut buf)?;
+    }
+
+    let mut canvas = vec![Color::TRANSPARENT; width as usize * height as usize];
+    let mut frames = Vec::new();
+    for _ in 0..frame_count {
+        let info = reader.next_frame(&mut buf)?;
+        let control = reader.info().frame_control.unwrap_or(FrameControl {
+            width,
+            height,
+            ..FrameControl::default()
+        });
+        if control.x_offset.saturating_add(control.width) > width
+            || control.y_offset.saturating_add(control.height) > height
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "APNG frame doesn't fit in the image",
+            ));
+        }
+
+        let previous = (control.dispose_op == DisposeOp::Previous).then(|| canvas.clone());
+        let to_rgba = png_pixel_to_rgba(info.color_type)?;
+        let region = |index: usize| {
+            let x = control.x_offset as usize + index % control.width as usize;
+            let y = control.y_offset as usize + index / control.width as usize;
+            y * width as usize + x
+        };
+
+        let pixels = buf[..info.buffer_size()].chunks_exact(info.color_type.samples());
+        for (index, pixel) in pixels.enumerate() {
+            let source = Color::from_rgba_bytes(to_rgba(pixel));
+            let destination = &mut canvas[region(index)];
+            *destination = match control.blend_op {
+                BlendOp::Source => source,
+                BlendOp::Over => blend_over(*destination, source),
+            };
+        }
+
+        frames.push(Frame {
+            image: Image {
+                width,
+                height,
+                data: canvas
+                    .iter()
+                    .map(|color| premultiply_alpha(color.argb(), premultiply, gamma_correct))
+                    .collect(),
+            },
+            delay: frame_delay(&control),
+        });
+
+        match control.dispose_op {
+            DisposeOp::None => {}
+            DisposeOp::Background => {
+                for index in 0..(control.width * control.height) as usize {
+                    canvas[region(index)] = Color::TRANSPARENT;
+                }
+            }
+            DisposeOp::Previous => canvas = previous.unwrap(),
+        }
+    }
+
+    Ok(Some(Animation::new(frames)))
+}
+
+#[cfg(test)]
+mod test_animation {
+    use super::*;
+
+    /// a 2x1 APNG with two frames: red then blue, shown for 100ms and 250ms
+    fn two_frame_apng() -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, 2, 1);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_animated(2, 0).unwrap();
+        let mut writer = encoder.write_header().unwrap();
+        writer.set_frame_delay(1, 10).unwrap();
+        writer
+            .write_image_data(&[0xFF, 0, 0, 0xFF, 0xFF, 0, 0, 0x80])
+            .unwrap();
+        writer.set_frame_delay(25, 100).unwrap();
+        writer
+            .write_image_data(&[0, 0, 0xFF, 0xFF, 0, 0, 0xFF, 0x80])
+            .unwrap();
+        writer.finish().unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_decode_two_frames() {
+        let animation = decode_apng(two_frame_apng().as_slice(), false, false)
+            .unwrap()
+            .unwrap();
+        assert_eq!(animation.frames.len(), 2);
+        assert_eq!(animation.frames[0].delay, Duration::from_millis(100));
+        assert_eq!(animation.frames[1].delay, Duration::from_millis(250));
+        assert_eq!(animation.frames[0].image.data, [0xFFFF0000, 0x80FF0000]);
+        assert_eq!(animation.frames[1].image.data, [0xFF0000FF, 0x800000FF]);
+    }
+
+    #[test]
+    fn test_premultiplied() {
+        let animation = decode_apng(two_frame_apng().as_slice(), true, false)
+            .unwrap()
+            .unwrap();
+        assert_eq!(animation.frames[1].image.data, [0xFF0000FF, 0x80000080]);
+    }
+
+    /// plain PNGs have no animation control chunk, so they're left to the regular image loader
+    #[test]
+    fn test_static_png() {
+        let file = BufReader::new(File::open("tests/resources/test.png").unwrap());
+        assert!(decode_apng(file, false, false).unwrap().is_none());
+        assert!(load_animation("tests/resources/test.png", false, false)
+            .unwrap()
+            .is_none());
+        assert!(load_animation("tests/resources/test.jpg", false, false)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_advance() {
+        let mut animation = decode_apng(two_frame_apng().as_slice(), false, false)
+            .unwrap()
+            .unwrap();
+        let start = Instant::now();
+        assert!(!animation.advance(start));
+        assert!(!animation.advance(start + Duration::from_millis(99)));
+        assert!(animation.advance(start + Duration::from_millis(100)));
+        assert_eq!(animation.current().data[0], 0xFF0000FF);
+        assert!(!animation.advance(start + Duration::from_millis(349)));
+        assert!(animation.advance(start + Duration::from_millis(350)));
+        assert_eq!(animation.current().data[0], 0xFFFF0000);
+    }
+
+    /// after a long stall playback carries on from where it was instead of fast-forwarding
+    #[test]
+    fn test_stall() {
+        let mut animation = decode_apng(two_frame_apng().as_slice(), false, false)
+            .unwrap()
+            .unwrap();
+        let start = Instant::now();
+        animation.advance(start);
+        assert!(animation.advance(start + Duration::from_secs(60)));
+        let resumed = start + Duration::from_secs(60);
+        assert!(!animation.advance(resumed + Duration::from_millis(99)));
+    }
+
+    #[test]
+    fn test_frame_budget() {
+        assert!(check_frame_budget(2, 2, 1).is_ok());
+        assert!(check_frame_budget(60, 256, 256).is_ok());
+        let error = check_frame_budget(10_000, 1920, 1080).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            png::DecodingError::LimitsExceeded.to_string()
+        );
+        // tiny frames still cost something each
+        assert!(check_frame_budget(u32::MAX, 1, 1).is_err());
+        assert!(check_frame_budget(u32::MAX, u32::MAX, u32::MAX).is_err());
+    }
+
+    /// an animation claiming more frames than fit in memory is turned away before any of them get decoded
+    #[test]
+    fn test_too_many_frames() {
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, 1024, 1024);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_animated(1000, 0).unwrap();
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(&vec![0; 1024 * 1024 * 4]).unwrap();
+        drop(writer);
+        let error = decode_apng(bytes.as_slice(), false, false).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            png::DecodingError::LimitsExceeded.to_string()
+        );
+    }
+
+    #[test]
+    fn test_blend_over() {
+        let red = Color::from_argb(0xFFFF0000);
+        let half_blue = Color::from_argb(0x800000FF);
+        assert_eq!(blend_over(red, half_blue).argb(), 0xFF7F0080);
+        assert_eq!(blend_over(red, Color::TRANSPARENT), red);
+        assert_eq!(blend_over(Color::TRANSPARENT, half_blue), half_blue);
+    }
+}
@@ -0,0 +1,237 @@
+// This file is part of simple-crosshair-overlay and is licenced under the GNU GPL v3.0.
+// See LICENSE file for full text.
+// Copyright © 2023 Michael Ripley
+
+//! A tiny BMP decoder covering the uncompressed 24 and 32-bit bitmaps that image editors and screenshot tools save.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use super::{rgba_to_argb, Image};
+
+const FILE_HEADER_SIZE: usize = 14;
+/// size of `BITMAPINFOHEADER`, the oldest header we support. Newer headers just add fields to the end of it.
+const INFO_HEADER_SIZE: usize = 40;
+/// where the color masks of a `BI_BITFIELDS` bitmap start
+const MASKS_OFFSET: usize = FILE_HEADER_SIZE + INFO_HEADER_SIZE;
+/// headers at least this big have an alpha mask after the color masks
+const ALPHA_MASK_HEADER_SIZE: usize = 56;
+
+const BI_RGB: u32 = 0;
+const BI_BITFIELDS: u32 = 3;
+const BI_ALPHABITFIELDS: u32 = 6;
+
+/// load a BMP file into an in-memory image, premultiplying alpha if `premultiply` is set
+pub fn load_bmp<T>(path: T, premultiply: bool, gamma_correct: bool) -> io::Result<Box<Image>>
+where
+    T: AsRef<Path>,
+{
+    decode_bmp(&fs::read(path)?, premultiply, gamma_correct)
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn unsupported(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> io::Result<u16> {
+    bytes
+        .get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or_else(|| invalid("BMP is truncated"))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> io::Result<u32> {
+    bytes
+        .get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| invalid("BMP is truncated"))
+}
+
+/// Pulls one channel out of a pixel with a `BI_BITFIELDS` mask, scaled to 8 bits
+#[derive(Clone, Copy)]
+struct Channel {
+    mask: u32,
+    shift: u32,
+    max: u32,
+}
+
+impl Channel {
+    fn new(mask: u32) -> Channel {
+        let shift = if mask == 0 { 0 } else { mask.trailing_zeros() };
+        Channel {
+            mask,
+            shift,
+            max: mask >> shift,
+        }
+    }
+
+    /// the channel's value, or `missing` if the mask is empty
+    fn read(self, pixel: u32, missing: u8) -> u8 {
+        if self.max == 0 {
+            missing
+        } else {
+            let value = (pixel & self.mask) >> self.shift;
+            ((value as u64 * u8::MAX as u64 + self.max as u64 / 2) / self.max as u64) as u8
+        }
+    }
+}
+
+/// Decode a whole BMP file. Bitmaps without alpha come out fully opaque.
+fn decode_bmp(bytes: &[u8], premultiply: bool, gamma_correct: bool) -> io::Result<Box<Image>> {
+    if !bytes.starts_with(b"BM") {
+        return Err(invalid("not a BMP file"));
+    }
+    let pixel_offset = read_u32(bytes, 10)? as usize;
+    let header_size = read_u32(bytes, 14)? as usize;
+    if header_size < INFO_HEADER_SIZE {
+        return Err(unsupported(format!(
+            "BMP header of {header_size} bytes is too old. Please re-save your BMP with a newer image editor."
+        )));
+    }
+    let width = read_u32(bytes, 18)? as i32;
+    let height = read_u32(bytes, 22)? as i32;
+    let bits_per_pixel = read_u16(bytes, 28)?;
+    let compression = read_u32(bytes, 30)?;
+    if width <= 0 || height == 0 {
+        return Err(invalid("BMP has no pixels"));
+    }
+
+    // negative heights are stored top row first, otherwise the bottom row comes first
+    let top_down = height < 0;
+    let width = width.unsigned_abs();
+    let height = height.unsigned_abs();
+
+    let [red, green, blue, alpha] = match (compression, bits_per_pixel) {
+        (BI_RGB, 24 | 32) => [0x00FF0000, 0x0000FF00, 0x000000FF, 0].map(Channel::new),
+        (BI_BITFIELDS | BI_ALPHABITFIELDS, 32) => {
+            let alpha_mask =
+                if compression == BI_ALPHABITFIELDS || header_size >= ALPHA_MASK_HEADER_SIZE {
+                    read_u32(bytes, MASKS_OFFSET + 12)?
+                } else {
+                    0
+                };
+            [
+                read_u32(bytes, MASKS_OFFSET)?,
+                read_u32(bytes, MASKS_OFFSET + 4)?,
+                read_u32(bytes, MASKS_OFFSET + 8)?,
+                alpha_mask,
+            ]
+            .map(Channel::new)
+        }
+        _ => {
+            return Err(unsupported(format!(
+                "BMP was {bits_per_pixel}-bit with compression type {compression}. Only uncompressed 24 and 32-bit BMPs are supported. Please re-save your BMP in one of those formats."
+            )))
+        }
+    };
+
+    let pixel_bytes = bits_per_pixel as usize / 8;
+    // rows are padded to a multiple of 4 bytes
+    let stride = (width as usize * pixel_bytes).next_multiple_of(4);
+    let pixels = bytes
+        .get(pixel_offset..)
+        .filter(|pixels| pixels.len() >= stride * height as usize)
+        .ok_or_else(|| invalid("BMP is truncated"))?;
+
+    let mut data = Vec::with_capacity(width as usize * height as usize);
+    for row in 0..height as usize {
+        let stored_row = if top_down {
+            row
+        } else {
+            height as usize - 1 - row
+        };
+        let row_bytes = &pixels[stored_row * stride..][..width as usize * pixel_bytes];
+        data.extend(row_bytes.chunks_exact(pixel_bytes).map(|pixel| {
+            let pixel =
+                u32::from_le_bytes([pixel[0], pixel[1], pixel[2], *pixel.get(3).unwrap_or(&0)]);
+            let rgba = [
+                red.read(pixel, 0),
+                green.read(pixel, 0),
+                blue.read(pixel, 0),
+                alpha.read(pixel, u8::MAX),
+            ];
+            rgba_to_argb(u32::from_le_bytes(rgba), premultiply, gamma_correct)
+        }));
+    }
+
+    Ok(Box::new(Image {
+        width,
+        height,
+        data,
+    }))
+}
+
+#[cfg(test)]
+mod test_bmp {
+    use super::*;
+
+    #[test]
+    fn test_load_24_bit() {
+        let image = load_bmp("tests/resources/test.bmp", false, false).unwrap();
+        assert_eq!((image.width, image.height), (3, 2));
+        assert_eq!(
+            image.data,
+            [0xFFFF0000, 0xFF00FF00, 0xFF0000FF, 0xFFFFFFFF, 0xFF000000, 0xFF808080]
+        );
+    }
+
+    /// build a 2x1 top-down 32-bit bitmap with a V5 header and the given masks
+    fn bitfields_bmp(masks: [u32; 4], pixels: [u32; 2]) -> Vec<u8> {
+        const V5_HEADER_SIZE: u32 = 124;
+        let pixel_offset = FILE_HEADER_SIZE as u32 + V5_HEADER_SIZE;
+        let mut bytes = b"BM".to_vec();
+        bytes.extend((pixel_offset + 8).to_le_bytes());
+        bytes.extend(0u32.to_le_bytes());
+        bytes.extend(pixel_offset.to_le_bytes());
+        bytes.extend(V5_HEADER_SIZE.to_le_bytes());
+        bytes.extend(2i32.to_le_bytes());
+        bytes.extend((-1i32).to_le_bytes());
+        bytes.extend(1u16.to_le_bytes());
+        bytes.extend(32u16.to_le_bytes());
+        bytes.extend(BI_BITFIELDS.to_le_bytes());
+        bytes.resize(MASKS_OFFSET, 0);
+        for mask in masks {
+            bytes.extend(mask.to_le_bytes());
+        }
+        bytes.resize(pixel_offset as usize, 0);
+        for pixel in pixels {
+            bytes.extend(pixel.to_le_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_alpha_bitfields() {
+        let bytes = bitfields_bmp(
+            [0x00FF0000, 0x0000FF00, 0x000000FF, 0xFF000000],
+            [0x80FF8040, 0x00000000],
+        );
+        let image = decode_bmp(&bytes, false, false).unwrap();
+        assert_eq!((image.width, image.height), (2, 1));
+        assert_eq!(image.data, [0x80FF8040, 0x00000000]);
+
+        let image = decode_bmp(&bytes, true, false).unwrap();
+        assert_eq!(image.data, [0x80804020, 0x00000000]);
+    }
+
+    /// 5 bits per channel gets scaled up to 8
+    #[test]
+    fn test_narrow_bitfields() {
+        let bytes = bitfields_bmp([0x7C00, 0x03E0, 0x001F, 0], [0x7C00, 0x001F]);
+        let image = decode_bmp(&bytes, false, false).unwrap();
+        assert_eq!(image.data, [0xFFFF0000, 0xFF0000FF]);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(decode_bmp(b"PNG", false, false).is_err());
+        let mut bytes = bitfields_bmp([0x00FF0000, 0x0000FF00, 0x000000FF, 0], [0, 0]);
+        bytes.truncate(bytes.len() - 1);
+        assert!(decode_bmp(&bytes, false, false).is_err());
+    }
+}
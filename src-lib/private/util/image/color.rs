@@ -0,0 +1,220 @@
+// This file is part of simple-crosshair-overlay and is licenced under the GNU GPL v3.0.
+// See LICENSE file for full text.
+// Copyright © 2023 Michael Ripley
+
+//! A packed color type, so it's always clear what order a color's bytes are in
+
+use std::fmt::{self, Debug, Formatter, UpperHex};
+
+/// A color packed the way window buffers want it: `0xAARRGGBB`, which is `[b, g, r, a]` in little endian byte order.
+///
+/// Whether the channels have been multiplied by alpha isn't part of the type. Colors start out straight, and
+/// [`Color::premultiplied`] and [`Color::straight`] convert between the two.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Color(u32);
+
+impl Color {
+    /// fully transparent black, which is the same whether premultiplied or not
+    pub const TRANSPARENT: Color = Color(0);
+
+    /// Wrap a color that's already packed as `0xAARRGGBB`
+    pub const fn from_argb(argb: u32) -> Color {
+        Color(argb)
+    }
+
+    /// Pack an opaque color from its red, green, and blue channels, e.g. a pixel read off the screen
+    pub const fn from_rgb(r: u8, g: u8, b: u8) -> Color {
+        Color(u32::from_le_bytes([b, g, r, 0xFF]))
+    }
+
+    /// Pack a color from bytes in RGBA order, which is how PNGs store them
+    pub const fn from_rgba_bytes(rgba: [u8; 4]) -> Color {
+        let [r, g, b, a] = rgba;
+        Color(u32::from_le_bytes([b, g, r, a]))
+    }
+
+    /// The color packed as `0xAARRGGBB`, ready to be written into a window buffer
+    pub const fn argb(self) -> u32 {
+        self.0
+    }
+
+    /// The color's bytes in RGBA order
+    pub const fn to_rgba_bytes(self) -> [u8; 4] {
+        let [b, g, r, a] = self.0.to_le_bytes();
+        [r, g, b, a]
+    }
+
+    pub const fn alpha(self) -> u8 {
+        self.to_rgba_bytes()[3]
+    }
+
+    /// The same color with its alpha replaced. Must only be called on a straight color.
+    pub const fn with_alpha(self, alpha: u8) -> Color {
+        Color((self.0 & 0x00FFFFFF) | (alpha as u32) << 24)
+    }
+
+    /// The same color at full opacity. An opaque color is the same whether it's premultiplied or not.
+    pub const fn opaque(self) -> Color {
+        Color(self.0 | 0xFF000000)
+    }
+
+    /// Multiply each channel by alpha, in linear light if `gamma_correct` is set. Must only be called on a straight
+    /// color.
+    pub fn premultiplied(self, gamma_correct: bool) -> Color {
+        Color(super::premultiply_alpha(self.0, true, gamma_correct))
+    }
+
+    /// Undo [`Color::premultiplied`] with the same `gamma_correct`. Precision lost during the multiplication can't be
+    /// recovered.
+    pub fn straight(self, gamma_correct: bool) -> Color {
+        Color(super::unpremultiply_alpha(self.0, true, gamma_correct))
+    }
+
+    /// The hue, saturation, and value of a straight color, ignoring alpha. The hue goes around once over the whole `u8`
+    /// range, the same as [`super::hsv_to_argb`] expects.
+    pub fn to_hsv(self) -> [u8; 3] {
+        let [r, g, b, _a] = self.to_rgba_bytes();
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        if max == min {
+            return [0, 0, max];
+        }
+        let chroma = (max - min) as f32;
+        let saturation = (chroma / max as f32 * 255.0).round() as u8;
+        // which sixth of the color wheel we're in, and how far along it
+        let sixths = if max == r {
+            (g as f32 - b as f32) / chroma
+        } else if max == g {
+            (b as f32 - r as f32) / chroma + 2.0
+        } else {
+            (r as f32 - g as f32) / chroma + 4.0
+        };
+        let hue = (sixths * 256.0 / 6.0).round().rem_euclid(256.0) as u8;
+        [hue, saturation, max]
+    }
+
+    /// Convert a straight color to what the window buffer wants, which is premultiplied if `premultiply` is set.
+    /// Usually `premultiply` and `gamma_correct` come from the settings, which default to
+    /// [`super::PREMULTIPLY_ALPHA_DEFAULT`] and `false`.
+    pub fn for_buffer(self, premultiply: bool, gamma_correct: bool) -> Color {
+        if premultiply {
+            self.premultiplied(gamma_correct)
+        } else {
+            self
+        }
+    }
+}
+
+impl From<Color> for u32 {
+    fn from(color: Color) -> u32 {
+        color.argb()
+    }
+}
+
+impl Debug for Color {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Color({:08X})", self.0)
+    }
+}
+
+/// Formats the same way as the config file: `AARRGGBB`
+impl UpperHex for Color {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        UpperHex::fmt(&self.0, f)
+    }
+}
+
+#[cfg(test)]
+mod test_color {
+    use super::*;
+
+    #[test]
+    fn test_argb_round_trip() {
+        let color = Color::from_argb(0x80FF8040);
+        assert_eq!(color.argb(), 0x80FF8040);
+        assert_eq!(u32::from(color), 0x80FF8040);
+        assert_eq!(color.alpha(), 0x80);
+    }
+
+    #[test]
+    fn test_rgba_bytes() {
+        let color = Color::from_rgba_bytes([0xFF, 0x80, 0x40, 0x20]);
+        assert_eq!(color.argb(), 0x20FF8040);
+        assert_eq!(color.to_rgba_bytes(), [0xFF, 0x80, 0x40, 0x20]);
+    }
+
+    #[test]
+    fn test_with_alpha() {
+        let color = Color::from_argb(0x80FF8040);
+        assert_eq!(color.with_alpha(0x10), Color::from_argb(0x10FF8040));
+        assert_eq!(color.with_alpha(0xFF), color.opaque());
+    }
+
+    #[test]
+    fn test_to_hsv() {
+        assert_eq!(Color::from_argb(0x80808080).to_hsv(), [0, 0, 0x80]);
+        assert_eq!(Color::from_argb(0xFF000000).to_hsv(), [0, 0, 0]);
+        assert_eq!(Color::from_argb(0xFFFF0000).to_hsv(), [0, 255, 255]);
+        assert_eq!(Color::from_argb(0xFF00FF00).to_hsv(), [85, 255, 255]);
+        assert_eq!(Color::from_argb(0xFF0000FF).to_hsv(), [171, 255, 255]);
+        // just short of wrapping back around to red
+        assert_eq!(Color::from_argb(0xFFFF0001).to_hsv(), [0, 255, 255]);
+        assert_eq!(Color::from_argb(0x10402000).to_hsv(), [21, 255, 0x40]);
+    }
+
+    /// converting back with the integer HSV helper lands on (almost) the same color
+    #[test]
+    fn test_to_hsv_round_trip() {
+        use crate::private::util::image::hsv_to_argb;
+
+        for argb in [0xFFFF8040, 0xFF123456, 0xFF80FF80, 0xFFC0C0C0] {
+            let [h, s, v] = Color::from_argb(argb).to_hsv();
+            let round_trip = Color::from_argb(hsv_to_argb(h, s, v));
+            for (expected, actual) in Color::from_argb(argb)
+                .to_rgba_bytes()
+                .into_iter()
+                .zip(round_trip.to_rgba_bytes())
+            {
+                assert!(
+                    expected.abs_diff(actual) <= 4,
+                    "{argb:08X} came back as {round_trip:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_rgb() {
+        let color = Color::from_rgb(0x12, 0x34, 0x56);
+        assert_eq!(color.argb(), 0xFF123456);
+        // already opaque, so premultiplying is a no-op
+        assert_eq!(color.premultiplied(false), color);
+    }
+
+    #[test]
+    fn test_premultiplied() {
+        let color = Color::from_argb(0x80FF8040);
+        assert_eq!(color.premultiplied(false), Color::from_argb(0x80804020));
+        assert_eq!(
+            color.premultiplied(false).straight(false),
+            Color::from_argb(0x80FF8040)
+        );
+        assert_eq!(color.for_buffer(true, false), color.premultiplied(false));
+        assert_eq!(color.for_buffer(false, false), color);
+    }
+
+    #[test]
+    fn test_opaque() {
+        let color = Color::from_argb(0x40FF0000);
+        assert_eq!(color.opaque(), Color::from_argb(0xFFFF0000));
+        assert_eq!(color.opaque().premultiplied(false), color.opaque());
+        assert_eq!(Color::TRANSPARENT.premultiplied(false), Color::TRANSPARENT);
+    }
+
+    #[test]
+    fn test_format() {
+        let color = Color::from_argb(0x0AFF8040);
+        assert_eq!(format!("{color:08X}"), "0AFF8040");
+        assert_eq!(format!("{color:?}"), "Color(0AFF8040)");
+    }
+}
@@ -0,0 +1,1947 @@
+// This file is part of simple-crosshair-overlay and is licenced under the GNU GPL v3.0.
+// See LICENSE file for full text.
+// Copyright © 2023 Michael Ripley
+
+//! Routines for drawing the generated crosshairs into a buffer.
+//!
+//! All shapes are centered on the center of the buffer, which lands between pixels for even sizes. This is what gives
+//! us 0.5 pixel alignment.
+
+use super::Color;
+use crate::private::settings::{CenterMarker, RenderMode, Settings};
+use crate::private::util::{ghost, magnifier, osd};
+
+/// Draws the crosshair image, generated crosshair, or color picker into a buffer of exactly [`Settings::content_size`],
+/// along with the ghost crosshair if there is one
+pub fn render_content(buffer: &mut [u32], width: usize, height: usize, settings: &Settings) {
+    let Some(ghost) = settings.ghost() else {
+        render_crosshair(buffer, width, height, settings);
+        return;
+    };
+
+    // draw the main crosshair off to the side, then drop it into the padded buffer
+    let crosshair_size = settings.crosshair_size();
+    let crosshair_width = crosshair_size.width as usize;
+    let crosshair_height = crosshair_size.height as usize;
+    let mut crosshair = vec![0; crosshair_width * crosshair_height];
+    render_crosshair(&mut crosshair, crosshair_width, crosshair_height, settings);
+
+    buffer.fill(settings.background_color().argb());
+    let (x, y) = ghost::main_origin(crosshair_size, ghost);
+    magnifier::blit(buffer, width, &crosshair, crosshair_width, x, y);
+    let (ghost_x, ghost_y) = ghost::ghost_origin(crosshair_size, ghost);
+    draw_ghost(
+        buffer,
+        width,
+        height,
+        (ghost_x, ghost_y),
+        ghost.size,
+        settings.ghost_color(),
+    );
+}
+
+/// Dim a finished buffer by the `opacity` setting. This is skipped while there's a transparency key, as a dimmed key
+/// wouldn't match anymore.
+pub fn apply_opacity(buffer: &mut [u32], settings: &Settings) {
+    let opacity = settings.persisted.opacity;
+    if opacity == u8::MAX || settings.persisted.transparency_key.is_some() {
+        return;
+    }
+    let premultiplied = settings.persisted.premultiply_alpha;
+    for pixel in buffer {
+        *pixel = super::fade(*pixel, opacity, premultiplied);
+    }
+}
+
+/// Draws everything the overlay window shows into a buffer of exactly [`Settings::size`]: the content, the magnifier,
+/// the color picker cursor, and the on-screen display, dimmed by the opacity setting. This needs no window, so it's
+/// what tests and benchmarks should use to check a whole frame.
+pub fn render_window(
+    buffer: &mut [u32],
+    width: usize,
+    height: usize,
+    settings: &Settings,
+    magnifier_sample: Option<&[u32]>,
+    picker_cursor: (usize, usize),
+) {
+    if let Some(message) = settings.osd_message() {
+        // draw everything else off to the side, then drop it into the middle of the padded window
+        let inner_size = settings.inner_size();
+        let inner_width = inner_size.width as usize;
+        let inner_height = inner_size.height as usize;
+        let mut inner = vec![0; inner_width * inner_height];
+        render_inner(
+            &mut inner,
+            inner_width,
+            inner_height,
+            settings,
+            magnifier_sample,
+            picker_cursor,
+        );
+
+        let text_size = osd::text_size(message);
+        let (inner_x, inner_y) = osd::content_origin(inner_size, text_size);
+        buffer.fill(settings.background_color().argb());
+        magnifier::blit(buffer, width, &inner, inner_width, inner_x, inner_y);
+        let (text_x, text_y) = osd::text_origin(settings.size(), text_size);
+        osd::draw_text(buffer, width, text_x, text_y, message, settings.osd_color());
+    } else {
+        render_inner(
+            buffer,
+            width,
+            height,
+            settings,
+            magnifier_sample,
+            picker_cursor,
+        );
+    }
+    apply_opacity(buffer, settings);
+}
+
+/// Draw everything but the on-screen display into a buffer of exactly [`Settings::inner_size`]
+fn render_inner(
+    buffer: &mut [u32],
+    width: usize,
+    height: usize,
+    settings: &Settings,
+    magnifier_sample: Option<&[u32]>,
+    picker_cursor: (usize, usize),
+) {
+    if settings.magnifier_visible() {
+        // draw the content off to the side, then drop it into the middle of the padded window
+        let content_size = settings.content_size();
+        let content_width = content_size.width as usize;
+        let content_height = content_size.height as usize;
+        let mut content = vec![0; content_width * content_height];
+        render_content(&mut content, content_width, content_height, settings);
+
+        let padding = magnifier::MAGNIFIER_VIEW_SIZE as usize;
+        buffer.fill(settings.background_color().argb());
+        magnifier::blit(buffer, width, &content, content_width, padding, padding);
+        if let Some(sample) = magnifier_sample {
+            magnifier::draw_magnified(
+                buffer,
+                width,
+                sample,
+                magnifier::MAGNIFIER_SAMPLE_SIZE as usize,
+                magnifier::MAGNIFIER_ZOOM as usize,
+                settings.color,
+            );
+        }
+    } else {
+        render_content(buffer, width, height, settings);
+        if settings.get_pick_color() {
+            super::draw_picker_cursor(buffer, picker_cursor);
+        }
+    }
+}
+
+/// Draws the crosshair image, generated crosshair, or color picker into a buffer of exactly
+/// [`Settings::crosshair_size`]
+fn render_crosshair(buffer: &mut [u32], width: usize, height: usize, settings: &Settings) {
+    match settings.render_mode {
+        RenderMode::Image | RenderMode::Animated => {
+            // draw our image, scaled to whatever size the settings picked
+            settings.image().unwrap().resize_into(buffer, width, height);
+        }
+        RenderMode::Crosshair | RenderMode::TShape => {
+            let drawn = render_generated(
+                buffer,
+                width,
+                height,
+                settings,
+                |buffer, color, background| {
+                    draw_crosshair(
+                        buffer,
+                        width,
+                        height,
+                        settings.persisted.line_thickness,
+                        settings.persisted.outline_thickness,
+                        settings.persisted.taper,
+                        color,
+                        settings.outline_color(),
+                    );
+                    draw_center_gap(
+                        buffer,
+                        width,
+                        height,
+                        settings.persisted.center_gap,
+                        background,
+                    );
+                    if settings.persisted.gap_dots {
+                        draw_gap_dots(
+                            buffer,
+                            width,
+                            height,
+                            settings.persisted.center_gap,
+                            settings.persisted.line_thickness,
+                            color,
+                        );
+                    }
+                    if settings.render_mode == RenderMode::TShape {
+                        clear_top_arm(
+                            buffer,
+                            width,
+                            height,
+                            settings.persisted.line_thickness,
+                            settings.persisted.outline_thickness,
+                            color,
+                            settings.outline_color(),
+                            background,
+                        );
+                    }
+                },
+            );
+            // the shadow goes under the center marker too, so it's drawn last
+            if let (true, Some(shadow_color)) = (drawn, settings.shadow_color()) {
+                draw_shadow(
+                    buffer,
+                    width,
+                    height,
+                    settings.persisted.shadow_offset,
+                    shadow_color,
+                    settings.background_color(),
+                );
+            }
+        }
+        RenderMode::CircleDot => {
+            render_generated(buffer, width, height, settings, |buffer, color, _| {
+                draw_ring(
+                    buffer,
+                    width,
+                    height,
+                    settings.persisted.ring_radius,
+                    settings.persisted.ring_thickness,
+                    color,
+                );
+                draw_filled_disc(buffer, width, height, settings.persisted.dot_radius, color);
+            });
+        }
+        RenderMode::Donut => {
+            render_generated(buffer, width, height, settings, |buffer, color, _| {
+                draw_donut(
+                    buffer,
+                    width,
+                    height,
+                    settings.persisted.inner_radius,
+                    settings.persisted.outer_radius,
+                    color,
+                );
+            });
+        }
+        RenderMode::Circle => {
+            render_generated(buffer, width, height, settings, |buffer, color, _| {
+                draw_circle(
+                    buffer,
+                    width,
+                    height,
+                    settings.persisted.circle_thickness,
+                    color,
+                );
+            });
+        }
+        RenderMode::Cross => {
+            render_generated(buffer, width, height, settings, |buffer, color, _| {
+                draw_diagonal_cross(buffer, width, height, color);
+            });
+        }
+        RenderMode::ColorPicker => {
+            super::draw_color_picker(buffer);
+            super::desaturate_color_picker(buffer, settings.picker_saturation);
+        }
+    }
+}
+
+/// The part every generated crosshair shares: clear to the background, let `draw` put the shape on top with the
+/// crosshair and background colors, then add the center marker. Returns `false` without calling `draw` when there
+/// simply aren't enough pixels to draw a crosshair, in which case the whole buffer becomes a dot.
+fn render_generated(
+    buffer: &mut [u32],
+    width: usize,
+    height: usize,
+    settings: &Settings,
+    draw: impl FnOnce(&mut [u32], Color, Color),
+) -> bool {
+    let color = settings.render_color();
+    let background = settings.background_color();
+    if width <= 2 || height <= 2 {
+        buffer.fill(color.argb());
+        return false;
+    }
+    buffer.fill(background.argb());
+    draw(buffer, color, background);
+    draw_center_marker(
+        buffer,
+        width,
+        height,
+        settings.persisted.center_marker,
+        color,
+        background,
+    );
+    true
+}
+
+/// Slip a drop shadow under whatever's already been drawn: every non-background pixel gets copied `offset` pixels
+/// away in `color`, but only onto background so the original stays on top. Anything shifted past the edge is dropped.
+pub fn draw_shadow(
+    buffer: &mut [u32],
+    width: usize,
+    height: usize,
+    offset: (i32, i32),
+    color: Color,
+    background: Color,
+) {
+    let (dx, dy) = offset;
+    let shape = buffer.to_vec();
+    for (y, row) in shape.chunks_exact(width).take(height).enumerate() {
+        let Some(shadow_y) = y.checked_add_signed(dy as isize).filter(|&y| y < height) else {
+            continue;
+        };
+        for (x, &pixel) in row.iter().enumerate() {
+            if pixel == background.argb() {
+                continue;
+            }
+            let Some(shadow_x) = x.checked_add_signed(dx as isize).filter(|&x| x < width) else {
+                continue;
+            };
+            let index = shadow_y * width + shadow_x;
+            if shape[index] == background.argb() {
+                buffer[index] = color.argb();
+            }
+        }
+    }
+}
+
+/// Draw the `+` crosshair: lines `fill_thickness` thick, on top of an outline that sticks out `outline_thickness`
+/// pixels on each side. An `outline_thickness` of 0 skips the outline. A nonzero `taper` thins the lines along each
+/// arm, see [`tapered_thickness`].
+#[allow(clippy::too_many_arguments)]
+pub fn draw_crosshair(
+    buffer: &mut [u32],
+    width: usize,
+    height: usize,
+    fill_thickness: u32,
+    outline_thickness: u32,
+    taper: f32,
+    color: Color,
+    outline_color: Color,
+) {
+    if taper == 0.0 {
+        if outline_thickness != 0 {
+            let outline_thickness =
+                fill_thickness.saturating_add(outline_thickness.saturating_mul(2));
+            draw_cross(buffer, width, height, outline_thickness, outline_color);
+        }
+        draw_cross(buffer, width, height, fill_thickness, color);
+    } else {
+        if outline_thickness != 0 {
+            let extra = outline_thickness.saturating_mul(2);
+            draw_tapered_cross(
+                buffer,
+                width,
+                height,
+                fill_thickness,
+                extra,
+                taper,
+                outline_color,
+            );
+        }
+        draw_tapered_cross(buffer, width, height, fill_thickness, 0, taper, color);
+    }
+}
+
+/// Erase the top arm of a crosshair drawn by [`draw_crosshair`], leaving a `T` with its stem pointing down. The
+/// horizontal line and its outline are kept whole, and the stem no longer pokes through the top of the outline.
+#[allow(clippy::too_many_arguments)]
+pub fn clear_top_arm(
+    buffer: &mut [u32],
+    width: usize,
+    height: usize,
+    fill_thickness: u32,
+    outline_thickness: u32,
+    color: Color,
+    outline_color: Color,
+    background: Color,
+) {
+    let thickness = fill_thickness.saturating_add(outline_thickness.saturating_mul(2));
+    let outline_top = (height - centered_line_width(thickness, height)) / 2;
+    let fill_top = (height - centered_line_width(fill_thickness, height)) / 2;
+    buffer[..width * outline_top].fill(background.argb());
+    if outline_thickness != 0 {
+        for pixel in &mut buffer[width * outline_top..width * fill_top] {
+            if *pixel == color.argb() {
+                *pixel = outline_color.argb();
+            }
+        }
+    }
+}
+
+/// How many pixels wide a line centered across `length` pixels should be. Lines get an extra pixel when the parities
+/// of `thickness` and `length` don't match, as otherwise they couldn't be centered. Lines never get wider than
+/// `length`.
+fn centered_line_width(thickness: u32, length: usize) -> usize {
+    let thickness = thickness.max(1) as usize;
+    let width = if thickness % 2 == length % 2 {
+        thickness
+    } else {
+        thickness + 1
+    };
+    width.min(length)
+}
+
+/// Draw a simple crosshair. Think a `+` shape. Lines are `thickness` pixels thick, plus one more if needed to stay
+/// centered.
+pub fn draw_cross(buffer: &mut [u32], width: usize, height: usize, thickness: u32, color: Color) {
+    let color = color.argb();
+
+    // horizontal line
+    let line_height = centered_line_width(thickness, height);
+    let start = width * ((height - line_height) / 2);
+    buffer[start..start + width * line_height].fill(color);
+
+    // vertical line
+    let line_width = centered_line_width(thickness, width);
+    let left = (width - line_width) / 2;
+    for row in buffer.chunks_exact_mut(width) {
+        row[left..left + line_width].fill(color);
+    }
+}
+
+/// Draw an `X` shape: two one pixel lines from corner to corner
+pub fn draw_diagonal_cross(buffer: &mut [u32], width: usize, height: usize, color: Color) {
+    draw_line(buffer, width, 0, 0, width - 1, height - 1, color);
+    draw_line(buffer, width, width - 1, 0, 0, height - 1, color);
+}
+
+/// Draw a one pixel line between two pixels, inclusive, with Bresenham's line algorithm. Both ends must be in the
+/// buffer.
+pub fn draw_line(
+    buffer: &mut [u32],
+    width: usize,
+    x0: usize,
+    y0: usize,
+    x1: usize,
+    y1: usize,
+    color: Color,
+) {
+    let color = color.argb();
+    let (x1, y1) = (x1 as i64, y1 as i64);
+    let (mut x, mut y) = (x0 as i64, y0 as i64);
+    let dx = (x1 - x).abs();
+    let dy = -(y1 - y).abs();
+    let step_x = if x < x1 { 1 } else { -1 };
+    let step_y = if y < y1 { 1 } else { -1 };
+    let mut error = dx + dy;
+    loop {
+        buffer[y as usize * width + x as usize] = color;
+        if x == x1 && y == y1 {
+            break;
+        }
+        let doubled_error = 2 * error;
+        if doubled_error >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if doubled_error <= dx {
+            error += dx;
+            y += step_y;
+        }
+    }
+}
+
+/// Thickness of an arm `distance` pixels out from the center pixel(s), where the tip is `arm_length` pixels out. The
+/// thick end is always `thickness`, and the thin end is `thickness` scaled down by `taper`. A positive `taper` puts the
+/// thin end at the tip, and a negative one puts it at the center. Never thinner than 1 pixel.
+fn tapered_thickness(thickness: u32, taper: f32, distance: usize, arm_length: usize) -> u32 {
+    let along = distance as f32 / arm_length.max(1) as f32;
+    let toward_thin_end = if taper >= 0.0 { along } else { 1.0 - along };
+    let scale = 1.0 - taper.abs().min(1.0) * toward_thin_end;
+    ((thickness as f32 * scale).round() as u32).max(1)
+}
+
+/// how far a pixel at `position` is from the center pixel(s) along a line of `length` pixels
+fn distance_from_center(position: usize, length: usize) -> usize {
+    let (first, last) = center_bounds(length);
+    let position = position as isize;
+    (first - position).max(position - last).max(0) as usize
+}
+
+/// Like [`draw_cross`], but each column of the horizontal line and each row of the vertical line gets its own thickness
+/// from [`tapered_thickness`], plus `extra` pixels for outlines.
+fn draw_tapered_cross(
+    buffer: &mut [u32],
+    width: usize,
+    height: usize,
+    thickness: u32,
+    extra: u32,
+    taper: f32,
+    color: Color,
+) {
+    let argb = color.argb();
+    let arm_width = center_bounds(width).0 as usize;
+    let arm_height = center_bounds(height).0 as usize;
+
+    // horizontal line, one column at a time
+    for x in 0..width {
+        let thickness =
+            tapered_thickness(thickness, taper, distance_from_center(x, width), arm_width);
+        let line_height = centered_line_width(thickness.saturating_add(extra), height);
+        let top = (height - line_height) / 2;
+        for y in top..top + line_height {
+            buffer[y * width + x] = argb;
+        }
+    }
+
+    // vertical line, one row at a time
+    for (y, row) in buffer.chunks_exact_mut(width).enumerate() {
+        let thickness = tapered_thickness(
+            thickness,
+            taper,
+            distance_from_center(y, height),
+            arm_height,
+        );
+        let line_width = centered_line_width(thickness.saturating_add(extra), width);
+        let left = (width - line_width) / 2;
+        row[left..left + line_width].fill(argb);
+    }
+}
+
+/// inclusive bounds of the center pixel(s) along a dimension. These are equal on odd sizes.
+fn center_bounds(length: usize) -> (isize, isize) {
+    ((length as isize - 1) / 2, length as isize / 2)
+}
+
+/// Fill a rectangle given by inclusive bounds, skipping anything that would land outside the buffer
+fn fill_clipped(
+    buffer: &mut [u32],
+    width: usize,
+    height: usize,
+    (left, right): (isize, isize),
+    (top, bottom): (isize, isize),
+    color: Color,
+) {
+    let (left, right) = (left.max(0), right.min(width as isize - 1));
+    let (top, bottom) = (top.max(0), bottom.min(height as isize - 1));
+    if left > right || top > bottom {
+        return;
+    }
+    for row in buffer
+        .chunks_exact_mut(width)
+        .take(bottom as usize + 1)
+        .skip(top as usize)
+    {
+        row[left as usize..=right as usize].fill(color.argb());
+    }
+}
+
+/// Cut the middle out of the crosshair, leaving `gap` pixels of `background` on each side of the center pixel(s)
+pub fn draw_center_gap(
+    buffer: &mut [u32],
+    width: usize,
+    height: usize,
+    gap: u32,
+    background: Color,
+) {
+    if gap == 0 {
+        return;
+    }
+    let gap = gap as isize;
+    let (left, right) = center_bounds(width);
+    let (top, bottom) = center_bounds(height);
+    fill_clipped(
+        buffer,
+        width,
+        height,
+        (left - gap, right + gap),
+        (top - gap, bottom + gap),
+        background,
+    );
+}
+
+/// Draw a square dot at the inner end of each arm of a gapped crosshair. Dots stick out one pixel past each side of the
+/// line and are as deep as they are wide. Nothing is drawn without a gap, as there'd be no inner end to mark.
+pub fn draw_gap_dots(
+    buffer: &mut [u32],
+    width: usize,
+    height: usize,
+    gap: u32,
+    thickness: u32,
+    color: Color,
+) {
+    if gap == 0 {
+        return;
+    }
+    let gap = gap as isize;
+    let (left, right) = center_bounds(width);
+    let (top, bottom) = center_bounds(height);
+
+    // left and right arms run along the horizontal line
+    let line_height = centered_line_width(thickness, height) as isize;
+    let line_top = (height as isize - line_height) / 2;
+    let rows = (line_top - 1, line_top + line_height);
+    let depth = line_height + 2;
+    fill_clipped(
+        buffer,
+        width,
+        height,
+        (left - gap - depth, left - gap - 1),
+        rows,
+        color,
+    );
+    fill_clipped(
+        buffer,
+        width,
+        height,
+        (right + gap + 1, right + gap + depth),
+        rows,
+        color,
+    );
+
+    // top and bottom arms run along the vertical line
+    let line_width = centered_line_width(thickness, width) as isize;
+    let line_left = (width as isize - line_width) / 2;
+    let columns = (line_left - 1, line_left + line_width);
+    let depth = line_width + 2;
+    fill_clipped(
+        buffer,
+        width,
+        height,
+        columns,
+        (top - gap - depth, top - gap - 1),
+        color,
+    );
+    fill_clipped(
+        buffer,
+        width,
+        height,
+        columns,
+        (bottom + gap + 1, bottom + gap + depth),
+        color,
+    );
+}
+
+/// Draw a thin `+` filling the `size` by `size` square with its top-left corner at `origin`. Anything that would land
+/// outside the buffer is skipped.
+pub fn draw_ghost(
+    buffer: &mut [u32],
+    width: usize,
+    height: usize,
+    (x, y): (usize, usize),
+    size: u32,
+    color: Color,
+) {
+    let size = size.max(1) as usize;
+    let line = centered_line_width(1, size);
+    let (x, y, size, line) = (x as isize, y as isize, size as isize, line as isize);
+    let line_start = (size - line) / 2;
+    fill_clipped(
+        buffer,
+        width,
+        height,
+        (x, x + size - 1),
+        (y + line_start, y + line_start + line - 1),
+        color,
+    );
+    fill_clipped(
+        buffer,
+        width,
+        height,
+        (x + line_start, x + line_start + line - 1),
+        (y, y + size - 1),
+        color,
+    );
+}
+
+/// Draw the center marker over whatever's already in the buffer. Anything that would land outside the buffer is skipped.
+/// Hollow markers punch through to `background`.
+pub fn draw_center_marker(
+    buffer: &mut [u32],
+    width: usize,
+    height: usize,
+    marker: CenterMarker,
+    color: Color,
+    background: Color,
+) {
+    let color = color.argb();
+    let background = background.argb();
+    let (left, right) = center_bounds(width);
+    let (top, bottom) = center_bounds(height);
+    let mut set = |x: isize, y: isize, color: u32| {
+        if (0..width as isize).contains(&x) && (0..height as isize).contains(&y) {
+            buffer[width * y as usize + x as usize] = color;
+        }
+    };
+
+    match marker {
+        CenterMarker::None => {}
+        CenterMarker::Dot => {
+            for y in top..=bottom {
+                for x in left..=right {
+                    set(x, y, color);
+                }
+            }
+        }
+        CenterMarker::HollowDot => {
+            for y in top - 1..=bottom + 1 {
+                for x in left - 1..=right + 1 {
+                    let inside = (left..=right).contains(&x) && (top..=bottom).contains(&y);
+                    set(x, y, if inside { background } else { color });
+                }
+            }
+        }
+        CenterMarker::CrossPixel => {
+            for y in top - 1..=bottom + 1 {
+                for x in left..=right {
+                    set(x, y, color);
+                }
+            }
+            for x in [left - 1, right + 1] {
+                for y in top..=bottom {
+                    set(x, y, color);
+                }
+            }
+        }
+    }
+}
+
+/// Squared distance from the center of pixel `(x, y)` to the center of the buffer
+#[inline(always)]
+fn distance_squared(x: usize, y: usize, width: usize, height: usize) -> f32 {
+    let dx = x as f32 + 0.5 - width as f32 / 2.0;
+    let dy = y as f32 + 0.5 - height as f32 / 2.0;
+    dx * dx + dy * dy
+}
+
+/// Set every pixel whose center is within `[inner_radius, outer_radius]` of the buffer's center
+fn draw_annulus(
+    buffer: &mut [u32],
+    width: usize,
+    height: usize,
+    inner_radius: f32,
+    outer_radius: f32,
+    color: Color,
+) {
+    let color = color.argb();
+    let inner_squared = inner_radius.max(0.0).powi(2);
+    let outer_squared = outer_radius.powi(2);
+    for y in 0..height {
+        for x in 0..width {
+            let distance_squared = distance_squared(x, y, width, height);
+            if distance_squared >= inner_squared && distance_squared <= outer_squared {
+                buffer[width * y + x] = color;
+            }
+        }
+    }
+}
+
+/// Draw a hollow ring, `thickness` pixels wide, with its outside edge at `radius`
+pub fn draw_ring(
+    buffer: &mut [u32],
+    width: usize,
+    height: usize,
+    radius: u32,
+    thickness: u32,
+    color: Color,
+) {
+    let radius = radius as f32;
+    draw_annulus(
+        buffer,
+        width,
+        height,
+        radius - thickness as f32,
+        radius,
+        color,
+    );
+}
+
+/// Draw a filled disc. A radius of 0 still gives the center pixel(s), so a dot never fully disappears.
+pub fn draw_filled_disc(
+    buffer: &mut [u32],
+    width: usize,
+    height: usize,
+    radius: u32,
+    color: Color,
+) {
+    // The closest pixel centers are 0 away from the buffer center on odd sizes, and sqrt(0.5) away on even sizes.
+    // Padding the radius to 0.75 catches those while staying clear of the next closest pixels.
+    let radius = (radius as f32).max(0.75);
+    draw_annulus(buffer, width, height, 0.0, radius, color);
+}
+
+/// Fill everything between `inner_radius` and `outer_radius`. An `inner_radius` of 0 gives a filled disc.
+pub fn draw_donut(
+    buffer: &mut [u32],
+    width: usize,
+    height: usize,
+    inner_radius: u32,
+    outer_radius: u32,
+    color: Color,
+) {
+    if inner_radius == 0 {
+        draw_filled_disc(buffer, width, height, outer_radius, color);
+    } else {
+        draw_annulus(
+            buffer,
+            width,
+            height,
+            inner_radius as f32,
+            outer_radius as f32,
+            color,
+        );
+    }
+}
+
+/// How far out the midpoint circle algorithm puts the ring at height `v`, for a ring of radius `radius`. Everything is
+/// in half-pixels, so that rings centered between pixels work too. The result has the same parity as `radius`.
+fn midpoint_extent(radius: i64, v: i64) -> i64 {
+    // the algorithm steps inward whenever the midpoint between the current and next pixel is outside the ring
+    let mut u = radius;
+    while u > 0 && (u - 1) * (u - 1) + v * v > radius * radius {
+        u -= 2;
+    }
+    u
+}
+
+/// Draw a crisp hollow ring `thickness` pixels wide with the midpoint circle algorithm. The ring fits in the largest
+/// square centered in the buffer.
+pub fn draw_circle(buffer: &mut [u32], width: usize, height: usize, thickness: u32, color: Color) {
+    let color = color.argb();
+    let diameter = width.min(height);
+    let left = (width - diameter) / 2;
+    let top = (height - diameter) / 2;
+
+    // Work in half-pixels from the center of the square, so pixel centers are an integer distance away no matter
+    // whether the diameter is even or odd. The outermost pixel centers are on the ring.
+    let outer_radius = diameter as i64 - 1;
+    let inner_radius = outer_radius - 2 * (thickness.max(1) as i64 - 1);
+    let mut plot = |u: i64, v: i64| {
+        let x = left + ((outer_radius + u) / 2) as usize;
+        let y = top + ((outer_radius + v) / 2) as usize;
+        buffer[y * width + x] = color;
+    };
+
+    // walk one octant, from the middle of the right edge up to the diagonal, and mirror it into the other seven
+    let mut v = outer_radius % 2;
+    loop {
+        let outer = midpoint_extent(outer_radius, v);
+        if v > outer {
+            break;
+        }
+        // the inner edge's octant ends first, after which the ring is filled in all the way to the diagonal
+        let inner = if inner_radius > 0 {
+            midpoint_extent(inner_radius, v).max(v)
+        } else {
+            v
+        };
+        for u in (inner..=outer).step_by(2) {
+            for (a, b) in [(u, v), (v, u)] {
+                plot(a, b);
+                plot(-a, b);
+                plot(a, -b);
+                plot(-a, -b);
+            }
+        }
+        v += 2;
+    }
+}
+
+#[cfg(test)]
+mod test_circle {
+    use super::*;
+
+    const COLOR: Color = Color::from_argb(0xFFFF0000);
+
+    fn render(width: usize, height: usize, thickness: u32) -> Vec<String> {
+        let mut buffer = vec![0; width * height];
+        draw_circle(&mut buffer, width, height, thickness, COLOR);
+        buffer
+            .chunks(width)
+            .map(|row| {
+                row.iter()
+                    .map(|&pixel| if pixel == COLOR.argb() { '#' } else { '.' })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_odd() {
+        assert_eq!(
+            render(7, 7, 1),
+            ["..###..", ".#...#.", "#.....#", "#.....#", "#.....#", ".#...#.", "..###..",]
+        );
+    }
+
+    #[test]
+    fn test_even() {
+        assert_eq!(
+            render(8, 8, 1),
+            [
+                "..####..", ".#....#.", "#......#", "#......#", "#......#", "#......#", ".#....#.",
+                "..####..",
+            ]
+        );
+    }
+
+    /// thick rings are filled in without any holes between the edges
+    #[test]
+    fn test_thick() {
+        assert_eq!(
+            render(9, 9, 2),
+            [
+                "...###...",
+                ".#######.",
+                ".##...##.",
+                "##.....##",
+                "##.....##",
+                "##.....##",
+                ".##...##.",
+                ".#######.",
+                "...###...",
+            ]
+        );
+    }
+
+    /// a ring thicker than its radius is just a filled disc
+    #[test]
+    fn test_too_thick() {
+        let picture = render(5, 5, 10);
+        assert_eq!(picture, [".###.", "#####", "#####", "#####", ".###."]);
+    }
+
+    /// a non-square buffer gets the biggest circle that fits, centered
+    #[test]
+    fn test_wide() {
+        assert_eq!(render(7, 3, 1), ["...#...", "..#.#..", "...#..."]);
+    }
+
+    /// the circle is always symmetric, whatever its size
+    #[test]
+    fn test_symmetric() {
+        for size in 3..40 {
+            for thickness in 1..4 {
+                let picture = render(size, size, thickness);
+                let flipped: Vec<String> = picture.iter().rev().cloned().collect();
+                assert_eq!(picture, flipped, "size {size} thickness {thickness}");
+                let mirrored: Vec<String> = picture
+                    .iter()
+                    .map(|row| row.chars().rev().collect())
+                    .collect();
+                assert_eq!(picture, mirrored, "size {size} thickness {thickness}");
+                // every edge gets touched
+                assert!(picture[0].contains('#'));
+                assert!(picture.iter().all(|row| row.contains('#')));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_draw {
+    use super::*;
+
+    const COLOR: Color = Color::from_argb(0xFFFF0000);
+    const PIXEL: u32 = COLOR.argb();
+
+    fn blank(size: usize) -> Vec<u32> {
+        vec![0; size * size]
+    }
+
+    /// windows too thin for a crosshair are filled in completely, whatever the crosshair
+    #[test]
+    fn test_too_thin() {
+        for mode in RenderMode::GENERATED {
+            for (width, height) in [(1, 1), (2, 2), (100, 2), (1, 7)] {
+                let mut settings = Settings::default();
+                settings.render_mode = mode;
+                settings.color = COLOR;
+                let mut buffer = vec![0; width * height];
+                render_crosshair(&mut buffer, width, height, &settings);
+                assert!(
+                    buffer.iter().all(|&pixel| pixel == PIXEL),
+                    "{mode:?} at {width}x{height}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_ring_perimeter() {
+        let size = 16;
+        let mut buffer = blank(size);
+        draw_ring(&mut buffer, size, size, 8, 1, COLOR);
+
+        // edges at the middle of each side
+        assert_eq!(buffer[size * 8], PIXEL);
+        assert_eq!(buffer[size * 8 + size - 1], PIXEL);
+        assert_eq!(buffer[7], PIXEL);
+        assert_eq!(buffer[size * (size - 1) + 7], PIXEL);
+
+        // corners and center are outside the ring
+        assert_eq!(buffer[0], 0);
+        assert_eq!(buffer[size * size - 1], 0);
+        assert_eq!(buffer[size * 8 + 8], 0);
+    }
+
+    #[test]
+    fn test_ring_thickness() {
+        let size = 16;
+        let mut buffer = blank(size);
+        draw_ring(&mut buffer, size, size, 8, 3, COLOR);
+        let row = &buffer[size * 8..size * 9];
+        assert_eq!(&row[0..4], &[PIXEL, PIXEL, PIXEL, 0]);
+    }
+
+    /// the full circle-with-dot reticle: ring perimeter and center dot are both set, with a gap between them
+    #[test]
+    fn test_circle_dot() {
+        let size = 20;
+        let mut buffer = blank(size);
+        draw_ring(&mut buffer, size, size, 10, 2, COLOR);
+        draw_filled_disc(&mut buffer, size, size, 2, COLOR);
+        let row = &buffer[size * 10..size * 11];
+        assert_eq!(
+            row,
+            &[
+                PIXEL, PIXEL, 0, 0, 0, 0, 0, 0, PIXEL, PIXEL, PIXEL, PIXEL, 0, 0, 0, 0, 0, 0,
+                PIXEL, PIXEL
+            ]
+        );
+    }
+
+    #[test]
+    fn test_zero_radius_disc_even() {
+        let size = 4;
+        let mut buffer = blank(size);
+        draw_filled_disc(&mut buffer, size, size, 0, COLOR);
+        let set: Vec<usize> = (0..buffer.len()).filter(|&i| buffer[i] == PIXEL).collect();
+        assert_eq!(set, vec![5, 6, 9, 10]);
+    }
+
+    #[test]
+    fn test_zero_radius_disc_odd() {
+        let size = 5;
+        let mut buffer = blank(size);
+        draw_filled_disc(&mut buffer, size, size, 0, COLOR);
+        let set: Vec<usize> = (0..buffer.len()).filter(|&i| buffer[i] == PIXEL).collect();
+        assert_eq!(set, vec![12]);
+    }
+
+    #[test]
+    fn test_cross() {
+        let size = 5;
+        let mut buffer = blank(size);
+        draw_cross(&mut buffer, size, size, 1, COLOR);
+        for i in 0..size {
+            assert_eq!(buffer[size * 2 + i], PIXEL);
+            assert_eq!(buffer[size * i + 2], PIXEL);
+        }
+        assert_eq!(buffer[0], 0);
+    }
+
+    /// the pixels set in a `width`×`height` buffer after drawing a line between two points
+    fn line(width: usize, height: usize, from: (usize, usize), to: (usize, usize)) -> Vec<usize> {
+        let mut buffer = vec![0; width * height];
+        draw_line(&mut buffer, width, from.0, from.1, to.0, to.1, COLOR);
+        (0..buffer.len()).filter(|&i| buffer[i] == PIXEL).collect()
+    }
+
+    #[test]
+    fn test_line() {
+        // horizontal, vertical, and diagonal lines hit every pixel between the ends
+        assert_eq!(line(5, 5, (1, 2), (3, 2)), vec![11, 12, 13]);
+        assert_eq!(line(5, 5, (4, 0), (4, 2)), vec![4, 9, 14]);
+        assert_eq!(line(5, 5, (4, 0), (0, 4)), vec![4, 8, 12, 16, 20]);
+        // shallow lines take one pixel per column
+        assert_eq!(line(5, 3, (0, 0), (4, 2)), vec![0, 6, 7, 13, 14]);
+        assert_eq!(line(1, 1, (0, 0), (0, 0)), vec![0]);
+    }
+
+    #[test]
+    fn test_diagonal_cross() {
+        let size = 5;
+        let mut buffer = blank(size);
+        draw_diagonal_cross(&mut buffer, size, size, COLOR);
+        let set: Vec<usize> = (0..buffer.len()).filter(|&i| buffer[i] == PIXEL).collect();
+        assert_eq!(set, vec![0, 4, 6, 8, 12, 16, 18, 20, 24]);
+    }
+
+    #[test]
+    fn test_donut() {
+        let size = 17;
+        let center = size / 2;
+        let mut buffer = blank(size);
+        draw_donut(&mut buffer, size, size, 4, 8, COLOR);
+
+        // inside the annulus
+        assert_eq!(buffer[size * center + center + 6], PIXEL);
+        assert_eq!(buffer[size * center], PIXEL);
+        // in the hole
+        assert_eq!(buffer[size * center + center], 0);
+        assert_eq!(buffer[size * center + center + 3], 0);
+        // outside the donut
+        assert_eq!(buffer[0], 0);
+    }
+
+    #[test]
+    fn test_donut_without_hole() {
+        let size = 17;
+        let center = size / 2;
+        let mut buffer = blank(size);
+        draw_donut(&mut buffer, size, size, 0, 8, COLOR);
+        assert_eq!(buffer[size * center + center], PIXEL);
+        assert_eq!(buffer[size * center + center + 3], PIXEL);
+    }
+}
+
+#[cfg(test)]
+mod test_crosshair_thickness {
+    use super::*;
+
+    const COLOR: Color = Color::from_argb(0xFFFF0000);
+    const OUTLINE: Color = Color::from_argb(0xFF000000);
+
+    /// draw a crosshair into a blank buffer and turn it into a picture, one string per row
+    fn render(size: usize, fill_thickness: u32, outline_thickness: u32) -> Vec<String> {
+        let mut buffer = vec![0; size * size];
+        draw_crosshair(
+            &mut buffer,
+            size,
+            size,
+            fill_thickness,
+            outline_thickness,
+            0.0,
+            COLOR,
+            OUTLINE,
+        );
+        buffer
+            .chunks(size)
+            .map(|row| {
+                row.iter()
+                    .map(|&pixel| match pixel {
+                        pixel if pixel == COLOR.argb() => '#',
+                        pixel if pixel == OUTLINE.argb() => 'o',
+                        _ => '.',
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// a thickness of 1 must look exactly like the crosshair from before thickness was configurable
+    #[test]
+    fn test_default_thickness() {
+        assert_eq!(
+            render(5, 1, 0),
+            ["..#..", "..#..", "#####", "..#..", "..#.."]
+        );
+        assert_eq!(render(4, 1, 0), [".##.", "####", "####", ".##."]);
+    }
+
+    #[test]
+    fn test_thickness_bumped_for_centering() {
+        assert_eq!(
+            render(7, 2, 0),
+            ["..###..", "..###..", "#######", "#######", "#######", "..###..", "..###.."]
+        );
+    }
+
+    #[test]
+    fn test_outline() {
+        assert_eq!(
+            render(7, 1, 1),
+            ["..o#o..", "..o#o..", "ooo#ooo", "#######", "ooo#ooo", "..o#o..", "..o#o.."]
+        );
+    }
+
+    #[test]
+    fn test_thick_outline() {
+        assert_eq!(
+            render(9, 3, 2),
+            [
+                ".oo###oo.",
+                "ooo###ooo",
+                "ooo###ooo",
+                "#########",
+                "#########",
+                "#########",
+                "ooo###ooo",
+                "ooo###ooo",
+                ".oo###oo.",
+            ]
+        );
+    }
+
+    /// Thicknesses 1 through 3 at even and odd sizes. Each line is `thickness` wide, plus one when the parity doesn't
+    /// match the window, and always has the same amount of space on either side.
+    #[test]
+    fn test_thickness_parity() {
+        for (size, thickness, expected_width) in [
+            (6, 1, 2),
+            (6, 2, 2),
+            (6, 3, 4),
+            (7, 1, 1),
+            (7, 2, 3),
+            (7, 3, 3),
+        ] {
+            let picture = render(size, thickness, 0);
+            let vertical = &picture[0];
+            let leading = vertical.find('#').unwrap();
+            let trailing = size - vertical.rfind('#').unwrap() - 1;
+            assert_eq!(
+                vertical.matches('#').count(),
+                expected_width,
+                "size {size} thickness {thickness}"
+            );
+            assert_eq!(leading, trailing, "size {size} thickness {thickness}");
+
+            let horizontal_rows = picture.iter().filter(|row| !row.contains('.')).count();
+            assert_eq!(
+                horizontal_rows, expected_width,
+                "size {size} thickness {thickness}"
+            );
+        }
+    }
+
+    /// lines and outlines too thick for the window get clipped to it instead of panicking
+    #[test]
+    fn test_clamped_to_window() {
+        assert_eq!(render(3, 1, 8), ["o#o", "###", "o#o"]);
+        assert_eq!(render(3, 8, 1), ["###", "###", "###"]);
+    }
+}
+
+#[cfg(test)]
+mod test_t_shape {
+    use super::*;
+
+    const COLOR: Color = Color::from_argb(0xFFFF0000);
+    const OUTLINE: Color = Color::from_argb(0xFF000000);
+
+    fn render(size: usize, thickness: u32, outline: u32) -> Vec<String> {
+        let mut buffer = vec![0; size * size];
+        draw_crosshair(
+            &mut buffer,
+            size,
+            size,
+            thickness,
+            outline,
+            0.0,
+            COLOR,
+            OUTLINE,
+        );
+        clear_top_arm(
+            &mut buffer,
+            size,
+            size,
+            thickness,
+            outline,
+            COLOR,
+            OUTLINE,
+            Color::TRANSPARENT,
+        );
+        buffer
+            .chunks(size)
+            .map(|row| {
+                row.iter()
+                    .map(|&pixel| match pixel {
+                        pixel if pixel == COLOR.argb() => '#',
+                        pixel if pixel == OUTLINE.argb() => 'o',
+                        _ => '.',
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_odd() {
+        assert_eq!(
+            render(5, 1, 0),
+            [".....", ".....", "#####", "..#..", "..#.."]
+        );
+    }
+
+    #[test]
+    fn test_even() {
+        assert_eq!(render(4, 1, 0), ["....", "####", "####", ".##."]);
+    }
+
+    /// the outline around the horizontal line stays
+    #[test]
+    fn test_outline() {
+        assert_eq!(
+            render(7, 1, 1),
+            [".......", ".......", "ooooooo", "#######", "ooo#ooo", "..o#o..", "..o#o..",]
+        );
+    }
+
+    /// the whole top half of the vertical line is see-through, at any size
+    #[test]
+    fn test_top_half_transparent() {
+        for size in 3..20 {
+            let mut buffer = vec![0; size * size];
+            draw_crosshair(&mut buffer, size, size, 1, 0, 0.0, COLOR, OUTLINE);
+            clear_top_arm(
+                &mut buffer,
+                size,
+                size,
+                1,
+                0,
+                COLOR,
+                OUTLINE,
+                Color::TRANSPARENT,
+            );
+            let line_height = centered_line_width(1, size);
+            let top = (size - line_height) / 2;
+            for row in 0..top {
+                assert!(
+                    buffer[row * size..(row + 1) * size]
+                        .iter()
+                        .all(|&pixel| pixel == 0),
+                    "size {size} row {row}"
+                );
+            }
+            // the stem still reaches the bottom
+            assert_eq!(buffer[(size - 1) * size + size / 2], COLOR.argb());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_taper {
+    use super::*;
+
+    const COLOR: Color = Color::from_argb(0xFFFF0000);
+    const OUTLINE: Color = Color::from_argb(0xFF000000);
+
+    fn render(size: usize, thickness: u32, outline: u32, taper: f32) -> Vec<String> {
+        let mut buffer = vec![0; size * size];
+        draw_crosshair(
+            &mut buffer,
+            size,
+            size,
+            thickness,
+            outline,
+            taper,
+            COLOR,
+            OUTLINE,
+        );
+        buffer
+            .chunks(size)
+            .map(|row| {
+                row.iter()
+                    .map(|&pixel| match pixel {
+                        pixel if pixel == COLOR.argb() => '#',
+                        pixel if pixel == OUTLINE.argb() => 'o',
+                        _ => '.',
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// how many pixels of fill are in each row of the top arm, going from the tip toward the center. Only rows the
+    /// horizontal line can't reach are counted.
+    fn top_arm_widths(picture: &[String], thickness: usize) -> Vec<usize> {
+        picture[..(picture.len() - thickness) / 2]
+            .iter()
+            .map(|row| row.chars().filter(|&pixel| pixel == '#').count())
+            .collect()
+    }
+
+    #[test]
+    fn test_thinner_toward_tips() {
+        assert_eq!(
+            render(11, 5, 0, 1.0),
+            [
+                ".....#.....",
+                ".....#.....",
+                "....###....",
+                "....###....",
+                "..#######..",
+                "###########",
+                "..#######..",
+                "....###....",
+                "....###....",
+                ".....#.....",
+                ".....#.....",
+            ]
+        );
+
+        let widths = top_arm_widths(&render(21, 9, 0, 1.0), 9);
+        assert_eq!(widths, [1, 1, 3, 3, 5, 5]);
+    }
+
+    #[test]
+    fn test_thinner_toward_center() {
+        assert_eq!(
+            render(11, 5, 0, -1.0),
+            [
+                "...#####...",
+                "...#####...",
+                "....###....",
+                "##..###..##",
+                "####.#.####",
+                "###########",
+                "####.#.####",
+                "##..###..##",
+                "....###....",
+                "...#####...",
+                "...#####...",
+            ]
+        );
+
+        let widths = top_arm_widths(&render(21, 9, 0, -1.0), 9);
+        assert_eq!(widths, [9, 9, 7, 7, 5, 5]);
+    }
+
+    #[test]
+    fn test_partial_taper() {
+        let widths = top_arm_widths(&render(21, 9, 0, 0.5), 9);
+        assert_eq!(widths, [5, 5, 5, 7, 7, 7]);
+    }
+
+    /// the outline follows the taper instead of staying uniform
+    #[test]
+    fn test_outline() {
+        assert_eq!(
+            render(11, 3, 1, 1.0),
+            [
+                "....o#o....",
+                "....o#o....",
+                "....o#o....",
+                "...o###o...",
+                "ooo#####ooo",
+                "###########",
+                "ooo#####ooo",
+                "...o###o...",
+                "....o#o....",
+                "....o#o....",
+                "....o#o....",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_thickness_at_ends() {
+        assert_eq!(tapered_thickness(8, 0.5, 0, 10), 8);
+        assert_eq!(tapered_thickness(8, 0.5, 10, 10), 4);
+        assert_eq!(tapered_thickness(8, -0.5, 0, 10), 4);
+        assert_eq!(tapered_thickness(8, -0.5, 10, 10), 8);
+        assert_eq!(tapered_thickness(8, 1.0, 10, 10), 1);
+    }
+}
+
+#[cfg(test)]
+mod test_shadow {
+    use super::*;
+
+    const COLOR: Color = Color::from_argb(0xFFFF0000);
+    const SHADOW: Color = Color::from_argb(0x80000000);
+
+    /// draw a 5x5 crosshair with a shadow and turn it into a picture, one string per row
+    fn render(offset: (i32, i32)) -> Vec<String> {
+        let size = 5;
+        let mut buffer = vec![0; size * size];
+        draw_cross(&mut buffer, size, size, 1, COLOR);
+        draw_shadow(&mut buffer, size, size, offset, SHADOW, Color::TRANSPARENT);
+        buffer
+            .chunks(size)
+            .map(|row| {
+                row.iter()
+                    .map(|&pixel| match pixel {
+                        pixel if pixel == COLOR.argb() => '#',
+                        pixel if pixel == SHADOW.argb() => 's',
+                        _ => '.',
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_shadow_under_crosshair() {
+        assert_eq!(
+            render((1, 1)),
+            ["..#..", "..#s.", "#####", ".s#ss", "..#s."]
+        );
+        assert_eq!(
+            render((-1, 0)),
+            [".s#..", ".s#..", "#####", ".s#..", ".s#.."]
+        );
+    }
+
+    /// shadows shifted past the edge are clipped instead of panicking
+    #[test]
+    fn test_shadow_out_of_bounds() {
+        let plain = ["..#..", "..#..", "#####", "..#..", "..#.."];
+        assert_eq!(render((5, 0)), plain);
+        assert_eq!(render((0, -5)), plain);
+        assert_eq!(render((i32::MIN, i32::MAX)), plain);
+    }
+}
+
+#[cfg(test)]
+mod test_ghost {
+    use super::*;
+    use crate::private::util::ghost::Ghost;
+
+    const COLOR: Color = Color::from_argb(0xFFFF0000);
+    const GHOST: Color = Color::from_argb(0xFF00FF00);
+
+    #[test]
+    fn test_both_crosshairs() {
+        let mut settings = Settings::default();
+        settings.set_color(COLOR);
+        settings.persisted.window_height = 5;
+        settings.persisted.window_width = 5;
+        settings.persisted.ghost = Some(Ghost {
+            dx: 0,
+            dy: 6,
+            size: 3,
+            color: Some(GHOST),
+        });
+        let size = settings.content_size();
+        assert_eq!(size.width, 5);
+        assert_eq!(size.height, 15);
+
+        let (width, height) = (size.width as usize, size.height as usize);
+        let mut buffer = vec![0; width * height];
+        render_content(&mut buffer, width, height, &settings);
+        let picture: Vec<String> = buffer
+            .chunks(width)
+            .map(|row| {
+                row.iter()
+                    .map(|&pixel| match pixel {
+                        pixel if pixel == COLOR.argb() => '#',
+                        pixel if pixel == GHOST.argb() => 'g',
+                        _ => '.',
+                    })
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            picture,
+            [
+                ".....", ".....", ".....", ".....", ".....", "..#..", "..#..", "#####", "..#..",
+                "..#..", ".....", ".....", "..g..", ".ggg.", "..g..",
+            ]
+        );
+    }
+
+    /// the ghost hides with the rest of the crosshair while picking a color
+    #[test]
+    fn test_not_over_color_picker() {
+        let mut settings = Settings::default();
+        settings.persisted.ghost = Some(Ghost {
+            dx: 0,
+            dy: 100,
+            size: 3,
+            color: None,
+        });
+        settings.set_pick_color(true);
+        assert!(settings.ghost().is_none());
+        assert_eq!(settings.content_size(), settings.crosshair_size());
+    }
+}
+
+#[cfg(test)]
+mod test_center_gap {
+    use super::*;
+
+    const COLOR: Color = Color::from_argb(0xFFFF0000);
+
+    /// draw a gapped crosshair into a blank buffer and turn it into a picture, one string per row
+    fn render(size: usize, thickness: u32, gap: u32, dots: bool) -> Vec<String> {
+        let mut buffer = vec![0; size * size];
+        draw_crosshair(&mut buffer, size, size, thickness, 0, 0.0, COLOR, COLOR);
+        draw_center_gap(&mut buffer, size, size, gap, Color::TRANSPARENT);
+        if dots {
+            draw_gap_dots(&mut buffer, size, size, gap, thickness, COLOR);
+        }
+        buffer
+            .chunks(size)
+            .map(|row| {
+                row.iter()
+                    .map(|&pixel| if pixel == COLOR.argb() { '#' } else { '.' })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_gap() {
+        assert_eq!(
+            render(9, 1, 2, false),
+            [
+                "....#....",
+                "....#....",
+                ".........",
+                ".........",
+                "##.....##",
+                ".........",
+                ".........",
+                "....#....",
+                "....#....",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_gap_dots() {
+        assert_eq!(
+            render(15, 1, 2, true),
+            [
+                ".......#.......",
+                ".......#.......",
+                "......###......",
+                "......###......",
+                "......###......",
+                "...............",
+                "..###.....###..",
+                "#####.....#####",
+                "..###.....###..",
+                "...............",
+                "......###......",
+                "......###......",
+                "......###......",
+                ".......#.......",
+                ".......#.......",
+            ]
+        );
+    }
+
+    /// even sizes have two center pixels, and the gap is measured from both of them
+    #[test]
+    fn test_gap_dots_even() {
+        assert_eq!(
+            render(14, 1, 1, true),
+            [
+                "......##......",
+                ".....####.....",
+                ".....####.....",
+                ".....####.....",
+                ".....####.....",
+                ".####....####.",
+                "#####....#####",
+                "#####....#####",
+                ".####....####.",
+                ".....####.....",
+                ".....####.....",
+                ".....####.....",
+                ".....####.....",
+                "......##......",
+            ]
+        );
+    }
+
+    /// no gap means no dots, and dots that don't fit get clipped
+    #[test]
+    fn test_gap_dots_edge_cases() {
+        assert_eq!(render(5, 1, 0, true), render(5, 1, 0, false));
+        assert_eq!(
+            render(5, 1, 1, true),
+            [".###.", "#...#", "#...#", "#...#", ".###."]
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_center_marker {
+    use super::*;
+
+    const COLOR: Color = Color::from_argb(0xFFFF0000);
+    const PIXEL: u32 = COLOR.argb();
+
+    /// draw a marker into a blank buffer and turn it into a picture, one string per row
+    fn render(size: usize, marker: CenterMarker) -> Vec<String> {
+        let mut buffer = vec![0; size * size];
+        draw_center_marker(&mut buffer, size, size, marker, COLOR, Color::TRANSPARENT);
+        picture(&buffer, size)
+    }
+
+    fn picture(buffer: &[u32], width: usize) -> Vec<String> {
+        buffer
+            .chunks(width)
+            .map(|row| {
+                row.iter()
+                    .map(|&pixel| if pixel == PIXEL { '#' } else { '.' })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_none() {
+        assert_eq!(
+            render(5, CenterMarker::None),
+            [".....", ".....", ".....", ".....", "....."]
+        );
+    }
+
+    #[test]
+    fn test_dot() {
+        assert_eq!(
+            render(5, CenterMarker::Dot),
+            [".....", ".....", "..#..", ".....", "....."]
+        );
+        assert_eq!(
+            render(4, CenterMarker::Dot),
+            ["....", ".##.", ".##.", "...."]
+        );
+    }
+
+    #[test]
+    fn test_hollow_dot() {
+        assert_eq!(
+            render(5, CenterMarker::HollowDot),
+            [".....", ".###.", ".#.#.", ".###.", "....."]
+        );
+        assert_eq!(
+            render(6, CenterMarker::HollowDot),
+            ["......", ".####.", ".#..#.", ".#..#.", ".####.", "......"]
+        );
+    }
+
+    #[test]
+    fn test_cross_pixel() {
+        assert_eq!(
+            render(5, CenterMarker::CrossPixel),
+            [".....", "..#..", ".###.", "..#..", "....."]
+        );
+        assert_eq!(
+            render(6, CenterMarker::CrossPixel),
+            ["......", "..##..", ".####.", ".####.", "..##..", "......"]
+        );
+    }
+
+    /// markers that would poke out of a tiny buffer get clipped instead of panicking
+    #[test]
+    fn test_clipped() {
+        assert_eq!(render(1, CenterMarker::HollowDot), ["."]);
+        assert_eq!(render(2, CenterMarker::CrossPixel), ["##", "##"]);
+    }
+
+    /// a hollow center on the `+` crosshair leaves a single see-through pixel where the arms meet
+    #[test]
+    fn test_hollow_center_on_crosshair() {
+        let mut settings = Settings::default();
+        settings.persisted.window_width = 7;
+        settings.persisted.window_height = 7;
+        settings.persisted.center_marker = CenterMarker::HollowDot;
+        settings.color = COLOR;
+        let mut buffer = vec![0; 7 * 7];
+        render_content(&mut buffer, 7, 7, &settings);
+        assert_eq!(
+            picture(&buffer, 7),
+            ["...#...", "...#...", "..###..", "###.###", "..###..", "...#...", "...#..."]
+        );
+    }
+
+    /// with a transparency key the background, including the hole in a hollow marker, is the opaque key color
+    #[test]
+    fn test_transparency_key() {
+        const KEY: Color = Color::from_argb(0x00FF00FF);
+        let mut settings = Settings::default();
+        settings.persisted.window_width = 7;
+        settings.persisted.window_height = 7;
+        settings.persisted.center_marker = CenterMarker::HollowDot;
+        settings.persisted.transparency_key = Some(KEY);
+        settings.color = COLOR;
+        let mut buffer = vec![0; 7 * 7];
+        render_content(&mut buffer, 7, 7, &settings);
+        assert_eq!(buffer[0], KEY.opaque().argb());
+        assert_eq!(buffer[7 * 3 + 3], KEY.opaque().argb());
+        assert!(buffer
+            .iter()
+            .all(|&pixel| pixel == PIXEL || pixel == KEY.opaque().argb()));
+    }
+}
+
+#[cfg(test)]
+mod test_opacity {
+    use super::*;
+    use crate::private::util::image::{fade, multiply_color_channels_u8};
+
+    const HALF: u8 = 127;
+
+    /// render everything and dim it, like the window does
+    fn render(settings: &Settings) -> Vec<u32> {
+        let size = settings.content_size();
+        let mut buffer = vec![0; size.width as usize * size.height as usize];
+        render_content(
+            &mut buffer,
+            size.width as usize,
+            size.height as usize,
+            settings,
+        );
+        apply_opacity(&mut buffer, settings);
+        buffer
+    }
+
+    fn settings(opacity: u8, premultiply_alpha: bool) -> Settings {
+        let mut settings = Settings::default();
+        settings.persisted.opacity = opacity;
+        settings.persisted.premultiply_alpha = premultiply_alpha;
+        // recompute the buffer color for the new premultiply setting
+        settings.set_color(settings.straight_color());
+        settings
+    }
+
+    fn alpha(pixel: u32) -> u8 {
+        pixel.to_le_bytes()[3]
+    }
+
+    #[test]
+    fn test_generated_crosshair() {
+        for premultiply_alpha in [false, true] {
+            let opaque = render(&settings(u8::MAX, premultiply_alpha));
+            let dimmed = render(&settings(HALF, premultiply_alpha));
+            let center = opaque.len() / 2 + 8;
+            let original = Color::from_argb(opaque[center]);
+            assert_eq!(alpha(original.argb()), 0xB2);
+            assert_eq!(alpha(dimmed[center]), 89);
+            if premultiply_alpha {
+                // still premultiplied, so the color channels dim along with the alpha
+                assert_eq!(dimmed[center], fade(original.argb(), HALF, true));
+                assert_eq!(dimmed[center].to_le_bytes()[2], 89);
+            } else {
+                assert_eq!(dimmed[center], 0x59FF0000);
+            }
+        }
+    }
+
+    #[test]
+    fn test_image() {
+        for premultiply_alpha in [false, true] {
+            let mut opaque = settings(u8::MAX, premultiply_alpha);
+            opaque
+                .load_image("tests/resources/test.png".into())
+                .unwrap();
+            let mut dimmed = settings(HALF, premultiply_alpha);
+            dimmed
+                .load_image("tests/resources/test.png".into())
+                .unwrap();
+            for (&original, &pixel) in render(&opaque).iter().zip(render(&dimmed).iter()) {
+                assert_eq!(
+                    alpha(pixel),
+                    multiply_color_channels_u8(alpha(original), HALF)
+                );
+            }
+        }
+    }
+
+    /// dimming would stop the key color from matching
+    #[test]
+    fn test_transparency_key() {
+        let mut settings = settings(HALF, false);
+        settings.persisted.transparency_key = Some(Color::from_argb(0xFF00FF00));
+        let mut buffer = vec![0xFF00FF00, 0xB2FF0000];
+        apply_opacity(&mut buffer, &settings);
+        assert_eq!(buffer, [0xFF00FF00, 0xB2FF0000]);
+    }
+}
+
+#[cfg(test)]
+mod test_render_window {
+    use super::*;
+
+    use std::time::Instant;
+
+    fn settings(render_mode: RenderMode, size: u32) -> Settings {
+        let mut settings = Settings::default();
+        settings.render_mode = render_mode;
+        settings.persisted.window_width = size;
+        settings.persisted.window_height = size;
+        settings
+    }
+
+    fn render_whole_window(settings: &Settings, picker_cursor: (usize, usize)) -> Vec<u32> {
+        let size = settings.size();
+        let mut buffer = vec![0; size.width as usize * size.height as usize];
+        render_window(
+            &mut buffer,
+            size.width as usize,
+            size.height as usize,
+            settings,
+            None,
+            picker_cursor,
+        );
+        buffer
+    }
+
+    fn render_just_content(settings: &Settings) -> Vec<u32> {
+        let size = settings.content_size();
+        let mut buffer = vec![0; size.width as usize * size.height as usize];
+        render_content(
+            &mut buffer,
+            size.width as usize,
+            size.height as usize,
+            settings,
+        );
+        buffer
+    }
+
+    /// with nothing else going on, the window is exactly the content
+    #[test]
+    fn test_matches_content() {
+        for render_mode in RenderMode::GENERATED {
+            for size in [16, 33, 64] {
+                let mut settings = settings(render_mode, size);
+                settings.persisted.ring_radius = size / 2;
+                assert_eq!(settings.size(), settings.content_size());
+                assert_eq!(
+                    render_whole_window(&settings, (0, 0)),
+                    render_just_content(&settings),
+                    "{render_mode:?} at {size}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_opacity() {
+        let mut settings = settings(RenderMode::Crosshair, 16);
+        settings.persisted.opacity = 127;
+        let mut expected = render_just_content(&settings);
+        apply_opacity(&mut expected, &settings);
+        assert_eq!(render_whole_window(&settings, (0, 0)), expected);
+    }
+
+    /// the content lands in the middle of the padded window, with the text below it
+    #[test]
+    fn test_osd() {
+        const MESSAGE: &str = "SCALE 100";
+        let mut settings = settings(RenderMode::Crosshair, 16);
+        settings.persisted.osd = true;
+        settings.osd.show(MESSAGE, Instant::now());
+        let window_size = settings.size();
+        let content_size = settings.content_size();
+        assert!(window_size.width > content_size.width);
+
+        let buffer = render_whole_window(&settings, (0, 0));
+        let content = render_just_content(&settings);
+        let (x, y) = osd::content_origin(content_size, osd::text_size(MESSAGE));
+        let window_width = window_size.width as usize;
+        let content_width = content_size.width as usize;
+        for (row, content_row) in content.chunks(content_width).enumerate() {
+            let start = (y + row) * window_width + x;
+            assert_eq!(&buffer[start..start + content_width], content_row);
+        }
+        // the text is in there somewhere below the content
+        let text_color = settings.osd_color().argb();
+        let content_bottom = y + content_size.height as usize;
+        assert!(buffer[content_bottom * window_width..].contains(&text_color));
+    }
+
+    #[test]
+    fn test_picker_cursor() {
+        let mut settings = settings(RenderMode::Crosshair, 16);
+        settings.set_pick_color(true);
+        let mut expected = render_just_content(&settings);
+        super::super::draw_picker_cursor(&mut expected, (40, 50));
+        assert_eq!(render_whole_window(&settings, (40, 50)), expected);
+        assert_ne!(expected, render_just_content(&settings));
+    }
+}
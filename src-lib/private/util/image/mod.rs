@@ -4,14 +4,26 @@
 
 //! Image processing and color utilities
 
+use std::ffi::OsStr;
 use std::fs::File;
+use std::io::{BufReader, BufWriter};
 use std::path::Path;
 use std::{io, mem};
 
+use jpeg_decoder::PixelFormat;
 use png::ColorType;
+use winit::dpi::PhysicalSize;
 
+use crate::private::settings::CenterRounding;
 use crate::private::util::numeric::{DivCeil, DivFloor};
 
+pub use color::Color;
+
+pub mod animation;
+mod bmp;
+mod color;
+pub mod draw;
+
 #[cfg(any(test, feature = "benchmark"))]
 pub mod precise;
 
@@ -28,12 +40,129 @@ pub struct Image {
     pub data: Vec<u32>,
 }
 
+impl Image {
+    /// Scale to a new size with nearest-neighbor sampling, which keeps the hard edges of pixel-art reticles crisp
+    pub fn resize(&self, width: u32, height: u32) -> Image {
+        let mut data = vec![0; width as usize * height as usize];
+        self.resize_into(&mut data, width as usize, height as usize);
+        Image {
+            width,
+            height,
+            data,
+        }
+    }
+
+    /// Like [`Image::resize`], but into an existing buffer of exactly `width` * `height` pixels
+    pub fn resize_into(&self, buffer: &mut [u32], width: usize, height: usize) {
+        debug_assert_eq!(
+            buffer.len(),
+            width * height,
+            "resize_into() passed buffer of wrong size"
+        );
+        if (width, height) == (self.width as usize, self.height as usize) {
+            buffer.copy_from_slice(&self.data);
+            return;
+        }
+
+        // sample the source pixel under the center of each destination pixel
+        let sample = |destination: usize, destination_length: usize, source_length: u32| {
+            ((destination as u64 * 2 + 1) * source_length as u64 / (destination_length as u64 * 2))
+                as usize
+        };
+        for (y, row) in buffer.chunks_exact_mut(width).enumerate() {
+            let source_row = sample(y, height, self.height) * self.width as usize;
+            for (x, pixel) in row.iter_mut().enumerate() {
+                *pixel = self.data[source_row + sample(x, width, self.width)];
+            }
+        }
+    }
+
+    /// Save as an 8-bit RGBA PNG. Pixels are written as-is, so premultiplied images stay premultiplied.
+    pub fn save_png<T>(&self, path: T) -> io::Result<()>
+    where
+        T: AsRef<Path>,
+    {
+        save_png(path, self.width, self.height, &self.data, false, false)
+    }
+}
+
+/// Save an ARGB buffer as an 8-bit RGBA PNG. If `premultiply` is set the buffer is premultiplied, like the window
+/// buffer can be, and gets converted back to straight alpha first.
+pub fn save_png<T>(
+    path: T,
+    width: u32,
+    height: u32,
+    buffer: &[u32],
+    premultiply: bool,
+    gamma_correct: bool,
+) -> io::Result<()>
+where
+    T: AsRef<Path>,
+{
+    debug_assert_eq!(
+        buffer.len(),
+        width as usize * height as usize,
+        "save_png() passed buffer of wrong size"
+    );
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(file, width, height);
+    encoder.set_color(ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    let data: Vec<u8> = buffer
+        .iter()
+        .flat_map(|&pixel| {
+            Color::from_argb(unpremultiply_alpha(pixel, premultiply, gamma_correct)).to_rgba_bytes()
+        })
+        .collect();
+    writer.write_image_data(&data)?;
+    Ok(())
+}
+
+/// The size to shrink an image to so that it fits within `fraction` of the monitor, keeping its aspect ratio. Returns
+/// `None` if the image already fits, or the monitor size isn't known yet.
+pub fn fit_size(
+    image: PhysicalSize<u32>,
+    monitor: PhysicalSize<u32>,
+    fraction: f32,
+) -> Option<PhysicalSize<u32>> {
+    if monitor.width == 0 || monitor.height == 0 || image.width == 0 || image.height == 0 {
+        return None;
+    }
+    let max_width = (monitor.width as f64 * fraction as f64).floor().max(1.0) as u32;
+    let max_height = (monitor.height as f64 * fraction as f64).floor().max(1.0) as u32;
+    if image.width <= max_width && image.height <= max_height {
+        return None;
+    }
+
+    let scale =
+        (max_width as f64 / image.width as f64).min(max_height as f64 / image.height as f64);
+    let scaled = |length: u32, max: u32| ((length as f64 * scale).round() as u32).clamp(1, max);
+    Some(PhysicalSize::new(
+        scaled(image.width, max_width),
+        scaled(image.height, max_height),
+    ))
+}
+
 const COLOR_PICKER_NUM_SECTIONS: u8 = 6;
 /// floor(256/6)
 const COLOR_PICKER_SECTION_WIDTH: usize = 42;
 /// side-length of the color picker box
 pub const COLOR_PICKER_SIZE: usize =
     COLOR_PICKER_SECTION_WIDTH * (COLOR_PICKER_NUM_SECTIONS as usize);
+// picker coordinates get squeezed into a u8
+const _: () = assert!(COLOR_PICKER_SIZE <= 256);
+
+/// The color picker on its own, e.g. for checking the hue and value mapping by eye
+pub fn color_picker_image() -> Image {
+    let mut data = vec![0; COLOR_PICKER_SIZE * COLOR_PICKER_SIZE];
+    draw_color_picker(&mut data);
+    Image {
+        width: COLOR_PICKER_SIZE as u32,
+        height: COLOR_PICKER_SIZE as u32,
+        data,
+    }
+}
 
 #[inline(always)]
 pub fn draw_color_picker(buffer: &mut [u32]) {
@@ -83,12 +212,58 @@ pub fn draw_color_picker(buffer: &mut [u32]) {
     }
 }
 
-/// calculate an ARGB color from picked coordinates from the color picker
+/// Wash out an already drawn color picker to match `saturation`, where 255 leaves it alone
+pub fn desaturate_color_picker(buffer: &mut [u32], saturation: u8) {
+    if saturation == u8::MAX {
+        return;
+    }
+    for pixel in buffer {
+        *pixel = desaturate(*pixel, saturation);
+    }
+}
+
+/// Pull each channel toward the brightest one. Every color the picker draws is fully saturated, so this gets the
+/// same result as converting it with a lower saturation in the first place.
+fn desaturate(argb: u32, saturation: u8) -> u32 {
+    let [b, g, r, a] = argb.to_le_bytes();
+    let value = r.max(g).max(b);
+    let desaturate = |channel: u8| value - multiply_color_channels_u8(value - channel, saturation);
+    u32::from_le_bytes([desaturate(b), desaturate(g), desaturate(r), a])
+}
+
+/// calculate a color from picked coordinates from the color picker. Coordinates past the edge of the picker are
+/// treated as being on the edge.
 /// this color does NOT have premultiplied alpha
-pub fn hue_alpha_color_from_coordinates(x: usize, y: usize, width: usize, height: usize) -> u32 {
-    debug_assert_eq!(width, COLOR_PICKER_SIZE);
-    debug_assert_eq!(height, COLOR_PICKER_SIZE);
-    x_y_to_argb_252(x as u8, y as u8)
+pub fn hue_alpha_color_from_coordinates(x: usize, y: usize, saturation: u8) -> Color {
+    let max = COLOR_PICKER_SIZE - 1;
+    let argb = picker_coordinates_to_argb(x.min(max) as u8, y.min(max) as u8);
+    Color::from_argb(desaturate(argb, saturation))
+}
+
+/// Move the color picker's keyboard cursor by `(dx, dy)` pixels, stopping at the edges of the picker
+pub fn move_picker_cursor(cursor: (usize, usize), dx: i32, dy: i32) -> (usize, usize) {
+    let max = COLOR_PICKER_SIZE as i64 - 1;
+    let (x, y) = cursor;
+    (
+        (x as i64 + dx as i64).clamp(0, max) as usize,
+        (y as i64 + dy as i64).clamp(0, max) as usize,
+    )
+}
+
+/// Draw the keyboard cursor on top of the color picker: a small `+` around the selected pixel, which is left alone
+/// so you can still see the color. Each cursor pixel inverts whatever's under it so it shows up on every hue.
+pub fn draw_picker_cursor(buffer: &mut [u32], cursor: (usize, usize)) {
+    const ARM_LENGTH: i64 = 3;
+    let size = COLOR_PICKER_SIZE as i64;
+    let (x, y) = (cursor.0 as i64, cursor.1 as i64);
+    for offset in (-ARM_LENGTH..=ARM_LENGTH).filter(|&offset| offset != 0) {
+        for (x, y) in [(x + offset, y), (x, y + offset)] {
+            if (0..size).contains(&x) && (0..size).contains(&y) {
+                let pixel = &mut buffer[(y * size + x) as usize];
+                *pixel = (*pixel ^ 0x00FFFFFF) | 0xFF000000;
+            }
+        }
+    }
 }
 
 /// see https://en.wikipedia.org/wiki/HSL_and_HSV#Color_conversion_formulae
@@ -129,6 +304,11 @@ pub fn hue_value_to_argb(hue: u8, value: u8) -> u32 {
     u32::from_le_bytes([b, g, r, MAX_COLOR])
 }
 
+/// the full HSV -> RGB conversion, done with integer math. See [`precise::hsv_to_argb`] for a slower, exact version.
+pub fn hsv_to_argb(hue: u8, saturation: u8, value: u8) -> u32 {
+    desaturate(hue_value_to_argb(hue, value), saturation)
+}
+
 /// this is a HSV -> RGB conversion, except S and V are always set to 100%, which simplifies things
 pub fn hue_alpha_to_argb(hue: u8, alpha: u8) -> u32 {
     const MAX_COLOR: u8 = 255;
@@ -154,8 +334,9 @@ pub fn hue_alpha_to_argb(hue: u8, alpha: u8) -> u32 {
     u32::from_le_bytes([b, g, r, alpha])
 }
 
-/// Given color picker coordinates, get a crosshair color
-fn x_y_to_argb_252(x: u8, y: u8) -> u32 {
+/// Given color picker coordinates, get a crosshair color. This matches what [`draw_color_picker`] draws at `(x, y)`,
+/// with the value turned into alpha.
+fn picker_coordinates_to_argb(x: u8, y: u8) -> u32 {
     const MAX_COLOR: u8 = 255;
 
     // we need the ceiling of each of the 5 boundaries between the 6 sections
@@ -166,8 +347,8 @@ fn x_y_to_argb_252(x: u8, y: u8) -> u32 {
     const SECTION_4: u8 = SECTION_3 + COLOR_PICKER_SECTION_WIDTH as u8;
     const SECTION_5: u8 = SECTION_4 + COLOR_PICKER_SECTION_WIDTH as u8;
 
-    // convert the hue into a nice sawtooth line going from 0->255 in each of the 6 sections
-    let raw_hue = x.wrapping_mul(6);
+    // convert the hue into the same sawtooth ramp that `draw_color_picker` uses in each of the 6 sections
+    let raw_hue = (x % COLOR_PICKER_SECTION_WIDTH as u8) * COLOR_PICKER_NUM_SECTIONS;
 
     let [r, g, b] = match x {
         hue if hue < SECTION_1 => [MAX_COLOR, raw_hue, 0],
@@ -181,10 +362,12 @@ fn x_y_to_argb_252(x: u8, y: u8) -> u32 {
     u32::from_le_bytes([b, g, r, MAX_COLOR - y])
 }
 
-/// Convert BE RGBA to LE ARGB, premultiplying alpha where required by the target platform.
+/// Windows wants premultiplied alpha in its window buffers, while other platforms want straight alpha.
+pub const PREMULTIPLY_ALPHA_DEFAULT: bool = cfg!(target_os = "windows");
+
+/// Convert BE RGBA to LE ARGB, premultiplying alpha if `premultiply` is set. `gamma_correct` does that in linear light.
 #[inline(always)]
-#[cfg(target_os = "windows")]
-fn rgba_to_argb(rgba_color: u32) -> u32 {
+fn rgba_to_argb(rgba_color: u32, premultiply: bool, gamma_correct: bool) -> u32 {
     // OPTIMIZATION NOTE: this could benefit from SIMD. However, it only happens when the user loads
     // a PNG from disk. So not only is this infrequent, the latency of doing all the number crunching
     // is going to be completely overshadowed by the incredible slowness of reading from disk. Not
@@ -193,46 +376,117 @@ fn rgba_to_argb(rgba_color: u32) -> u32 {
     // The PNG data is currently laid out as RGBA in BE order.
     // From a LE perspective, this means the actual data in the u32 is ABGR
     // Therefore, if we read this in LE order the bytes go RGBA.
-    let [r, g, b, a] = rgba_color.to_le_bytes();
-
-    // We want to pack the data back into ARGB. Provided in LE order that's BGRA.
-    u32::from_le_bytes([
-        multiply_color_channels_u8(b, a),
-        multiply_color_channels_u8(g, a),
-        multiply_color_channels_u8(r, a),
-        a,
-    ])
+    Color::from_rgba_bytes(rgba_color.to_le_bytes())
+        .for_buffer(premultiply, gamma_correct)
+        .argb()
 }
 
-/// Convert BE RGBA to LE ARGB, premultiplying alpha where required by the target platform.
-#[inline(always)]
-#[cfg(not(target_os = "windows"))]
-fn rgba_to_argb(rgba_color: u32) -> u32 {
-    // The PNG data is currently laid out as RGBA in BE order.
-    // From a LE perspective, this means the actual data in the u32 is ABGR
-    // Therefore, if we read this in LE order the bytes go RGBA.
-    let [r, g, b, a] = rgba_color.to_le_bytes();
+/// alpha values of the `--alpha-test` gradient, from fully transparent to fully opaque
+pub const ALPHA_TEST_STEPS: [u8; 5] = [0x00, 0x40, 0x80, 0xC0, 0xFF];
+/// the straight RGB color the `--alpha-test` gradient fades out
+const ALPHA_TEST_RGB: [u8; 3] = [0xFF, 0x80, 0x00];
+
+/// Run the `--alpha-test` gradient through the same conversion PNG pixels get. Returns each pixel as PNG bytes (RGBA)
+/// along with the ARGB that ends up in the window buffer.
+pub fn alpha_test_gradient(premultiply: bool, gamma_correct: bool) -> Vec<([u8; 4], u32)> {
+    let [r, g, b] = ALPHA_TEST_RGB;
+    ALPHA_TEST_STEPS
+        .iter()
+        .map(|&a| {
+            let rgba = [r, g, b, a];
+            let argb = rgba_to_argb(u32::from_le_bytes(rgba), premultiply, gamma_correct);
+            // the generated crosshairs take a different path to the buffer, which had better agree
+            debug_assert_eq!(
+                argb,
+                premultiply_alpha(u32::from_be_bytes([a, r, g, b]), premultiply, gamma_correct)
+            );
+            (rgba, argb)
+        })
+        .collect()
+}
 
-    // We want to pack the data back into ARGB. Provided in LE order that's BGRA.
-    u32::from_le_bytes([b, g, r, a])
+/// Premultiply alpha if `premultiply` is set, otherwise this is a no-op. `gamma_correct` multiplies in linear light.
+/// Usually both should come from the settings, where `premultiply` defaults to [`PREMULTIPLY_ALPHA_DEFAULT`].
+pub fn premultiply_alpha(color: u32, premultiply: bool, gamma_correct: bool) -> u32 {
+    if premultiply {
+        let multiply = if gamma_correct {
+            multiply_color_channels_u8_linear
+        } else {
+            multiply_color_channels_u8
+        };
+        let [b, g, r, a] = color.to_le_bytes();
+        u32::from_le_bytes([multiply(b, a), multiply(g, a), multiply(r, a), a])
+    } else {
+        color
+    }
 }
 
-/// Premultiply alpha if required by current platform. On this platform this performs the premultiplication.
-#[cfg(target_os = "windows")]
-pub fn premultiply_alpha(color: u32) -> u32 {
+/// Multiply a color's alpha by `opacity`. Premultiplied colors get their color channels multiplied too, which keeps them
+/// premultiplied.
+pub fn fade(color: u32, opacity: u8, premultiplied: bool) -> u32 {
     let [b, g, r, a] = color.to_le_bytes();
-    u32::from_le_bytes([
-        multiply_color_channels_u8(b, a),
-        multiply_color_channels_u8(g, a),
-        multiply_color_channels_u8(r, a),
-        a,
-    ])
+    if premultiplied {
+        u32::from_le_bytes([
+            multiply_color_channels_u8(b, opacity),
+            multiply_color_channels_u8(g, opacity),
+            multiply_color_channels_u8(r, opacity),
+            multiply_color_channels_u8(a, opacity),
+        ])
+    } else {
+        u32::from_le_bytes([b, g, r, multiply_color_channels_u8(a, opacity)])
+    }
+}
+
+/// Undo [`premultiply_alpha`], giving back straight ARGB. `premultiply` and `gamma_correct` must match what the color
+/// was premultiplied with, so if `premultiply` isn't set this is a no-op.
+pub fn unpremultiply_alpha(color: u32, premultiply: bool, gamma_correct: bool) -> u32 {
+    if premultiply {
+        let divide = if gamma_correct {
+            divide_color_channels_u8_linear
+        } else {
+            divide_color_channels_u8
+        };
+        let [b, g, r, a] = color.to_le_bytes();
+        u32::from_le_bytes([divide(b, a), divide(g, a), divide(r, a), a])
+    } else {
+        color
+    }
 }
 
-/// Premultiply alpha if required by current platform. On this platform this is a no-op.
-#[cfg(not(target_os = "windows"))]
-pub fn premultiply_alpha(color: u32) -> u32 {
-    color
+/// HSV value used for the near-black high contrast color
+const HIGH_CONTRAST_DARK_VALUE: u8 = 40;
+/// how much of the original saturation the near-white high contrast color keeps, out of 255
+const HIGH_CONTRAST_LIGHT_SATURATION: u8 = 64;
+
+/// Turn a color into a fully opaque one that stands out against a mostly dark or mostly light background, while keeping
+/// its hue. On a dark background this is a tinted near-white, and on a light background a near-black.
+/// The provided `color` must not have premultiplied alpha. As the result is opaque, it doesn't need premultiplying.
+pub fn high_contrast(color: Color, dark_background: bool) -> Color {
+    let [hue, saturation, _value] = color.to_hsv();
+    let (saturation, value) = if dark_background {
+        (
+            multiply_color_channels_u8(saturation, HIGH_CONTRAST_LIGHT_SATURATION),
+            u8::MAX,
+        )
+    } else {
+        (saturation, HIGH_CONTRAST_DARK_VALUE)
+    };
+    Color::from_argb(hsv_to_argb(hue, saturation, value))
+}
+
+/// calculates `c * 255 / a`, rounding to nearest. This is the inverse of [`multiply_color_channels_u8`], although
+/// precision lost during the multiplication obviously can't be recovered. A fully transparent pixel has no color
+/// information left, so that just gives 0.
+#[inline(always)]
+pub fn divide_color_channels_u8(c: u8, a: u8) -> u8 {
+    const MAX_COLOR: u16 = 255;
+
+    if a == 0 {
+        0
+    } else {
+        let a = a as u16;
+        ((c as u16 * MAX_COLOR + a / 2) / a).min(MAX_COLOR) as u8
+    }
 }
 
 /// calculates `a * b / 255`
@@ -256,14 +510,135 @@ pub fn multiply_color_channels_u8(a: u8, b: u8) -> u8 {
     ((a as u16 * b as u16 + HALF_COLOR) / MAX_COLOR) as u8
 }
 
-/// load a png file into an in-memory image
-pub fn load_png<T>(path: T) -> io::Result<Box<Image>>
+/// convert an sRGB encoded channel from 0 to 1 into linear light
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// the inverse of [`srgb_to_linear`]
+fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Like [`multiply_color_channels_u8`], but treats `c` as sRGB and does the multiplication in linear light. `a` is
+/// coverage, which is already linear. This is a lot slower, but semi-transparent colors come out closer to how they'd
+/// look if the OS blended them properly.
+pub fn multiply_color_channels_u8_linear(c: u8, a: u8) -> u8 {
+    const MAX_COLOR: f32 = 255.0;
+
+    let linear = srgb_to_linear(c as f32 / MAX_COLOR) * (a as f32 / MAX_COLOR);
+    (linear_to_srgb(linear) * MAX_COLOR).round() as u8
+}
+
+/// The inverse of [`multiply_color_channels_u8_linear`], in the same way that [`divide_color_channels_u8`] is the
+/// inverse of [`multiply_color_channels_u8`].
+pub fn divide_color_channels_u8_linear(c: u8, a: u8) -> u8 {
+    const MAX_COLOR: f32 = 255.0;
+
+    if a == 0 {
+        0
+    } else {
+        let linear = (srgb_to_linear(c as f32 / MAX_COLOR) / (a as f32 / MAX_COLOR)).min(1.0);
+        (linear_to_srgb(linear) * MAX_COLOR).round() as u8
+    }
+}
+
+/// Load an image file, picking the format from its extension. Anything that isn't a JPEG or BMP gets loaded as a PNG.
+/// `strict` only applies to PNGs, see [`load_png`].
+pub fn load_image<T>(
+    path: T,
+    premultiply: bool,
+    gamma_correct: bool,
+    strict: bool,
+) -> io::Result<Box<Image>>
+where
+    T: AsRef<Path>,
+{
+    let extension = path
+        .as_ref()
+        .extension()
+        .and_then(OsStr::to_str)
+        .map(str::to_ascii_lowercase);
+    match extension.as_deref() {
+        Some("jpg" | "jpeg") => load_jpeg(path, premultiply, gamma_correct),
+        Some("bmp") => bmp::load_bmp(path, premultiply, gamma_correct),
+        _ => load_png(path, premultiply, gamma_correct, strict),
+    }
+}
+
+/// load a jpeg file into an in-memory image. JPEGs have no alpha, so every pixel is fully opaque.
+fn load_jpeg<T>(path: T, premultiply: bool, gamma_correct: bool) -> io::Result<Box<Image>>
+where
+    T: AsRef<Path>,
+{
+    let mut decoder = jpeg_decoder::Decoder::new(BufReader::new(File::open(path)?));
+    let pixels = decoder
+        .decode()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let info = decoder
+        .info()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "JPEG has no image info"))?;
+
+    let to_rgba: fn(&[u8]) -> [u8; 4] = match info.pixel_format {
+        PixelFormat::RGB24 => |pixel| [pixel[0], pixel[1], pixel[2], u8::MAX],
+        PixelFormat::L8 => |pixel| [pixel[0], pixel[0], pixel[0], u8::MAX],
+        PixelFormat::L16 => |pixel| {
+            // keep the high byte of the native endian sample
+            let value = (u16::from_ne_bytes([pixel[0], pixel[1]]) >> 8) as u8;
+            [value, value, value, u8::MAX]
+        },
+        PixelFormat::CMYK32 => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "JPEG was in CMYK format. Only RGB and grayscale JPEGs are supported. Please re-save your JPEG as RGB.",
+        ))?,
+    };
+    let data = pixels
+        .chunks_exact(info.pixel_format.pixel_bytes())
+        .map(|pixel| {
+            rgba_to_argb(
+                u32::from_le_bytes(to_rgba(pixel)),
+                premultiply,
+                gamma_correct,
+            )
+        })
+        .collect();
+
+    Ok(Box::new(Image {
+        width: info.width as u32,
+        height: info.height as u32,
+        data,
+    }))
+}
+
+/// load a png file into an in-memory image, premultiplying alpha if `premultiply` is set. When `strict` is set anything
+/// but 8-bit RGBA is rejected, otherwise other color types get converted.
+pub fn load_png<T>(
+    path: T,
+    premultiply: bool,
+    gamma_correct: bool,
+    strict: bool,
+) -> io::Result<Box<Image>>
 where
     T: AsRef<Path>,
 {
     let file = File::open(path)?;
-    let decoder = png::Decoder::new(file);
+    let mut decoder = png::Decoder::new(file);
+    if !strict {
+        // turns indexed colors into RGB(A) and 16-bit channels into 8-bit, which leaves only a few layouts to convert
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+    }
     let mut reader = decoder.read_info()?;
+    if !strict && reader.output_color_type().0 != ColorType::Rgba {
+        return load_converted_png(reader, premultiply, gamma_correct);
+    }
 
     // The PNG decoder wants a u8 buffer to store its RGBA data... but winit wants ARGB u32 data.
     // Here I make a buffer of the correct size to hold the reader's data, but as u32's instead of u8's.
@@ -302,7 +677,7 @@ where
     // post-process color layout in each pixel
     buf_as_u32
         .iter_mut()
-        .for_each(|pixel| *pixel = rgba_to_argb(pixel.to_owned()));
+        .for_each(|pixel| *pixel = rgba_to_argb(pixel.to_owned(), premultiply, gamma_correct));
 
     let image = Image {
         width: info.width,
@@ -313,6 +688,54 @@ where
     Ok(Box::new(image))
 }
 
+/// Finish loading a PNG that isn't RGBA by converting each pixel to RGBA
+fn load_converted_png<R: io::Read>(
+    mut reader: png::Reader<R>,
+    premultiply: bool,
+    gamma_correct: bool,
+) -> io::Result<Box<Image>> {
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf)?;
+    let buf = &buf[..info.buffer_size()];
+
+    let to_rgba = png_pixel_to_rgba(info.color_type)?;
+    let data = buf
+        .chunks_exact(info.color_type.samples())
+        .map(|pixel| {
+            rgba_to_argb(
+                u32::from_le_bytes(to_rgba(pixel)),
+                premultiply,
+                gamma_correct,
+            )
+        })
+        .collect();
+
+    Ok(Box::new(Image {
+        width: info.width,
+        height: info.height,
+        data,
+    }))
+}
+
+/// converts one pixel of decoded PNG data to RGBA bytes
+type PixelToRgba = fn(&[u8]) -> [u8; 4];
+
+/// How to turn one pixel of 8-bit PNG output into RGBA bytes. Only works for PNGs decoded with
+/// [`png::Transformations::normalize_to_color8`].
+fn png_pixel_to_rgba(color_type: ColorType) -> io::Result<PixelToRgba> {
+    Ok(match color_type {
+        ColorType::Rgb => |pixel| [pixel[0], pixel[1], pixel[2], u8::MAX],
+        ColorType::Grayscale => |pixel| [pixel[0], pixel[0], pixel[0], u8::MAX],
+        ColorType::GrayscaleAlpha => |pixel| [pixel[0], pixel[0], pixel[0], pixel[1]],
+        ColorType::Rgba => |pixel| [pixel[0], pixel[1], pixel[2], pixel[3]],
+        // the decoder expands indexed colors, so this can't happen
+        ColorType::Indexed => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "PNG palette was not expanded",
+        ))?,
+    })
+}
+
 /// calculate the coordinates of the center of a rectangle.
 /// `x` and `y` are the coordinates of the top left corner.
 /// `width` and `height` are the dimensions of the rectangle.
@@ -326,6 +749,36 @@ pub fn rectangle_center(x: i32, y: i32, width: i32, height: i32) -> (i32, i32) {
     )
 }
 
+/// Like [`rectangle_center`], but with a choice of which way to round when the center falls between two pixels
+pub fn rounded_rectangle_center(
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    rounding: CenterRounding,
+) -> (i32, i32) {
+    (
+        rounded_center(x, width, rounding),
+        rounded_center(y, height, rounding),
+    )
+}
+
+/// center of a span of `length` pixels starting at `start`
+fn rounded_center(start: i32, length: i32, rounding: CenterRounding) -> i32 {
+    let floor = start + length.div_floor_placeholder(2);
+    if length % 2 == 0 {
+        // the center is exactly on a pixel boundary, so there's nothing to round
+        return floor;
+    }
+    match rounding {
+        CenterRounding::Floor => floor,
+        CenterRounding::Ceil => floor + 1,
+        // the exact center is `floor + 0.5`, so it's on the positive side of 0 exactly when `floor` is
+        CenterRounding::Round if floor >= 0 => floor + 1,
+        CenterRounding::Round => floor,
+    }
+}
+
 #[cfg(test)]
 mod test_pixel_format {
     use super::*;
@@ -357,10 +810,32 @@ mod test_pixel_format {
         let green = 40u8;
         let blue = 60u8;
         let png_data = u32::from_le_bytes([red, green, blue, alpha]); // laid out backwards in memory, so we write it forwards in LE
-        let argb_data = rgba_to_argb(png_data);
+        let argb_data = rgba_to_argb(png_data, false, false);
         assert_eq!(argb_data.to_le_bytes(), [blue, green, red, alpha]); // laid out properly in memory, so we write it backwards in LE
     }
 
+    #[test]
+    fn test_pixel_format_conversion_premultiplied() {
+        let png_data = u32::from_le_bytes([20, 40, 60, 127]);
+        assert_eq!(
+            rgba_to_argb(png_data, false, false).to_le_bytes(),
+            [60, 40, 20, 127]
+        );
+        assert_eq!(
+            rgba_to_argb(png_data, true, false).to_le_bytes(),
+            [30, 20, 10, 127]
+        );
+    }
+
+    /// the setting, not the platform, decides which conversion path is used
+    #[test]
+    fn test_premultiply_setting() {
+        let color: u32 = 0x80FF8040;
+        assert_eq!(premultiply_alpha(color, false, false), color);
+        assert_eq!(premultiply_alpha(color, true, false), 0x80804020);
+        assert_eq!(unpremultiply_alpha(color, false, false), color);
+    }
+
     /// This should be a no-op.
     #[test]
     fn test_premultiply_alpha_noop() {
@@ -402,6 +877,216 @@ mod test_pixel_format {
     }
 }
 
+#[cfg(test)]
+mod test_unpremultiply_alpha {
+    use super::*;
+
+    /// some colors people might actually pick, in straight ARGB
+    const COLORS: [u32; 6] = [
+        0xFFFF0000, 0xB2FF0000, 0x80FFFFFF, 0xC0123456, 0xE600FF7F, 0x99ABCDEF,
+    ];
+
+    /// compare each channel, allowing for rounding error
+    fn assert_close(actual: u32, expected: u32) {
+        for (actual_channel, expected_channel) in
+            actual.to_le_bytes().iter().zip(expected.to_le_bytes())
+        {
+            assert!(
+                actual_channel.abs_diff(expected_channel) <= 1,
+                "expected {expected:08X} but got {actual:08X}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for color in COLORS {
+            for premultiply in [false, true] {
+                assert_close(
+                    unpremultiply_alpha(
+                        premultiply_alpha(color, premultiply, false),
+                        premultiply,
+                        false,
+                    ),
+                    color,
+                );
+            }
+        }
+    }
+
+    /// same as above, but exercise the channel math even on platforms where premultiplication is a no-op
+    #[test]
+    fn test_channel_round_trip() {
+        for color in COLORS {
+            let [b, g, r, a] = color.to_le_bytes();
+            for c in [b, g, r] {
+                let round_trip = divide_color_channels_u8(multiply_color_channels_u8(c, a), a);
+                assert!(round_trip.abs_diff(c) <= 1, "mismatch for c={c} a={a}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_opaque_is_exact() {
+        for c in 0..=255 {
+            assert_eq!(divide_color_channels_u8(c, 255), c);
+        }
+    }
+
+    #[test]
+    fn test_transparent() {
+        assert_eq!(divide_color_channels_u8(0, 0), 0);
+        assert_eq!(divide_color_channels_u8(255, 0), 0);
+    }
+
+    /// invalid premultiplied data (color brighter than alpha) should saturate instead of overflowing
+    #[test]
+    fn test_saturates() {
+        assert_eq!(divide_color_channels_u8(255, 1), 255);
+    }
+}
+
+#[cfg(test)]
+mod test_gamma_correct {
+    use super::*;
+
+    const ALPHAS: [u8; 5] = [0x20, 0x40, 0x80, 0xC0, 0xE0];
+
+    /// total distance of `multiply` from the precise linear-light result, over every color channel value
+    fn linear_error(multiply: fn(u8, u8) -> u8, a: u8) -> f64 {
+        (0..=255)
+            .map(|c| (multiply(c, a) as f64 - precise::multiply_color_channels_linear(c, a)).abs())
+            .sum()
+    }
+
+    #[test]
+    fn test_closer_to_linear_reference() {
+        for a in ALPHAS {
+            let fast = linear_error(multiply_color_channels_u8, a);
+            let linear = linear_error(multiply_color_channels_u8_linear, a);
+            assert!(
+                linear < fast,
+                "a={a}: linear path was off by {linear}, fast path by {fast}"
+            );
+        }
+    }
+
+    /// only rounding should separate the linear path from the reference
+    #[test]
+    fn test_rounding() {
+        for a in ALPHAS {
+            for c in 0..=255 {
+                let expected = precise::multiply_color_channels_linear(c, a);
+                let actual = multiply_color_channels_u8_linear(c, a) as f64;
+                assert!(
+                    (actual - expected).abs() < 0.51,
+                    "c={c} a={a}: expected {expected} but got {actual}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_opaque_and_transparent() {
+        for c in 0..=255 {
+            assert_eq!(multiply_color_channels_u8_linear(c, 255), c);
+            assert_eq!(divide_color_channels_u8_linear(c, 255), c);
+            assert_eq!(multiply_color_channels_u8_linear(c, 0), 0);
+        }
+        assert_eq!(divide_color_channels_u8_linear(255, 0), 0);
+    }
+
+    /// `gamma_correct` picks the linear path for every channel, both ways
+    #[test]
+    fn test_premultiply_alpha() {
+        let color: u32 = 0x80FF8040;
+        let [b, g, r, a] = color.to_le_bytes();
+        let linear = u32::from_le_bytes([
+            multiply_color_channels_u8_linear(b, a),
+            multiply_color_channels_u8_linear(g, a),
+            multiply_color_channels_u8_linear(r, a),
+            a,
+        ]);
+        assert_eq!(premultiply_alpha(color, true, true), linear);
+        assert_ne!(premultiply_alpha(color, true, false), linear);
+        assert_eq!(premultiply_alpha(color, false, true), color);
+        let [b, g, r, a] = linear.to_le_bytes();
+        assert_eq!(
+            unpremultiply_alpha(linear, true, true),
+            u32::from_le_bytes([
+                divide_color_channels_u8_linear(b, a),
+                divide_color_channels_u8_linear(g, a),
+                divide_color_channels_u8_linear(r, a),
+                a,
+            ])
+        );
+    }
+
+    /// very transparent colors lose too much precision to survive the trip, same as with the fast path
+    #[test]
+    fn test_round_trip() {
+        for a in ALPHAS.into_iter().filter(|&a| a >= 0x80) {
+            for c in 0..=255 {
+                let round_trip =
+                    divide_color_channels_u8_linear(multiply_color_channels_u8_linear(c, a), a);
+                assert!(round_trip.abs_diff(c) <= 1, "mismatch for c={c} a={a}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_high_contrast {
+    use super::*;
+
+    const MID_GRAY: Color = Color::from_argb(0x80808080);
+
+    #[test]
+    fn test_gray_on_dark() {
+        assert_eq!(high_contrast(MID_GRAY, true).argb(), 0xFFFFFFFF);
+    }
+
+    #[test]
+    fn test_gray_on_light() {
+        assert_eq!(high_contrast(MID_GRAY, false).argb(), 0xFF282828);
+    }
+
+    #[test]
+    fn test_keeps_hue() {
+        // translucent red
+        assert_eq!(
+            high_contrast(Color::from_argb(0xB2FF0000), true).argb(),
+            0xFFFFBFBF
+        );
+        assert_eq!(
+            high_contrast(Color::from_argb(0xB2FF0000), false).argb(),
+            0xFF280000
+        );
+
+        // a dim orange: red is the max channel and green sits halfway to it
+        assert_eq!(
+            high_contrast(Color::from_argb(0xFF402000), true).argb(),
+            0xFFFFDFBF
+        );
+        assert_eq!(
+            high_contrast(Color::from_argb(0xFF402000), false).argb(),
+            0xFF281400
+        );
+    }
+
+    #[test]
+    fn test_black() {
+        assert_eq!(
+            high_contrast(Color::from_argb(0xFF000000), true).argb(),
+            0xFFFFFFFF
+        );
+        assert_eq!(
+            high_contrast(Color::from_argb(0xFF000000), false).argb(),
+            0xFF282828
+        );
+    }
+}
+
 #[cfg(test)]
 mod test_rectangle_center {
     use super::*;
@@ -446,6 +1131,54 @@ mod test_rectangle_center {
     }
 }
 
+#[cfg(test)]
+mod test_rounded_rectangle_center {
+    use super::*;
+
+    fn center(x: i32, width: i32, rounding: CenterRounding) -> i32 {
+        rounded_rectangle_center(x, 0, width, 2, rounding).0
+    }
+
+    /// even sizes have an exact center, so the rounding mode doesn't matter
+    #[test]
+    fn test_even() {
+        for rounding in [
+            CenterRounding::Floor,
+            CenterRounding::Round,
+            CenterRounding::Ceil,
+        ] {
+            assert_eq!(center(0, 1920, rounding), 960);
+            assert_eq!(center(-1920, 1920, rounding), -960);
+        }
+    }
+
+    /// floor is the same as plain old [`rectangle_center`]
+    #[test]
+    fn test_floor() {
+        assert_eq!(center(0, 1921, CenterRounding::Floor), 960);
+        assert_eq!(center(-1921, 1921, CenterRounding::Floor), -961);
+        assert_eq!(
+            rounded_rectangle_center(397, -1081, 1921, 1081, CenterRounding::Floor),
+            rectangle_center(397, -1081, 1921, 1081)
+        );
+    }
+
+    #[test]
+    fn test_ceil() {
+        assert_eq!(center(0, 1921, CenterRounding::Ceil), 961);
+        assert_eq!(center(-1921, 1921, CenterRounding::Ceil), -960);
+    }
+
+    #[test]
+    fn test_round() {
+        assert_eq!(center(0, 1921, CenterRounding::Round), 961);
+        assert_eq!(center(-1921, 1921, CenterRounding::Round), -961);
+        // a monitor straddling 0 with its center at -0.5
+        assert_eq!(center(-1, 1, CenterRounding::Round), -1);
+        assert_eq!(center(0, 1, CenterRounding::Round), 1);
+    }
+}
+
 #[cfg(test)]
 mod test_color_picker {
     use super::*;
@@ -531,13 +1264,61 @@ mod test_color_picker {
         }
     }
 
+    #[test]
+    fn test_hsv_to_argb() {
+        let max_error = 5f64;
+
+        for hue in (0..=255).step_by(3) {
+            for saturation in (0..=255).step_by(5) {
+                for value in (0..=255).step_by(5) {
+                    let actual_argb = hsv_to_argb(hue, saturation, value);
+                    let expected_argb = precise::hsv_to_argb(hue, saturation, value);
+                    let error = color_error(actual_argb, expected_argb);
+                    assert!(
+                        error <= max_error,
+                        "precise and optimized hsv->argb differ: @ hsv ({}, {}, {}), {:08X} != {:08X}, error={}",
+                        hue,
+                        saturation,
+                        value,
+                        actual_argb,
+                        expected_argb,
+                        error
+                    );
+                }
+            }
+        }
+    }
+
+    /// a washed out picker still matches the colors its clicks produce
+    #[test]
+    fn test_desaturated_picker() {
+        const SATURATION: u8 = 128;
+        let mut buffer = vec![0; COLOR_PICKER_SIZE * COLOR_PICKER_SIZE];
+        draw_color_picker(&mut buffer);
+        desaturate_color_picker(&mut buffer, SATURATION);
+
+        // the top row is drawn at full value and full alpha
+        for (x, &pixel) in buffer[..COLOR_PICKER_SIZE].iter().enumerate() {
+            let picked = hue_alpha_color_from_coordinates(x, 0, SATURATION).argb();
+            let error = color_error(picked, pixel);
+            assert!(
+                error <= 5.0,
+                "picked color doesn't match the picker @ x {x}, {picked:08X} != {pixel:08X}, error={error}"
+            );
+        }
+        assert_eq!(hue_alpha_color_from_coordinates(0, 0, 0).argb(), 0xFFFFFFFF);
+        assert_eq!(
+            hue_alpha_color_from_coordinates(0, 0, SATURATION).argb(),
+            0xFFFF7F7F
+        );
+    }
+
     /// make sure the optimized color picker behaves generally as expected
     #[test]
     fn test_optimized_color_picker() {
-        const BUFFER_DIMENSION: usize = 252;
-        const BUFFER_SIZE: usize = BUFFER_DIMENSION * BUFFER_DIMENSION;
+        const MAX: usize = COLOR_PICKER_SIZE - 1;
 
-        let mut buffer = vec![0; BUFFER_SIZE];
+        let mut buffer = vec![0; COLOR_PICKER_SIZE * COLOR_PICKER_SIZE];
         draw_color_picker(&mut buffer);
 
         // make sure various pixels are nonzero
@@ -545,9 +1326,39 @@ mod test_color_picker {
         assert_ne!(buffer[buffer.len() - 1], 0, "last pixel should be set");
 
         check_picked_color(&buffer, 0, 0);
-        check_picked_color(&buffer, 0, 252 - 1);
-        check_picked_color(&buffer, 252 - 1, 0);
-        check_picked_color(&buffer, 252 - 1, 252 - 1);
+        check_picked_color(&buffer, 0, MAX);
+        check_picked_color(&buffer, MAX, 0);
+        check_picked_color(&buffer, MAX, MAX);
+        check_picked_color(&buffer, MAX / 2, MAX / 2);
+    }
+
+    /// the corners of the picker are red on the left, almost back to red on the right, and fade out toward the bottom
+    #[test]
+    fn test_picker_corners() {
+        const MAX: usize = COLOR_PICKER_SIZE - 1;
+        let alpha_bottom = (255 - MAX) as u32;
+        // the last column is one step short of wrapping all the way back around to red
+        let blue_right =
+            255 - (MAX % COLOR_PICKER_SECTION_WIDTH) as u32 * COLOR_PICKER_NUM_SECTIONS as u32;
+
+        let corner = |x, y| hue_alpha_color_from_coordinates(x, y, u8::MAX).argb();
+        assert_eq!(corner(0, 0), 0xFFFF0000);
+        assert_eq!(corner(MAX, 0), 0xFFFF0000 | blue_right);
+        assert_eq!(corner(0, MAX), (alpha_bottom << 24) | 0xFF0000);
+        assert_eq!(
+            corner(MAX, MAX),
+            (alpha_bottom << 24) | 0xFF0000 | blue_right
+        );
+    }
+
+    /// clicks just past the edge of the picker don't wrap around to the other side
+    #[test]
+    fn test_picker_past_edge() {
+        const MAX: usize = COLOR_PICKER_SIZE - 1;
+        assert_eq!(
+            hue_alpha_color_from_coordinates(COLOR_PICKER_SIZE + 10, 300, u8::MAX),
+            hue_alpha_color_from_coordinates(MAX, MAX, u8::MAX)
+        );
     }
 
     #[derive(Debug)]
@@ -597,14 +1408,12 @@ mod test_color_picker {
     }
 
     fn check_picked_color(buffer: &[u32], x: usize, y: usize) {
-        const BUFFER_DIMENSION: usize = 252;
-
-        let picker_color = rgb_to_hsv_precise(buffer[y * BUFFER_DIMENSION + x]);
+        let picker_color = rgb_to_hsv_precise(buffer[y * COLOR_PICKER_SIZE + x]);
         let HsvColor { h, s: _, v } = picker_color;
         let expected_color = HsvColor { h, s: 1.0, v: 1.0 };
         let expected_alpha = (v * 255.0).round() as u8;
 
-        let calculated_color = x_y_to_argb_252(x as u8, y as u8);
+        let calculated_color = hue_alpha_color_from_coordinates(x, y, u8::MAX).argb();
         let actual_color = rgb_to_hsv_precise(calculated_color);
         let [_, _, _, actual_alpha] = calculated_color.to_le_bytes();
         assert_eq!(
@@ -618,12 +1427,209 @@ mod test_color_picker {
     }
 }
 
+#[cfg(test)]
+mod test_picker_cursor {
+    use super::*;
+
+    const MAX: usize = COLOR_PICKER_SIZE - 1;
+
+    #[test]
+    fn test_move() {
+        assert_eq!(move_picker_cursor((10, 10), 1, -1), (11, 9));
+        assert_eq!(move_picker_cursor((10, 10), 0, 0), (10, 10));
+    }
+
+    #[test]
+    fn test_clamped_to_grid() {
+        assert_eq!(move_picker_cursor((0, 0), -1, -64), (0, 0));
+        assert_eq!(move_picker_cursor((MAX, MAX), 1, 64), (MAX, MAX));
+        assert_eq!(move_picker_cursor((3, MAX - 3), -64, 64), (0, MAX));
+        assert_eq!(move_picker_cursor((0, 0), i32::MAX, i32::MIN), (MAX, 0));
+    }
+
+    /// the cursor can sit right on the edge without drawing out of bounds, and never covers the selected pixel
+    #[test]
+    fn test_draw_at_edges() {
+        let mut buffer = vec![0xFF000000; COLOR_PICKER_SIZE * COLOR_PICKER_SIZE];
+        for cursor in [(0, 0), (MAX, MAX), (0, MAX), (MAX / 2, MAX / 2)] {
+            draw_picker_cursor(&mut buffer, cursor);
+            assert_eq!(buffer[cursor.1 * COLOR_PICKER_SIZE + cursor.0], 0xFF000000);
+        }
+        assert_eq!(buffer[1], 0xFFFFFFFF);
+        assert_eq!(buffer[COLOR_PICKER_SIZE], 0xFFFFFFFF);
+    }
+}
+
+#[cfg(test)]
+mod test_fit {
+    use super::*;
+
+    const HD: PhysicalSize<u32> = PhysicalSize::new(1920, 1080);
+
+    #[test]
+    fn test_fit_size() {
+        // already fits
+        assert_eq!(fit_size(PhysicalSize::new(100, 100), HD, 0.5), None);
+        assert_eq!(fit_size(PhysicalSize::new(960, 540), HD, 0.5), None);
+
+        // limited by width
+        assert_eq!(
+            fit_size(PhysicalSize::new(4000, 2000), HD, 0.5),
+            Some(PhysicalSize::new(960, 480))
+        );
+        // limited by height
+        assert_eq!(
+            fit_size(PhysicalSize::new(3000, 3000), HD, 1.0),
+            Some(PhysicalSize::new(1080, 1080))
+        );
+        // very lopsided images never shrink to nothing
+        assert_eq!(
+            fit_size(PhysicalSize::new(100_000, 10), HD, 0.5),
+            Some(PhysicalSize::new(960, 1))
+        );
+    }
+
+    #[test]
+    fn test_unknown_monitor() {
+        assert_eq!(
+            fit_size(PhysicalSize::new(4000, 2000), PhysicalSize::default(), 0.5),
+            None
+        );
+    }
+
+    #[test]
+    fn test_resize() {
+        let image = Image {
+            width: 4,
+            height: 2,
+            data: vec![0, 1, 2, 3, 4, 5, 6, 7],
+        };
+        let resized = image.resize(2, 1);
+        assert_eq!((resized.width, resized.height), (2, 1));
+        assert_eq!(resized.data, vec![5, 7]);
+
+        let resized = image.resize(8, 2);
+        assert_eq!(
+            resized.data,
+            vec![0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7]
+        );
+    }
+}
+
 #[cfg(test)]
 mod test_png {
     use super::*;
 
     #[test]
     fn test_load_png() {
-        load_png("tests/resources/test.png").unwrap();
+        load_png(
+            "tests/resources/test.png",
+            PREMULTIPLY_ALPHA_DEFAULT,
+            false,
+            true,
+        )
+        .unwrap();
+        load_png(
+            "tests/resources/test.png",
+            PREMULTIPLY_ALPHA_DEFAULT,
+            false,
+            false,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_strict_rejects_rgb() {
+        let error = load_png("tests/resources/test_rgb.png", false, false, true)
+            .err()
+            .unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert!(error.to_string().contains("Rgb format"), "{error}");
+    }
+
+    #[test]
+    fn test_lax_converts_rgb() {
+        let image = load_png("tests/resources/test_rgb.png", false, false, false).unwrap();
+        assert_eq!((image.width, image.height), (2, 2));
+        assert_eq!(image.data, [0xFFFF0000, 0xFF00FF00, 0xFF0000FF, 0xFFFFFFFF]);
+    }
+
+    #[test]
+    fn test_lax_converts_grayscale() {
+        let image = load_png("tests/resources/test_grayscale.png", false, false, false).unwrap();
+        assert_eq!(image.data, [0xFF000000, 0xFF808080, 0xFFC0C0C0, 0xFFFFFFFF]);
+    }
+
+    /// palette entries with transparency come through as transparent pixels
+    #[test]
+    fn test_lax_converts_indexed() {
+        let image = load_png("tests/resources/test_indexed.png", false, false, false).unwrap();
+        assert_eq!(image.data, [0xFFFF0000, 0x0000FF00, 0x0000FF00, 0xFFFF0000]);
+        assert!(load_png("tests/resources/test_indexed.png", false, false, true).is_err());
+    }
+
+    /// JPEGs are lossy, so only check that the solid orange fixture comes out close to orange and fully opaque
+    #[test]
+    fn test_load_jpeg() {
+        let image = load_image("tests/resources/test.jpg", false, false, true).unwrap();
+        assert_eq!((image.width, image.height), (8, 8));
+        for &pixel in &image.data {
+            let [blue, green, red, alpha] = pixel.to_le_bytes();
+            assert_eq!(alpha, u8::MAX);
+            assert!(red >= 250, "red was {red}");
+            assert!(green.abs_diff(128) <= 4, "green was {green}");
+            assert!(blue <= 5, "blue was {blue}");
+        }
+    }
+
+    /// the extension picks the decoder, regardless of case
+    #[test]
+    fn test_load_image_dispatch() {
+        let image = load_image("tests/resources/test.bmp", false, false, true).unwrap();
+        assert_eq!((image.width, image.height), (3, 2));
+        let image = load_image("tests/resources/test.png", false, false, true).unwrap();
+        assert_eq!(
+            image.data,
+            load_png("tests/resources/test.png", false, false, true)
+                .unwrap()
+                .data
+        );
+
+        let mut path = std::env::temp_dir();
+        path.push("DELETEME_simple-crosshair-overlay-test-uppercase.JPG");
+        std::fs::copy("tests/resources/test.jpg", &path).expect("copy failed");
+        let image = load_image(&path, false, false, true);
+        std::fs::remove_file(&path).expect("cleanup failed");
+        assert_eq!(image.unwrap().width, 8);
+    }
+
+    #[test]
+    fn test_save_round_trip() {
+        let image = Image {
+            width: 2,
+            height: 1,
+            data: vec![0x80FF8040, 0x00000000],
+        };
+        let mut path = std::env::temp_dir();
+        path.push("DELETEME_simple-crosshair-overlay-test-save.png");
+        image.save_png(&path).expect("save failed");
+        let loaded = load_png(&path, false, false, true).expect("load failed");
+        std::fs::remove_file(&path).expect("cleanup failed");
+        assert_eq!((loaded.width, loaded.height), (2, 1));
+        assert_eq!(loaded.data, image.data);
+    }
+
+    /// a premultiplied buffer, like the window's, is saved with straight alpha
+    #[test]
+    fn test_save_premultiplied_round_trip() {
+        let buffer = [0xFFFF8040, 0x80800000, 0x00000000];
+        let mut path = std::env::temp_dir();
+        path.push("DELETEME_simple-crosshair-overlay-test-save-premultiplied.png");
+        save_png(&path, 3, 1, &buffer, true, false).expect("save failed");
+        let straight = load_png(&path, false, false, true).expect("load failed");
+        let premultiplied = load_png(&path, true, false, true).expect("load failed");
+        std::fs::remove_file(&path).expect("cleanup failed");
+        assert_eq!(straight.data, [0xFFFF8040, 0x80FF0000, 0x00000000]);
+        assert_eq!(premultiplied.data, buffer);
     }
 }
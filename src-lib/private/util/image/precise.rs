@@ -32,9 +32,11 @@ pub fn hsv_to_argb(hue: u8, saturation: u8, value: u8) -> u32 {
         _ => [chroma, 0.0, intermediate_color],
     };
 
-    let r = (r * 255.0).round() as u8;
-    let g = (g * 255.0).round() as u8;
-    let b = (b * 255.0).round() as u8;
+    // lift every channel so the brightest one lands on `value`
+    let m = value - chroma;
+    let r = ((r + m) * 255.0).round() as u8;
+    let g = ((g + m) * 255.0).round() as u8;
+    let b = ((b + m) * 255.0).round() as u8;
 
     u32::from_le_bytes([b, g, r, 255])
 }
@@ -43,3 +45,19 @@ pub fn hsv_to_argb(hue: u8, saturation: u8, value: u8) -> u32 {
 pub fn multiply_color_channels_u8(c: u8, a: u8) -> u8 {
     (c as f64 * a as f64 / 255f64).round() as u8
 }
+
+/// gamma-correct alpha premultiply implemented with f64 precision and without any rounding
+pub fn multiply_color_channels_linear(c: u8, a: u8) -> f64 {
+    let c = c as f64 / 255.0;
+    let linear = if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    } * (a as f64 / 255.0);
+    let srgb = if linear <= 0.0031308 {
+        linear * 12.92
+    } else {
+        1.055 * linear.powf(1.0 / 2.4) - 0.055
+    };
+    srgb * 255.0
+}
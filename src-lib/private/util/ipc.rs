@@ -0,0 +1,264 @@
+// This file is part of simple-crosshair-overlay and is licenced under the GNU GPL v3.0.
+// See LICENSE file for full text.
+// Copyright © 2023 Michael Ripley
+
+//! Local IPC endpoint so other programs, like streaming software, can follow what the overlay is doing. Every client
+//! gets one line of JSON as soon as it connects, and another line each time the state changes.
+
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use debug_print::debug_println;
+use serde::Serialize;
+
+use crate::private::platform::IpcListener;
+use crate::private::settings::{PersistedSettings, RenderMode};
+
+/// how many lines can queue up for a client that isn't reading before we give up on it
+const CLIENT_BACKLOG: usize = 16;
+/// how long to wait after a failed accept before trying again, so a broken listener doesn't spin
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// The Unix domain socket for the config at `config_path`: next to it, with the same name and a `.sock` extension, so
+/// overlays started with different `--config` files don't fight over one socket
+pub fn socket_path(config_path: &Path) -> PathBuf {
+    config_path.with_extension("sock")
+}
+
+/// The Windows named pipe for the config at `config_path`. Pipe names can't have backslashes past the `\\.\pipe\`
+/// prefix, so the path's backslashes become forward slashes.
+pub fn pipe_name(config_path: &Path) -> String {
+    let path = config_path.to_string_lossy().replace('\\', "/");
+    format!(r"\\.\pipe\simple-crosshair-overlay/{path}")
+}
+
+/// Everything an IPC client gets told about the overlay
+#[derive(Serialize)]
+pub struct Snapshot<'a> {
+    pub visible: bool,
+    pub render_mode: RenderMode,
+    pub settings: &'a PersistedSettings,
+}
+
+impl Snapshot<'_> {
+    /// Serialize to a single line of JSON, newline included
+    pub fn to_json_line(&self) -> serde_json::Result<String> {
+        let mut json = serde_json::to_string(self)?;
+        json.push('\n');
+        Ok(json)
+    }
+}
+
+/// State shared between the IPC threads
+#[derive(Default)]
+struct Clients {
+    /// the most recent snapshot, which new clients get right away
+    latest: Option<String>,
+    /// queues feeding each client's writer thread
+    senders: Vec<SyncSender<String>>,
+}
+
+impl Clients {
+    /// Queue a line for every client. Clients that have gone away or stopped reading get dropped, so one stuck client
+    /// never holds up the others.
+    fn broadcast(&mut self, line: &str) {
+        self.senders
+            .retain(|sender| sender.try_send(line.to_string()).is_ok());
+    }
+
+    /// start sending to a new client, beginning with the latest snapshot
+    fn add(&mut self, sender: SyncSender<String>) {
+        let caught_up = match &self.latest {
+            Some(line) => sender.try_send(line.clone()).is_ok(),
+            None => true,
+        };
+        if caught_up {
+            self.senders.push(sender);
+        }
+    }
+}
+
+/// Give a client its own thread to write on, so a slow client only ever blocks itself. Lines go out in the order
+/// they're queued.
+fn spawn_client(mut writer: Box<dyn Write + Send>) -> io::Result<SyncSender<String>> {
+    let (sender, receiver) = mpsc::sync_channel::<String>(CLIENT_BACKLOG);
+    thread::Builder::new()
+        .name("ipc-client".to_string())
+        .spawn(move || {
+            // ends once the client goes away, or gets dropped for falling behind
+            for line in receiver {
+                if writer.write_all(line.as_bytes()).is_err() {
+                    break;
+                }
+            }
+        })?;
+    Ok(sender)
+}
+
+pub struct IpcWorker {
+    snapshot_sender: mpsc::Sender<String>,
+    /// the last line we sent, so we only bother the worker when something actually changed
+    last_sent: Option<String>,
+}
+
+impl IpcWorker {
+    /// send the current state to every client, unless it's the same as what they've already got
+    pub fn send(&mut self, snapshot: &Snapshot) {
+        let Ok(line) = snapshot.to_json_line() else {
+            return;
+        };
+        if self.last_sent.as_ref() != Some(&line) {
+            let _ = self.snapshot_sender.send(line.clone());
+            self.last_sent = Some(line);
+        }
+    }
+}
+
+/// Start listening for IPC clients of the config at `config_path`. This spawns one thread to accept connections,
+/// another to send snapshots out, and one more per client to do the writing.
+pub fn spawn_worker(config_path: &Path) -> io::Result<IpcWorker> {
+    let listener = IpcListener::bind(config_path)?;
+    let clients = Arc::new(Mutex::new(Clients::default()));
+    let (snapshot_sender, snapshot_receiver) = mpsc::channel::<String>();
+
+    let accept_clients = clients.clone();
+    thread::Builder::new()
+        .name("ipc-listener".to_string())
+        .spawn(move || loop {
+            // a failed accept usually only affects that one client, so keep listening, just not in a tight loop
+            let sender = match listener.accept().and_then(spawn_client) {
+                Ok(sender) => sender,
+                Err(_e) => {
+                    debug_println!("failed to accept IPC client: {_e}");
+                    thread::sleep(ACCEPT_RETRY_DELAY);
+                    continue;
+                }
+            };
+            accept_clients.lock().unwrap().add(sender);
+        })?;
+
+    thread::Builder::new()
+        .name("ipc-worker".to_string())
+        .spawn(move || {
+            // ends once the IpcWorker is dropped
+            while let Ok(line) = snapshot_receiver.recv() {
+                let mut clients = clients.lock().unwrap();
+                clients.broadcast(&line);
+                clients.latest = Some(line);
+            }
+        })?;
+
+    Ok(IpcWorker {
+        snapshot_sender,
+        last_sent: None,
+    })
+}
+
+#[cfg(test)]
+mod test_ipc {
+    use serde_json::Value;
+
+    use super::*;
+
+    #[test]
+    fn test_snapshot_json() {
+        let settings = PersistedSettings::default();
+        let snapshot = Snapshot {
+            visible: true,
+            render_mode: RenderMode::Crosshair,
+            settings: &settings,
+        };
+        let line = snapshot.to_json_line().unwrap();
+        assert!(line.ends_with('\n'));
+        assert_eq!(line.matches('\n').count(), 1);
+
+        let json: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(json["visible"], Value::Bool(true));
+        assert_eq!(json["render_mode"], "Crosshair");
+        assert_eq!(json["settings"]["window_width"], settings.window_width);
+        assert_eq!(json["settings"]["color"], "B2FF0000");
+        assert_eq!(json["settings"]["ipc_enabled"], Value::Bool(false));
+    }
+
+    #[test]
+    fn test_socket_path() {
+        assert_eq!(
+            socket_path(Path::new(
+                "/home/me/.config/simple-crosshair-overlay/config.toml"
+            )),
+            Path::new("/home/me/.config/simple-crosshair-overlay/config.sock")
+        );
+        assert_eq!(
+            socket_path(Path::new("other.toml")),
+            Path::new("other.sock")
+        );
+    }
+
+    #[test]
+    fn test_pipe_name() {
+        assert_eq!(
+            pipe_name(Path::new(r"C:\Users\me\config.toml")),
+            r"\\.\pipe\simple-crosshair-overlay/C:/Users/me/config.toml"
+        );
+    }
+
+    #[test]
+    fn test_broadcast_drops_closed_clients() {
+        let mut clients = Clients::default();
+        let (open, _open_receiver) = mpsc::sync_channel(CLIENT_BACKLOG);
+        let (closed, closed_receiver) = mpsc::sync_channel(CLIENT_BACKLOG);
+        drop(closed_receiver);
+        clients.add(open);
+        clients.add(closed);
+        clients.broadcast("{}\n");
+        assert_eq!(clients.senders.len(), 1);
+    }
+
+    /// a client that stops reading gets dropped once its backlog fills up, instead of blocking everyone
+    #[test]
+    fn test_broadcast_drops_stalled_clients() {
+        let mut clients = Clients::default();
+        let (stalled, _stalled_receiver) = mpsc::sync_channel(CLIENT_BACKLOG);
+        clients.add(stalled);
+        for _ in 0..CLIENT_BACKLOG {
+            clients.broadcast("{}\n");
+        }
+        assert_eq!(clients.senders.len(), 1);
+        clients.broadcast("{}\n");
+        assert!(clients.senders.is_empty());
+    }
+
+    /// new clients start with the latest snapshot, then get every line after it in order
+    #[test]
+    fn test_client_gets_lines_in_order() {
+        struct Forward(mpsc::Sender<Vec<u8>>);
+        impl Write for Forward {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.0
+                    .send(buf.to_vec())
+                    .map_err(|_| io::ErrorKind::BrokenPipe)?;
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let (written_sender, written) = mpsc::channel();
+        let mut clients = Clients {
+            latest: Some("a\n".to_string()),
+            ..Default::default()
+        };
+        clients.add(spawn_client(Box::new(Forward(written_sender))).unwrap());
+        clients.broadcast("b\n");
+        clients.broadcast("c\n");
+        let timeout = Duration::from_secs(30);
+        for expected in ["a\n", "b\n", "c\n"] {
+            assert_eq!(written.recv_timeout(timeout).unwrap(), expected.as_bytes());
+        }
+    }
+}
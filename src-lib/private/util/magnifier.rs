@@ -0,0 +1,190 @@
+// This file is part of simple-crosshair-overlay and is licenced under the GNU GPL v3.0.
+// See LICENSE file for full text.
+// Copyright © 2023 Michael Ripley
+
+//! Math for the adjust mode magnifier, which shows a zoomed view of whatever is under the crosshair.
+//!
+//! The magnifier view gets drawn in the top-left corner of the overlay. To make room for it the window is padded
+//! equally on all sides, which keeps the crosshair exactly where it was.
+
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+
+use crate::private::util::image::Color;
+
+/// side-length of the square screen region that gets sampled
+pub const MAGNIFIER_SAMPLE_SIZE: u32 = 16;
+/// how many view pixels each sampled pixel becomes
+pub const MAGNIFIER_ZOOM: u32 = 6;
+/// side-length of the square magnified view
+pub const MAGNIFIER_VIEW_SIZE: u32 = MAGNIFIER_SAMPLE_SIZE * MAGNIFIER_ZOOM;
+/// length of the tick marks on the view's frame that point at the crosshair center
+const TICK_LENGTH: usize = 4;
+
+/// top-left corner of the screen region to sample so that it's centered on `center`
+pub fn sample_origin(center: PhysicalPosition<i32>, sample_size: u32) -> PhysicalPosition<i32> {
+    let half = (sample_size / 2) as i32;
+    PhysicalPosition::new(center.x - half, center.y - half)
+}
+
+/// the sampled pixel shown at a given pixel of the magnified view
+#[inline(always)]
+pub fn view_to_sample(view_x: usize, view_y: usize, zoom: usize) -> (usize, usize) {
+    (view_x / zoom, view_y / zoom)
+}
+
+/// window size needed to fit both the content and the magnifier view without moving the content's center
+pub fn padded_size(content: PhysicalSize<u32>, view_size: u32) -> PhysicalSize<u32> {
+    PhysicalSize::new(
+        content.width.saturating_add(view_size * 2),
+        content.height.saturating_add(view_size * 2),
+    )
+}
+
+/// Draw a nearest-neighbor upscale of `sample` into the top-left corner of `buffer`, with a frame in `color` so it
+/// stands out from whatever is behind the overlay.
+pub fn draw_magnified(
+    buffer: &mut [u32],
+    buffer_width: usize,
+    sample: &[u32],
+    sample_size: usize,
+    zoom: usize,
+    color: Color,
+) {
+    let color = color.argb();
+    let view_size = sample_size * zoom;
+    for view_y in 0..view_size {
+        let row = &mut buffer[view_y * buffer_width..view_y * buffer_width + view_size];
+        for (view_x, pixel) in row.iter_mut().enumerate() {
+            let (sample_x, sample_y) = view_to_sample(view_x, view_y, zoom);
+            *pixel = sample[sample_y * sample_size + sample_x];
+        }
+    }
+
+    // frame
+    let last = view_size - 1;
+    for i in 0..view_size {
+        buffer[i] = color;
+        buffer[last * buffer_width + i] = color;
+        buffer[i * buffer_width] = color;
+        buffer[i * buffer_width + last] = color;
+    }
+
+    // ticks pointing at the center
+    let center = view_size / 2;
+    for i in 0..TICK_LENGTH.min(center) {
+        buffer[i * buffer_width + center] = color;
+        buffer[(last - i) * buffer_width + center] = color;
+        buffer[center * buffer_width + i] = color;
+        buffer[center * buffer_width + last - i] = color;
+    }
+}
+
+/// copy a `source_width` wide image into `buffer` with its top-left corner at `(x, y)`
+pub fn blit(
+    buffer: &mut [u32],
+    buffer_width: usize,
+    source: &[u32],
+    source_width: usize,
+    x: usize,
+    y: usize,
+) {
+    for (row_index, row) in source.chunks_exact(source_width).enumerate() {
+        let start = (y + row_index) * buffer_width + x;
+        buffer[start..start + source_width].copy_from_slice(row);
+    }
+}
+
+#[cfg(test)]
+mod test_magnifier {
+    use super::*;
+
+    #[test]
+    fn test_sample_origin() {
+        assert_eq!(
+            sample_origin(PhysicalPosition::new(100, 50), 16),
+            PhysicalPosition::new(92, 42)
+        );
+        assert_eq!(
+            sample_origin(PhysicalPosition::new(0, 0), 16),
+            PhysicalPosition::new(-8, -8)
+        );
+    }
+
+    #[test]
+    fn test_view_to_sample() {
+        assert_eq!(view_to_sample(0, 0, 6), (0, 0));
+        assert_eq!(view_to_sample(5, 5, 6), (0, 0));
+        assert_eq!(view_to_sample(6, 11, 6), (1, 1));
+        assert_eq!(view_to_sample(95, 12, 6), (15, 2));
+    }
+
+    /// the view must cover the whole sample without running past it
+    #[test]
+    fn test_view_covers_sample() {
+        let zoom = MAGNIFIER_ZOOM as usize;
+        let last = MAGNIFIER_VIEW_SIZE as usize - 1;
+        let last_sample = MAGNIFIER_SAMPLE_SIZE as usize - 1;
+        assert_eq!(view_to_sample(last, last, zoom), (last_sample, last_sample));
+    }
+
+    /// padding must not move the center of the content
+    #[test]
+    fn test_padding_preserves_center() {
+        for content in [
+            PhysicalSize::new(16, 16),
+            PhysicalSize::new(17, 17),
+            PhysicalSize::new(32, 15),
+        ] {
+            let padded = padded_size(content, MAGNIFIER_VIEW_SIZE);
+            assert_eq!(
+                padded.width / 2,
+                content.width / 2 + MAGNIFIER_VIEW_SIZE,
+                "{content:?}"
+            );
+            assert_eq!(
+                padded.height / 2,
+                content.height / 2 + MAGNIFIER_VIEW_SIZE,
+                "{content:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_draw_magnified() {
+        const FRAME: u32 = 0xFFFF0000;
+        let sample_size = 4;
+        let zoom = 3;
+        let buffer_width = 20;
+        let sample: Vec<u32> = (0..(sample_size * sample_size) as u32).collect();
+        let mut buffer = vec![u32::MAX; buffer_width * 15];
+        draw_magnified(
+            &mut buffer,
+            buffer_width,
+            &sample,
+            sample_size,
+            zoom,
+            Color::from_argb(FRAME),
+        );
+
+        // frame corners
+        assert_eq!(buffer[0], FRAME);
+        assert_eq!(buffer[11 * buffer_width + 11], FRAME);
+        // interior pixels come from the right sample
+        assert_eq!(buffer[buffer_width + 1], 0);
+        assert_eq!(buffer[4 * buffer_width + 5], 5);
+        assert_eq!(buffer[10 * buffer_width + 10], 15);
+        // nothing drawn outside of the view
+        assert_eq!(buffer[12], u32::MAX);
+        assert_eq!(buffer[12 * buffer_width], u32::MAX);
+    }
+
+    #[test]
+    fn test_blit() {
+        let mut buffer = vec![0; 5 * 4];
+        blit(&mut buffer, 5, &[1, 2, 3, 4], 2, 2, 1);
+        assert_eq!(
+            buffer,
+            vec![0, 0, 0, 0, 0, 0, 0, 1, 2, 0, 0, 0, 3, 4, 0, 0, 0, 0, 0, 0]
+        );
+    }
+}
@@ -5,6 +5,18 @@
 //! Various utilities
 
 pub mod custom_serializer;
+pub mod diagnostics;
 pub mod dialog;
+pub mod geometry;
+pub mod ghost;
+pub mod history;
+pub mod icon;
 pub mod image;
+pub mod ipc;
+pub mod magnifier;
+pub mod monitor;
 pub mod numeric;
+pub mod osd;
+#[cfg(feature = "remote-config")]
+pub mod remote_config;
+pub mod timing;
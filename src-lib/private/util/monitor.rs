@@ -0,0 +1,920 @@
+// This file is part of simple-crosshair-overlay and is licenced under the GNU GPL v3.0.
+// See LICENSE file for full text.
+// Copyright © 2023 Michael Ripley
+
+//! Plain monitor descriptions, so monitor logic can be tested without a real display attached
+
+use std::fmt::{self, Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::monitor::MonitorHandle;
+
+use crate::private::settings::CenterRounding;
+use crate::private::util::image;
+
+/// The parts of a monitor we care about
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MonitorInfo {
+    pub name: Option<String>,
+    pub position: PhysicalPosition<i32>,
+    pub size: PhysicalSize<u32>,
+}
+
+impl From<&MonitorHandle> for MonitorInfo {
+    fn from(monitor: &MonitorHandle) -> Self {
+        MonitorInfo {
+            name: monitor.name(),
+            position: monitor.position(),
+            size: monitor.size(),
+        }
+    }
+}
+
+/// An entry in the `monitors` config list. Either a 1-indexed monitor number or a monitor name.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MonitorSelector {
+    Index(u32),
+    Name(String),
+}
+
+impl Display for MonitorSelector {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            MonitorSelector::Index(index) => write!(f, "monitor {index}"),
+            MonitorSelector::Name(name) => write!(f, "monitor \"{name}\""),
+        }
+    }
+}
+
+/// Result of matching the configured monitor selectors against the monitors that actually exist
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct MonitorSelection {
+    /// 0-indexed monitors to show the overlay on, in config order with duplicates removed
+    pub indices: Vec<usize>,
+    /// selectors that didn't match any monitor
+    pub invalid: Vec<MonitorSelector>,
+}
+
+/// Figure out which monitors the selectors refer to. Names are matched exactly, as that's the only way
+/// to tell apart two monitors of the same model.
+pub fn resolve_monitor_selection(
+    selectors: &[MonitorSelector],
+    monitors: &[MonitorInfo],
+) -> MonitorSelection {
+    let mut selection = MonitorSelection::default();
+    for selector in selectors {
+        let index = match selector {
+            MonitorSelector::Index(index) => index
+                .checked_sub(1)
+                .and_then(|index| usize::try_from(index).ok())
+                .filter(|&index| index < monitors.len()),
+            MonitorSelector::Name(name) => monitors
+                .iter()
+                .position(|monitor| monitor.name.as_deref() == Some(name.as_str())),
+        };
+        match index {
+            Some(index) => {
+                if !selection.indices.contains(&index) {
+                    selection.indices.push(index);
+                }
+            }
+            None => selection.invalid.push(selector.clone()),
+        }
+    }
+    selection
+}
+
+/// Find the monitor called `name`, or use the 0-indexed `fallback` if there's no name or no monitor by that name is
+/// connected. Names are matched exactly, like in [`resolve_monitor_selection`].
+pub fn resolve_named_monitor(
+    name: Option<&str>,
+    fallback: usize,
+    monitors: &[MonitorInfo],
+) -> usize {
+    name.and_then(|name| {
+        monitors
+            .iter()
+            .position(|monitor| monitor.name.as_deref() == Some(name))
+    })
+    .unwrap_or(fallback)
+}
+
+/// Pick the monitor to put a window on: the 0-indexed `index` if it exists, otherwise the primary monitor, otherwise
+/// whichever monitor comes first. `None` only if there are no monitors at all, which happens on some headless and
+/// remote desktop setups.
+pub fn pick_monitor(
+    monitors: impl IntoIterator<Item = MonitorInfo>,
+    index: usize,
+    primary: Option<MonitorInfo>,
+) -> Option<MonitorInfo> {
+    let mut monitors = monitors.into_iter();
+    let first = monitors.next();
+    let indexed = match index {
+        0 => first.clone(),
+        index => monitors.nth(index - 1),
+    };
+    indexed.or(primary).or(first)
+}
+
+/// The order to create overlay windows in, which is also the order they get raised to always-on-top. Windows are
+/// created in config order, except that the `top` overlay goes last so it ends up above the rest. `top` is ignored if
+/// it isn't one of the selected monitors.
+pub fn stacking_order(
+    selection: &MonitorSelection,
+    top: Option<&MonitorSelector>,
+    monitors: &[MonitorInfo],
+) -> Vec<usize> {
+    let mut order = selection.indices.clone();
+    let top = top.and_then(|top| {
+        resolve_monitor_selection(std::slice::from_ref(top), monitors)
+            .indices
+            .first()
+            .copied()
+    });
+    if let Some(position) = top.and_then(|top| order.iter().position(|&index| index == top)) {
+        let top = order.remove(position);
+        order.push(top);
+    }
+    order
+}
+
+/// Common spots on a monitor the crosshair can be snapped to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnapAnchor {
+    Center,
+    Top,
+    Bottom,
+    Left,
+    Right,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl SnapAnchor {
+    pub const ALL: [SnapAnchor; 9] = [
+        SnapAnchor::Center,
+        SnapAnchor::Top,
+        SnapAnchor::Bottom,
+        SnapAnchor::Left,
+        SnapAnchor::Right,
+        SnapAnchor::TopLeft,
+        SnapAnchor::TopRight,
+        SnapAnchor::BottomLeft,
+        SnapAnchor::BottomRight,
+    ];
+
+    /// what to show for this anchor in the tray menu
+    pub fn label(self) -> &'static str {
+        match self {
+            SnapAnchor::Center => "Center",
+            SnapAnchor::Top => "Top Center",
+            SnapAnchor::Bottom => "Bottom Center",
+            SnapAnchor::Left => "Left Center",
+            SnapAnchor::Right => "Right Center",
+            SnapAnchor::TopLeft => "Top Left",
+            SnapAnchor::TopRight => "Top Right",
+            SnapAnchor::BottomLeft => "Bottom Left",
+            SnapAnchor::BottomRight => "Bottom Right",
+        }
+    }
+
+    /// The pixel offset from the centered position that puts the window at this anchor, with the window kept fully on
+    /// the monitor. This is the inverse of the centering math used to place the window.
+    pub fn offset(
+        self,
+        monitor: PhysicalSize<u32>,
+        window: PhysicalSize<u32>,
+        rounding: CenterRounding,
+    ) -> (i32, i32) {
+        let monitor_width = i32::try_from(monitor.width).unwrap_or(i32::MAX);
+        let monitor_height = i32::try_from(monitor.height).unwrap_or(i32::MAX);
+        let window_width = i32::try_from(window.width).unwrap_or(i32::MAX);
+        let window_height = i32::try_from(window.height).unwrap_or(i32::MAX);
+        let (center_x, center_y) =
+            image::rounded_rectangle_center(0, 0, monitor_width, monitor_height, rounding);
+        let centered_x = center_x - window_width / 2;
+        let centered_y = center_y - window_height / 2;
+
+        let (target_x, target_y) = match self {
+            SnapAnchor::Center => (centered_x, centered_y),
+            SnapAnchor::Top => (centered_x, 0),
+            SnapAnchor::Bottom => (centered_x, monitor_height - window_height),
+            SnapAnchor::Left => (0, centered_y),
+            SnapAnchor::Right => (monitor_width - window_width, centered_y),
+            SnapAnchor::TopLeft => (0, 0),
+            SnapAnchor::TopRight => (monitor_width - window_width, 0),
+            SnapAnchor::BottomLeft => (0, monitor_height - window_height),
+            SnapAnchor::BottomRight => {
+                (monitor_width - window_width, monitor_height - window_height)
+            }
+        };
+        (target_x - centered_x, target_y - centered_y)
+    }
+}
+
+/// prefix of the tray menu item IDs used for picking a monitor
+const MONITOR_MENU_ID_PREFIX: &str = "monitor-";
+
+/// The tray menu item ID for the 0-indexed monitor. IDs are fixed so that the menu can be rebuilt on another thread.
+pub fn monitor_menu_id(index: usize) -> String {
+    format!("{MONITOR_MENU_ID_PREFIX}{index}")
+}
+
+/// What to show for the 0-indexed monitor in the tray menu
+pub fn monitor_menu_label(index: usize, monitor: &MonitorInfo) -> String {
+    let name = monitor.name.as_deref().unwrap_or("Unknown");
+    format!(
+        "{}: {name} ({}×{})",
+        index + 1,
+        monitor.size.width,
+        monitor.size.height
+    )
+}
+
+/// Figure out which 0-indexed monitor a tray menu item ID refers to. Returns `None` for IDs that aren't monitor items,
+/// or refer to a monitor that no longer exists.
+pub fn monitor_from_menu_id(id: &str, monitors: &[MonitorInfo]) -> Option<usize> {
+    id.strip_prefix(MONITOR_MENU_ID_PREFIX)?
+        .parse::<usize>()
+        .ok()
+        .filter(|&index| index < monitors.len())
+}
+
+/// A short key identifying a set of monitors by their names and resolutions, so a monitor choice can be remembered
+/// separately for e.g. docked and undocked setups. Positions are left out, as they tend to shift around without the
+/// setup really changing. This uses FNV-1a instead of std's hasher, as the key ends up in the config file and needs to
+/// stay the same between builds.
+pub fn layout_key(monitors: &[MonitorInfo]) -> String {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+
+    let mut hash = FNV_OFFSET_BASIS;
+    let mut write = |bytes: &[u8]| {
+        for &byte in bytes {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    };
+    for monitor in monitors {
+        // the separator keeps e.g. "ab" + "c" from hashing the same as "a" + "bc"
+        write(monitor.name.as_deref().unwrap_or("").as_bytes());
+        write(&[0]);
+        write(&monitor.size.width.to_le_bytes());
+        write(&monitor.size.height.to_le_bytes());
+    }
+    format!("{hash:016x}")
+}
+
+/// Make sure a window at `position` is actually visible somewhere on the virtual desktop. If the center of the window
+/// already lies within any monitor it's left alone. Otherwise it gets moved as little as possible to fit within the
+/// nearest monitor. A window that's bigger than that monitor gets centered on it instead.
+///
+/// Returns `None` if the position was fine as-is, or there are no monitors to compare against.
+pub fn keep_on_desktop(
+    position: PhysicalPosition<i32>,
+    window_size: PhysicalSize<u32>,
+    monitors: &[MonitorInfo],
+) -> Option<PhysicalPosition<i32>> {
+    // i64 so that huge windows and far-flung monitors can't overflow
+    let center_x = position.x as i64 + window_size.width as i64 / 2;
+    let center_y = position.y as i64 + window_size.height as i64 / 2;
+
+    let nearest = monitors
+        .iter()
+        .min_by_key(|monitor| distance_squared(monitor, center_x, center_y))?;
+    if distance_squared(nearest, center_x, center_y) == 0 {
+        return None;
+    }
+
+    let x = clamp_span(
+        position.x as i64,
+        window_size.width as i64,
+        nearest.position.x as i64,
+        nearest.size.width as i64,
+    );
+    let y = clamp_span(
+        position.y as i64,
+        window_size.height as i64,
+        nearest.position.y as i64,
+        nearest.size.height as i64,
+    );
+    Some(PhysicalPosition::new(x, y))
+}
+
+/// the 0-indexed monitor that `point` is on, e.g. the one under the cursor
+pub fn monitor_at(point: PhysicalPosition<i32>, monitors: &[MonitorInfo]) -> Option<usize> {
+    monitors
+        .iter()
+        .position(|monitor| distance_squared(monitor, point.x as i64, point.y as i64) == 0)
+}
+
+/// squared distance from a point to the closest point in the monitor, which is zero if the point is inside it
+fn distance_squared(monitor: &MonitorInfo, x: i64, y: i64) -> i64 {
+    let left = monitor.position.x as i64;
+    let top = monitor.position.y as i64;
+    let right = left + monitor.size.width as i64;
+    let bottom = top + monitor.size.height as i64;
+    let dx = if x < left {
+        left - x
+    } else {
+        (x - (right - 1)).max(0)
+    };
+    let dy = if y < top {
+        top - y
+    } else {
+        (y - (bottom - 1)).max(0)
+    };
+    dx.saturating_mul(dx).saturating_add(dy.saturating_mul(dy))
+}
+
+/// fit a span of `length` starting at `start` into the span of `bound_length` starting at `bound_start`
+fn clamp_span(start: i64, length: i64, bound_start: i64, bound_length: i64) -> i32 {
+    let clamped = if length > bound_length {
+        bound_start - (length - bound_length) / 2
+    } else {
+        start.clamp(bound_start, bound_start + bound_length - length)
+    };
+    clamped.clamp(i32::MIN as i64, i32::MAX as i64) as i32
+}
+
+/// Lay out monitors as a plain text table for `--list-monitors`. Each monitor is paired with its scale factor, which
+/// [`MonitorInfo`] doesn't keep as nothing else needs it. Monitor numbers are 1-indexed, same as the config.
+pub fn format_monitor_table(monitors: &[(MonitorInfo, f64)]) -> String {
+    const HEADER: [&str; 5] = ["MONITOR", "NAME", "POSITION", "SIZE", "SCALE"];
+    let rows: Vec<[String; 5]> = monitors
+        .iter()
+        .enumerate()
+        .map(|(index, (monitor, scale_factor))| {
+            [
+                (index + 1).to_string(),
+                monitor
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| "Unknown".to_string()),
+                format!("{}, {}", monitor.position.x, monitor.position.y),
+                format!("{}×{}", monitor.size.width, monitor.size.height),
+                format!("{scale_factor}"),
+            ]
+        })
+        .collect();
+
+    let mut widths = HEADER.map(|header| header.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut table = String::new();
+    let header = HEADER.map(str::to_string);
+    for row in std::iter::once(&header).chain(&rows) {
+        let line = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        table.push_str(line.trim_end());
+        table.push('\n');
+    }
+    table
+}
+
+#[cfg(test)]
+mod test_monitor_selection {
+    use super::*;
+
+    fn monitor(name: &str, x: i32) -> MonitorInfo {
+        MonitorInfo {
+            name: Some(name.to_string()),
+            position: PhysicalPosition::new(x, 0),
+            size: PhysicalSize::new(1920, 1080),
+        }
+    }
+
+    fn triple_monitors() -> Vec<MonitorInfo> {
+        vec![
+            monitor("LEFT", -1920),
+            monitor("CENTER", 0),
+            monitor("RIGHT", 1920),
+        ]
+    }
+
+    #[test]
+    fn test_empty() {
+        let selection = resolve_monitor_selection(&[], &triple_monitors());
+        assert_eq!(selection, MonitorSelection::default());
+    }
+
+    #[test]
+    fn test_indices() {
+        let selectors = [MonitorSelector::Index(2), MonitorSelector::Index(3)];
+        let selection = resolve_monitor_selection(&selectors, &triple_monitors());
+        assert_eq!(selection.indices, vec![1, 2]);
+        assert!(selection.invalid.is_empty());
+    }
+
+    #[test]
+    fn test_names() {
+        let selectors = [
+            MonitorSelector::Name("CENTER".to_string()),
+            MonitorSelector::Name("LEFT".to_string()),
+        ];
+        let selection = resolve_monitor_selection(&selectors, &triple_monitors());
+        assert_eq!(selection.indices, vec![1, 0]);
+        assert!(selection.invalid.is_empty());
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let selectors = [
+            MonitorSelector::Index(2),
+            MonitorSelector::Name("CENTER".to_string()),
+            MonitorSelector::Index(2),
+        ];
+        let selection = resolve_monitor_selection(&selectors, &triple_monitors());
+        assert_eq!(selection.indices, vec![1]);
+        assert!(selection.invalid.is_empty());
+    }
+
+    #[test]
+    fn test_invalid() {
+        let selectors = [
+            MonitorSelector::Index(0),
+            MonitorSelector::Index(1),
+            MonitorSelector::Index(4),
+            MonitorSelector::Name("center".to_string()),
+        ];
+        let selection = resolve_monitor_selection(&selectors, &triple_monitors());
+        assert_eq!(selection.indices, vec![0]);
+        assert_eq!(
+            selection.invalid,
+            vec![
+                MonitorSelector::Index(0),
+                MonitorSelector::Index(4),
+                MonitorSelector::Name("center".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unnamed_monitor() {
+        let mut monitors = triple_monitors();
+        monitors[0].name = None;
+        let selectors = [MonitorSelector::Name("LEFT".to_string())];
+        let selection = resolve_monitor_selection(&selectors, &monitors);
+        assert!(selection.indices.is_empty());
+        assert_eq!(selection.invalid.len(), 1);
+    }
+
+    #[test]
+    fn test_menu_items() {
+        let monitors = triple_monitors();
+        for index in 0..monitors.len() {
+            let id = monitor_menu_id(index);
+            assert_eq!(monitor_from_menu_id(&id, &monitors), Some(index));
+        }
+        assert_eq!(monitor_menu_label(2, &monitors[2]), "3: RIGHT (1920×1080)");
+    }
+
+    #[test]
+    fn test_menu_items_invalid() {
+        let monitors = triple_monitors();
+        assert_eq!(monitor_from_menu_id(&monitor_menu_id(3), &monitors), None);
+        assert_eq!(monitor_from_menu_id("monitor-", &monitors), None);
+        assert_eq!(monitor_from_menu_id("monitor--1", &monitors), None);
+        assert_eq!(monitor_from_menu_id("1", &monitors), None);
+        assert_eq!(monitor_from_menu_id("", &monitors), None);
+
+        // a monitor was unplugged since the menu was built
+        assert_eq!(
+            monitor_from_menu_id(&monitor_menu_id(2), &monitors[..2]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_menu_label_unnamed() {
+        let mut monitors = triple_monitors();
+        monitors[0].name = None;
+        assert_eq!(
+            monitor_menu_label(0, &monitors[0]),
+            "1: Unknown (1920×1080)"
+        );
+    }
+
+    #[test]
+    fn test_stacking_order() {
+        let monitors = triple_monitors();
+        let selection = resolve_monitor_selection(
+            &[
+                MonitorSelector::Index(3),
+                MonitorSelector::Index(1),
+                MonitorSelector::Index(2),
+            ],
+            &monitors,
+        );
+        assert_eq!(stacking_order(&selection, None, &monitors), vec![2, 0, 1]);
+        assert_eq!(
+            stacking_order(
+                &selection,
+                Some(&MonitorSelector::Name("RIGHT".to_string())),
+                &monitors
+            ),
+            vec![0, 1, 2]
+        );
+        assert_eq!(
+            stacking_order(&selection, Some(&MonitorSelector::Index(1)), &monitors),
+            vec![2, 1, 0]
+        );
+
+        // a top overlay that isn't shown anywhere changes nothing
+        let selection = resolve_monitor_selection(&[MonitorSelector::Index(2)], &monitors);
+        assert_eq!(
+            stacking_order(&selection, Some(&MonitorSelector::Index(3)), &monitors),
+            vec![1]
+        );
+        assert_eq!(
+            stacking_order(&selection, Some(&MonitorSelector::Index(9)), &monitors),
+            vec![1]
+        );
+    }
+
+    #[test]
+    fn test_deserialize() {
+        #[derive(Deserialize)]
+        struct Wrapper {
+            monitors: Vec<MonitorSelector>,
+        }
+        let wrapper: Wrapper = toml::from_str(r#"monitors = [2, "DELL U2719D"]"#).unwrap();
+        assert_eq!(
+            wrapper.monitors,
+            vec![
+                MonitorSelector::Index(2),
+                MonitorSelector::Name("DELL U2719D".to_string()),
+            ]
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_resolve_named_monitor {
+    use super::*;
+
+    fn monitor(name: Option<&str>) -> MonitorInfo {
+        MonitorInfo {
+            name: name.map(str::to_string),
+            position: PhysicalPosition::new(0, 0),
+            size: PhysicalSize::new(1920, 1080),
+        }
+    }
+
+    fn monitors() -> Vec<MonitorInfo> {
+        vec![monitor(Some("LEFT")), monitor(None), monitor(Some("RIGHT"))]
+    }
+
+    #[test]
+    fn test_by_name() {
+        assert_eq!(resolve_named_monitor(Some("RIGHT"), 0, &monitors()), 2);
+        assert_eq!(resolve_named_monitor(Some("LEFT"), 2, &monitors()), 0);
+    }
+
+    /// e.g. the OS put the monitors in a different order since the name was saved
+    #[test]
+    fn test_reordered() {
+        let mut reordered = monitors();
+        reordered.reverse();
+        assert_eq!(resolve_named_monitor(Some("RIGHT"), 2, &reordered), 0);
+    }
+
+    #[test]
+    fn test_fallback() {
+        assert_eq!(resolve_named_monitor(None, 1, &monitors()), 1);
+        assert_eq!(resolve_named_monitor(Some("UNPLUGGED"), 1, &monitors()), 1);
+        // names are matched exactly
+        assert_eq!(resolve_named_monitor(Some("left"), 1, &monitors()), 1);
+        assert_eq!(resolve_named_monitor(Some("LEFT"), 1, &[]), 1);
+    }
+}
+
+#[cfg(test)]
+mod test_pick_monitor {
+    use super::*;
+
+    fn monitor(name: &str, x: i32) -> MonitorInfo {
+        MonitorInfo {
+            name: Some(name.to_string()),
+            position: PhysicalPosition::new(x, 0),
+            size: PhysicalSize::new(1920, 1080),
+        }
+    }
+
+    fn monitors() -> Vec<MonitorInfo> {
+        vec![
+            monitor("LEFT", 0),
+            monitor("MIDDLE", 1920),
+            monitor("RIGHT", 3840),
+        ]
+    }
+
+    #[test]
+    fn test_indexed() {
+        assert_eq!(pick_monitor(monitors(), 0, None), Some(monitor("LEFT", 0)));
+        assert_eq!(
+            pick_monitor(monitors(), 2, Some(monitor("MIDDLE", 1920))),
+            Some(monitor("RIGHT", 3840))
+        );
+    }
+
+    #[test]
+    fn test_primary() {
+        assert_eq!(
+            pick_monitor(monitors(), 3, Some(monitor("MIDDLE", 1920))),
+            Some(monitor("MIDDLE", 1920))
+        );
+    }
+
+    #[test]
+    fn test_first() {
+        assert_eq!(pick_monitor(monitors(), 3, None), Some(monitor("LEFT", 0)));
+        assert_eq!(
+            pick_monitor(monitors(), usize::MAX, None),
+            Some(monitor("LEFT", 0))
+        );
+    }
+
+    #[test]
+    fn test_empty() {
+        assert_eq!(pick_monitor(Vec::new(), 0, None), None);
+        assert_eq!(pick_monitor(Vec::new(), 2, None), None);
+    }
+
+    /// some platforms report a primary monitor even when they won't list any
+    #[test]
+    fn test_primary_only() {
+        assert_eq!(
+            pick_monitor(Vec::new(), 0, Some(monitor("LEFT", 0))),
+            Some(monitor("LEFT", 0))
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_monitor_at {
+    use super::*;
+
+    fn monitors() -> Vec<MonitorInfo> {
+        [(0, 1920), (1920, 2560)]
+            .into_iter()
+            .map(|(x, width)| MonitorInfo {
+                name: None,
+                position: PhysicalPosition::new(x, 0),
+                size: PhysicalSize::new(width, 1080),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_monitor_at() {
+        let monitors = monitors();
+        assert_eq!(monitor_at(PhysicalPosition::new(0, 0), &monitors), Some(0));
+        assert_eq!(
+            monitor_at(PhysicalPosition::new(1919, 1079), &monitors),
+            Some(0)
+        );
+        assert_eq!(
+            monitor_at(PhysicalPosition::new(1920, 0), &monitors),
+            Some(1)
+        );
+        assert_eq!(
+            monitor_at(PhysicalPosition::new(1920, 1080), &monitors),
+            None
+        );
+        assert_eq!(monitor_at(PhysicalPosition::new(-1, 0), &monitors), None);
+        assert_eq!(monitor_at(PhysicalPosition::new(0, 0), &[]), None);
+    }
+}
+
+#[cfg(test)]
+mod test_keep_on_desktop {
+    use super::*;
+
+    const WINDOW: PhysicalSize<u32> = PhysicalSize::new(16, 16);
+
+    fn monitor(x: i32, y: i32, width: u32, height: u32) -> MonitorInfo {
+        MonitorInfo {
+            name: None,
+            position: PhysicalPosition::new(x, y),
+            size: PhysicalSize::new(width, height),
+        }
+    }
+
+    /// a primary monitor with a secondary one up and to the left of it
+    fn negative_origin_monitors() -> Vec<MonitorInfo> {
+        vec![monitor(0, 0, 1920, 1080), monitor(-1280, -1024, 1280, 1024)]
+    }
+
+    #[test]
+    fn test_no_monitors() {
+        assert_eq!(
+            keep_on_desktop(PhysicalPosition::new(-50_000, 0), WINDOW, &[]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_on_screen_untouched() {
+        let monitors = negative_origin_monitors();
+        // middle of the primary
+        assert_eq!(
+            keep_on_desktop(PhysicalPosition::new(952, 532), WINDOW, &monitors),
+            None
+        );
+        // middle of the negative-origin secondary
+        assert_eq!(
+            keep_on_desktop(PhysicalPosition::new(-648, -520), WINDOW, &monitors),
+            None
+        );
+        // hanging partially off an edge is fine as long as the center is visible
+        assert_eq!(
+            keep_on_desktop(PhysicalPosition::new(-8, 500), WINDOW, &monitors),
+            None
+        );
+    }
+
+    /// the gap below the secondary monitor and left of the primary doesn't exist on any screen
+    #[test]
+    fn test_gap_between_monitors() {
+        let monitors = negative_origin_monitors();
+        let position = keep_on_desktop(PhysicalPosition::new(-200, 500), WINDOW, &monitors);
+        assert_eq!(position, Some(PhysicalPosition::new(0, 500)));
+
+        let position = keep_on_desktop(PhysicalPosition::new(-700, 40), WINDOW, &monitors);
+        assert_eq!(position, Some(PhysicalPosition::new(-700, -16)));
+    }
+
+    #[test]
+    fn test_far_off_desktop() {
+        let monitors = negative_origin_monitors();
+        let position = keep_on_desktop(PhysicalPosition::new(-90_000, -90_000), WINDOW, &monitors);
+        assert_eq!(position, Some(PhysicalPosition::new(-1280, -1024)));
+
+        let position = keep_on_desktop(
+            PhysicalPosition::new(i32::MAX, i32::MAX),
+            PhysicalSize::new(u32::MAX, u32::MAX),
+            &monitors,
+        );
+        assert!(position.is_some());
+    }
+
+    /// a window that can't fit on the monitor at all gets centered on it
+    #[test]
+    fn test_tiny_monitor() {
+        let monitors = [monitor(-100, -100, 10, 10)];
+        let position = keep_on_desktop(PhysicalPosition::new(500, 500), WINDOW, &monitors);
+        assert_eq!(position, Some(PhysicalPosition::new(-103, -103)));
+    }
+}
+
+#[cfg(test)]
+mod test_snap_anchor {
+    use super::*;
+
+    const HD: PhysicalSize<u32> = PhysicalSize::new(1920, 1080);
+
+    #[test]
+    fn test_offsets() {
+        let window = PhysicalSize::new(16, 16);
+        let offset = |anchor: SnapAnchor| anchor.offset(HD, window, CenterRounding::Floor);
+        assert_eq!(offset(SnapAnchor::Center), (0, 0));
+        assert_eq!(offset(SnapAnchor::Top), (0, -532));
+        assert_eq!(offset(SnapAnchor::Bottom), (0, 532));
+        assert_eq!(offset(SnapAnchor::Left), (-952, 0));
+        assert_eq!(offset(SnapAnchor::Right), (952, 0));
+        assert_eq!(offset(SnapAnchor::TopLeft), (-952, -532));
+        assert_eq!(offset(SnapAnchor::TopRight), (952, -532));
+        assert_eq!(offset(SnapAnchor::BottomLeft), (-952, 532));
+        assert_eq!(offset(SnapAnchor::BottomRight), (952, 532));
+    }
+
+    /// odd sizes don't center evenly, but the edges still have to line up exactly
+    #[test]
+    fn test_odd_sizes() {
+        let monitor = PhysicalSize::new(1921, 1081);
+        let window = PhysicalSize::new(15, 15);
+        let (center_x, center_y) = (1921 / 2 - 15 / 2, 1081 / 2 - 15 / 2);
+        for anchor in SnapAnchor::ALL {
+            let (dx, dy) = anchor.offset(monitor, window, CenterRounding::Floor);
+            let (x, y) = (center_x + dx, center_y + dy);
+            assert!((0..=1921 - 15).contains(&x), "{anchor:?} x = {x}");
+            assert!((0..=1081 - 15).contains(&y), "{anchor:?} y = {y}");
+        }
+        assert_eq!(
+            SnapAnchor::BottomRight.offset(monitor, window, CenterRounding::Floor),
+            (953, 533)
+        );
+        assert_eq!(
+            SnapAnchor::TopLeft.offset(monitor, window, CenterRounding::Floor),
+            (-953, -533)
+        );
+
+        // rounding the other way moves the center, so the offsets to reach the same edges shift to match
+        assert_eq!(
+            SnapAnchor::BottomRight.offset(monitor, window, CenterRounding::Ceil),
+            (952, 532)
+        );
+        assert_eq!(
+            SnapAnchor::TopLeft.offset(monitor, window, CenterRounding::Ceil),
+            (-954, -534)
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_monitor_table {
+    use super::*;
+
+    #[test]
+    fn test_table() {
+        let monitors = [
+            (
+                MonitorInfo {
+                    name: Some("DELL U2719D".to_string()),
+                    position: PhysicalPosition::new(0, 0),
+                    size: PhysicalSize::new(2560, 1440),
+                },
+                1.25,
+            ),
+            (
+                MonitorInfo {
+                    name: None,
+                    position: PhysicalPosition::new(-1920, 180),
+                    size: PhysicalSize::new(1920, 1080),
+                },
+                1.0,
+            ),
+        ];
+        assert_eq!(
+            format_monitor_table(&monitors),
+            "\
+MONITOR  NAME         POSITION    SIZE       SCALE
+1        DELL U2719D  0, 0        2560×1440  1.25
+2        Unknown      -1920, 180  1920×1080  1
+"
+        );
+    }
+
+    #[test]
+    fn test_no_monitors() {
+        assert_eq!(
+            format_monitor_table(&[]),
+            "MONITOR  NAME  POSITION  SIZE  SCALE\n"
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_layout_key {
+    use super::*;
+
+    fn monitor(name: &str, x: i32, width: u32) -> MonitorInfo {
+        MonitorInfo {
+            name: Some(name.to_string()),
+            position: PhysicalPosition::new(x, 0),
+            size: PhysicalSize::new(width, 1080),
+        }
+    }
+
+    #[test]
+    fn test_ignores_position() {
+        assert_eq!(
+            layout_key(&[monitor("A", 0, 1920)]),
+            layout_key(&[monitor("A", 1920, 1920)])
+        );
+    }
+
+    #[test]
+    fn test_distinguishes_layouts() {
+        let laptop = layout_key(&[monitor("A", 0, 1920)]);
+        assert_ne!(laptop, layout_key(&[monitor("A", 0, 2560)]));
+        assert_ne!(laptop, layout_key(&[monitor("B", 0, 1920)]));
+        assert_ne!(
+            laptop,
+            layout_key(&[monitor("A", 0, 1920), monitor("B", 1920, 1920)])
+        );
+        assert_ne!(
+            layout_key(&[monitor("AB", 0, 1920), monitor("C", 0, 1920)]),
+            layout_key(&[monitor("A", 0, 1920), monitor("BC", 0, 1920)])
+        );
+    }
+
+    /// the key ends up in config files, so it can't change between builds
+    #[test]
+    fn test_stable() {
+        assert_eq!(layout_key(&[]), "cbf29ce484222325");
+    }
+}
@@ -6,6 +6,11 @@
 
 use std::time::Duration;
 
+/// Move a hue in degrees along by `degrees_per_second` for `elapsed`, wrapping around to stay within 0..360
+pub fn step_hue(hue: f32, degrees_per_second: f32, elapsed: Duration) -> f32 {
+    (hue + degrees_per_second * elapsed.as_secs_f32()).rem_euclid(360.0)
+}
+
 pub fn fps_to_tick_interval(fps: u32) -> Duration {
     let millis = 1000.div_ceil_placeholder(fps);
     Duration::from_millis(millis as u64)
@@ -81,6 +86,39 @@ impl DivFloor for i32 {
     }
 }
 
+#[cfg(test)]
+mod test_step_hue {
+    use super::*;
+
+    const TICK: Duration = Duration::from_millis(20);
+
+    fn after_ticks(ticks: u32, degrees_per_second: f32) -> f32 {
+        (0..ticks).fold(0.0, |hue, _| step_hue(hue, degrees_per_second, TICK))
+    }
+
+    #[test]
+    fn test_ticks() {
+        assert_eq!(after_ticks(0, 90.0), 0.0);
+        assert!((after_ticks(1, 90.0) - 1.8).abs() < 1e-4);
+        assert!((after_ticks(50, 90.0) - 90.0).abs() < 1e-3);
+        assert!((after_ticks(150, 90.0) - 270.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_wraps() {
+        // one full turn and then 10 more ticks
+        let hue = after_ticks(210, 90.0);
+        assert!((hue - 18.0).abs() < 1e-2, "{hue}");
+        assert!((step_hue(350.0, 60.0, Duration::from_secs(1)) - 50.0).abs() < 1e-4);
+    }
+
+    /// a negative speed cycles backwards
+    #[test]
+    fn test_backwards() {
+        assert!((step_hue(10.0, -60.0, Duration::from_secs(1)) - 310.0).abs() < 1e-4);
+    }
+}
+
 #[cfg(test)]
 mod test_div_rounding {
     use super::*;
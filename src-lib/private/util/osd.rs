@@ -0,0 +1,294 @@
+// This file is part of simple-crosshair-overlay and is licenced under the GNU GPL v3.0.
+// See LICENSE file for full text.
+// Copyright © 2023 Michael Ripley
+
+//! A tiny on-screen display for confirming hotkey actions that otherwise have no visible effect.
+//!
+//! Messages get drawn below the crosshair with a built-in bitmap font. To make room the window is padded equally on
+//! all sides, which keeps the crosshair exactly where it was.
+
+use std::time::{Duration, Instant};
+
+use winit::dpi::PhysicalSize;
+
+use crate::private::util::image::Color;
+
+/// how long a message stays up, including the fade-out
+pub const OSD_DURATION: Duration = Duration::from_millis(1000);
+/// how long the fade-out at the end of [`OSD_DURATION`] takes
+const OSD_FADE: Duration = Duration::from_millis(300);
+/// how many screen pixels each font pixel becomes
+const OSD_SCALE: u32 = 2;
+/// space between the content and the message
+const OSD_GAP: u32 = 4;
+const GLYPH_WIDTH: u32 = 3;
+const GLYPH_HEIGHT: u32 = 5;
+/// glyph width plus one column of spacing
+const GLYPH_ADVANCE: u32 = GLYPH_WIDTH + 1;
+
+/// The message currently on screen, if any, and how faded out it is
+#[derive(Default)]
+pub struct Osd {
+    message: Option<(String, Instant)>,
+    alpha: u8,
+}
+
+impl Osd {
+    /// Show a message, replacing whatever was up before
+    pub fn show(&mut self, message: impl Into<String>, now: Instant) {
+        self.message = Some((message.into(), now));
+        self.alpha = u8::MAX;
+    }
+
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_ref().map(|(message, _)| message.as_str())
+    }
+
+    /// opacity of the message, which drops to 0 over the last part of [`OSD_DURATION`]
+    pub fn alpha(&self) -> u8 {
+        self.alpha
+    }
+
+    /// Advance the fade-out. Returns `true` if the message needs to be redrawn, either because it faded a bit more or
+    /// because it's gone now.
+    pub fn tick(&mut self, now: Instant) -> bool {
+        let Some((_, shown_at)) = self.message else {
+            return false;
+        };
+        let elapsed = now.saturating_duration_since(shown_at);
+        if elapsed >= OSD_DURATION {
+            self.message = None;
+            self.alpha = 0;
+            return true;
+        }
+
+        let remaining = OSD_DURATION - elapsed;
+        let alpha = if remaining >= OSD_FADE {
+            u8::MAX
+        } else {
+            (u8::MAX as f32 * remaining.as_secs_f32() / OSD_FADE.as_secs_f32()).round() as u8
+        };
+        let changed = alpha != self.alpha;
+        self.alpha = alpha;
+        changed
+    }
+}
+
+/// Each row of a glyph is 3 bits, with the highest bit on the left
+fn glyph(character: char) -> [u8; GLYPH_HEIGHT as usize] {
+    match character.to_ascii_uppercase() {
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b110, 0b001, 0b010, 0b100, 0b111],
+        '3' => [0b110, 0b001, 0b010, 0b001, 0b110],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b110, 0b001, 0b110],
+        '6' => [0b011, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b110],
+        '+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        ',' => [0b000, 0b000, 0b000, 0b010, 0b100],
+        // anything the font doesn't cover, including space, is left blank
+        _ => [0; GLYPH_HEIGHT as usize],
+    }
+}
+
+/// how much room a message takes up on screen
+pub fn text_size(text: &str) -> PhysicalSize<u32> {
+    let characters = text.chars().count() as u32;
+    let width = (characters * GLYPH_ADVANCE).saturating_sub(1);
+    PhysicalSize::new(width * OSD_SCALE, GLYPH_HEIGHT * OSD_SCALE)
+}
+
+/// Window size needed to fit both the content and a message centered below it without moving the content's center
+pub fn padded_size(content: PhysicalSize<u32>, text: PhysicalSize<u32>) -> PhysicalSize<u32> {
+    let (pad_x, pad_y) = padding(content, text);
+    PhysicalSize::new(
+        content.width.saturating_add(pad_x * 2),
+        content.height.saturating_add(pad_y * 2),
+    )
+}
+
+/// where the top-left corner of the content goes in a window of [`padded_size`]
+pub fn content_origin(content: PhysicalSize<u32>, text: PhysicalSize<u32>) -> (usize, usize) {
+    let (pad_x, pad_y) = padding(content, text);
+    (pad_x as usize, pad_y as usize)
+}
+
+/// where the top-left corner of the message goes in a window of [`padded_size`]
+pub fn text_origin(padded: PhysicalSize<u32>, text: PhysicalSize<u32>) -> (usize, usize) {
+    (
+        (padded.width.saturating_sub(text.width) / 2) as usize,
+        padded.height.saturating_sub(text.height) as usize,
+    )
+}
+
+fn padding(content: PhysicalSize<u32>, text: PhysicalSize<u32>) -> (u32, u32) {
+    (
+        text.width.saturating_sub(content.width).div_ceil(2),
+        text.height + OSD_GAP,
+    )
+}
+
+/// Draw a message with its top-left corner at `(x, y)`. Anything that would land outside the buffer is skipped.
+pub fn draw_text(
+    buffer: &mut [u32],
+    buffer_width: usize,
+    x: usize,
+    y: usize,
+    text: &str,
+    color: Color,
+) {
+    let color = color.argb();
+    let buffer_height = buffer.len() / buffer_width;
+    let scale = OSD_SCALE as usize;
+    for (index, character) in text.chars().enumerate() {
+        let glyph_x = x + index * (GLYPH_ADVANCE as usize) * scale;
+        for (row, bits) in glyph(character).into_iter().enumerate() {
+            for column in 0..GLYPH_WIDTH as usize {
+                if bits & (1 << (GLYPH_WIDTH as usize - 1 - column)) == 0 {
+                    continue;
+                }
+                for pixel_y in y + row * scale..y + (row + 1) * scale {
+                    for pixel_x in glyph_x + column * scale..glyph_x + (column + 1) * scale {
+                        if pixel_x < buffer_width && pixel_y < buffer_height {
+                            buffer[pixel_y * buffer_width + pixel_x] = color;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_osd {
+    use super::*;
+
+    const FRAME: Duration = Duration::from_millis(16);
+
+    /// tick at roughly 60 fps until the message goes away, returning how many frames that took
+    fn frames_until_gone(osd: &mut Osd, start: Instant) -> u32 {
+        let mut frame = 0;
+        while osd.message().is_some() {
+            frame += 1;
+            osd.tick(start + FRAME * frame);
+        }
+        frame
+    }
+
+    #[test]
+    fn test_lifetime() {
+        let start = Instant::now();
+        let mut osd = Osd::default();
+        assert!(!osd.tick(start));
+
+        osd.show("ADJUST ON", start);
+        assert_eq!(osd.message(), Some("ADJUST ON"));
+        assert_eq!(osd.alpha(), u8::MAX);
+
+        // fully opaque until the fade starts
+        assert!(!osd.tick(start + Duration::from_millis(500)));
+        assert_eq!(osd.alpha(), u8::MAX);
+
+        // fading
+        assert!(osd.tick(start + Duration::from_millis(775)));
+        assert_eq!(osd.alpha(), 191);
+        assert_eq!(osd.message(), Some("ADJUST ON"));
+
+        // gone, and only reports that once
+        assert!(osd.tick(start + OSD_DURATION));
+        assert_eq!(osd.message(), None);
+        assert!(!osd.tick(start + OSD_DURATION + FRAME));
+    }
+
+    #[test]
+    fn test_simulated_ticks() {
+        let start = Instant::now();
+        let mut osd = Osd::default();
+        osd.show("MONITOR 2", start);
+        // 1000ms at 16ms per frame
+        assert_eq!(frames_until_gone(&mut osd, start), 63);
+    }
+
+    /// a new message restarts the timer
+    #[test]
+    fn test_replaced() {
+        let start = Instant::now();
+        let mut osd = Osd::default();
+        osd.show("ADJUST ON", start);
+        osd.tick(start + Duration::from_millis(900));
+        osd.show("MONITOR 2", start + Duration::from_millis(900));
+        assert_eq!(osd.alpha(), u8::MAX);
+        assert!(!osd.tick(start + OSD_DURATION));
+        assert_eq!(osd.message(), Some("MONITOR 2"));
+    }
+
+    #[test]
+    fn test_layout() {
+        let text = text_size("HI");
+        assert_eq!(text, PhysicalSize::new(14, 10));
+
+        // text wider than the content pads it on both sides
+        let content = PhysicalSize::new(5, 5);
+        let padded = padded_size(content, text);
+        assert_eq!(padded, PhysicalSize::new(15, 33));
+        assert_eq!(content_origin(content, text), (5, 14));
+        assert_eq!(text_origin(padded, text), (0, 23));
+
+        // content wider than the text only gets padded vertically
+        let content = PhysicalSize::new(64, 64);
+        assert_eq!(padded_size(content, text), PhysicalSize::new(64, 92));
+    }
+
+    #[test]
+    fn test_draw_text() {
+        const COLOR: Color = Color::from_argb(0xFFFFFFFF);
+        let size = text_size("I");
+        let (width, height) = (size.width as usize, size.height as usize);
+        let mut buffer = vec![0; width * height];
+        draw_text(&mut buffer, width, 0, 0, "I", COLOR);
+        let picture: Vec<String> = buffer
+            .chunks(width)
+            .step_by(OSD_SCALE as usize)
+            .map(|row| {
+                row.iter()
+                    .step_by(OSD_SCALE as usize)
+                    .map(|&pixel| if pixel == COLOR.argb() { '#' } else { '.' })
+                    .collect()
+            })
+            .collect();
+        assert_eq!(picture, ["###", ".#.", ".#.", ".#.", "###"]);
+
+        // text running off the edge is clipped
+        draw_text(&mut buffer, width, 2, 2, "WWW", COLOR);
+    }
+}
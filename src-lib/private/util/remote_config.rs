@@ -0,0 +1,208 @@
+// This file is part of simple-crosshair-overlay and is licenced under the GNU GPL v3.0.
+// See LICENSE file for full text.
+// Copyright © 2023 Michael Ripley
+
+//! Loading a shared settings file from a URL, so a whole team can use the same crosshair.
+//!
+//! The last config that downloaded and parsed successfully is cached, so a network hiccup on startup doesn't cost you
+//! your crosshair.
+
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+use debug_print::debug_println;
+
+use crate::private::settings::Settings;
+
+/// Where the last good remote config is kept: next to the local config at `config_path`, so overlays started with
+/// different `--config` files don't share a cache
+pub fn cache_path(config_path: &Path) -> PathBuf {
+    let stem = config_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+    config_path.with_file_name(format!("{stem}_remote.toml"))
+}
+
+/// Something that can download a config. This is only a trait so tests don't need a network.
+pub trait Fetch {
+    fn fetch(&self, url: &str) -> io::Result<String>;
+}
+
+/// Fetches over HTTPS
+pub struct HttpsFetch;
+
+impl Fetch for HttpsFetch {
+    fn fetch(&self, url: &str) -> io::Result<String> {
+        ureq::get(url)
+            .timeout(std::time::Duration::from_secs(5))
+            .call()
+            .map_err(io::Error::other)?
+            .into_string()
+    }
+}
+
+/// Download the config at `url`, falling back to the cached copy if the download fails or the downloaded config is
+/// invalid. A valid download replaces the cache.
+pub fn load<F, P>(url: &str, fetcher: &F, cache_path: P) -> io::Result<Settings>
+where
+    F: Fetch,
+    P: AsRef<Path>,
+{
+    let cache_path = cache_path.as_ref();
+    match download(url, fetcher) {
+        Ok((settings, string)) => {
+            if let Err(_e) = fs::write(cache_path, string) {
+                debug_println!("failed to cache remote config: {_e}");
+            }
+            Ok(settings)
+        }
+        Err(download_error) => {
+            debug_println!("failed to load remote config, trying cache: {download_error}");
+            let string = fs::read_to_string(cache_path).map_err(|_| download_error)?;
+            Settings::from_toml(&string)
+        }
+    }
+}
+
+fn download<F: Fetch>(url: &str, fetcher: &F) -> io::Result<(Settings, String)> {
+    if !url.starts_with("https://") {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("remote config URL must use https: {url}"),
+        ));
+    }
+    let string = fetcher.fetch(url)?;
+    let settings = Settings::from_toml(&string)?;
+    Ok((settings, string))
+}
+
+/// Find the value of `--config-url` in the command line arguments. Both `--config-url URL` and `--config-url=URL`
+/// work.
+pub fn config_url_arg<I>(args: I) -> Option<String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--config-url" {
+            return args.next();
+        } else if let Some(url) = arg.strip_prefix("--config-url=") {
+            return Some(url.to_string());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test_remote_config {
+    use crate::private::util::image::Color;
+
+    use super::*;
+
+    const URL: &str = "https://example.com/crosshair.toml";
+
+    const GREEN: Color = Color::from_argb(0xFF00FF00);
+
+    /// serves one fixed response no matter what's asked for
+    struct FixtureFetch(Option<String>);
+
+    impl Fetch for FixtureFetch {
+        fn fetch(&self, _url: &str) -> io::Result<String> {
+            self.0
+                .clone()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::TimedOut, "no network"))
+        }
+    }
+
+    /// a complete config that only differs from the defaults in color
+    fn green_config() -> String {
+        let mut settings = Settings::default();
+        settings.set_color(GREEN);
+        toml::to_string(&settings.persisted).unwrap()
+    }
+
+    /// a cache path unique to one test, starting out empty
+    fn temp_cache(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "simple-crosshair-overlay-test-{}-{name}.toml",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_download_is_cached() {
+        let cache = temp_cache("cached");
+        let settings = load(URL, &FixtureFetch(Some(green_config())), &cache).unwrap();
+        assert_eq!(settings.color, GREEN);
+
+        // the network goes down, but we still have the last good config
+        let settings = load(URL, &FixtureFetch(None), &cache).unwrap();
+        assert_eq!(settings.color, GREEN);
+        fs::remove_file(cache).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_download_keeps_cache() {
+        let cache = temp_cache("invalid");
+        load(URL, &FixtureFetch(Some(green_config())), &cache).unwrap();
+        let settings = load(URL, &FixtureFetch(Some("color = 7".to_string())), &cache).unwrap();
+        assert_eq!(settings.color, GREEN);
+        assert_eq!(fs::read_to_string(&cache).unwrap(), green_config());
+        fs::remove_file(cache).unwrap();
+    }
+
+    #[test]
+    fn test_no_network_no_cache() {
+        let cache = temp_cache("empty");
+        let error = load(URL, &FixtureFetch(None), &cache).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+    }
+
+    #[test]
+    fn test_https_only() {
+        let cache = temp_cache("http");
+        let error = load(
+            "http://example.com/crosshair.toml",
+            &FixtureFetch(Some(green_config())),
+            &cache,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert!(!cache.exists());
+    }
+
+    #[test]
+    fn test_cache_path() {
+        assert_eq!(
+            cache_path(Path::new("dir/config.toml")),
+            Path::new("dir/config_remote.toml")
+        );
+        assert_eq!(
+            cache_path(Path::new("dir/other.json")),
+            Path::new("dir/other_remote.toml")
+        );
+        assert_ne!(
+            cache_path(Path::new("a/config.toml")),
+            cache_path(Path::new("b/config.toml"))
+        );
+    }
+
+    #[test]
+    fn test_config_url_arg() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        assert_eq!(config_url_arg(args(&["app"])), None);
+        assert_eq!(
+            config_url_arg(args(&["app", "--config-url", URL])),
+            Some(URL.to_string())
+        );
+        assert_eq!(
+            config_url_arg(args(&["app", &format!("--config-url={URL}")])),
+            Some(URL.to_string())
+        );
+        assert_eq!(config_url_arg(args(&["app", "--config-url"])), None);
+    }
+}
@@ -0,0 +1,381 @@
+// This file is part of simple-crosshair-overlay and is licenced under the GNU GPL v3.0.
+// See LICENSE file for full text.
+// Copyright © 2023 Michael Ripley
+
+//! Tick-driven timers. These take the current time as a parameter so they can be tested without actually waiting.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Tracks how long it's been since the user last interacted with the overlay
+pub struct AutoHideTimer {
+    timeout: Option<Duration>,
+    last_activity: Instant,
+    expired: bool,
+}
+
+impl AutoHideTimer {
+    /// A `timeout` of `None` creates a timer that never expires
+    pub fn new(timeout: Option<Duration>, now: Instant) -> Self {
+        AutoHideTimer {
+            timeout,
+            last_activity: now,
+            expired: false,
+        }
+    }
+
+    /// note user activity, restarting the countdown
+    pub fn reset(&mut self, now: Instant) {
+        self.last_activity = now;
+        self.expired = false;
+    }
+
+    /// Returns `true` on the first tick after the timeout elapses. Further ticks return `false` until the timer is
+    /// [reset](Self::reset).
+    pub fn tick(&mut self, now: Instant) -> bool {
+        match self.timeout {
+            Some(timeout) if !self.expired => {
+                self.expired = now.saturating_duration_since(self.last_activity) >= timeout;
+                self.expired
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Lets something happen at most once per interval, no matter how often it's ticked
+pub struct Throttle {
+    interval: Duration,
+    last_fired: Instant,
+}
+
+impl Throttle {
+    /// The first firing happens one `interval` after `now`
+    pub fn new(interval: Duration, now: Instant) -> Self {
+        Throttle {
+            interval,
+            last_fired: now,
+        }
+    }
+
+    /// Returns `true` if at least one interval has passed since the last time this returned `true`
+    pub fn tick(&mut self, now: Instant) -> bool {
+        let ready = now.saturating_duration_since(self.last_fired) >= self.interval;
+        if ready {
+            self.last_fired = now;
+        }
+        ready
+    }
+}
+
+/// how many ticks in a row have to go by without anything happening before ticking slows down
+pub const IDLE_AFTER_TICKS: u32 = 120;
+
+/// Slows ticking down once nothing has happened for a while, and brings it right back up as soon as something does
+pub struct IdleBackoff {
+    active_interval: Duration,
+    /// `None` never slows down
+    idle_interval: Option<Duration>,
+    quiet_ticks: u32,
+}
+
+impl IdleBackoff {
+    pub fn new(active_interval: Duration, idle_interval: Option<Duration>) -> Self {
+        IdleBackoff {
+            active_interval,
+            idle_interval,
+            quiet_ticks: 0,
+        }
+    }
+
+    /// Change both intervals, e.g. after the fps settings changed. Whether we're idle stays the same.
+    pub fn set_intervals(&mut self, active_interval: Duration, idle_interval: Option<Duration>) {
+        self.active_interval = active_interval;
+        self.idle_interval = idle_interval;
+    }
+
+    /// `true` once enough quiet ticks have gone by
+    pub fn is_idle(&self) -> bool {
+        self.idle_interval.is_some() && self.quiet_ticks >= IDLE_AFTER_TICKS
+    }
+
+    /// Count a tick, where `active` means something happened on it. Returns the interval to wait before the next tick.
+    pub fn tick(&mut self, active: bool) -> Duration {
+        self.quiet_ticks = if active {
+            0
+        } else {
+            self.quiet_ticks.saturating_add(1)
+        };
+        self.interval()
+    }
+
+    /// the interval to wait between ticks right now
+    pub fn interval(&self) -> Duration {
+        match self.idle_interval {
+            Some(idle_interval) if self.is_idle() => idle_interval,
+            _ => self.active_interval,
+        }
+    }
+}
+
+/// The interval between ticks, shared with the thread that sends them so it can be changed on the fly
+#[derive(Clone)]
+pub struct SharedTickInterval {
+    nanos: Arc<AtomicU64>,
+}
+
+impl SharedTickInterval {
+    pub fn new(interval: Duration) -> Self {
+        SharedTickInterval {
+            nanos: Arc::new(AtomicU64::new(Self::to_nanos(interval))),
+        }
+    }
+
+    pub fn get(&self) -> Duration {
+        Duration::from_nanos(self.nanos.load(Ordering::Relaxed))
+    }
+
+    /// Takes effect after the tick that's currently being waited on
+    pub fn set(&self, interval: Duration) {
+        self.nanos
+            .store(Self::to_nanos(interval), Ordering::Relaxed);
+    }
+
+    /// a u64 of nanoseconds is over 500 years, so saturating is fine
+    fn to_nanos(interval: Duration) -> u64 {
+        u64::try_from(interval.as_nanos()).unwrap_or(u64::MAX)
+    }
+}
+
+/// Spawn a thread that calls `tick` forever, sleeping for whatever the interval currently is between calls
+pub fn spawn_ticker<F>(name: &str, interval: SharedTickInterval, tick: F)
+where
+    F: FnMut() + Send + 'static,
+{
+    thread::Builder::new()
+        .name(name.to_string())
+        .spawn(move || {
+            run_ticker(&interval, tick, |duration| {
+                thread::sleep(duration);
+                true
+            })
+        })
+        .unwrap(); // if we fail to spawn a thread something is super wrong and we ought to panic
+}
+
+/// The ticker loop, with the sleeping passed in so tests don't have to wait. Stops once `sleep` returns `false`.
+fn run_ticker<F, S>(interval: &SharedTickInterval, mut tick: F, mut sleep: S)
+where
+    F: FnMut(),
+    S: FnMut(Duration) -> bool,
+{
+    loop {
+        tick();
+        if !sleep(interval.get()) {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_auto_hide_timer {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn test_disabled() {
+        let start = Instant::now();
+        let mut timer = AutoHideTimer::new(None, start);
+        assert!(!timer.tick(start + Duration::from_secs(3600)));
+    }
+
+    #[test]
+    fn test_expires_once() {
+        let start = Instant::now();
+        let mut timer = AutoHideTimer::new(Some(TIMEOUT), start);
+        assert!(!timer.tick(start + Duration::from_secs(4)));
+        assert!(timer.tick(start + TIMEOUT));
+        assert!(!timer.tick(start + Duration::from_secs(6)));
+    }
+
+    #[test]
+    fn test_activity_resets() {
+        let start = Instant::now();
+        let mut timer = AutoHideTimer::new(Some(TIMEOUT), start);
+        assert!(!timer.tick(start + Duration::from_secs(4)));
+        timer.reset(start + Duration::from_secs(4));
+        assert!(!timer.tick(start + Duration::from_secs(8)));
+        assert!(timer.tick(start + Duration::from_secs(9)));
+    }
+
+    #[test]
+    fn test_reset_after_expiry() {
+        let start = Instant::now();
+        let mut timer = AutoHideTimer::new(Some(TIMEOUT), start);
+        assert!(timer.tick(start + TIMEOUT));
+        timer.reset(start + Duration::from_secs(10));
+        assert!(!timer.tick(start + Duration::from_secs(14)));
+        assert!(timer.tick(start + Duration::from_secs(15)));
+    }
+}
+
+#[cfg(test)]
+mod test_throttle {
+    use super::*;
+
+    const INTERVAL: Duration = Duration::from_secs(1);
+    const FRAME: Duration = Duration::from_millis(16);
+
+    #[test]
+    fn test_waits_for_interval() {
+        let start = Instant::now();
+        let mut throttle = Throttle::new(INTERVAL, start);
+        assert!(!throttle.tick(start));
+        assert!(!throttle.tick(start + Duration::from_millis(999)));
+        assert!(throttle.tick(start + INTERVAL));
+        assert!(!throttle.tick(start + INTERVAL));
+    }
+
+    /// ticking at roughly 60 fps for 10 seconds fires about once a second
+    #[test]
+    fn test_simulated_ticks() {
+        let start = Instant::now();
+        let mut throttle = Throttle::new(INTERVAL, start);
+        let fired = (1..=625)
+            .filter(|&frame| throttle.tick(start + FRAME * frame))
+            .count();
+        assert_eq!(fired, 9);
+    }
+
+    /// a long stall only fires once, rather than catching up on everything that was missed
+    #[test]
+    fn test_no_catch_up() {
+        let start = Instant::now();
+        let mut throttle = Throttle::new(INTERVAL, start);
+        assert!(throttle.tick(start + Duration::from_secs(60)));
+        assert!(!throttle.tick(start + Duration::from_secs(60) + FRAME));
+    }
+}
+
+#[cfg(test)]
+mod test_idle_backoff {
+    use super::*;
+
+    const ACTIVE: Duration = Duration::from_millis(16);
+    const IDLE: Duration = Duration::from_millis(100);
+
+    /// tick `count` times without anything happening, returning the last interval
+    fn quiet(backoff: &mut IdleBackoff, count: u32) -> Duration {
+        (0..count).map(|_| backoff.tick(false)).last().unwrap()
+    }
+
+    #[test]
+    fn test_backs_off() {
+        let mut backoff = IdleBackoff::new(ACTIVE, Some(IDLE));
+        assert_eq!(quiet(&mut backoff, IDLE_AFTER_TICKS - 1), ACTIVE);
+        assert!(!backoff.is_idle());
+        assert_eq!(backoff.tick(false), IDLE);
+        assert!(backoff.is_idle());
+        assert_eq!(quiet(&mut backoff, 1000), IDLE);
+    }
+
+    #[test]
+    fn test_activity_snaps_back() {
+        let mut backoff = IdleBackoff::new(ACTIVE, Some(IDLE));
+        quiet(&mut backoff, IDLE_AFTER_TICKS);
+        assert_eq!(backoff.tick(true), ACTIVE);
+        assert!(!backoff.is_idle());
+        // and the countdown starts over
+        assert_eq!(quiet(&mut backoff, IDLE_AFTER_TICKS - 1), ACTIVE);
+    }
+
+    #[test]
+    fn test_activity_delays_backoff() {
+        let mut backoff = IdleBackoff::new(ACTIVE, Some(IDLE));
+        quiet(&mut backoff, IDLE_AFTER_TICKS - 1);
+        backoff.tick(true);
+        assert_eq!(quiet(&mut backoff, IDLE_AFTER_TICKS - 1), ACTIVE);
+    }
+
+    #[test]
+    fn test_disabled() {
+        let mut backoff = IdleBackoff::new(ACTIVE, None);
+        assert_eq!(quiet(&mut backoff, IDLE_AFTER_TICKS * 10), ACTIVE);
+        assert!(!backoff.is_idle());
+    }
+
+    #[test]
+    fn test_set_intervals() {
+        let mut backoff = IdleBackoff::new(ACTIVE, Some(IDLE));
+        quiet(&mut backoff, IDLE_AFTER_TICKS);
+        backoff.set_intervals(ACTIVE, Some(IDLE * 2));
+        assert_eq!(backoff.interval(), IDLE * 2);
+        backoff.set_intervals(ACTIVE, None);
+        assert_eq!(backoff.interval(), ACTIVE);
+    }
+}
+
+#[cfg(test)]
+mod test_ticker {
+    use std::sync::mpsc;
+
+    use super::*;
+
+    #[test]
+    fn test_shared_interval() {
+        let interval = SharedTickInterval::new(Duration::from_millis(16));
+        let clone = interval.clone();
+        clone.set(Duration::from_millis(8));
+        assert_eq!(interval.get(), Duration::from_millis(8));
+    }
+
+    /// slowing the ticker down takes effect without restarting it
+    #[test]
+    fn test_live_update() {
+        let interval = SharedTickInterval::new(Duration::from_millis(1));
+        let mut ticks = 0;
+        let mut sleeps = Vec::new();
+        run_ticker(
+            &interval,
+            || ticks += 1,
+            |duration| {
+                sleeps.push(duration);
+                // change the interval from outside partway through, like the main thread would
+                if sleeps.len() == 3 {
+                    interval.set(Duration::from_secs(3600));
+                }
+                sleeps.len() < 5
+            },
+        );
+        assert_eq!(ticks, 5);
+        assert_eq!(
+            sleeps,
+            [
+                Duration::from_millis(1),
+                Duration::from_millis(1),
+                Duration::from_millis(1),
+                Duration::from_secs(3600),
+                Duration::from_secs(3600),
+            ]
+        );
+    }
+
+    /// the real thread keeps ticking
+    #[test]
+    fn test_spawn_ticker() {
+        let (sender, receiver) = mpsc::channel();
+        spawn_ticker(
+            "test-ticker",
+            SharedTickInterval::new(Duration::from_millis(1)),
+            move || {
+                let _ = sender.send(());
+            },
+        );
+        for _ in 0..3 {
+            receiver.recv_timeout(Duration::from_secs(30)).unwrap();
+        }
+    }
+}
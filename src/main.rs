@@ -5,16 +5,24 @@
 #![windows_subsystem = "windows"] // necessary to remove the console window on Windows
 
 use std::io;
+use std::path::{Path, PathBuf};
 
 use debug_print::debug_println;
-use winit::event_loop::{DeviceEvents, EventLoop};
-use winit::window::{CursorGrabMode, Window};
+use winit::application::ApplicationHandler;
+use winit::event::{StartCause, WindowEvent};
+use winit::event_loop::{ActiveEventLoop, DeviceEvents, EventLoop};
+use winit::window::{CursorGrabMode, Window, WindowId};
 
 use simple_crosshair_overlay::private::platform;
-use simple_crosshair_overlay::private::settings::Settings;
-use simple_crosshair_overlay::private::settings::CONFIG_PATH;
-use simple_crosshair_overlay::private::util::dialog;
+use simple_crosshair_overlay::private::settings::{self, Settings, CONFIG_PATH};
+use simple_crosshair_overlay::private::util::monitor::{self, MonitorInfo};
+#[cfg(feature = "remote-config")]
+use simple_crosshair_overlay::private::util::remote_config;
+use simple_crosshair_overlay::private::util::timing::{self, SharedTickInterval};
+use simple_crosshair_overlay::private::util::{diagnostics, dialog, image};
 
+#[cfg(feature = "gui")]
+mod settings_window;
 mod tray;
 mod window;
 
@@ -26,49 +34,165 @@ mod build_constants {
 }
 
 fn main() {
+    // lets several overlays with different setups run side by side
+    let config_path =
+        settings::config_path_arg(std::env::args().skip(1)).unwrap_or_else(|| CONFIG_PATH.clone());
+
+    // this doesn't need a display, so it happens before the event loop exists
+    if std::env::args().skip(1).any(|arg| arg == "--print-config") {
+        print_config(&config_path);
+        return;
+    }
+    if std::env::args().skip(1).any(|arg| arg == "--alpha-test") {
+        alpha_test(&config_path);
+        return;
+    }
+    if let Some(path) = dump_picker_arg(std::env::args()) {
+        dump_picker(path);
+        return;
+    }
+
     // Initialize Eventloop before everything
     let event_loop: EventLoop<window::UserEvent> = EventLoop::new().unwrap();
     // in theory Wait is now the default ControlFlow, so the following isn't needed:
     // event_loop.set_control_flow(ControlFlow::Wait);
 
+    if std::env::args().skip(1).any(|arg| arg == "--list-monitors") {
+        list_monitors(event_loop);
+        return;
+    }
+
     // settings has a decent quantity of data in it, but it never really gets moved so we can just leave it on the stack
     // the image buffer is internally boxed so don't worry about that
-    let settings = match Settings::load() {
-        Ok(settings) => settings,
-        Err(e) if e.kind() == io::ErrorKind::NotFound => Settings::default(), // generate new settings file when it doesn't exist
-        Err(e) => {
-            dialog::show_warning(format!(
-                "Error loading settings file \"{}\". Resetting to default settings.\n\n{}",
-                CONFIG_PATH.display(),
-                e
-            ));
-            Settings::default()
-        }
-    };
+    let (settings, persist) = load_settings(&config_path);
 
     // only functional on Linux targets
     event_loop.listen_device_events(DeviceEvents::Never);
 
     // start sending tick events
-    start_tick_sender(&settings, &event_loop);
+    let tick_interval = SharedTickInterval::new(settings.tick_interval);
+    start_tick_sender(tick_interval.clone(), &event_loop);
 
     // create the winit application
-    let mut window_state = window::State::new(settings, &event_loop);
+    let mut window_state =
+        window::State::new(settings, config_path, persist, tick_interval, &event_loop);
 
     // pass control to the event loop
     event_loop.run_app(&mut window_state).unwrap();
 }
 
-fn start_tick_sender(settings: &Settings, event_loop: &EventLoop<window::UserEvent>) {
+/// Print the settings the overlay would actually run with, after defaults and validation have been applied
+fn print_config(config_path: &Path) {
+    platform::attach_parent_console();
+    print!("{}", load_settings(config_path).0.to_toml());
+}
+
+/// Print how semi-transparent pixels get converted for the window buffer with the configured `premultiply_alpha` and
+/// `gamma_correct`
+fn alpha_test(config_path: &Path) {
+    platform::attach_parent_console();
+    let (settings, _) = load_settings(config_path);
+    print!(
+        "{}",
+        diagnostics::alpha_test_report(
+            settings.persisted.premultiply_alpha,
+            settings.persisted.gamma_correct
+        )
+    );
+}
+
+/// The output path from `--dump-picker <out.png>`, if that was passed. A missing path comes back empty, which fails
+/// to save with a sensible error.
+fn dump_picker_arg<I>(args: I) -> Option<PathBuf>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter().skip(1);
+    args.position(|arg| arg == "--dump-picker")?;
+    Some(args.next().unwrap_or_default().into())
+}
+
+/// Save the color picker to a PNG, so its colors can be inspected without running the overlay
+fn dump_picker(path: PathBuf) {
+    platform::attach_parent_console();
+    match image::color_picker_image().save_png(&path) {
+        Ok(()) => println!("Saved color picker to \"{}\"", path.display()),
+        Err(e) => {
+            eprintln!("Error saving color picker to \"{}\": {e}", path.display());
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Print a table of monitors and exit, without ever showing the overlay or tray icon
+fn list_monitors(event_loop: EventLoop<window::UserEvent>) {
+    platform::attach_parent_console();
+    event_loop.run_app(&mut MonitorLister).unwrap();
+}
+
+/// Monitors can only be listed once the event loop is running, so this is a tiny app that does that and nothing else
+struct MonitorLister;
+
+impl ApplicationHandler<window::UserEvent> for MonitorLister {
+    fn new_events(&mut self, event_loop: &ActiveEventLoop, cause: StartCause) {
+        if matches!(cause, StartCause::Init) {
+            let monitors: Vec<(MonitorInfo, f64)> = event_loop
+                .available_monitors()
+                .map(|monitor| (MonitorInfo::from(&monitor), monitor.scale_factor()))
+                .collect();
+            print!("{}", monitor::format_monitor_table(&monitors));
+            event_loop.exit();
+        }
+    }
+
+    fn resumed(&mut self, _event_loop: &ActiveEventLoop) {}
+
+    fn window_event(
+        &mut self,
+        _event_loop: &ActiveEventLoop,
+        _window_id: WindowId,
+        _event: WindowEvent,
+    ) {
+    }
+}
+
+/// Load the settings to run with. The `bool` is `false` if they came from `--config-url`, in which case they must not be
+/// saved over the local config at `config_path`.
+fn load_settings(config_path: &Path) -> (Settings, bool) {
+    #[cfg(feature = "remote-config")]
+    if let Some(url) = remote_config::config_url_arg(std::env::args()) {
+        match remote_config::load(
+            &url,
+            &remote_config::HttpsFetch,
+            remote_config::cache_path(config_path),
+        ) {
+            Ok(settings) => return (settings, false),
+            Err(e) => dialog::show_warning(format!(
+                "Error loading remote settings from \"{url}\". Using local settings instead.\n\n{e}"
+            )),
+        }
+    }
+
+    let settings = match Settings::load(config_path) {
+        Ok(settings) => settings,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Settings::default(), // generate new settings file when it doesn't exist
+        Err(e) => {
+            dialog::show_warning(format!(
+                "Error loading settings file \"{}\". Resetting to default settings.\n\n{}",
+                config_path.display(),
+                e
+            ));
+            Settings::default()
+        }
+    };
+    (settings, true)
+}
+
+fn start_tick_sender(tick_interval: SharedTickInterval, event_loop: &EventLoop<window::UserEvent>) {
     let user_event_sender = event_loop.create_proxy();
-    let key_process_interval = settings.tick_interval;
-    std::thread::Builder::new()
-        .name("tick-sender".to_string())
-        .spawn(move || loop {
-            let _ = user_event_sender.send_event(());
-            std::thread::sleep(key_process_interval);
-        })
-        .unwrap(); // if we fail to spawn a thread something is super wrong and we ought to panic
+    timing::spawn_ticker("tick-sender", tick_interval, move || {
+        let _ = user_event_sender.send_event(());
+    });
 }
 
 /// Updates the window state after entering or exiting color picker mode
@@ -0,0 +1,103 @@
+// This file is part of simple-crosshair-overlay and is licenced under the GNU GPL v3.0.
+// See LICENSE file for full text.
+// Copyright © 2023-2024 Michael Ripley
+
+//! The optional settings window. The layout and drawing live in the library's `gui` module; this just owns the
+//! actual window.
+
+use std::num::NonZeroU32;
+use std::rc::Rc;
+
+use winit::dpi::PhysicalPosition;
+use winit::event_loop::ActiveEventLoop;
+use winit::window::{Window, WindowId};
+
+use simple_crosshair_overlay::private::gui::{self, Click, KeyCapture};
+use simple_crosshair_overlay::private::settings::Settings;
+
+type Surface = softbuffer::Surface<Rc<Window>, Rc<Window>>;
+
+pub struct SettingsWindow {
+    window: Rc<Window>,
+    surface: Surface,
+    cursor_position: PhysicalPosition<f64>,
+    /// the keybinding being recorded, if any
+    key_capture: Option<KeyCapture>,
+}
+
+impl SettingsWindow {
+    pub fn new(active_event_loop: &ActiveEventLoop, settings: &Settings) -> Self {
+        let window_attributes = Window::default_attributes()
+            .with_title("Simple Crosshair Overlay Settings")
+            .with_resizable(false)
+            .with_inner_size(gui::window_size(settings));
+        let window = Rc::new(active_event_loop.create_window(window_attributes).unwrap());
+        // same deal as the overlay windows: the context is only used right here, and the surface dies with the window
+        let context = softbuffer::Context::new(window.clone()).unwrap();
+        let surface = Surface::new(&context, window.clone()).unwrap();
+        SettingsWindow {
+            window,
+            surface,
+            cursor_position: PhysicalPosition::default(),
+            key_capture: None,
+        }
+    }
+
+    pub fn id(&self) -> WindowId {
+        self.window.id()
+    }
+
+    pub fn focus(&self) {
+        self.window.focus_window();
+    }
+
+    pub fn request_redraw(&self) {
+        self.window.request_redraw();
+    }
+
+    pub fn set_cursor_position(&mut self, position: PhysicalPosition<f64>) {
+        self.cursor_position = position;
+    }
+
+    /// what's under the cursor, if anything
+    pub fn clicked(&self, settings: &Settings) -> Option<Click> {
+        gui::hit_test(self.cursor_position.x, self.cursor_position.y, settings)
+    }
+
+    /// start recording a new combination for the keybinding called `name` in the config
+    pub fn start_key_capture(&mut self, name: String) {
+        self.key_capture = Some(KeyCapture::new(name));
+        self.window.request_redraw();
+    }
+
+    pub fn key_capture_mut(&mut self) -> Option<&mut KeyCapture> {
+        self.key_capture.as_mut()
+    }
+
+    pub fn stop_key_capture(&mut self) {
+        self.key_capture = None;
+        self.window.request_redraw();
+    }
+
+    pub fn draw(&mut self, settings: &Settings) {
+        // the keybinding list can change length when a save slot is loaded
+        let size = gui::window_size(settings);
+        if self.window.inner_size() != size {
+            let _ = self.window.request_inner_size(size);
+        }
+        self.surface
+            .resize(
+                NonZeroU32::new(size.width).unwrap(),
+                NonZeroU32::new(size.height).unwrap(),
+            )
+            .unwrap();
+        let mut buffer = self.surface.buffer_mut().unwrap();
+        gui::draw(
+            &mut buffer,
+            size.width as usize,
+            settings,
+            self.key_capture.as_ref(),
+        );
+        buffer.present().unwrap();
+    }
+}
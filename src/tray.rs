@@ -7,6 +7,11 @@ use debug_print::debug_println;
 use tray_icon::menu::{CheckMenuItem, IsMenuItem, MenuItem, Result as MenuResult, Submenu};
 use tray_icon::{menu::Menu, TrayIcon, TrayIconBuilder};
 
+use simple_crosshair_overlay::private::platform;
+use simple_crosshair_overlay::private::util::icon;
+use simple_crosshair_overlay::private::util::image::Color;
+use simple_crosshair_overlay::private::util::monitor::{self, MonitorInfo, SnapAnchor};
+
 use crate::{build_constants, ICON_TOOLTIP};
 
 pub fn build_tray_icon() -> (MenuItems, TrayIcon) {
@@ -14,7 +19,8 @@ pub fn build_tray_icon() -> (MenuItems, TrayIcon) {
     #[cfg(not(target_os = "linux"))]
     let tray_menu = Menu::new();
 
-    let menu_items = MenuItems::default();
+    #[allow(unused_mut)]
+    let mut menu_items = MenuItems::default();
 
     // windows: do not use a submenu
     #[cfg(target_os = "windows")]
@@ -43,9 +49,16 @@ pub fn build_tray_icon() -> (MenuItems, TrayIcon) {
 
     #[cfg(target_os = "linux")]
     {
-        use std::sync::{Arc, Condvar, Mutex};
+        use std::sync::{mpsc, Arc, Condvar, Mutex};
         use std::time::Duration;
 
+        // GTK menus can only be touched from the GTK thread, so monitor submenu rebuilds get sent over to it
+        let (monitor_menu_sender, monitor_menu_receiver) = mpsc::channel::<(Vec<String>, usize)>();
+        menu_items.monitor_menu_sender = Some(monitor_menu_sender);
+        // same deal for the tray icon itself
+        let (icon_sender, icon_receiver) = mpsc::channel::<Color>();
+        menu_items.icon_sender = Some(icon_sender);
+
         let condvar_pair = Arc::new((Mutex::new(false), Condvar::new()));
 
         // start GTK background thread
@@ -79,6 +92,14 @@ pub fn build_tray_icon() -> (MenuItems, TrayIcon) {
                 loop {
                     gtk::main_iteration_do(false);
                     //TODO: channel MenuItem state around?
+                    while let Ok((labels, selected)) = monitor_menu_receiver.try_recv() {
+                        rebuild_monitor_submenu(&menu_items.monitor_submenu, &labels, selected);
+                    }
+                    while let Ok(color) = icon_receiver.try_recv() {
+                        if let Some(tray_icon) = &tray_icon {
+                            let _ = tray_icon.set_icon(Some(get_colored_icon(color)));
+                        }
+                    }
                     std::thread::yield_now();
                 }
                 debug_println!("GTK main loop returned!? Weird.");
@@ -117,15 +138,50 @@ fn get_icon() -> tray_icon::Icon {
     .unwrap()
 }
 
+/// Generate a tray icon graphic filled with the crosshair color. Alpha is ignored so the swatch is always visible.
+fn get_colored_icon(color: Color) -> tray_icon::Icon {
+    let [r, g, b, _] = color.to_rgba_bytes();
+    tray_icon::Icon::from_rgba(
+        icon::generate_icon_rgba_colored(build_constants::TRAY_ICON_DIMENSION, [r, g, b]),
+        build_constants::TRAY_ICON_DIMENSION,
+        build_constants::TRAY_ICON_DIMENSION,
+    )
+    .unwrap()
+}
+
 /// Contains the menu items in our tray menu
 #[derive(Clone)]
 pub struct MenuItems {
     pub visible_button: CheckMenuItem,
     pub adjust_button: CheckMenuItem,
     pub color_pick_button: CheckMenuItem,
+    /// picks the crosshair color from the next left click anywhere on screen
+    pub eyedropper_button: CheckMenuItem,
+    pub magnifier_button: CheckMenuItem,
+    pub hotkeys_button: CheckMenuItem,
+    pub lock_button: CheckMenuItem,
+    /// has one item per monitor, which gets filled in by [`MenuItems::set_monitors`]
+    pub monitor_submenu: Submenu,
+    #[cfg(target_os = "linux")]
+    monitor_menu_sender: Option<std::sync::mpsc::Sender<(Vec<String>, usize)>>,
+    #[cfg(target_os = "linux")]
+    icon_sender: Option<std::sync::mpsc::Sender<Color>>,
+    pub snap_submenu: Submenu,
+    /// one item per snap anchor, which get added to [`MenuItems::snap_submenu`]
+    pub snap_buttons: Vec<(MenuItem, SnapAnchor)>,
     pub image_pick_button: MenuItem,
+    /// only enabled while an image is loaded
+    pub show_image_button: CheckMenuItem,
+    /// only enabled once an image has been loaded at some point
+    pub reload_image_button: MenuItem,
+    pub next_style_button: MenuItem,
+    pub export_png_button: MenuItem,
+    pub hex_color_button: MenuItem,
     pub reset_button: MenuItem,
+    #[cfg(feature = "gui")]
+    pub settings_button: MenuItem,
     pub about_button: MenuItem,
+    pub diagnostics_button: MenuItem,
     pub exit_button: MenuItem,
 }
 
@@ -134,18 +190,61 @@ impl Default for MenuItems {
         let visible_button = CheckMenuItem::new("Visible", true, true, None);
         let adjust_button = CheckMenuItem::new("Adjust", true, false, None);
         let color_pick_button = CheckMenuItem::new("Pick Color", true, false, None);
+        let eyedropper_button = CheckMenuItem::new(
+            "Pick Color From Screen",
+            platform::SCREEN_SAMPLE_SUPPORTED,
+            false,
+            None,
+        );
+        let magnifier_button =
+            CheckMenuItem::new("Magnifier", platform::SCREEN_CAPTURE_SUPPORTED, false, None);
+        let hotkeys_button = CheckMenuItem::new("Hotkeys Enabled", true, true, None);
+        let lock_button = CheckMenuItem::new("Locked", true, false, None);
+        let monitor_submenu = Submenu::new("Monitor", true);
+        let snap_submenu = Submenu::new("Snap To", true);
+        let snap_buttons = SnapAnchor::ALL
+            .into_iter()
+            .map(|anchor| (MenuItem::new(anchor.label(), true, None), anchor))
+            .collect();
         let image_pick_button = MenuItem::new("Load Image", true, None);
+        let show_image_button = CheckMenuItem::new("Show Image", false, false, None);
+        let reload_image_button = MenuItem::new("Reload Last Image", false, None);
+        let next_style_button = MenuItem::new("Next Crosshair Style", true, None);
+        let export_png_button = MenuItem::new("Export PNG", true, None);
+        let hex_color_button = MenuItem::new("Set Color (hex)…", true, None);
         let reset_button = MenuItem::new("Reset Overlay", true, None);
+        #[cfg(feature = "gui")]
+        let settings_button = MenuItem::new("Settings…", true, None);
         let about_button = MenuItem::new("About", true, None);
+        let diagnostics_button = MenuItem::new("Copy Diagnostics", true, None);
         let exit_button = MenuItem::new("Exit", true, None);
 
         MenuItems {
             visible_button,
             adjust_button,
             color_pick_button,
+            eyedropper_button,
+            magnifier_button,
+            hotkeys_button,
+            lock_button,
+            monitor_submenu,
+            #[cfg(target_os = "linux")]
+            monitor_menu_sender: None,
+            #[cfg(target_os = "linux")]
+            icon_sender: None,
+            snap_submenu,
+            snap_buttons,
             image_pick_button,
+            show_image_button,
+            reload_image_button,
+            next_style_button,
+            export_png_button,
+            hex_color_button,
             reset_button,
+            #[cfg(feature = "gui")]
+            settings_button,
             about_button,
+            diagnostics_button,
             exit_button,
         }
     }
@@ -160,11 +259,75 @@ impl MenuItems {
         menu.append(&self.visible_button).unwrap();
         menu.append(&self.adjust_button).unwrap();
         menu.append(&self.color_pick_button).unwrap();
+        menu.append(&self.eyedropper_button).unwrap();
+        menu.append(&self.magnifier_button).unwrap();
+        menu.append(&self.hotkeys_button).unwrap();
+        menu.append(&self.lock_button).unwrap();
+        menu.append(&self.monitor_submenu).unwrap();
+        for (button, _) in &self.snap_buttons {
+            self.snap_submenu.append(button).unwrap();
+        }
+        menu.append(&self.snap_submenu).unwrap();
         menu.append(&self.image_pick_button).unwrap();
+        menu.append(&self.show_image_button).unwrap();
+        menu.append(&self.reload_image_button).unwrap();
+        menu.append(&self.next_style_button).unwrap();
+        menu.append(&self.export_png_button).unwrap();
+        menu.append(&self.hex_color_button).unwrap();
         menu.append(&self.reset_button).unwrap();
+        #[cfg(feature = "gui")]
+        menu.append(&self.settings_button).unwrap();
         menu.append(&self.about_button).unwrap();
+        menu.append(&self.diagnostics_button).unwrap();
         menu.append(&self.exit_button).unwrap();
     }
+
+    /// Replace the monitor submenu's items with one item per monitor, with the 0-indexed `selected` monitor checked.
+    pub fn set_monitors(&self, monitors: &[MonitorInfo], selected: usize) {
+        let labels: Vec<String> = monitors
+            .iter()
+            .enumerate()
+            .map(|(index, monitor)| monitor::monitor_menu_label(index, monitor))
+            .collect();
+
+        #[cfg(not(target_os = "linux"))]
+        rebuild_monitor_submenu(&self.monitor_submenu, &labels, selected);
+
+        #[cfg(target_os = "linux")]
+        if let Some(sender) = &self.monitor_menu_sender {
+            let _ = sender.send((labels, selected));
+        }
+    }
+
+    /// Tint the tray icon to match the crosshair `color`. On Linux the icon lives on the GTK thread, so `tray_icon` is
+    /// ignored there.
+    #[cfg_attr(target_os = "linux", allow(unused_variables))]
+    pub fn set_icon_color(&self, tray_icon: Option<&TrayIcon>, color: Color) {
+        #[cfg(not(target_os = "linux"))]
+        if let Some(tray_icon) = tray_icon {
+            let _ = tray_icon.set_icon(Some(get_colored_icon(color)));
+        }
+
+        #[cfg(target_os = "linux")]
+        if let Some(sender) = &self.icon_sender {
+            let _ = sender.send(color);
+        }
+    }
+}
+
+/// Throw out the old monitor items and build new ones. Items get fixed IDs so clicks can be mapped back to a monitor.
+fn rebuild_monitor_submenu(submenu: &Submenu, labels: &[String], selected: usize) {
+    while submenu.remove_at(0).is_some() {}
+    for (index, label) in labels.iter().enumerate() {
+        let item = CheckMenuItem::with_id(
+            monitor::monitor_menu_id(index),
+            label,
+            true,
+            index == selected,
+            None,
+        );
+        submenu.append(&item).unwrap();
+    }
 }
 
 /// Surprisingly tray-icon doesn't provide a trait for the Menu.append() behavior several structs
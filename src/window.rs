@@ -3,126 +3,321 @@
 // Copyright © 2023-2024 Michael Ripley
 
 use std::num::NonZeroU32;
+use std::path::PathBuf;
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 use debug_print::debug_println;
 use tray_icon::dpi::{PhysicalPosition, PhysicalSize};
 use tray_icon::menu::{MenuEvent, MenuEventReceiver};
 use tray_icon::TrayIcon;
 use winit::application::ApplicationHandler;
-use winit::event::{DeviceEvent, DeviceId, ElementState, MouseButton, StartCause, WindowEvent};
+use winit::event::{
+    DeviceEvent, DeviceId, ElementState, MouseButton, MouseScrollDelta, StartCause, WindowEvent,
+};
 use winit::event_loop::{ActiveEventLoop, EventLoop};
 use winit::window::{CursorIcon, Window, WindowId, WindowLevel};
 
+use simple_crosshair_overlay::private::controller::{Dirty, Effect, MenuAction};
 use simple_crosshair_overlay::private::platform;
-use simple_crosshair_overlay::private::platform::HotkeyManager;
-use simple_crosshair_overlay::private::settings::{RenderMode, Settings, CONFIG_PATH};
+use simple_crosshair_overlay::private::platform::{Controller, HotkeyManager, MouseReader};
+use simple_crosshair_overlay::private::settings::{OverlayLevel, Settings};
+use simple_crosshair_overlay::private::util::diagnostics::Diagnostics;
 use simple_crosshair_overlay::private::util::dialog::DialogWorker;
-use simple_crosshair_overlay::private::util::{dialog, image};
+use simple_crosshair_overlay::private::util::image::{draw, Color};
+use simple_crosshair_overlay::private::util::ipc::{self, IpcWorker, Snapshot};
+use simple_crosshair_overlay::private::util::monitor::{self, MonitorInfo};
+use simple_crosshair_overlay::private::util::timing::{IdleBackoff, SharedTickInterval, Throttle};
+use simple_crosshair_overlay::private::util::{dialog, geometry, image, magnifier};
 
+#[cfg(feature = "gui")]
+use simple_crosshair_overlay::private::gui::{self, CaptureStep, Click};
+
+#[cfg(feature = "gui")]
+use crate::settings_window::SettingsWindow;
 use crate::tray::MenuItems;
 use crate::{build_constants, handle_color_pick, tray};
 
 pub type UserEvent = ();
+
+/// how often to check whether monitors were plugged in, unplugged, or rearranged
+const MONITOR_POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// how often to take back the top spot when `reassert_topmost` is on
+const REASSERT_TOPMOST_INTERVAL: Duration = Duration::from_secs(1);
+/// how often the overlay may move to catch up with the cursor when `follow_cursor` is on, so high fps settings don't
+/// flood the OS with window moves
+const FOLLOW_CURSOR_INTERVAL: Duration = Duration::from_millis(8);
 type Surface = softbuffer::Surface<Rc<Window>, Rc<Window>>;
 
 pub struct State<'a> {
-    context: Option<Context>,
-    settings: Settings,
-    hotkey_manager: HotkeyManager,
+    /// one context per window. There's only more than one if multiple monitors are configured.
+    contexts: Vec<Context>,
+    /// owns the settings and hotkeys, and decides what every hotkey and tray click does
+    controller: Controller,
+    /// the config file the settings get saved to, from `--config` or the default location
+    config_path: PathBuf,
+    /// `false` when the settings came from `--config-url`, so they never overwrite the local config
+    persist_settings: bool,
     /// native dialogs block a thread, so we'll spin up a single thread to loop through queued dialogs.
     /// If we ever need to show multiple dialogs, they just get queued.
     dialog_worker: DialogWorker,
+    /// sends state to local IPC clients, if `ipc_enabled` is on
+    ipc_worker: Option<IpcWorker>,
     /// we keep the tray icon in an Option so that we can take() it later to drop
     tray_icon: Option<TrayIcon>,
+    /// the color the tray icon was last tinted with, so it only gets regenerated when the color changes
+    tray_icon_color: Option<Color>,
     menu_items: MenuItems,
     last_focused_window: Option<platform::WindowHandle>,
     last_mouse_position: PhysicalPosition<f64>,
+    /// global mouse reader for the drag handle, created the first time the handle is needed
+    mouse_reader: Option<MouseReader>,
+    /// `true` if cursor hittest is currently enabled because the cursor is over the drag handle, or click-through is off
+    drag_handle_hittest: bool,
+    /// `true` if the left mouse button was held as of the last tick
+    mouse_left_was_pressed: bool,
+    /// like `mouse_left_was_pressed`, but for the eyedropper. Starts out `true` so the click on the tray menu that turned
+    /// the eyedropper on doesn't count.
+    eyedropper_left_was_pressed: bool,
+    /// window offset and cursor position from the start of an in-progress drag
+    drag_start: Option<((i32, i32), PhysicalPosition<i32>)>,
     menu_channel: &'a MenuEventReceiver,
-    /// if set to true, the next redraw will be forced even for known buffer contents
-    force_redraw: bool,
-    window_position_dirty: bool,
-    window_scale_dirty: bool,
-    window_visible: bool,
+    last_monitor_poll: Instant,
+    reassert_topmost: Throttle,
+    follow_cursor: Throttle,
+    /// how often the tick sender wakes us up, which follows the fps setting
+    tick_interval: SharedTickInterval,
+    /// drops the tick rate to `idle_fps` while nothing's happening
+    idle_backoff: IdleBackoff,
+    /// the settings window, while it's open
+    #[cfg(feature = "gui")]
+    settings_window: Option<SettingsWindow>,
 }
 
 /// Window context
 struct Context {
     window: Rc<Window>,
     surface: Surface,
+    /// 0-indexed monitor this window is pinned to, or `None` to follow `Settings::layout_monitor_index`
+    monitor_index: Option<usize>,
+    desired_window_position: PhysicalPosition<i32>,
+    /// if set to true, the next redraw of this window will be forced even for known buffer contents
+    force_redraw: bool,
+    /// most recent capture of the screen under the crosshair, for the magnifier
+    magnifier_sample: Option<Vec<u32>>,
 }
 
 impl Context {
-    fn new(active_event_loop: &ActiveEventLoop, settings: &mut Settings) -> Self {
+    fn new(
+        active_event_loop: &ActiveEventLoop,
+        settings: &Settings,
+        monitor_index: Option<usize>,
+        visible: bool,
+    ) -> Self {
         // unsafe note: these three structs MUST live and die together.
         // It is highly illegal to use the context or surface after the window is dropped.
         // The context only gets used right here, so that's fine.
         // As of this writing, none of these get moved out of this struct. Therefore, they all get dropped at the same time, which is safe.
-        let window = Rc::new(init_window(active_event_loop, settings));
+        let window = Rc::new(create_window(active_event_loop, settings));
         let context = softbuffer::Context::new(window.clone()).unwrap();
         let surface: Surface = Surface::new(&context, window.clone()).unwrap();
-        Context { window, surface }
+        let mut context = Context {
+            window,
+            surface,
+            monitor_index,
+            desired_window_position: PhysicalPosition::default(),
+            force_redraw: false,
+            magnifier_sample: None,
+        };
+        context.init_window(settings, visible);
+        context
+    }
+
+    fn set_window_position(&mut self, settings: &Settings) {
+        // the cursor decides where the window goes, see State::follow_cursor
+        if settings.persisted.follow_cursor {
+            return;
+        }
+        let position = settings.compute_window_coordinates(&self.window, self.monitor_index);
+        self.move_to(position);
+    }
+
+    fn move_to(&mut self, position: PhysicalPosition<i32>) {
+        self.desired_window_position = position;
+        self.window.set_outer_position(position);
+    }
+
+    fn validate_window_position(&self, position: PhysicalPosition<i32>) {
+        if position != self.desired_window_position {
+            debug_println!("resetting window position");
+            self.window.set_outer_position(self.desired_window_position);
+        }
+    }
+
+    /// Handles both window size and position change side effects.
+    fn on_window_size_or_position_change(&mut self, settings: &Settings) {
+        settings.set_window_size(&self.window);
+        self.set_window_position(settings);
+        self.window.request_redraw(); // needed in case the window size didn't change but the image was replaced
+
+        /*
+        TODO: scaling jitter problem
+            When the application is scaled really quickly via key-repeat spam it struggles to scale, move, and redraw the window in perfect sync.
+            To fix this I'd have to completely rearchitect how scaling works. Ideas:
+            1. Temporarily size the window to full screen, thereby eliminating all but the redraws
+            2. Stop relying on key repeat and instead remember key state and use ticks for your update intervals
+        */
+    }
+
+    /// Slightly cheaper special case that can only handle window position changes. Do not use this if the window size may have changed.
+    fn on_window_position_change(&mut self, settings: &Settings) {
+        self.set_window_position(settings);
+    }
+
+    /// Grab the part of the screen under the crosshair, and schedule a redraw to show it
+    fn update_magnifier(&mut self, settings: &Settings) {
+        let PhysicalSize { width, height } = settings.size();
+        let center = PhysicalPosition::new(
+            self.desired_window_position.x + (width / 2) as i32,
+            self.desired_window_position.y + (height / 2) as i32,
+        );
+        let origin = magnifier::sample_origin(center, magnifier::MAGNIFIER_SAMPLE_SIZE);
+        self.magnifier_sample =
+            platform::capture_screen_region(origin, magnifier::MAGNIFIER_SAMPLE_SIZE);
+        self.force_redraw = true;
+        self.window.request_redraw();
+    }
+
+    /// Finish setting up a freshly created window and show it, unless it's supposed to start out hidden.
+    fn init_window(&mut self, settings: &Settings, visible: bool) {
+        // contrary to all my expectations this call appears to work reliably
+        self.set_window_position(settings);
+
+        // this call is very fragile (read: shit) and sometimes simply doesn't do anything.
+        // There's a fallback call up in the event loop that saves us when this fails.
+        settings.set_window_size(&self.window);
+
+        // once the window is ready, show it
+        self.window.set_visible(true);
+
+        // set these weirder settings AFTER the window is visible to avoid even more buggy Windows behavior
+        // Windows particularly hates if you unset cursor_hittest while the window is hidden
+        self.window.set_cursor_hittest(false).unwrap();
+        self.window
+            .set_window_level(settings.persisted.window_level.window_level());
+        self.window.set_cursor(CursorIcon::Crosshair); // Yo Dawg, I herd you like crosshairs so I put a crosshair in your crosshair so you can aim while you aim.
+
+        // only hide it once all of the above has been applied to a visible window
+        if !visible {
+            self.window.set_visible(false);
+        }
     }
 }
 
 impl<'a> State<'a> {
-    pub fn new(settings: Settings, _event_loop: &EventLoop<UserEvent>) -> Self {
+    pub fn new(
+        settings: Settings,
+        config_path: PathBuf,
+        persist_settings: bool,
+        tick_interval: SharedTickInterval,
+        _event_loop: &EventLoop<UserEvent>,
+    ) -> Self {
         // HotkeyManager has a decent quantity of data in it, but again it never really gets moved so we can just leave it on the stack
-        let hotkey_manager: HotkeyManager = HotkeyManager::new(&settings.persisted.key_bindings)
-            .unwrap_or_else(|e| {
-                dialog::show_warning(format!("{e}\n\nUsing default hotkeys."));
-                HotkeyManager::default()
-            });
-
+        let hotkey_manager: HotkeyManager = HotkeyManager::new(
+            &settings.persisted.key_bindings,
+            settings.persisted.scale_easing,
+        )
+        .unwrap_or_else(|e| {
+            dialog::show_warning(format!("{e}\n\nUsing default hotkeys."));
+            HotkeyManager::default()
+        });
+
+        let dialog_worker = dialog::spawn_worker(settings.persisted.batch_warnings);
+        let ipc_worker = settings
+            .persisted
+            .ipc_enabled
+            .then(|| {
+                ipc::spawn_worker(&config_path)
+                    .map_err(|e| {
+                        dialog::show_warning(format!("Failed to start IPC server.\n\n{e}"))
+                    })
+                    .ok()
+            })
+            .flatten();
+        let idle_backoff = IdleBackoff::new(settings.tick_interval, settings.idle_tick_interval());
+        let controller = Controller::new(settings, hotkey_manager, Instant::now());
         let (menu_items, tray_icon) = tray::build_tray_icon();
-        State {
-            context: None,
-            settings,
-            hotkey_manager,
-            dialog_worker: dialog::spawn_worker(),
+        let mut state = State {
+            contexts: Vec::new(),
+            controller,
+            config_path,
+            persist_settings,
+            dialog_worker,
+            ipc_worker,
             tray_icon: Some(tray_icon),
+            tray_icon_color: None,
             menu_items,
             last_focused_window: None,
             last_mouse_position: Default::default(),
+            mouse_reader: None,
+            drag_handle_hittest: false,
+            mouse_left_was_pressed: false,
+            eyedropper_left_was_pressed: true,
+            drag_start: None,
             menu_channel: MenuEvent::receiver(),
-            force_redraw: false,
-            window_position_dirty: false,
-            window_scale_dirty: false,
-            window_visible: true,
-        }
+            last_monitor_poll: Instant::now(),
+            reassert_topmost: Throttle::new(REASSERT_TOPMOST_INTERVAL, Instant::now()),
+            follow_cursor: Throttle::new(FOLLOW_CURSOR_INTERVAL, Instant::now()),
+            tick_interval,
+            idle_backoff,
+            #[cfg(feature = "gui")]
+            settings_window: None,
+        };
+        state.sync_tray();
+        state.send_ipc_snapshot();
+        state
     }
 
     fn post_event_work(&mut self, active_event_loop: &ActiveEventLoop) {
-        let window: &Window = &self.context.as_ref().unwrap().window;
+        self.poll_monitors(active_event_loop);
 
         if let Ok(path) = self.dialog_worker.try_recv_file_path() {
             self.menu_items.image_pick_button.set_enabled(true);
 
             if let Some(path) = path {
-                match self.settings.load_png(path) {
-                    Ok(()) => {
-                        self.force_redraw = true;
-                        self.window_scale_dirty = true;
-                    }
-                    Err(e) => dialog::show_warning(format!("Error loading PNG.\n\n{}", e)),
-                }
+                self.controller.load_image(path);
+            }
+        }
+
+        if let Ok(path) = self.dialog_worker.try_recv_save_path() {
+            self.menu_items.export_png_button.set_enabled(true);
+
+            if let Some(path) = path {
+                self.controller.export_png(path);
+            }
+        }
+
+        if let Ok(text) = self.dialog_worker.try_recv_hex_color() {
+            self.menu_items.hex_color_button.set_enabled(true);
+
+            if let Some(text) = text {
+                self.controller.set_color_hex(&text, Instant::now());
             }
         }
 
         while let Ok(event) = self.menu_channel.try_recv() {
-            match event.id {
+            let now = Instant::now();
+            let action = match event.id {
                 id if id == self.menu_items.exit_button.id() => {
                     // drop the tray icon, solving the funny Windows issue where it lingers after application close
                     #[cfg(not(target_os = "linux"))]
                     self.tray_icon.take();
-                    window.set_visible(false);
-                    if let Err(e) = self.settings.save() {
-                        dialog::show_warning(format!(
-                            "Error saving settings to \"{}\".\n\n{}",
-                            CONFIG_PATH.display(),
-                            e
-                        ));
+                    for context in &self.contexts {
+                        context.window.set_visible(false);
                     }
+                    let adjust_mode = self.controller.adjust_mode;
+                    self.controller.settings.remember_adjust_mode(adjust_mode);
+                    self.save_settings();
 
                     // kill the dialog worker and wait for it to finish
                     // this makes the application remain open until the user has clicked through any queued dialogs
@@ -133,156 +328,668 @@ impl<'a> State<'a> {
                     active_event_loop.exit();
                     break;
                 }
-                id if id == self.menu_items.visible_button.id() => {
-                    window.set_visible(self.menu_items.visible_button.is_checked());
+                id if id == self.menu_items.visible_button.id() => Some(MenuAction::Visible(
+                    self.menu_items.visible_button.is_checked(),
+                )),
+                id if id == self.menu_items.adjust_button.id() => Some(MenuAction::Adjust(
+                    self.menu_items.adjust_button.is_checked(),
+                )),
+                id if id == self.menu_items.magnifier_button.id() => Some(MenuAction::Magnifier(
+                    self.menu_items.magnifier_button.is_checked(),
+                )),
+                id if id == self.menu_items.reset_button.id() => Some(MenuAction::Reset),
+                id if id == self.menu_items.show_image_button.id() => Some(MenuAction::ToggleImage),
+                id if id == self.menu_items.reload_image_button.id() => {
+                    Some(MenuAction::ReloadLastImage)
                 }
-                id if id == self.menu_items.reset_button.id() => {
-                    self.settings.reset();
-                    self.force_redraw = true;
-                    self.window_scale_dirty = true;
+                id if id == self.menu_items.next_style_button.id() => {
+                    Some(MenuAction::NextCrosshairMode)
                 }
-                id if id == self.menu_items.color_pick_button.id() => {
-                    let pick_color = self.menu_items.color_pick_button.is_checked();
-                    self.settings.set_pick_color(pick_color);
-                    handle_color_pick(pick_color, window, &mut self.last_focused_window, false);
-                    self.window_scale_dirty = true;
+                id if id == self.menu_items.color_pick_button.id() => Some(MenuAction::PickColor(
+                    self.menu_items.color_pick_button.is_checked(),
+                )),
+                id if id == self.menu_items.eyedropper_button.id() => Some(MenuAction::Eyedropper(
+                    self.menu_items.eyedropper_button.is_checked(),
+                )),
+                id if id == self.menu_items.hotkeys_button.id() => Some(
+                    MenuAction::HotkeysEnabled(self.menu_items.hotkeys_button.is_checked()),
+                ),
+                id if id == self.menu_items.lock_button.id() => {
+                    Some(MenuAction::Locked(self.menu_items.lock_button.is_checked()))
                 }
                 id if id == self.menu_items.image_pick_button.id() => {
                     self.menu_items.image_pick_button.set_enabled(false);
-                    dialog::request_png();
+                    dialog::request_image();
+                    None
+                }
+                id if id == self.menu_items.export_png_button.id() => {
+                    self.menu_items.export_png_button.set_enabled(false);
+                    dialog::request_save_png();
+                    None
+                }
+                id if id == self.menu_items.hex_color_button.id() => {
+                    self.menu_items.hex_color_button.set_enabled(false);
+                    dialog::request_hex_color();
+                    None
+                }
+                #[cfg(feature = "gui")]
+                id if id == self.menu_items.settings_button.id() => {
+                    match &self.settings_window {
+                        Some(settings_window) => settings_window.focus(),
+                        None => {
+                            self.settings_window = Some(SettingsWindow::new(
+                                active_event_loop,
+                                &self.controller.settings,
+                            ))
+                        }
+                    }
+                    None
                 }
                 id if id == self.menu_items.about_button.id() => {
                     dialog::show_info(format!(
-                        "{}\nversion {} {}",
+                        "{}\nversion {} {}\n\n{}",
                         build_constants::APPLICATION_NAME,
                         env!("CARGO_PKG_VERSION"),
-                        env!("GIT_COMMIT_HASH")
+                        env!("GIT_COMMIT_HASH"),
+                        self.controller.settings.describe()
                     ));
+                    None
+                }
+                id if id == self.menu_items.diagnostics_button.id() => {
+                    dialog::copy_to_clipboard(self.diagnostics());
+                    None
+                }
+                id => {
+                    if let Some(monitor_index) =
+                        monitor::monitor_from_menu_id(id.as_ref(), &self.controller.known_monitors)
+                    {
+                        Some(MenuAction::SelectMonitor(monitor_index))
+                    } else {
+                        self.menu_items
+                            .snap_buttons
+                            .iter()
+                            .find(|(button, _)| button.id() == &id)
+                            .map(|&(_, anchor)| anchor)
+                            .map(|anchor| MenuAction::Snap(anchor, self.snap_monitor_size()))
+                    }
                 }
-                _ => (),
+            };
+            if let Some(action) = action {
+                self.controller.on_menu(action, now);
             }
         }
 
-        if self.window_scale_dirty {
-            on_window_size_or_position_change(window, &mut self.settings);
-            self.window_scale_dirty = false;
-            self.window_position_dirty = false;
-        } else if self.window_position_dirty {
-            on_window_position_change(window, &mut self.settings);
-            self.window_position_dirty = false;
+        // a drag isn't finished until the mouse is released, so it shouldn't be an undo step yet
+        if self.drag_start.is_none() {
+            self.controller.commit_history();
         }
-    }
-}
 
-impl<'a> ApplicationHandler<UserEvent> for State<'a> {
-    fn new_events(&mut self, event_loop: &ActiveEventLoop, cause: StartCause) {
-        if matches!(cause, StartCause::Init) {
-            self.context = Some(Context::new(event_loop, &mut self.settings))
+        let had_effects = self.apply_effects();
+        self.update_tray_icon_color();
+
+        let dirty = std::mem::take(&mut self.controller.dirty);
+        if had_effects || dirty != Dirty::default() {
+            self.send_ipc_snapshot();
+        }
+        if dirty != Dirty::default() && self.idle_backoff.is_idle() {
+            // e.g. a tray click, which doesn't come in with a tick
+            self.idle_backoff.tick(true);
+            self.tick_interval.set(self.idle_backoff.interval());
+        }
+        let settings = &self.controller.settings;
+        if dirty.window_scale {
+            for context in &mut self.contexts {
+                context.on_window_size_or_position_change(settings);
+            }
+        } else if dirty.window_position {
+            for context in &mut self.contexts {
+                context.on_window_position_change(settings);
+            }
         }
-    }
 
-    fn resumed(&mut self, _event_loop: &ActiveEventLoop) {
-        // only used on iOS/Android/Web
+        for context in &mut self.contexts {
+            if dirty.force_redraw {
+                context.force_redraw = true;
+            }
+            if dirty.request_redraw {
+                context.window.request_redraw();
+            }
+        }
+
+        // hotkeys can change what the settings window shows, so keep it in sync with the overlay
+        #[cfg(feature = "gui")]
+        if dirty != Default::default() {
+            if let Some(settings_window) = &self.settings_window {
+                settings_window.request_redraw();
+            }
+        }
     }
 
-    fn user_event(&mut self, event_loop: &ActiveEventLoop, _event: UserEvent) {
-        let window: &Window = &self.context.as_ref().unwrap().window;
+    /// Let IPC clients know what the overlay looks like now
+    fn send_ipc_snapshot(&mut self) {
+        if let Some(ipc_worker) = &mut self.ipc_worker {
+            let controller = &self.controller;
+            ipc_worker.send(&Snapshot {
+                visible: controller.visible,
+                render_mode: controller.settings.render_mode,
+                settings: &controller.settings.persisted,
+            });
+        }
+    }
 
-        self.hotkey_manager.poll_keys();
-        self.hotkey_manager.process_keys();
+    /// Write the settings to disk, warning if that didn't work
+    fn save_settings(&self) {
+        if !self.persist_settings {
+            debug_println!("not saving settings loaded from --config-url");
+            return;
+        }
+        if let Err(e) = self.controller.settings.save(&self.config_path) {
+            dialog::show_warning(format!(
+                "Error saving settings to \"{}\".\n\n{}",
+                self.config_path.display(),
+                e
+            ));
+        }
+    }
 
-        let adjust_mode = self.menu_items.adjust_button.is_checked();
-        if adjust_mode {
-            if self.hotkey_manager.move_up() != 0 {
-                self.settings.persisted.window_dy -= self.hotkey_manager.move_up() as i32;
-                self.window_position_dirty = true;
+    #[cfg(feature = "gui")]
+    fn settings_window_event(&mut self, event: WindowEvent) {
+        let Some(settings_window) = &mut self.settings_window else {
+            return;
+        };
+        match event {
+            WindowEvent::RedrawRequested => settings_window.draw(&self.controller.settings),
+            WindowEvent::CursorMoved { position, .. } => {
+                settings_window.set_cursor_position(position)
             }
+            // clicks while recording a keybinding are part of the new binding
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Left,
+                ..
+            } if settings_window.key_capture_mut().is_none() => {
+                match settings_window.clicked(&self.controller.settings) {
+                    Some(Click::Button(field, step)) => {
+                        gui::apply(&mut self.controller.settings, field, step);
+                        self.controller.settings_changed();
+                    }
+                    Some(Click::KeyBinding(name)) => settings_window.start_key_capture(name),
+                    None => {}
+                }
+            }
+            WindowEvent::CloseRequested => {
+                self.settings_window = None;
+                self.save_settings();
+            }
+            _ => {}
+        }
+    }
 
-            if self.hotkey_manager.move_down() != 0 {
-                self.settings.persisted.window_dy += self.hotkey_manager.move_down() as i32;
-                self.window_position_dirty = true;
+    /// Feed the held keys to the settings window if it's recording a keybinding, and apply the binding once it's done.
+    /// Returns `true` while it's still recording.
+    #[cfg(feature = "gui")]
+    fn update_key_capture(&mut self) -> bool {
+        let Some(settings_window) = &mut self.settings_window else {
+            return false;
+        };
+        let Some(key_capture) = settings_window.key_capture_mut() else {
+            return false;
+        };
+        match key_capture.update(&self.controller.hotkey_manager.pressed_keys()) {
+            CaptureStep::Recording => return true,
+            CaptureStep::Cancelled => {}
+            CaptureStep::Done(combination) => {
+                let name = key_capture.name().to_string();
+                if let Err(e) = self.controller.set_key_binding(&name, combination) {
+                    dialog::show_warning(format!("Couldn't change the {name} keybinding: {e}"));
+                }
             }
+        }
+        settings_window.stop_key_capture();
+        false
+    }
 
-            if self.hotkey_manager.move_left() != 0 {
-                self.settings.persisted.window_dx -= self.hotkey_manager.move_left() as i32;
-                self.window_position_dirty = true;
+    /// Do everything the controller asked for that needs the windows or the tray. Returns `true` if there was anything
+    /// to do.
+    fn apply_effects(&mut self) -> bool {
+        let mut sync_tray = false;
+        let effects = self.controller.take_effects();
+        let had_effects = !effects.is_empty();
+        for effect in effects {
+            match effect {
+                Effect::SetVisible(visible) => {
+                    for context in &self.contexts {
+                        context.window.set_visible(visible);
+                    }
+                }
+                Effect::ColorPick { enabled, hotkey } => self.handle_color_pick(enabled, hotkey),
+                Effect::MonitorSelected(monitor_index) => self
+                    .menu_items
+                    .set_monitors(&self.controller.known_monitors, monitor_index),
+                Effect::TickIntervalChanged => {
+                    let settings = &self.controller.settings;
+                    self.idle_backoff
+                        .set_intervals(settings.tick_interval, settings.idle_tick_interval());
+                    self.tick_interval.set(self.idle_backoff.interval());
+                }
+                Effect::SyncTray => sync_tray = true,
+                Effect::WindowLevelChanged => {
+                    let window_level = self.controller.settings.persisted.window_level;
+                    for context in &self.contexts {
+                        context.window.set_window_level(window_level.window_level());
+                    }
+                }
             }
+        }
+        if sync_tray {
+            self.sync_tray();
+        }
+        had_effects
+    }
+
+    /// Make the tray's check marks match the controller
+    fn sync_tray(&self) {
+        let controller = &self.controller;
+        let settings = &controller.settings;
+        let menu_items = &self.menu_items;
+        menu_items.visible_button.set_checked(controller.visible);
+        menu_items.adjust_button.set_checked(controller.adjust_mode);
+        menu_items
+            .color_pick_button
+            .set_checked(settings.get_pick_color());
+        menu_items
+            .magnifier_button
+            .set_checked(controller.magnifier_enabled);
+        menu_items
+            .hotkeys_button
+            .set_checked(settings.persisted.hotkeys_enabled);
+        menu_items.lock_button.set_checked(controller.locked);
+        menu_items
+            .eyedropper_button
+            .set_checked(controller.eyedropper);
+        // the show image item can only be used while there's an image
+        menu_items
+            .show_image_button
+            .set_enabled(settings.image().is_some());
+        menu_items
+            .show_image_button
+            .set_checked(settings.image_shown());
+        menu_items
+            .reload_image_button
+            .set_enabled(settings.last_image_path().is_some());
+    }
 
-            if self.hotkey_manager.move_right() != 0 {
-                self.settings.persisted.window_dx += self.hotkey_manager.move_right() as i32;
-                self.window_position_dirty = true;
+    /// Regenerate the tray icon if the crosshair color changed since it was last drawn
+    fn update_tray_icon_color(&mut self) {
+        let color = self.controller.settings.straight_color();
+        if self.tray_icon_color != Some(color) {
+            self.tray_icon_color = Some(color);
+            self.menu_items
+                .set_icon_color(self.tray_icon.as_ref(), color);
+        }
+    }
+
+    /// Keep the overlay centered on the cursor when `follow_cursor` is on. Returns `true` if the overlay moved.
+    fn follow_cursor(&mut self, now: Instant) -> bool {
+        if !self.controller.settings.persisted.follow_cursor || !self.follow_cursor.tick(now) {
+            return false;
+        }
+
+        let mouse = self
+            .mouse_reader
+            .get_or_insert_with(MouseReader::default)
+            .poll();
+        let position =
+            geometry::centered_on_cursor(mouse.position, self.controller.settings.size());
+        let mut moved = false;
+        for context in &mut self.contexts {
+            if context.desired_window_position != position {
+                context.move_to(position);
+                moved = true;
             }
+        }
+        moved
+    }
 
-            if self.hotkey_manager.cycle_monitor() {
-                self.settings.monitor_index =
-                    (self.settings.monitor_index + 1) % window.available_monitors().count();
-                self.window_scale_dirty = true;
+    /// The size of the monitor to snap on: whichever overlay's monitor the cursor is on, or the first overlay's
+    fn snap_monitor_size(&mut self) -> PhysicalSize<u32> {
+        let mouse = self
+            .mouse_reader
+            .get_or_insert_with(MouseReader::default)
+            .poll();
+        let known_monitors = &self.controller.known_monitors;
+        let cursor_monitor = monitor::monitor_at(mouse.position, known_monitors);
+        let pinned = || {
+            self.contexts
+                .iter()
+                .filter_map(|context| context.monitor_index)
+        };
+        pinned()
+            .find(|&index| Some(index) == cursor_monitor)
+            .or_else(|| pinned().next())
+            .and_then(|index| known_monitors.get(index))
+            .map(|monitor| monitor.size)
+            // a single overlay follows the picked monitor, which is what monitor_size already is
+            .unwrap_or(self.controller.settings.monitor_size)
+    }
+
+    /// Put the overlay back on top of any other topmost windows that covered it
+    fn reassert_topmost(&mut self, now: Instant) {
+        if !platform::REASSERT_TOPMOST_SUPPORTED
+            || !self.controller.settings.persisted.reassert_topmost
+            || self.controller.settings.persisted.window_level != OverlayLevel::AlwaysOnTop
+            || !self.reassert_topmost.tick(now)
+        {
+            return;
+        }
+        for context in &self.contexts {
+            // winit skips setting a level the window already has, so drop it first to force the OS to restack us
+            context.window.set_window_level(WindowLevel::Normal);
+            context.window.set_window_level(WindowLevel::AlwaysOnTop);
+        }
+    }
+
+    /// Rebuild the tray's monitor menu if the monitors changed since the last poll
+    fn poll_monitors(&mut self, event_loop: &ActiveEventLoop) {
+        let now = Instant::now();
+        if now.duration_since(self.last_monitor_poll) < MONITOR_POLL_INTERVAL {
+            return;
+        }
+        self.last_monitor_poll = now;
+
+        let monitors: Vec<MonitorInfo> = event_loop
+            .available_monitors()
+            .map(|monitor| MonitorInfo::from(&monitor))
+            .collect();
+        if monitors != self.controller.known_monitors {
+            debug_println!("monitors changed, rebuilding monitor menu");
+            let monitor_index = self.controller.settings.layout_monitor_index(&monitors);
+            self.menu_items.set_monitors(&monitors, monitor_index);
+            self.controller.known_monitors = monitors;
+            // this might be a setup we remember a different monitor for
+            self.controller.dirty.window_position = true;
+        }
+    }
+
+    /// The first window is the one that owns focus for the color picker. Any others just need to
+    /// become clickable so the picker can be closed from them too.
+    fn handle_color_pick(&mut self, color_pick: bool, hotkey: bool) {
+        if let Some((first, rest)) = self.contexts.split_first() {
+            handle_color_pick(
+                color_pick,
+                &first.window,
+                &mut self.last_focused_window,
+                hotkey,
+            );
+            for context in rest {
+                context.window.set_cursor_hittest(color_pick).unwrap(); // fails on non Windows/Mac/Linux platforms
             }
+        }
+    }
+
+    /// Create a window on each configured monitor, or a single window if no monitors are configured
+    fn create_contexts(&mut self, event_loop: &ActiveEventLoop) {
+        let monitors: Vec<MonitorInfo> = event_loop
+            .available_monitors()
+            .map(|monitor| MonitorInfo::from(&monitor))
+            .collect();
+        let selection = monitor::resolve_monitor_selection(
+            &self.controller.settings.persisted.monitors,
+            &monitors,
+        );
+        if !selection.invalid.is_empty() {
+            let invalid: Vec<String> = selection
+                .invalid
+                .iter()
+                .map(|selector| selector.to_string())
+                .collect();
+            dialog::show_warning(format!(
+                "Ignoring unknown monitors in config: {}.\n\nThere are {} monitors available.",
+                invalid.join(", "),
+                monitors.len()
+            ));
+        }
 
-            if self.settings.is_scalable() && self.hotkey_manager.scale_increase() != 0 {
-                self.settings.persisted.window_height += self.hotkey_manager.scale_increase();
-                self.settings.persisted.window_width = self.settings.persisted.window_height;
-                self.window_scale_dirty = true;
+        // percent-based crosshair sizes follow the first monitor the overlay is on
+        let monitor_index = self.controller.settings.layout_monitor_index(&monitors);
+        let size_monitor_index = selection.indices.first().copied().unwrap_or(monitor_index);
+        if let Some(monitor) = monitors.get(size_monitor_index).or(monitors.first()) {
+            self.controller.settings.monitor_size = monitor.size;
+            self.controller.settings.fit_image();
+        }
+
+        // picking a monitor makes no sense when windows are pinned to specific monitors
+        self.menu_items
+            .monitor_submenu
+            .set_enabled(selection.indices.is_empty());
+        self.menu_items.set_monitors(&monitors, monitor_index);
+        self.controller.known_monitors = monitors;
+        self.last_monitor_poll = Instant::now();
+
+        if selection.indices.is_empty() {
+            self.contexts.push(Context::new(
+                event_loop,
+                &self.controller.settings,
+                None,
+                self.controller.visible,
+            ));
+        } else {
+            // windows are created one after another so their positions and window levels get applied in a
+            // predictable order, with the top overlay last
+            let order = monitor::stacking_order(
+                &selection,
+                self.controller.settings.persisted.top_overlay.as_ref(),
+                &self.controller.known_monitors,
+            );
+            for monitor_index in order {
+                self.contexts.push(Context::new(
+                    event_loop,
+                    &self.controller.settings,
+                    Some(monitor_index),
+                    self.controller.visible,
+                ));
             }
+        }
+    }
+}
 
-            if self.settings.is_scalable() && self.hotkey_manager.scale_decrease() != 0 {
-                self.settings.persisted.window_height = self
-                    .settings
-                    .persisted
-                    .window_height
-                    .checked_sub(self.hotkey_manager.scale_decrease())
-                    .unwrap_or(1)
-                    .max(1);
-                self.settings.persisted.window_width = self.settings.persisted.window_height;
-                self.window_scale_dirty = true;
+impl<'a> State<'a> {
+    /// Gather up everything we'd want to know about the user's setup for a bug report
+    fn diagnostics(&self) -> String {
+        let monitors: Vec<MonitorInfo> = self.contexts[0]
+            .window
+            .available_monitors()
+            .map(|monitor| MonitorInfo::from(&monitor))
+            .collect();
+        Diagnostics {
+            application_name: build_constants::APPLICATION_NAME,
+            version: env!("CARGO_PKG_VERSION"),
+            git_commit_hash: env!("GIT_COMMIT_HASH"),
+            os: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+            monitors: &monitors,
+            render_mode: &self.controller.settings.render_mode,
+            config_path: &self.config_path,
+        }
+        .format()
+    }
+
+    /// Make the window clickable while the cursor is over the drag handle, and move the overlay
+    /// around while the handle is being dragged. The handle only works in adjust mode. With click-through
+    /// turned off the whole window acts as a handle, in or out of adjust mode.
+    fn update_drag_handle(&mut self, adjust_mode: bool) {
+        let handle_size = self.controller.settings.persisted.drag_handle_size;
+        let draggable = !self.controller.click_through;
+        // keep going while there's still a hittest to undo after click-through was turned back on
+        if handle_size == 0 && !draggable && !self.drag_handle_hittest {
+            return;
+        }
+
+        // the color picker manages cursor hittest on its own
+        if self.controller.settings.get_pick_color() {
+            self.drag_handle_hittest = false;
+            self.drag_start = None;
+            return;
+        }
+
+        let mouse = self
+            .mouse_reader
+            .get_or_insert_with(MouseReader::default)
+            .poll();
+        let left_just_pressed = mouse.left_pressed && !self.mouse_left_was_pressed;
+        self.mouse_left_was_pressed = mouse.left_pressed;
+
+        if let Some((start_offset, start_cursor)) = self.drag_start {
+            if (adjust_mode || draggable) && mouse.left_pressed {
+                let offset = geometry::drag_offset(start_offset, start_cursor, mouse.position);
+                let persisted = &mut self.controller.settings.persisted;
+                if offset != (persisted.window_dx, persisted.window_dy) {
+                    (persisted.window_dx, persisted.window_dy) = offset;
+                    self.controller.dirty.window_position = true;
+                }
+                return;
             }
+            self.drag_start = None;
+        }
 
-            // adjust button is already checked
-            if self.hotkey_manager.toggle_adjust() {
-                self.menu_items.adjust_button.set_checked(false)
+        let size = self.controller.settings.size();
+        // a handle as big as the window covers all of it
+        let handle_size = if draggable { u32::MAX } else { handle_size };
+        let hit = (adjust_mode || draggable)
+            && self.contexts.iter().any(|context| {
+                geometry::drag_handle_hit(
+                    mouse.position,
+                    context.desired_window_position,
+                    size,
+                    handle_size,
+                )
+            });
+        // while dragging by the whole window there's no need to wait for the cursor to be over it, and waiting would
+        // make the first click fall through to whatever is behind the overlay
+        let hittest = hit || draggable;
+        if hittest != self.drag_handle_hittest {
+            for context in &self.contexts {
+                context.window.set_cursor_hittest(hittest).unwrap(); // fails on non Windows/Mac/Linux platforms
             }
-        } else if self.hotkey_manager.toggle_adjust() {
-            // adjust button is NOT checked
-            self.menu_items.adjust_button.set_checked(true)
+            self.drag_handle_hittest = hittest;
+        }
+
+        if hit && left_just_pressed {
+            let persisted = &self.controller.settings.persisted;
+            self.drag_start = Some(((persisted.window_dx, persisted.window_dy), mouse.position));
+        }
+    }
+
+    /// While the eyedropper is out, pick the crosshair color from wherever the next left click lands
+    fn update_eyedropper(&mut self) {
+        if !self.controller.eyedropper {
+            self.eyedropper_left_was_pressed = true;
+            return;
         }
 
-        if self.hotkey_manager.toggle_hidden() {
-            self.window_visible = !self.window_visible;
-            window.set_visible(self.window_visible);
-            if !self.window_visible {
-                self.menu_items.adjust_button.set_checked(false)
+        let mouse = self
+            .mouse_reader
+            .get_or_insert_with(MouseReader::default)
+            .poll();
+        let left_just_pressed = mouse.left_pressed && !self.eyedropper_left_was_pressed;
+        self.eyedropper_left_was_pressed = mouse.left_pressed;
+        if !left_just_pressed {
+            return;
+        }
+
+        match platform::sample_screen_pixel(mouse.position) {
+            Some(color) => self.controller.pick_screen_color(color, Instant::now()),
+            None => {
+                self.controller.eyedropper = false;
+                self.menu_items.eyedropper_button.set_checked(false);
+                dialog::show_warning(
+                    "Couldn't read the screen. Picking colors from the screen doesn't work on Wayland.".to_string(),
+                );
             }
         }
+    }
+}
+
+impl<'a> ApplicationHandler<UserEvent> for State<'a> {
+    fn new_events(&mut self, event_loop: &ActiveEventLoop, cause: StartCause) {
+        if matches!(cause, StartCause::Init) {
+            self.create_contexts(event_loop);
+        }
+    }
 
-        // only enable this hotkey if the color picker is already visible OR if adjust mode is on
-        if self.hotkey_manager.toggle_color_picker()
-            && (adjust_mode || self.settings.get_pick_color())
-        {
-            let color_pick = self.settings.toggle_pick_color();
-            self.menu_items.color_pick_button.set_checked(color_pick);
-            handle_color_pick(color_pick, window, &mut self.last_focused_window, true);
-            self.window_scale_dirty = true;
+    fn resumed(&mut self, _event_loop: &ActiveEventLoop) {
+        // only used on iOS/Android/Web
+    }
+
+    fn user_event(&mut self, event_loop: &ActiveEventLoop, _event: UserEvent) {
+        self.controller.hotkey_manager.poll_keys();
+        // the keys being recorded as a new keybinding shouldn't trigger the old ones
+        #[cfg(feature = "gui")]
+        let recording = self.update_key_capture();
+        #[cfg(not(feature = "gui"))]
+        let recording = false;
+        if !recording {
+            self.controller.on_tick(Instant::now());
         }
 
+        if self.controller.settings.magnifier_visible() {
+            for context in &mut self.contexts {
+                context.update_magnifier(&self.controller.settings);
+            }
+        }
+
+        self.update_drag_handle(self.controller.adjust_mode);
+        self.update_eyedropper();
+        self.reassert_topmost(Instant::now());
+        let followed = self.follow_cursor(Instant::now());
+
+        let active =
+            self.controller.is_active() || self.drag_start.is_some() || followed || recording;
+        self.tick_interval.set(self.idle_backoff.tick(active));
+
         self.post_event_work(event_loop);
     }
 
     fn window_event(
         &mut self,
         event_loop: &ActiveEventLoop,
-        _window_id: WindowId,
+        window_id: WindowId,
         event: WindowEvent,
     ) {
-        let context: &mut Context = self.context.as_mut().unwrap();
+        #[cfg(feature = "gui")]
+        if self
+            .settings_window
+            .as_ref()
+            .is_some_and(|settings_window| settings_window.id() == window_id)
+        {
+            self.settings_window_event(event);
+            self.post_event_work(event_loop);
+            return;
+        }
+
+        let Some(context) = self
+            .contexts
+            .iter_mut()
+            .find(|context| context.window.id() == window_id)
+        else {
+            return;
+        };
 
         match event {
             WindowEvent::RedrawRequested => {
                 // failsafe to resize the window before a redraw if necessary
                 // ...and of course it's fucking necessary
-                self.settings
+                self.controller
+                    .settings
                     .validate_window_size(&context.window, context.window.inner_size());
-                draw_window(&mut context.surface, &self.settings, self.force_redraw);
-                self.force_redraw = false;
+                draw_window(
+                    &mut context.surface,
+                    &self.controller.settings,
+                    context.magnifier_sample.as_deref(),
+                    self.controller.picker_cursor,
+                    context.force_redraw,
+                );
+                context.force_redraw = false;
             }
             WindowEvent::Moved(position) => {
                 // incredibly, if the taskbar is at the top or left of the screen Windows will
@@ -290,37 +997,47 @@ impl<'a> ApplicationHandler<UserEvent> for State<'a> {
                 // this happens and it's terrible, but luckily Windows tells me it's done this so
                 // that I can immediately detect and undo it.
                 debug_println!("window position changed to {:?}", position);
-                self.settings
-                    .validate_window_position(&context.window, position);
+                context.validate_window_position(position);
             }
             WindowEvent::Resized(size) => {
                 // See above nightmare scenario with the window position. I figure I might as well
                 // do the same thing for size just in case Windows also has some arcane, evil
                 // involuntary resizing behavior.
                 debug_println!("window size changed to {:?}", size);
-                self.settings.validate_window_size(&context.window, size);
+                self.controller
+                    .settings
+                    .validate_window_size(&context.window, size);
             }
             WindowEvent::CursorMoved { position, .. } => {
                 self.last_mouse_position = position;
+                if self.controller.settings.get_pick_color() {
+                    // keep the keyboard cursor where the mouse left off
+                    let max = image::COLOR_PICKER_SIZE - 1;
+                    let x = (position.x.max(0.0) as usize).min(max);
+                    let y = (position.y.max(0.0) as usize).min(max);
+                    self.controller.set_picker_cursor((x, y));
+                }
             }
+            // the drag handle can also make the window clickable, so make sure we're actually picking a color
             WindowEvent::MouseInput {
                 state: ElementState::Pressed,
                 button: MouseButton::Left,
                 ..
-            } => {
+            } if self.controller.settings.get_pick_color() => {
                 let PhysicalPosition { x, y } = self.last_mouse_position;
-                let x = x as usize;
-                let y = y as usize;
-
-                let PhysicalSize { width, height } = self.settings.size();
-                let width = width as usize;
-                let height = height as usize;
-
-                self.settings
-                    .set_color(image::hue_alpha_color_from_coordinates(x, y, width, height));
-                self.menu_items.color_pick_button.set_checked(false);
-                handle_color_pick(false, &context.window, &mut self.last_focused_window, false);
-                self.window_scale_dirty = true;
+                self.controller
+                    .pick_color(x as usize, y as usize, Instant::now());
+            }
+            // scrolling over the picker changes its saturation
+            WindowEvent::MouseWheel { delta, .. } if self.controller.settings.get_pick_color() => {
+                let scroll = match delta {
+                    MouseScrollDelta::LineDelta(_, y) => y as f64,
+                    MouseScrollDelta::PixelDelta(position) => position.y,
+                };
+                if scroll != 0.0 {
+                    self.controller
+                        .scroll_picker_saturation(scroll > 0.0, Instant::now());
+                }
             }
             _ => {}
         }
@@ -349,30 +1066,16 @@ impl<'a> ApplicationHandler<UserEvent> for State<'a> {
     }
 }
 
-/// Handles both window size and position change side effects.
-fn on_window_size_or_position_change(window: &Window, settings: &mut Settings) {
-    settings.set_window_size(window);
-    settings.set_window_position(window);
-    window.request_redraw(); // needed in case the window size didn't change but the image was replaced
-
-    /*
-    TODO: scaling jitter problem
-        When the application is scaled really quickly via key-repeat spam it struggles to scale, move, and redraw the window in perfect sync.
-        To fix this I'd have to completely rearchitect how scaling works. Ideas:
-        1. Temporarily size the window to full screen, thereby eliminating all but the redraws
-        2. Stop relying on key repeat and instead remember key state and use ticks for your update intervals
-    */
-}
-
-/// Slightly cheaper special case that can only handle window position changes. Do not use this if the window size may have changed.
-fn on_window_position_change(window: &Window, settings: &mut Settings) {
-    settings.set_window_position(window);
-}
-
 /// Draws a crosshair image, or a simple red crosshair if no image is set. Normally this only
 /// redraws the buffer if it's uninitialized, but redraw can be forced by setting the `force`
 /// parameter to `true`.
-fn draw_window(surface: &mut Surface, settings: &Settings, force: bool) {
+fn draw_window(
+    surface: &mut Surface,
+    settings: &Settings,
+    magnifier_sample: Option<&[u32]>,
+    picker_cursor: (usize, usize),
+    force: bool,
+) {
     let PhysicalSize {
         width: window_width,
         height: window_height,
@@ -391,64 +1094,26 @@ fn draw_window(surface: &mut Surface, settings: &Settings, force: bool) {
 
     if force || buffer.age() == 0 {
         // only redraw if the buffer is uninitialized OR redraw is being forced
-        match settings.render_mode {
-            RenderMode::Image => {
-                // draw our image
-                buffer.copy_from_slice(settings.image().unwrap().data.as_slice());
-            }
-            RenderMode::Crosshair => {
-                // draw a generated crosshair
-
-                const FULL_ALPHA: u32 = 0x00000000;
-
-                if width <= 2 || height <= 2 {
-                    // edge case where there simply aren't enough pixels to draw a crosshair, so we just fall back to a dot
-                    buffer.fill(settings.color);
-                } else {
-                    // draw a simple crosshair. Think a `+` shape.
-                    buffer.fill(FULL_ALPHA);
-
-                    // horizontal line
-                    let start = width * (height / 2);
-                    for x in start..start + width {
-                        buffer[x] = settings.color;
-                    }
-
-                    // second horizontal line (if size is even we need this for centering)
-                    if height % 2 == 0 {
-                        let start = start - width;
-                        for x in start..start + width {
-                            buffer[x] = settings.color;
-                        }
-                    }
-
-                    // vertical line
-                    for y in 0..height {
-                        buffer[width * y + width / 2] = settings.color;
-                    }
-
-                    // second vertical line (if size is even we need this for centering)
-                    if width % 2 == 0 {
-                        for y in 0..height {
-                            buffer[width * y + width / 2 - 1] = settings.color;
-                        }
-                    }
-                }
-            }
-            RenderMode::ColorPicker => {
-                image::draw_color_picker(&mut buffer);
-            }
-        }
+        draw::render_window(
+            &mut buffer,
+            width,
+            height,
+            settings,
+            magnifier_sample,
+            picker_cursor,
+        );
     }
 
     buffer.present().unwrap();
 }
 
-/// Initialize the window. This gives a transparent, borderless window that's always on top and can be clicked through.
-fn init_window(active_event_loop: &ActiveEventLoop, settings: &mut Settings) -> Window {
+/// Create the window. This gives a transparent, borderless window that's always on top and can be clicked through.
+/// It stays invisible until [`Context::init_window`] positions it.
+/// With a transparency key set the window is left opaque, as the key color stands in for transparency.
+fn create_window(active_event_loop: &ActiveEventLoop, settings: &Settings) -> Window {
     let window_attributes = Window::default_attributes()
         .with_visible(false) // things get very buggy on Windows if you default the window to invisible...
-        .with_transparent(true)
+        .with_transparent(settings.persisted.transparency_key.is_none())
         .with_decorations(false)
         .with_resizable(false)
         .with_title("Simple Crosshair Overlay")
@@ -473,23 +1138,5 @@ fn init_window(active_event_loop: &ActiveEventLoop, settings: &mut Settings) ->
             .with_has_shadow(false)
     };
 
-    let window = active_event_loop.create_window(window_attributes).unwrap();
-
-    // contrary to all my expectations this call appears to work reliably
-    settings.set_window_position(&window);
-
-    // this call is very fragile (read: shit) and sometimes simply doesn't do anything.
-    // There's a fallback call up in the event loop that saves us when this fails.
-    settings.set_window_size(&window);
-
-    // once the window is ready, show it
-    window.set_visible(true);
-
-    // set these weirder settings AFTER the window is visible to avoid even more buggy Windows behavior
-    // Windows particularly hates if you unset cursor_hittest while the window is hidden
-    window.set_cursor_hittest(false).unwrap();
-    window.set_window_level(WindowLevel::AlwaysOnTop);
-    window.set_cursor(CursorIcon::Crosshair); // Yo Dawg, I herd you like crosshairs so I put a crosshair in your crosshair so you can aim while you aim.
-
-    window
+    active_event_loop.create_window(window_attributes).unwrap()
 }
@@ -0,0 +1,46 @@
+// This file is part of simple-crosshair-overlay and is licenced under the GNU GPL v3.0.
+// See LICENSE file for full text.
+// Copyright © 2023 Michael Ripley
+
+//! Runs the real binary to dump the color picker, then checks the PNG it wrote
+
+use std::fs;
+use std::process::Command;
+
+use simple_crosshair_overlay::private::util::image::{self, COLOR_PICKER_SIZE};
+
+#[test]
+fn test_dump_picker() {
+    let mut path = std::env::temp_dir();
+    path.push("DELETEME_simple-crosshair-overlay-test-picker.png");
+    let output = Command::new(env!("CARGO_BIN_EXE_simple-crosshair-overlay"))
+        .arg("--dump-picker")
+        .arg(&path)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "exited with {}", output.status);
+
+    let picker = image::load_png(&path, false, false, true).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(picker.width as usize, COLOR_PICKER_SIZE);
+    assert_eq!(picker.height as usize, COLOR_PICKER_SIZE);
+
+    // every corner is opaque, and even the darkest row isn't pure black
+    let last = COLOR_PICKER_SIZE - 1;
+    for (x, y) in [(0, 0), (last, 0), (0, last), (last, last)] {
+        let pixel = picker.data[y * COLOR_PICKER_SIZE + x];
+        assert_eq!(pixel >> 24, 0xFF, "corner ({x}, {y}) is {pixel:08X}");
+        assert_ne!(pixel & 0x00FFFFFF, 0, "corner ({x}, {y}) is black");
+    }
+    // top-left is pure red
+    assert_eq!(picker.data[0], 0xFFFF0000);
+}
+
+#[test]
+fn test_dump_picker_missing_path() {
+    let output = Command::new(env!("CARGO_BIN_EXE_simple-crosshair-overlay"))
+        .arg("--dump-picker")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
@@ -0,0 +1,30 @@
+// This file is part of simple-crosshair-overlay and is licenced under the GNU GPL v3.0.
+// See LICENSE file for full text.
+// Copyright © 2023 Michael Ripley
+
+//! Runs the real binary, so this needs a display to talk to
+
+use std::process::Command;
+
+/// winit can't create an event loop without a display server, so there's nothing to test on a headless Linux box
+fn headless() -> bool {
+    cfg!(target_os = "linux")
+        && std::env::var_os("DISPLAY").is_none()
+        && std::env::var_os("WAYLAND_DISPLAY").is_none()
+}
+
+#[test]
+fn test_list_monitors() {
+    if headless() {
+        eprintln!("skipping --list-monitors test: no display");
+        return;
+    }
+
+    let output = Command::new(env!("CARGO_BIN_EXE_simple-crosshair-overlay"))
+        .arg("--list-monitors")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "exited with {}", output.status);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("MONITOR"), "unexpected output: {stdout}");
+}
@@ -0,0 +1,41 @@
+// This file is part of simple-crosshair-overlay and is licenced under the GNU GPL v3.0.
+// See LICENSE file for full text.
+// Copyright © 2023 Michael Ripley
+
+//! Runs the real binary against a fixture config, so the developer's own config never gets involved
+
+use std::fs;
+use std::process::Command;
+
+use simple_crosshair_overlay::private::settings::{PersistedSettings, Settings};
+
+const FIXTURE: &str = "tests/resources/test_config.toml";
+
+#[test]
+fn test_print_config_round_trip() {
+    let directory = std::env::temp_dir().join(format!(
+        "DELETEME_simple-crosshair-overlay-test-print-config-{}",
+        std::process::id()
+    ));
+    fs::create_dir_all(&directory).unwrap();
+    let config_path = directory.join("config.toml");
+    fs::copy(FIXTURE, &config_path).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_simple-crosshair-overlay"))
+        .arg("--print-config")
+        .arg("--config")
+        .arg(&config_path)
+        .output()
+        .unwrap();
+    fs::remove_dir_all(&directory).unwrap();
+    assert!(output.status.success(), "exited with {}", output.status);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    // exactly the fixture, with defaults and validation applied
+    let fixture = Settings::from_toml(&fs::read_to_string(FIXTURE).unwrap()).unwrap();
+    assert_eq!(stdout, fixture.to_toml());
+    assert!(stdout.contains("color = \"FFFF0005\""));
+
+    let parsed: PersistedSettings = toml::from_str(&stdout).unwrap();
+    assert_eq!(toml::to_string(&parsed).unwrap(), stdout);
+}